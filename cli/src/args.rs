@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+/// The arguments of a (sub)command, split into flags (like `--check`), options with a value (like
+/// `--format json` or `--format=json`) and positional arguments. A lone `-` is positional (it
+/// stands for `stdin`), and everything after `--` is positional as well.
+#[derive(Debug, Default)]
+pub struct Args {
+    pub positional: Vec<String>,
+    flags: Vec<&'static str>,
+    options: HashMap<&'static str, String>,
+}
+
+impl Args {
+    /// Splits `args`, where `flags` and `options` are the names (without `--`) that the command
+    /// knows. Returns an error message for an unknown name, an option without a value, or a name
+    /// that is given twice.
+    pub fn parse(
+        args: &[String],
+        flags: &[&'static str],
+        options: &[&'static str],
+    ) -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                parsed.positional.extend(args.by_ref().cloned());
+                break;
+            }
+            let Some(name) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg.clone());
+                continue;
+            };
+            let (name, inline_value) = match name.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (name, None),
+            };
+            if let Some(&flag) = flags.iter().find(|&&f| f == name) {
+                if inline_value.is_some() {
+                    return Err(format!("the option '--{flag}' does not take a value"));
+                }
+                if parsed.flags.contains(&flag) {
+                    return Err(format!("the option '--{flag}' is given twice"));
+                }
+                parsed.flags.push(flag);
+            } else if let Some(&option) = options.iter().find(|&&o| o == name) {
+                let Some(value) = inline_value.or_else(|| args.next().cloned()) else {
+                    return Err(format!("the option '--{option}' needs a value"));
+                };
+                if parsed.options.insert(option, value).is_some() {
+                    return Err(format!("the option '--{option}' is given twice"));
+                }
+            } else {
+                return Err(format!("there is no option '--{name}'"));
+            }
+        }
+        Ok(parsed)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(|s| s.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        Args::parse(&args, &["check", "in-place"], &["format", "variables"])
+    }
+
+    #[test]
+    fn test_parse() {
+        let args = parse(&[
+            "a.txt",
+            "--check",
+            "--format",
+            "json",
+            "--variables=x,y",
+            "-",
+        ])
+        .unwrap();
+        assert_eq!(args.positional, vec!["a.txt", "-"]);
+        assert!(args.flag("check") && !args.flag("in-place"));
+        assert_eq!(args.option("format"), Some("json"));
+        assert_eq!(args.option("variables"), Some("x,y"));
+
+        let args = parse(&["--", "--check"]).unwrap();
+        assert_eq!(args.positional, vec!["--check"]);
+        assert!(!args.flag("check"));
+
+        assert_eq!(
+            parse(&["--bogus"]).unwrap_err(),
+            "there is no option '--bogus'"
+        );
+        assert_eq!(
+            parse(&["--format"]).unwrap_err(),
+            "the option '--format' needs a value"
+        );
+        assert_eq!(
+            parse(&["--check", "--check"]).unwrap_err(),
+            "the option '--check' is given twice"
+        );
+        assert_eq!(
+            parse(&["--check=yes"]).unwrap_err(),
+            "the option '--check' does not take a value"
+        );
+    }
+}
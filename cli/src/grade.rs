@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use fitch_proof::{Diagnostic, Exercise, Grade, ProofResult};
+
+use crate::args::Args;
+use crate::output::{diagnostic_json, json_string, Outcome, EXIT_CORRECT, EXIT_TEMPLATE};
+use crate::{usage, write_file};
+
+/// The grade of one submission, together with the file it came from.
+struct Submission {
+    file: String,
+    grade: Grade,
+}
+
+/// How often a kind of mistake was made across all submissions.
+struct ErrorKind {
+    code: &'static str,
+    occurrences: usize,
+    submissions: usize,
+}
+
+/// Runs `grade <exercise-file> <submission>... [--json <file>] [--csv <file>]`.
+///
+/// Every submission is a proof file, a directory (all files in it are graded) or a pattern with `*`
+/// and `?` in its file name, like `submissions/*.txt`. Hidden files and the exercise file itself
+/// are never graded. The files are graded in parallel, against the exercise file. The grades are written to `grades.json` and `grades.csv` (or the given
+/// files), and a summary, with the most common kinds of mistakes, is printed.
+pub fn run(program: &str, args: &[String]) -> i32 {
+    let args = Args::parse(args, &[], &["json", "csv"]).unwrap_or_else(|err| usage(program, &err));
+    let [exercise_file, patterns @ ..] = &args.positional[..] else {
+        usage(
+            program,
+            "give an exercise file and the submissions to grade",
+        );
+    };
+    if patterns.is_empty() {
+        usage(program, "give the submissions to grade");
+    }
+    let json_file = args.option("json").unwrap_or("grades.json");
+    let csv_file = args.option("csv").unwrap_or("grades.csv");
+
+    let exercise = match Exercise::from_toml(&crate::read_file(exercise_file)) {
+        Ok(exercise) => exercise,
+        Err(err) => {
+            eprintln!("The exercise file {exercise_file} is not valid: {err}");
+            return EXIT_TEMPLATE;
+        }
+    };
+    let mut files: Vec<PathBuf> = patterns.iter().flat_map(|p| submission_files(p)).collect();
+    // the exercise file may be in the same directory as the submissions
+    let exercise_path = std::fs::canonicalize(exercise_file).ok();
+    files.retain(|file| std::fs::canonicalize(file).ok() != exercise_path);
+    files.sort();
+    files.dedup();
+
+    let submissions = grade_all(&files, &exercise);
+    let error_kinds = error_kinds(&submissions);
+    write_file(json_file, &to_json(&submissions, &error_kinds));
+    write_file(csv_file, &to_csv(&submissions));
+    print!("{}", summary(&submissions, &error_kinds));
+    EXIT_CORRECT
+}
+
+/// Returns the files that a submission argument stands for: the file itself, the files in a
+/// directory, or the files that match a pattern.
+fn submission_files(pattern: &str) -> Vec<PathBuf> {
+    let path = Path::new(pattern);
+    if path.is_dir() {
+        return files_in(path, |_| true);
+    }
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    if !name.contains(['*', '?']) {
+        return vec![path.to_path_buf()];
+    }
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let pattern: Vec<char> = name.chars().collect();
+    files_in(dir, |file_name| {
+        matches_pattern(&pattern, &file_name.chars().collect::<Vec<_>>())
+    })
+}
+
+/// Returns the files in `dir` whose names satisfy `keep`, except hidden files.
+fn files_in(dir: &Path, keep: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        eprintln!(
+            "Oops, it seems like the directory {} could not be opened. Skipping it.",
+            dir.display()
+        );
+        return vec![];
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or(".");
+            !name.starts_with('.') && keep(name)
+        })
+        .collect()
+}
+
+/// Returns whether `name` matches `pattern`, in which `*` stands for any number of characters and
+/// `?` for exactly one.
+fn matches_pattern(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, _) => name.is_empty(),
+        (Some('*'), _) => {
+            matches_pattern(&pattern[1..], name)
+                || (!name.is_empty() && matches_pattern(pattern, &name[1..]))
+        }
+        (Some('?'), Some(_)) => matches_pattern(&pattern[1..], &name[1..]),
+        (Some(p), Some(c)) => p == c && matches_pattern(&pattern[1..], &name[1..]),
+        (Some(_), None) => false,
+    }
+}
+
+/// Grades the files, spread over as many threads as there are processors. The submissions are
+/// returned in the order of `files`; files that cannot be read are skipped.
+fn grade_all(files: &[PathBuf], exercise: &Exercise) -> Vec<Submission> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = files.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = files
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter_map(|file| grade_file(file, exercise))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    })
+}
+
+fn grade_file(file: &Path, exercise: &Exercise) -> Option<Submission> {
+    let Ok(proof) = std::fs::read_to_string(file) else {
+        eprintln!(
+            "Oops, it seems like the file {} could not be read. Skipping it.",
+            file.display()
+        );
+        return None;
+    };
+    Some(Submission {
+        file: file.display().to_string(),
+        grade: fitch_proof::grade_proof(&proof, exercise),
+    })
+}
+
+fn verdict(grade: &Grade) -> &'static str {
+    Outcome::of(&grade.result).verdict()
+}
+
+fn diagnostics(grade: &Grade) -> Vec<&Diagnostic> {
+    match &grade.result {
+        ProofResult::Correct => vec![],
+        ProofResult::Error(errs) => errs.iter().collect(),
+        ProofResult::FatalError(err) => vec![err],
+    }
+}
+
+/// Counts the kinds of mistakes (by their error code) over all submissions, from the most to the
+/// least common.
+fn error_kinds(submissions: &[Submission]) -> Vec<ErrorKind> {
+    let mut kinds: HashMap<&'static str, ErrorKind> = HashMap::new();
+    for submission in submissions {
+        let mut codes: Vec<&'static str> = diagnostics(&submission.grade)
+            .iter()
+            .map(|d| d.code.as_str())
+            .collect();
+        for &code in &codes {
+            kinds
+                .entry(code)
+                .or_insert(ErrorKind {
+                    code,
+                    occurrences: 0,
+                    submissions: 0,
+                })
+                .occurrences += 1;
+        }
+        codes.sort();
+        codes.dedup();
+        for code in codes {
+            // the unwrap works: every code was inserted above
+            kinds.get_mut(code).unwrap().submissions += 1;
+        }
+    }
+    let mut kinds: Vec<ErrorKind> = kinds.into_values().collect();
+    kinds.sort_by(|a, b| {
+        (b.submissions, b.occurrences, a.code).cmp(&(a.submissions, a.occurrences, b.code))
+    });
+    kinds
+}
+
+fn average_score(submissions: &[Submission]) -> f64 {
+    if submissions.is_empty() {
+        return 0.0;
+    }
+    let total: u32 = submissions.iter().map(|s| s.grade.score).sum();
+    total as f64 / submissions.len() as f64
+}
+
+fn summary(submissions: &[Submission], error_kinds: &[ErrorKind]) -> String {
+    let count = |v: &str| {
+        submissions
+            .iter()
+            .filter(|s| verdict(&s.grade) == v)
+            .count()
+    };
+    let mut summary = format!(
+        "Graded {} submissions: {} correct, {} incorrect, {} with a fatal error.\n\
+        Average score: {:.1}\n",
+        submissions.len(),
+        count("correct"),
+        count("incorrect"),
+        count("fatal"),
+        average_score(submissions)
+    );
+    if !error_kinds.is_empty() {
+        summary.push_str("Most common mistakes:\n");
+        for kind in error_kinds.iter().take(10) {
+            summary.push_str(&format!(
+                "  {}: {}, in {}\n",
+                kind.code,
+                plural(kind.occurrences, "time"),
+                plural(kind.submissions, "submission")
+            ));
+        }
+    }
+    summary
+}
+
+fn plural(n: usize, noun: &str) -> String {
+    match n {
+        1 => format!("1 {noun}"),
+        n => format!("{n} {noun}s"),
+    }
+}
+
+fn to_json(submissions: &[Submission], error_kinds: &[ErrorKind]) -> String {
+    let submissions_json: Vec<String> = submissions
+        .iter()
+        .map(|s| {
+            let errors: Vec<String> = diagnostics(&s.grade)
+                .iter()
+                .map(|d| diagnostic_json(d))
+                .collect();
+            format!(
+                "    {{\"file\": {}, \"verdict\": \"{}\", \"score\": {}, \"line_count\": {}, \
+                \"errors\": [{}]}}",
+                json_string(&s.file),
+                verdict(&s.grade),
+                s.grade.score,
+                s.grade.line_count,
+                errors.join(", ")
+            )
+        })
+        .collect();
+    let error_kinds_json: Vec<String> = error_kinds
+        .iter()
+        .map(|k| {
+            format!(
+                "      {{\"code\": \"{}\", \"occurrences\": {}, \"submissions\": {}}}",
+                k.code, k.occurrences, k.submissions
+            )
+        })
+        .collect();
+    let count = |v: &str| {
+        submissions
+            .iter()
+            .filter(|s| verdict(&s.grade) == v)
+            .count()
+    };
+    format!(
+        "{{\n  \"submissions\": [\n{}\n  ],\n  \"summary\": {{\n    \"submissions\": {},\n    \
+        \"correct\": {},\n    \"incorrect\": {},\n    \"fatal\": {},\n    \
+        \"average_score\": {:.1},\n    \"error_kinds\": [\n{}\n    ]\n  }}\n}}\n",
+        submissions_json.join(",\n"),
+        submissions.len(),
+        count("correct"),
+        count("incorrect"),
+        count("fatal"),
+        average_score(submissions),
+        error_kinds_json.join(",\n")
+    )
+}
+
+/// One row per submission; the error codes of a submission are separated by semicolons.
+fn to_csv(submissions: &[Submission]) -> String {
+    let mut csv = "file,verdict,score,line_count,error_count,errors\n".to_string();
+    for s in submissions {
+        let codes: Vec<&str> = diagnostics(&s.grade)
+            .iter()
+            .map(|d| d.code.as_str())
+            .collect();
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            csv_field(&s.file),
+            verdict(&s.grade),
+            s.grade.score,
+            s.grade.line_count,
+            codes.len(),
+            codes.join(";")
+        ));
+    }
+    csv
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
@@ -1,50 +1,320 @@
 extern crate fitch_proof;
 
+mod args;
+mod grade;
+mod output;
+
+use args::Args;
+use fitch_proof::{CheckOptions, Diagnostic, Exercise, Feedback, ProofResult, TemplateValidity};
+use output::{Format, Outcome, Report, EXIT_CORRECT, EXIT_FATAL, EXIT_INCORRECT, EXIT_USAGE};
+
 const DEFAULT_ALLOWED_VARIABLE_NAMES: &str = "x,y,z,u,v,w";
 
-/// The *proof* itself (what the student wrote) should be given as a command line argument.
+/// Without a subcommand, the CLI checks a proof the way it always has: the *proof* itself (what
+/// the student wrote) should be given as a command line argument, and the *proof template* via
+/// `stdin`.
+///
+/// With `--validate-template <template-file>`, no proof is checked; instead, the template in the
+/// file is checked to be valid (its conclusion should follow from its premises).
 ///
-/// The *proof template* should be given via `stdin`.
+/// With `--exercise <exercise-file>`, the proof is checked against an exercise file instead (see
+/// `fitch_proof::Exercise`): it holds the template, and also the allowed variable names, the rule
+/// set and the other settings of the exercise. Nothing is read from `stdin` then. Otherwise, the
+/// variable names can be given with `--variables x,y,z`.
 ///
-/// Currently, there is NO SUPPORT for a custom set of allowed variable names over the command
-/// line (it is only in the web GUI).
+/// With `--format json` or `--format junit`, the outcome is printed as JSON or as a JUnit report
+/// instead of as text. The exit code tells the outcome as well (see the constants in `output`):
+/// 0 if the proof is correct, 1 if it is not, 2 for a usage error, 3 if the proof cannot be
+/// checked at all (for example because a line cannot be parsed) and 4 if the template, the
+/// exercise file or the variable names are broken.
+/// Usage errors go to `stderr`.
+///
+/// The subcommands are:
+/// - `check`, which checks a proof like above, but reads the template from a file given with
+///   `--template` (and without it, checks the proof without a template);
+/// - `fmt` and `renumber`, which format a proof or fix its line numbers, and print the result,
+///   write it back (`--in-place`) or only report whether anything would change (`--check`);
+/// - `latex`, which prints a proof as LaTeX;
+/// - `grade`, which grades a whole directory of submissions against an exercise file at once
+///   (see `grade::run`).
+///
+/// A proof file `-` stands for `stdin`.
 fn main() {
     let args: Vec<String> = std::env::args().collect();
+    let program = &args[0];
+    let rest = &args[1..];
+
+    let exit_code = match rest.first().map(|s| s.as_str()) {
+        Some("check") => check(program, &rest[1..], false),
+        Some("fmt") => rewrite(
+            program,
+            &rest[1..],
+            fitch_proof::format_proof_to_result,
+            "formatted",
+        ),
+        Some("renumber") => rewrite(
+            program,
+            &rest[1..],
+            fitch_proof::fix_line_numbers_to_result,
+            "numbered correctly",
+        ),
+        Some("latex") => latex(program, &rest[1..]),
+        Some("grade") => grade::run(program, &rest[1..]),
+        Some("help" | "--help" | "-h") => {
+            println!("{}", usage_text(program));
+            EXIT_CORRECT
+        }
+        _ => check(program, rest, true),
+    };
+    std::process::exit(exit_code);
+}
+
+/// Checks a proof. Without a subcommand (`legacy`), the template is read from `stdin` unless
+/// `--no-template` or `--exercise` is given; with `check`, it is read from the `--template` file.
+fn check(program: &str, args: &[String], legacy: bool) -> i32 {
+    let (flags, options): (&[&str], &[&str]) = if legacy {
+        (
+            &["no-template"],
+            &["exercise", "variables", "format", "validate-template"],
+        )
+    } else {
+        (&[], &["template", "exercise", "variables", "format"])
+    };
+    let args = Args::parse(args, flags, options).unwrap_or_else(|err| usage(program, &err));
+    let format = match args.option("format") {
+        Some(name) => Format::from_name(name).unwrap_or_else(|| {
+            usage(
+                program,
+                &format!("there is no format '{name}'; use 'text', 'json' or 'junit'"),
+            )
+        }),
+        None => Format::Text,
+    };
 
-    if args.len() < 2 || args.len() > 3 {
-        println!("Usage: {} <proof-file> [--no-template]", args[0]);
-        std::process::exit(1);
+    let report = if let Some(template_file) = args.option("validate-template") {
+        if !args.positional.is_empty() {
+            usage(
+                program,
+                "give either a template to validate or a proof to check",
+            );
+        }
+        validate_template(template_file)
+    } else {
+        let [proof_file] = &args.positional[..] else {
+            usage(program, "give exactly one proof file");
+        };
+        if let Some(exercise_file) = args.option("exercise") {
+            if args.option("variables").is_some() || args.option("template").is_some() {
+                usage(
+                    program,
+                    "the exercise file sets the template and the variable names already",
+                );
+            }
+            check_exercise(proof_file, exercise_file)
+        } else {
+            let template_file = match args.option("template") {
+                Some(template_file) => Some(template_file),
+                None if legacy && !args.flag("no-template") => Some("-"),
+                None => None,
+            };
+            if proof_file == "-" && template_file == Some("-") {
+                usage(
+                    program,
+                    "the proof and the template cannot both come from stdin",
+                );
+            }
+            let template = template_file.map(read_template);
+            let variables = args
+                .option("variables")
+                .unwrap_or(DEFAULT_ALLOWED_VARIABLE_NAMES);
+            check_proof(proof_file, template, variables)
+        }
+    };
+    println!("{}", report.render(format));
+    report.outcome.exit_code()
+}
+
+/// Reads a template: one sentence per line.
+fn read_template(file: &str) -> Vec<String> {
+    read_input(file)
+        .lines()
+        .map(|s| s.trim().to_string())
+        .collect()
+}
+
+fn check_proof(proof_file: &str, template: Option<Vec<String>>, variables: &str) -> Report {
+    let proof = read_input(proof_file);
+    let options = CheckOptions {
+        template,
+        ..CheckOptions::default()
     }
+    .with_allowed_variable_names(variables);
+    let (result, _) = fitch_proof::check_proof_to_proofresult(&proof, &options);
+    Report::of_proof_result(proof_file, &result, vec![], Feedback::Full)
+}
 
-    let proof_file = &args[1];
-    let no_template = if args.len() == 3 {
-        if args[2] == "--no-template" {
-            true
+fn check_exercise(proof_file: &str, exercise_file: &str) -> Report {
+    let proof = read_input(proof_file);
+    let exercise = match Exercise::from_toml(&read_file(exercise_file)) {
+        Ok(exercise) => exercise,
+        Err(err) => {
+            let result = ProofResult::FatalError(err);
+            return Report::of_proof_result(proof_file, &result, vec![], Feedback::Full);
+        }
+    };
+    let (result, relaxations) =
+        fitch_proof::check_proof_to_proofresult(&proof, &CheckOptions::from(&exercise));
+    let notes = relaxations.iter().map(|r| r.to_string()).collect();
+    Report::of_proof_result(proof_file, &result, notes, exercise.feedback)
+}
+
+/// A valid template counts as correct, and a template that is not valid (or of which that could
+/// not be decided) as incorrect.
+fn validate_template(template_file: &str) -> Report {
+    let template = read_template(template_file);
+    let validity = fitch_proof::validate_template_to_validity(&template);
+    let (outcome, diagnostics) = match &validity {
+        Ok(TemplateValidity::Valid) => (Outcome::Correct, vec![]),
+        Ok(_) => (Outcome::Incorrect, vec![]),
+        Err(err) => (Outcome::TemplateError, vec![err.clone()]),
+    };
+    Report {
+        name: template_file.to_string(),
+        outcome,
+        diagnostics,
+        notes: vec![],
+        text: fitch_proof::render_template_validity(&validity),
+    }
+}
+
+/// Runs `fmt` or `renumber`: rewrites every proof file with `transform`, and prints the result,
+/// writes it back to the file (`--in-place`), or only lists the files that are not `what` yet
+/// (`--check`, which makes the exit code 1 if there are any).
+///
+/// A proof that cannot be parsed is reported on `stderr`, and makes the exit code 3.
+fn rewrite(
+    program: &str,
+    args: &[String],
+    transform: fn(&str) -> Result<String, Diagnostic>,
+    what: &str,
+) -> i32 {
+    let args =
+        Args::parse(args, &["check", "in-place"], &[]).unwrap_or_else(|err| usage(program, &err));
+    let (check, in_place) = (args.flag("check"), args.flag("in-place"));
+    let files = match &args.positional[..] {
+        [] => vec!["-".to_string()],
+        files => files.to_vec(),
+    };
+    if check && in_place {
+        usage(program, "use either '--check' or '--in-place'");
+    }
+    if in_place && files.iter().any(|f| f == "-") {
+        usage(program, "'--in-place' needs proof files, not stdin");
+    }
+    if !check && !in_place && files.len() > 1 {
+        usage(
+            program,
+            "give one proof file, or use '--check' or '--in-place'",
+        );
+    }
+
+    let mut exit_code = EXIT_CORRECT;
+    for file in &files {
+        let proof = read_input(file);
+        let rewritten = match transform(&proof) {
+            Ok(rewritten) => format!("{}\n", rewritten.trim_end()),
+            Err(err) => {
+                eprintln!("Fatal error in {file}: {err}");
+                exit_code = EXIT_FATAL;
+                continue;
+            }
+        };
+        let changed = rewritten.trim_end() != proof.trim_end();
+        if check {
+            if changed {
+                println!("{file} is not {what}");
+                if exit_code == EXIT_CORRECT {
+                    exit_code = EXIT_INCORRECT;
+                }
+            }
+        } else if in_place {
+            if changed {
+                write_file(file, &rewritten);
+            }
         } else {
-            println!("Usage: {} <proof-file> [--no-template]", args[0]);
-            std::process::exit(1);
+            print!("{rewritten}");
         }
-    } else {
-        false
+    }
+    exit_code
+}
+
+fn latex(program: &str, args: &[String]) -> i32 {
+    let args = Args::parse(args, &[], &[]).unwrap_or_else(|err| usage(program, &err));
+    let file = match &args.positional[..] {
+        [] => "-",
+        [file] => file,
+        _ => usage(program, "give one proof file"),
     };
+    match fitch_proof::export_to_latex_to_result(&read_input(file)) {
+        Ok(latex) => {
+            println!("{}", latex.trim_end());
+            EXIT_CORRECT
+        }
+        Err(err) => {
+            eprintln!("Fatal error in {file}: {err}");
+            EXIT_FATAL
+        }
+    }
+}
 
-    let Ok(proof) = std::fs::read_to_string(proof_file) else {
-        println!(
+/// Reads a file, or `stdin` if the file is `-`.
+fn read_input(file: &str) -> String {
+    if file != "-" {
+        return read_file(file);
+    }
+    let mut input = String::new();
+    if std::io::Read::read_to_string(&mut std::io::stdin(), &mut input).is_err() {
+        eprintln!("Oops, it seems like stdin could not be read. Aborting.");
+        std::process::exit(EXIT_USAGE)
+    }
+    input
+}
+
+fn read_file(file: &str) -> String {
+    let Ok(contents) = std::fs::read_to_string(file) else {
+        eprintln!(
             "Oops, it seems like the file {} could not be opened. Are you sure it exists? Aborting.",
-            proof_file
+            file
         );
-        std::process::exit(1)
+        std::process::exit(EXIT_USAGE)
     };
-    let variables = DEFAULT_ALLOWED_VARIABLE_NAMES.to_string();
+    contents
+}
 
-    let result: String = if no_template {
-        fitch_proof::check_proof(&proof, &variables)
-    } else {
-        let template: Vec<String> = std::io::stdin()
-            .lines()
-            .map(|s| s.unwrap().trim().to_string())
-            .collect();
-        fitch_proof::check_proof_with_template(&proof, template, &variables)
-    };
-    println!("{}", result);
-}
\ No newline at end of file
+fn write_file(file: &str, contents: &str) {
+    if std::fs::write(file, contents).is_err() {
+        eprintln!("Oops, it seems like the file {file} could not be written. Aborting.");
+        std::process::exit(EXIT_USAGE)
+    }
+}
+
+fn usage_text(program: &str) -> String {
+    [
+        format!("Usage: {program} <proof-file> [--no-template | --exercise <exercise-file>] [--variables <names>] [--format text|json|junit]"),
+        format!("       {program} --validate-template <template-file> [--format text|json|junit]"),
+        format!("       {program} check <proof-file> [--template <template-file> | --exercise <exercise-file>] [--variables <names>] [--format text|json|junit]"),
+        format!("       {program} fmt [--check | --in-place] [<proof-file>...]"),
+        format!("       {program} renumber [--check | --in-place] [<proof-file>...]"),
+        format!("       {program} latex [<proof-file>]"),
+        format!("       {program} grade <exercise-file> <submission>... [--json <file>] [--csv <file>]"),
+        "A proof file '-' stands for stdin. The variable names are separated by commas, like 'x,y,z'.".to_string(),
+    ]
+    .join("\n")
+}
+
+/// Prints what is wrong with the command line, and how to use the CLI, to `stderr`, and exits.
+fn usage(program: &str, problem: &str) -> ! {
+    eprintln!("Usage error: {problem}.");
+    eprintln!("{}", usage_text(program));
+    std::process::exit(EXIT_USAGE);
+}
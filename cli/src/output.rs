@@ -0,0 +1,255 @@
+use fitch_proof::{Diagnostic, ErrorCode, Feedback, ProofResult, Severity};
+
+/// The exit code when the proof is correct (or the template is valid).
+pub const EXIT_CORRECT: i32 = 0;
+/// The exit code when the proof is not correct (or the template is not valid).
+pub const EXIT_INCORRECT: i32 = 1;
+/// The exit code when the command line cannot be understood, or a file cannot be read or written.
+pub const EXIT_USAGE: i32 = 2;
+/// The exit code when the proof cannot be checked at all, for example because it cannot be parsed.
+pub const EXIT_FATAL: i32 = 3;
+/// The exit code when the template, the exercise file or the variable names are broken, so that no
+/// proof can be checked against them.
+pub const EXIT_TEMPLATE: i32 = 4;
+
+/// How the outcome of a check is printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The human-readable message of the web interface.
+    Text,
+    /// A JSON object with the verdict, the diagnostics and the notes.
+    Json,
+    /// A JUnit XML report with one test case, for CI systems.
+    Junit,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "junit" => Some(Format::Junit),
+            _ => None,
+        }
+    }
+}
+
+/// The outcome of checking one proof (or validating one template).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    Incorrect,
+    Fatal,
+    TemplateError,
+}
+
+impl Outcome {
+    pub fn of(result: &ProofResult) -> Outcome {
+        match result {
+            ProofResult::Correct => Outcome::Correct,
+            ProofResult::Error(errs) if errs.iter().any(is_parse_error) => Outcome::Fatal,
+            ProofResult::Error(_) => Outcome::Incorrect,
+            ProofResult::FatalError(err) if is_template_error(err) => Outcome::TemplateError,
+            ProofResult::FatalError(_) => Outcome::Fatal,
+        }
+    }
+
+    pub fn verdict(self) -> &'static str {
+        match self {
+            Outcome::Correct => "correct",
+            Outcome::Incorrect => "incorrect",
+            Outcome::Fatal => "fatal",
+            Outcome::TemplateError => "template-error",
+        }
+    }
+
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Correct => EXIT_CORRECT,
+            Outcome::Incorrect => EXIT_INCORRECT,
+            Outcome::Fatal => EXIT_FATAL,
+            Outcome::TemplateError => EXIT_TEMPLATE,
+        }
+    }
+}
+
+/// Whether an error is about a line of the proof that cannot be lexed or parsed. The checker still
+/// reports the other errors in such a proof, but the proof as a whole cannot be checked.
+pub fn is_parse_error(err: &Diagnostic) -> bool {
+    matches!(err.code, ErrorCode::Lexer | ErrorCode::Parser)
+}
+
+/// Whether a fatal error is about the template, the exercise or the variable names, rather than
+/// about the proof.
+pub fn is_template_error(err: &Diagnostic) -> bool {
+    matches!(
+        err.code,
+        ErrorCode::TemplateEmpty
+            | ErrorCode::TemplateUnparsable
+            | ErrorCode::ExerciseUnparsable
+            | ErrorCode::VariableNames
+    )
+}
+
+/// Everything that is printed about one checked proof.
+pub struct Report {
+    /// The name of the checked file, used as the name of the JUnit test case.
+    pub name: String,
+    pub outcome: Outcome,
+    /// The diagnostics that the user may see (so after applying the [Feedback] of an exercise).
+    pub diagnostics: Vec<Diagnostic>,
+    /// Remarks that are not mistakes, like the relaxations of the template matching policy.
+    pub notes: Vec<String>,
+    /// The human-readable message.
+    pub text: String,
+}
+
+impl Report {
+    /// Builds the report of a [ProofResult], showing as many diagnostics as `feedback` allows.
+    pub fn of_proof_result(
+        name: &str,
+        result: &ProofResult,
+        notes: Vec<String>,
+        feedback: Feedback,
+    ) -> Report {
+        let diagnostics = match (result, feedback) {
+            (ProofResult::Correct, _) | (_, Feedback::Verdict) => vec![],
+            (ProofResult::Error(errs), Feedback::FirstMistake) => {
+                errs.iter().take(1).cloned().collect()
+            }
+            (ProofResult::Error(errs), Feedback::Full) => errs.clone(),
+            (ProofResult::FatalError(err), _) => vec![err.clone()],
+        };
+        let text = std::iter::once(fitch_proof::render_proof_result_with_feedback(
+            result, feedback,
+        ))
+        .chain(notes.iter().map(|n| format!("Note: {n}")))
+        .collect::<Vec<_>>()
+        .join("\n");
+        Report {
+            name: name.to_string(),
+            outcome: Outcome::of(result),
+            diagnostics,
+            notes,
+            text,
+        }
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Text => self.text.clone(),
+            Format::Json => self.to_json(),
+            Format::Junit => self.to_junit(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let diagnostics: Vec<String> = self
+            .diagnostics
+            .iter()
+            .map(|d| format!("    {}", diagnostic_json(d)))
+            .collect();
+        let notes: Vec<String> = self.notes.iter().map(|n| json_string(n)).collect();
+        format!(
+            "{{\n  \"verdict\": \"{}\",\n  \"diagnostics\": [{}],\n  \"notes\": [{}]\n}}",
+            self.outcome.verdict(),
+            if diagnostics.is_empty() {
+                String::new()
+            } else {
+                format!("\n{}\n  ", diagnostics.join(",\n"))
+            },
+            notes.join(", ")
+        )
+    }
+
+    /// A proof that is not correct is a failure; a proof that cannot be checked is an error.
+    fn to_junit(&self) -> String {
+        let (failures, errors, body) = match self.outcome {
+            Outcome::Correct => (0, 0, String::new()),
+            Outcome::Incorrect => (
+                1,
+                0,
+                format!(
+                    "\n      <failure type=\"incorrect\" message=\"The proof is not correct.\">{}</failure>\n    ",
+                    xml_escape(&self.text)
+                ),
+            ),
+            Outcome::Fatal | Outcome::TemplateError => (
+                0,
+                1,
+                format!(
+                    "\n      <error type=\"{}\" message=\"{}\">{}</error>\n    ",
+                    self.outcome.verdict(),
+                    xml_escape(self.diagnostics.first().map_or("", |d| &d.message)),
+                    xml_escape(&self.text)
+                ),
+            ),
+        };
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <testsuites>\n  \
+            <testsuite name=\"fitch\" tests=\"1\" failures=\"{failures}\" errors=\"{errors}\">\n    \
+            <testcase classname=\"fitch\" name=\"{}\">{body}</testcase>\n  \
+            </testsuite>\n\
+            </testsuites>",
+            xml_escape(&self.name)
+        )
+    }
+}
+
+/// Returns a [Diagnostic] as a JSON object, on one line.
+pub fn diagnostic_json(d: &Diagnostic) -> String {
+    let severity = match d.severity {
+        Severity::Error => "error",
+        Severity::Fatal => "fatal",
+    };
+    let numbers = |ns: &[usize]| {
+        ns.iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let suggestions: Vec<String> = d.suggestions.iter().map(|s| json_string(s)).collect();
+    // columns count from 1, like in the messages
+    let location = d.location.as_ref().map_or("null".to_string(), |l| {
+        format!(
+            "{{\"line\": {}, \"start_column\": {}, \"end_column\": {}}}",
+            l.line,
+            l.span.start + 1,
+            l.span.end
+        )
+    });
+    format!(
+        "{{\"severity\": \"{severity}\", \"code\": \"{}\", \"line\": {}, \"related_lines\": [{}], \
+        \"rule\": {}, \"message\": {}, \"location\": {location}, \"suggestions\": [{}]}}",
+        d.code.as_str(),
+        d.line.map_or("null".to_string(), |n| n.to_string()),
+        numbers(&d.related_lines),
+        d.rule.map_or("null".to_string(), |r| json_string(r.name())),
+        json_string(&d.message),
+        suggestions.join(", ")
+    )
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut json = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
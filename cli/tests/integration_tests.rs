@@ -8,9 +8,92 @@ use std::process::{Command, Stdio};
 //  in the test_cases directory each test corresponds to 2 or 3 files:
 //      - test_X.txt           the proof itself
 //      - test_X.template      template to check against  [optional]
+//      - test_X.exercise      exercise file to check against, instead of a template  [optional]
 //      - test_X.expected      the expected output of the test
 
 
+// Template validation tests are in the template_cases directory, as pairs of files:
+//      - test_X.template      the template to validate
+//      - test_X.expected      the expected output of the test
+#[test]
+fn run_template_validation_tests() {
+    let cli_path = env!("CARGO_BIN_EXE_cli");
+    let test_cases_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/template_cases");
+
+    for entry in fs::read_dir(test_cases_dir).expect("Failed to read template_cases directory") {
+        let path = entry.expect("Failed to read directory entry").path();
+
+        if path.extension().and_then(|s| s.to_str()) == Some("template") {
+            let stem = path.file_stem().unwrap().to_str().unwrap();
+            println!("Running template validation test for: {}", stem);
+
+            let output = Command::new(cli_path)
+                .arg("--validate-template")
+                .arg(&path)
+                .output()
+                .expect("Failed to run the cli");
+            let expected_file = path.with_extension("expected");
+            let expected_output = fs::read_to_string(&expected_file)
+                .unwrap_or_else(|_| panic!("Failed to read expected file: {:?}", expected_file));
+
+            assert_eq!(
+                String::from_utf8_lossy(&output.stdout).trim(),
+                expected_output.trim(),
+                "Test failed for {}",
+                stem
+            );
+        }
+    }
+}
+
+// Batch grading tests are in the grade_cases directory: for an exercise file test_X.exercise,
+// the proofs in the submissions directory are graded, and compared to:
+//      - test_X.expected      the expected summary
+//      - test_X.csv.expected  the expected grades in CSV
+#[test]
+fn run_grade_tests() {
+    let cli_path = env!("CARGO_BIN_EXE_cli");
+    let grade_cases_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/grade_cases");
+    let output_dir = std::env::temp_dir().join(format!("fitch-grade-{}", std::process::id()));
+    fs::create_dir_all(&output_dir).expect("Failed to create the output directory");
+
+    for entry in fs::read_dir(&grade_cases_dir).expect("Failed to read grade_cases directory") {
+        let path = entry.expect("Failed to read directory entry").path();
+
+        if path.extension().and_then(|s| s.to_str()) == Some("exercise") {
+            let stem = path.file_stem().unwrap().to_str().unwrap();
+            println!("Running grade test for: {}", stem);
+
+            let csv_file = output_dir.join(format!("{stem}.csv"));
+            let output = Command::new(cli_path)
+                .current_dir(&grade_cases_dir)
+                .arg("grade")
+                .arg(path.file_name().unwrap())
+                .arg("submissions/*.txt")
+                .arg("--json")
+                .arg(output_dir.join(format!("{stem}.json")))
+                .arg("--csv")
+                .arg(&csv_file)
+                .output()
+                .expect("Failed to run the cli");
+
+            let expected_output = fs::read_to_string(path.with_extension("expected"))
+                .expect("Failed to read expected file");
+            assert_eq!(
+                String::from_utf8_lossy(&output.stdout).trim(),
+                expected_output.trim(),
+                "Test failed for {}",
+                stem
+            );
+            let expected_csv = fs::read_to_string(path.with_extension("csv.expected"))
+                .expect("Failed to read expected CSV file");
+            let csv = fs::read_to_string(&csv_file).expect("Failed to read the CSV file");
+            assert_eq!(csv, expected_csv, "Test failed for {}", stem);
+        }
+    }
+    fs::remove_dir_all(&output_dir).expect("Failed to remove the output directory");
+}
+
 #[test]
 fn run_integration_tests() {
     run_integration_tests_dir(Path::new("tests/test_cases"));
@@ -31,6 +114,7 @@ fn run_integration_tests_dir(dir : &Path) {
             println!("Running test for: {}", stem);
 
             let template_file = path.with_extension("template");
+            let exercise_file = path.with_extension("exercise");
             let expected_file = path.with_extension("expected");
 
             if !expected_file.exists() {
@@ -42,7 +126,9 @@ fn run_integration_tests_dir(dir : &Path) {
             command.arg(proof_file);
 
             let use_template = template_file.exists();
-            if !use_template {
+            if exercise_file.exists() {
+                command.arg("--exercise").arg(&exercise_file);
+            } else if !use_template {
                 command.arg("--no-template");
             }
 
@@ -56,7 +142,7 @@ fn run_integration_tests_dir(dir : &Path) {
 
             if use_template {
                 let template_content = fs::read_to_string(&template_file)
-                    .expect(&format!("Failed to read template file: {:?}", template_file));
+                    .unwrap_or_else(|_| panic!("Failed to read template file: {:?}", template_file));
                 let mut stdin = child.stdin.take().expect("Failed to open stdin");
                 stdin.write_all(template_content.as_bytes()).expect("Failed to write to stdin");
             }
@@ -70,9 +156,193 @@ fn run_integration_tests_dir(dir : &Path) {
             }
 
             let expected_output = fs::read_to_string(&expected_file)
-                .expect(&format!("Failed to read expected file: {:?}", expected_file));
+                .unwrap_or_else(|_| panic!("Failed to read expected file: {:?}", expected_file));
 
             assert_eq!(stdout.trim(), expected_output.trim(), "Test failed for {}", stem);
+
+            let expected_exit_codes: &[i32] = if expected_output.trim() == "The proof is correct!" {
+                &[0]
+            } else if expected_output.starts_with("Fatal error")
+                || expected_output.contains("parser failure")
+                || expected_output.contains("lexer failure")
+            {
+                &[3, 4]
+            } else {
+                &[1]
+            };
+            let exit_code = output.status.code().expect("The cli was killed");
+            assert!(
+                expected_exit_codes.contains(&exit_code),
+                "Wrong exit code {} for {}",
+                exit_code,
+                stem
+            );
+        }
+    }
+}
+
+// Output format tests are in the format_cases directory: each test_X.txt (with an optional
+// test_X.template) is checked with --format json and --format junit, and compared to:
+//      - test_X.json.expected   the expected JSON output
+//      - test_X.junit.expected  the expected JUnit output
+#[test]
+fn run_format_tests() {
+    let cli_path = env!("CARGO_BIN_EXE_cli");
+    let format_cases_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/format_cases");
+
+    for entry in fs::read_dir(&format_cases_dir).expect("Failed to read format_cases directory") {
+        let path = entry.expect("Failed to read directory entry").path();
+
+        if path.extension().and_then(|s| s.to_str()) == Some("txt") {
+            let stem = path.file_stem().unwrap().to_str().unwrap();
+            let template_file = path.with_extension("template");
+            for format in ["json", "junit"] {
+                println!("Running {} format test for: {}", format, stem);
+
+                let mut command = Command::new(cli_path);
+                command
+                    .current_dir(&format_cases_dir)
+                    .arg(path.file_name().unwrap())
+                    .arg("--format")
+                    .arg(format);
+                if template_file.exists() {
+                    command.stdin(fs::File::open(&template_file).expect("Failed to open template"));
+                } else {
+                    command.arg("--no-template");
+                }
+                let output = command.output().expect("Failed to run the cli");
+
+                let expected_file = path.with_extension(format!("{format}.expected"));
+                let expected_output = fs::read_to_string(&expected_file)
+                    .unwrap_or_else(|_| panic!("Failed to read expected file: {:?}", expected_file));
+                assert_eq!(
+                    String::from_utf8_lossy(&output.stdout).trim(),
+                    expected_output.trim(),
+                    "Test failed for {} in {}",
+                    stem,
+                    format
+                );
+            }
         }
     }
 }
+
+// Rewrite tests are in the rewrite_cases directory: each test_X.txt is run through the fmt,
+// renumber and latex subcommands, and compared to:
+//      - test_X.fmt.expected       the expected formatted proof
+//      - test_X.renumber.expected  the expected proof with fixed line numbers
+//      - test_X.latex.expected     the expected LaTeX
+// The expected proofs should pass `fmt --check` and `renumber --check` themselves.
+#[test]
+fn run_rewrite_tests() {
+    let cli_path = env!("CARGO_BIN_EXE_cli");
+    let rewrite_cases_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/rewrite_cases");
+
+    for entry in fs::read_dir(&rewrite_cases_dir).expect("Failed to read rewrite_cases directory") {
+        let path = entry.expect("Failed to read directory entry").path();
+
+        if path.extension().and_then(|s| s.to_str()) == Some("txt") {
+            let stem = path.file_stem().unwrap().to_str().unwrap();
+            for subcommand in ["fmt", "renumber", "latex"] {
+                println!("Running {} test for: {}", subcommand, stem);
+
+                let output = Command::new(cli_path)
+                    .arg(subcommand)
+                    .arg(&path)
+                    .output()
+                    .expect("Failed to run the cli");
+                assert_eq!(output.status.code(), Some(0), "Test failed for {} in {}", stem, subcommand);
+                let expected_file = path.with_extension(format!("{subcommand}.expected"));
+                let expected_output = fs::read_to_string(&expected_file)
+                    .unwrap_or_else(|_| panic!("Failed to read expected file: {:?}", expected_file));
+                assert_eq!(
+                    String::from_utf8_lossy(&output.stdout).trim_end(),
+                    expected_output.trim_end(),
+                    "Test failed for {} in {}",
+                    stem,
+                    subcommand
+                );
+            }
+
+            for subcommand in ["fmt", "renumber"] {
+                let check = |file: &Path| {
+                    Command::new(cli_path)
+                        .arg(subcommand)
+                        .arg("--check")
+                        .arg(file)
+                        .output()
+                        .expect("Failed to run the cli")
+                        .status
+                        .code()
+                };
+                let expected_file = path.with_extension(format!("{subcommand}.expected"));
+                assert_eq!(check(&path), Some(1), "{} --check passed for {}", subcommand, stem);
+                assert_eq!(check(&expected_file), Some(0), "{} --check failed for {}", subcommand, stem);
+            }
+        }
+    }
+}
+
+#[test]
+fn fmt_in_place() {
+    let cli_path = env!("CARGO_BIN_EXE_cli");
+    let file = std::env::temp_dir().join(format!("fitch-fmt-{}.txt", std::process::id()));
+    fs::write(&file, "1|P\n|---\n2|P   Reit:1\n").expect("Failed to write the proof");
+
+    let status = Command::new(cli_path).args(["fmt", "--in-place"]).arg(&file).status();
+    assert_eq!(status.expect("Failed to run the cli").code(), Some(0));
+    let formatted = fs::read_to_string(&file).expect("Failed to read the proof");
+    fs::remove_file(&file).expect("Failed to remove the proof");
+    assert_eq!(formatted, "1 | P\n  |----\n2 | P           Reit: 1\n");
+}
+
+#[test]
+fn usage_errors_go_to_stderr() {
+    let cli_path = env!("CARGO_BIN_EXE_cli");
+    for args in [&[][..], &["proof.txt", "--format", "yaml"], &["proof.txt", "--bogus"]] {
+        let output = Command::new(cli_path).args(args).output().expect("Failed to run the cli");
+        assert_eq!(output.status.code(), Some(2), "Wrong exit code for {:?}", args);
+        assert!(output.stdout.is_empty(), "Output on stdout for {:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).starts_with("Usage error:"), "No usage for {:?}", args);
+    }
+}
+
+#[test]
+fn unparsable_proof_is_fatal() {
+    let cli_path = env!("CARGO_BIN_EXE_cli");
+    let file = std::env::temp_dir().join(format!("fitch-stray-{}.txt", std::process::id()));
+    fs::write(&file, "1 | A\n  |----\n2 | A ∧ ^^ A\n3 | A   Reit: 1\n").expect("Failed to write the proof");
+
+    let output = Command::new(cli_path).arg(&file).arg("--no-template").output();
+    fs::remove_file(&file).expect("Failed to remove the proof");
+    let output = output.expect("Failed to run the cli");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("invalid character found: ^"));
+}
+
+#[test]
+fn grade_directory() {
+    let cli_path = env!("CARGO_BIN_EXE_cli");
+    let grade_cases_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/grade_cases");
+    let dir = std::env::temp_dir().join(format!("fitch-grade-dir-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("Failed to create the directory");
+    fs::copy(grade_cases_dir.join("socrates.exercise"), dir.join("socrates.exercise"))
+        .expect("Failed to copy the exercise");
+    fs::copy(grade_cases_dir.join("submissions/alice.txt"), dir.join("alice.txt"))
+        .expect("Failed to copy the submission");
+    fs::write(dir.join(".alice.txt.swp"), "not a proof").expect("Failed to write a hidden file");
+
+    // the exercise file and the hidden file in the directory are not graded
+    let output = Command::new(cli_path)
+        .current_dir(&dir)
+        .args(["grade", "socrates.exercise", "."])
+        .output();
+    fs::remove_dir_all(&dir).expect("Failed to remove the directory");
+    let output = output.expect("Failed to run the cli");
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .starts_with("Graded 1 submissions: 1 correct, 0 incorrect, 0 with a fatal error."),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
@@ -0,0 +1,602 @@
+use crate::data::*;
+use crate::fol::{self, Model, ModelEncoding, Signature};
+use crate::sat::{self, Prop, SatResult};
+use std::fmt;
+
+/// The largest number of blocks in the worlds in which [analytic_consequence] looks for a
+/// counterexample, for sentences of which it cannot tell how many blocks a counterexample needs.
+pub const MAX_BLOCKS: usize = 4;
+
+/// The number of columns (and of rows) of the grid on which the blocks stand.
+pub const GRID_SIZE: usize = 8;
+
+/// The number of conflicts after which a single search for a world is given up.
+const MAX_CONFLICTS: usize = 20000;
+
+/// The predicates of the blocks language of Tarski's World, with their arities.
+pub const PREDICATES: [(&str, usize); 18] = [
+    ("Tet", 1),
+    ("Cube", 1),
+    ("Dodec", 1),
+    ("Small", 1),
+    ("Medium", 1),
+    ("Large", 1),
+    ("SameShape", 2),
+    ("SameSize", 2),
+    ("Larger", 2),
+    ("Smaller", 2),
+    ("LeftOf", 2),
+    ("RightOf", 2),
+    ("FrontOf", 2),
+    ("BackOf", 2),
+    ("SameRow", 2),
+    ("SameCol", 2),
+    ("Adjoins", 2),
+    ("Between", 3),
+];
+
+const SHAPES: [&str; 3] = ["tetrahedron", "cube", "dodecahedron"];
+const SIZES: [&str; 3] = ["small", "medium", "large"];
+
+/// A block in a [World]. Columns are numbered from left to right and rows from back to front,
+/// both starting at 0.
+///
+/// A feature of the block that does not matter for the sentences in question is [None].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    /// An index in [SHAPES].
+    shape: Option<usize>,
+    /// An index in [SIZES]; a larger index is a larger size.
+    size: Option<usize>,
+    column: Option<usize>,
+    row: Option<usize>,
+    /// The constants that refer to the block.
+    names: Vec<String>,
+}
+
+/// A blocks world: a number of blocks on a grid, at most one per square. Block `i` is object `i`
+/// of `model`, which also has the interpretation of the symbols that are not in the blocks
+/// language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct World {
+    pub blocks: Vec<Block>,
+    pub model: Model,
+}
+
+impl fmt::Display for World {
+    /// Writes the world like this: `block 0 is a small cube in column 2, row 0, named a and b;
+    /// block 1 is a large dodecahedron in column 0, row 3; P = {1}`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts: Vec<String> = self
+            .blocks
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                let mut part = format!("block {i} is a ");
+                if let Some(size) = b.size {
+                    part += &format!("{} ", SIZES[size]);
+                }
+                part += b.shape.map_or("block", |shape| SHAPES[shape]);
+                match (b.column, b.row) {
+                    (Some(c), Some(r)) => part += &format!(" in column {c}, row {r}"),
+                    (Some(c), None) => part += &format!(" in column {c}"),
+                    (None, Some(r)) => part += &format!(" in row {r}"),
+                    (None, None) => {}
+                }
+                if let Some((last, rest)) = b.names.split_last() {
+                    if rest.is_empty() {
+                        part += &format!(", named {last}");
+                    } else {
+                        part += &format!(", named {} and {last}", rest.join(", "));
+                    }
+                }
+                part
+            })
+            .collect();
+        parts.extend(self.model.describe_symbols());
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// The outcome of [analytic_consequence].
+#[derive(Debug, PartialEq)]
+pub enum AnalyticResult {
+    /// No blocks world makes the premises true and the conclusion false.
+    Consequence,
+    /// In this world, all premises are true but the conclusion is false.
+    Counterexample(World),
+    /// No counterexample was found, but there may be one with more blocks, or the search was
+    /// given up.
+    Undecided,
+}
+
+/// Decides whether `conclusion` is an analytic consequence of `premises` in the blocks language,
+/// i.e. whether it is true in every blocks world in which the premises are true.
+///
+/// The predicates in [PREDICATES] get their meaning from the shapes, sizes and positions of the
+/// blocks: these meaning postulates are the definitions in [WorldEncoding::meaning]. Every other
+/// symbol (and a blocks predicate that is used with another arity) can be interpreted freely.
+///
+/// The worlds on a grid of [GRID_SIZE] by [GRID_SIZE] squares are searched for a counterexample,
+/// from 1 block up. The blocks of a world that are named or picked by an existential quantifier
+/// make up a smaller world in which the sentences have the same truth values, as long as there
+/// are no function symbols and (after moving the quantifiers to the front) no ∃ is in the scope
+/// of a ∀. For such sentences, the search goes up to that number of blocks, and if it finds no
+/// counterexample, the conclusion is accepted. For other sentences, it stops at [MAX_BLOCKS]
+/// blocks, and the answer is [AnalyticResult::Undecided] if it finds no counterexample.
+///
+/// The `signature` should contain the symbols of the sentences; symbols that are missing from it
+/// are added.
+pub fn analytic_consequence(
+    premises: &[&Wff],
+    conclusion: &Wff,
+    signature: &Signature,
+) -> AnalyticResult {
+    let negated_conclusion = Wff::Not(Box::new(conclusion.clone()));
+    let sentences: Vec<&Wff> = premises.iter().copied().chain([&negated_conclusion]).collect();
+    let mut signature = signature.clone();
+    sentences.iter().for_each(|w| signature.add_symbols_of(w));
+
+    // `complete` tells whether a counterexample, if there is one, has at most `max_blocks` blocks
+    let existentials =
+        sentences.iter().map(|w| existentials(w, true, false)).sum::<Option<usize>>();
+    let (max_blocks, complete) = match existentials {
+        Some(n) if signature.functions.is_empty() => ((signature.constants.len() + n).max(1), true),
+        _ => (MAX_BLOCKS, false),
+    };
+    let mut undecided = !complete;
+    for size in 1..=max_blocks {
+        let encoding = ModelEncoding::new(&signature, size);
+        let world = WorldEncoding::new(&signature, size, encoding.num_vars());
+        let mut formulas: Vec<Prop> =
+            sentences.iter().map(|w| encoding.wff(w, &mut vec![])).collect();
+        formulas.extend(encoding.constraints());
+        formulas.extend(world.constraints());
+        for (p, arity) in PREDICATES {
+            for args in fol::tuples(size, arity) {
+                if let Some(var) = encoding.predicate(p, &args) {
+                    formulas.push(Prop::iff(var, world.meaning(p, &args)));
+                }
+            }
+        }
+        match sat::satisfy(&formulas, world.first_var + world.num_vars(), MAX_CONFLICTS) {
+            SatResult::Sat(assignment) => {
+                let mut model = encoding.model(&assignment);
+                let blocks = (0..size)
+                    .map(|x| {
+                        let names = model.constants.iter().filter(|(_, y)| *y == x);
+                        world.block(&assignment, x, names.map(|(c, _)| c.to_owned()).collect())
+                    })
+                    .collect();
+                // the blocks already show their names and the blocks predicates
+                model.constants.clear();
+                model
+                    .predicates
+                    .retain(|(p, arity, _)| !PREDICATES.contains(&(p.as_str(), *arity)));
+                return AnalyticResult::Counterexample(World {
+                    blocks,
+                    model,
+                });
+            }
+            SatResult::Unsat => {}
+            SatResult::Unknown => undecided = true,
+        }
+    }
+    if undecided {
+        AnalyticResult::Undecided
+    } else {
+        AnalyticResult::Consequence
+    }
+}
+
+/* ------------------ PRIVATE -------------------- */
+
+/// Returns the number of existential quantifiers in `wff` (a ∀ counts as one if it is negated,
+/// and an ∃ does not), or [None] if one of them is in the scope of a universal quantifier, or the
+/// quantifiers cannot be told apart (inside a biconditional or a modal operator). `positive` tells
+/// whether `wff` is negated an even number of times, and `universal` whether it is in the scope of
+/// a universal quantifier.
+fn existentials(wff: &Wff, positive: bool, universal: bool) -> Option<usize> {
+    match wff {
+        Wff::Forall(_, w) | Wff::Exists(_, w) => {
+            let existential = matches!(wff, Wff::Exists(..)) == positive;
+            match (existential, universal) {
+                (true, true) => None,
+                (true, false) => Some(1 + existentials(w, positive, false)?),
+                (false, _) => existentials(w, positive, true),
+            }
+        }
+        Wff::And(li) | Wff::Or(li) => li.iter().map(|w| existentials(w, positive, universal)).sum(),
+        Wff::Not(w) => existentials(w, !positive, universal),
+        Wff::Implies(w1, w2) => {
+            Some(existentials(w1, !positive, universal)? + existentials(w2, positive, universal)?)
+        }
+        Wff::Bicond(w1, w2) => (!quantifies(w1) && !quantifies(w2)).then_some(0),
+        Wff::Necessarily(w) | Wff::Possibly(w) => (!quantifies(w)).then_some(0),
+        Wff::Bottom | Wff::Atomic(_) | Wff::PredApp(..) | Wff::Equals(..) => Some(0),
+    }
+}
+
+/// Checks whether a [Wff] contains a quantifier.
+fn quantifies(wff: &Wff) -> bool {
+    match wff {
+        Wff::Forall(..) | Wff::Exists(..) => true,
+        Wff::And(li) | Wff::Or(li) => li.iter().any(quantifies),
+        Wff::Not(w) | Wff::Necessarily(w) | Wff::Possibly(w) => quantifies(w),
+        Wff::Implies(w1, w2) | Wff::Bicond(w1, w2) => quantifies(w1) || quantifies(w2),
+        Wff::Bottom | Wff::Atomic(_) | Wff::PredApp(..) | Wff::Equals(..) => false,
+    }
+}
+
+/// The propositional variables that describe a world with `size` blocks: for each block, which of
+/// the shapes, sizes, columns and rows it has, and on which diagonals it is. They are numbered from
+/// `first_var`.
+///
+/// Only the features that the predicates in the signature depend on get variables; the others
+/// have 0 values in `counts`. This keeps the search from trying out, say, all positions of the
+/// blocks when only their sizes matter.
+struct WorldEncoding {
+    size: usize,
+    first_var: usize,
+    /// The number of values of each feature.
+    counts: [usize; 6],
+}
+
+/// The features of a block: a shape, a size, a column, a row, and the diagonals that go to the
+/// back right (rising) and to the front right (falling). The squares on a rising diagonal have
+/// the same sum of column and row; those on a falling diagonal have the same difference.
+const SHAPE: usize = 0;
+const SIZE: usize = 1;
+const COLUMN: usize = 2;
+const ROW: usize = 3;
+const RISING: usize = 4;
+const FALLING: usize = 5;
+
+/// The ways in which the values of a feature of two blocks are compared: equal, smaller, and
+/// differing by 1.
+const COMPARISONS: [fn(usize, usize) -> bool; 3] =
+    [|i, j| i == j, |i, j| i < j, |i, j| i.abs_diff(j) == 1];
+
+/// The number of values of each feature.
+const VALUES: [usize; 6] =
+    [SHAPES.len(), SIZES.len(), GRID_SIZE, GRID_SIZE, 2 * GRID_SIZE - 1, 2 * GRID_SIZE - 1];
+
+impl WorldEncoding {
+    fn new(signature: &Signature, size: usize, first_var: usize) -> WorldEncoding {
+        let mut counts = [0; 6];
+        for (p, arity) in &signature.predicates {
+            if !PREDICATES.contains(&(p.as_str(), *arity)) {
+                continue;
+            }
+            let features: &[usize] = match p.as_str() {
+                "Tet" | "Cube" | "Dodec" | "SameShape" => &[SHAPE],
+                "LeftOf" | "RightOf" | "SameCol" => &[COLUMN],
+                "FrontOf" | "BackOf" | "SameRow" => &[ROW],
+                "Adjoins" => &[COLUMN, ROW],
+                "Between" => &[COLUMN, ROW, RISING, FALLING],
+                _ => &[SIZE],
+            };
+            for &feature in features {
+                counts[feature] = VALUES[feature];
+            }
+        }
+        WorldEncoding {
+            size,
+            first_var,
+            counts,
+        }
+    }
+
+    fn vars_per_block(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    fn num_vars(&self) -> usize {
+        self.size * self.vars_per_block() + COMPARISONS.len() * VALUES.len() * self.size * self.size
+    }
+
+    /// The variable saying that `feature` (one of [SHAPE], [SIZE], [COLUMN] and [ROW]) of block
+    /// `x` has value `value`.
+    fn var(&self, x: usize, feature: usize, value: usize) -> Prop {
+        let offset: usize = self.counts[..feature].iter().sum();
+        Prop::Var(self.first_var + x * self.vars_per_block() + offset + value)
+    }
+
+    /// The variable saying that the values of `feature` of blocks `x` and `y` are related by the
+    /// comparison with index `comparison` in [COMPARISONS]. These variables come after those of
+    /// the blocks.
+    fn comparison_var(&self, comparison: usize, feature: usize, x: usize, y: usize) -> Prop {
+        let index = ((comparison * VALUES.len() + feature) * self.size + x) * self.size + y;
+        Prop::Var(self.first_var + self.size * self.vars_per_block() + index)
+    }
+
+    /// The formulas saying that each block has exactly one value for each feature, that its
+    /// diagonals go through its square, and that no two blocks are on the same square. (If only the
+    /// columns or only the rows matter, the blocks can always be spread out over the other ones.)
+    fn constraints(&self) -> Vec<Prop> {
+        let mut constraints = vec![];
+        for x in 0..self.size {
+            for (feature, &count) in self.counts.iter().enumerate().filter(|(_, &n)| n > 0) {
+                for i in 0..count {
+                    for j in i + 1..count {
+                        constraints.push(Prop::not(Prop::and([
+                            self.var(x, feature, i),
+                            self.var(x, feature, j),
+                        ])));
+                    }
+                }
+                constraints.push(Prop::or((0..count).map(|i| self.var(x, feature, i))));
+            }
+            if self.counts[RISING] > 0 {
+                for c in 0..GRID_SIZE {
+                    for r in 0..GRID_SIZE {
+                        let square = Prop::and([self.var(x, COLUMN, c), self.var(x, ROW, r)]);
+                        constraints.push(Prop::implies(square.clone(), self.var(x, RISING, c + r)));
+                        constraints.push(Prop::implies(
+                            square,
+                            self.var(x, FALLING, c + GRID_SIZE - 1 - r),
+                        ));
+                    }
+                }
+            }
+            if self.counts[COLUMN] > 0 && self.counts[ROW] > 0 {
+                for y in x + 1..self.size {
+                    constraints.push(Prop::not(Prop::and([
+                        self.same(COLUMN, x, y),
+                        self.same(ROW, x, y),
+                    ])));
+                }
+            }
+        }
+        // the comparisons are defined once, since the meaning postulates use them over and over
+        for (feature, &count) in self.counts.iter().enumerate().filter(|(_, &n)| n > 0) {
+            for (comparison, cmp) in COMPARISONS.iter().enumerate() {
+                for (x, y) in (0..self.size).flat_map(|x| (0..self.size).map(move |y| (x, y))) {
+                    let definition = Prop::or((0..count).flat_map(|i| {
+                        (0..count).filter(move |&j| cmp(i, j)).map(move |j| {
+                            Prop::and([self.var(x, feature, i), self.var(y, feature, j)])
+                        })
+                    }));
+                    constraints.push(Prop::iff(
+                        self.comparison_var(comparison, feature, x, y),
+                        definition,
+                    ));
+                }
+            }
+        }
+        constraints.extend(self.lemmas());
+        constraints
+    }
+
+    /// Formulas that follow from [WorldEncoding::constraints], but that the search would otherwise
+    /// have to find out again and again: the comparisons of values are orders, and two of the
+    /// lines through a square (its column, row and diagonals) fix the square.
+    fn lemmas(&self) -> Vec<Prop> {
+        let mut lemmas = vec![];
+        let blocks = || (0..self.size).flat_map(|x| (0..self.size).map(move |y| (x, y)));
+        for (feature, _) in self.counts.iter().enumerate().filter(|(_, &n)| n > 0) {
+            let (same, less) = (|x, y| self.same(feature, x, y), |x, y| self.less(feature, x, y));
+            for (x, y) in blocks() {
+                lemmas.push(Prop::or([less(x, y), same(x, y), less(y, x)]));
+                lemmas.push(Prop::not(Prop::and([less(x, y), same(x, y)])));
+                lemmas.push(Prop::not(Prop::and([less(x, y), less(y, x)])));
+                for z in 0..self.size {
+                    lemmas.push(Prop::implies(Prop::and([same(x, y), same(y, z)]), same(x, z)));
+                    lemmas.push(Prop::implies(Prop::and([less(x, y), less(y, z)]), less(x, z)));
+                }
+            }
+        }
+        let lines: Vec<usize> =
+            [COLUMN, ROW, RISING, FALLING].into_iter().filter(|&l| self.counts[l] > 0).collect();
+        for (i, &line1) in lines.iter().enumerate() {
+            for &line2 in &lines[i + 1..] {
+                for (x, y) in blocks() {
+                    let same_lines = Prop::and([self.same(line1, x, y), self.same(line2, x, y)]);
+                    lemmas.push(Prop::implies(
+                        same_lines,
+                        Prop::and([self.same(COLUMN, x, y), self.same(ROW, x, y)]),
+                    ));
+                }
+            }
+        }
+        lemmas
+    }
+
+    /// Block `x` and block `y` have the same value for `feature`.
+    fn same(&self, feature: usize, x: usize, y: usize) -> Prop {
+        self.comparison_var(0, feature, x, y)
+    }
+
+    /// The value of `feature` of block `x` is smaller than that of block `y`.
+    fn less(&self, feature: usize, x: usize, y: usize) -> Prop {
+        self.comparison_var(1, feature, x, y)
+    }
+
+    /// The values of `feature` of blocks `x` and `y` differ by exactly 1.
+    fn next_to(&self, feature: usize, x: usize, y: usize) -> Prop {
+        self.comparison_var(2, feature, x, y)
+    }
+
+    /// The meaning postulate of a predicate of the blocks language: the formula saying that it is
+    /// true of the blocks `args`.
+    ///
+    /// PRECONDITION (panics otherwise): the predicate and its arity are in [PREDICATES].
+    fn meaning(&self, predicate: &str, args: &[usize]) -> Prop {
+        let between = |feature: usize, x: usize, y: usize, z: usize| {
+            Prop::or([
+                Prop::and([self.less(feature, y, x), self.less(feature, x, z)]),
+                Prop::and([self.less(feature, z, x), self.less(feature, x, y)]),
+            ])
+        };
+        match (predicate, args) {
+            ("Tet", &[x]) => self.var(x, SHAPE, 0),
+            ("Cube", &[x]) => self.var(x, SHAPE, 1),
+            ("Dodec", &[x]) => self.var(x, SHAPE, 2),
+            ("Small", &[x]) => self.var(x, SIZE, 0),
+            ("Medium", &[x]) => self.var(x, SIZE, 1),
+            ("Large", &[x]) => self.var(x, SIZE, 2),
+            ("SameShape", &[x, y]) => self.same(SHAPE, x, y),
+            ("SameSize", &[x, y]) => self.same(SIZE, x, y),
+            ("Larger", &[x, y]) => self.less(SIZE, y, x),
+            ("Smaller", &[x, y]) => self.less(SIZE, x, y),
+            ("LeftOf", &[x, y]) => self.less(COLUMN, x, y),
+            ("RightOf", &[x, y]) => self.less(COLUMN, y, x),
+            ("FrontOf", &[x, y]) => self.less(ROW, y, x),
+            ("BackOf", &[x, y]) => self.less(ROW, x, y),
+            ("SameRow", &[x, y]) => self.same(ROW, x, y),
+            ("SameCol", &[x, y]) => self.same(COLUMN, x, y),
+            ("Adjoins", &[x, y]) => Prop::or([
+                Prop::and([self.same(ROW, x, y), self.next_to(COLUMN, x, y)]),
+                Prop::and([self.same(COLUMN, x, y), self.next_to(ROW, x, y)]),
+            ]),
+            ("Between", &[x, y, z]) => Prop::or([
+                Prop::and([self.same(ROW, x, y), self.same(ROW, x, z), between(COLUMN, x, y, z)]),
+                Prop::and([
+                    self.same(COLUMN, x, y),
+                    self.same(COLUMN, x, z),
+                    between(ROW, x, y, z),
+                ]),
+                Prop::and([
+                    self.same(RISING, x, y),
+                    self.same(RISING, x, z),
+                    between(COLUMN, x, y, z),
+                ]),
+                Prop::and([
+                    self.same(FALLING, x, y),
+                    self.same(FALLING, x, z),
+                    between(COLUMN, x, y, z),
+                ]),
+            ]),
+            _ => panic!("{predicate} with {} arguments is not in the blocks language", args.len()),
+        }
+    }
+
+    /// Reads block `x` off a satisfying assignment of the variables.
+    fn block(&self, assignment: &[bool], x: usize, names: Vec<String>) -> Block {
+        let value = |feature: usize| {
+            (0..self.counts[feature]).find(|&i| match self.var(x, feature, i) {
+                Prop::Var(v) => assignment[v],
+                _ => unreachable!(),
+            })
+        };
+        Block {
+            shape: value(SHAPE),
+            size: value(SIZE),
+            column: value(COLUMN),
+            row: value(ROW),
+            names,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_logical_expression_string;
+
+    fn wff(s: &str) -> Wff {
+        parse_logical_expression_string(s).unwrap()
+    }
+
+    fn consequence(premises: &[&str], conclusion: &str) -> AnalyticResult {
+        let premises: Vec<Wff> = premises.iter().map(|s| wff(s)).collect();
+        analytic_consequence(
+            &premises.iter().collect::<Vec<_>>(),
+            &wff(conclusion),
+            &Signature::default(),
+        )
+    }
+
+    #[test]
+    fn test_consequences() {
+        let cases: [(&[&str], &str); 12] = [
+            (&["Cube(a)"], "¬Tet(a)"),
+            (&["¬Tet(a)", "¬Dodec(a)"], "Cube(a)"),
+            (&["Larger(a, b)", "Larger(b, c)"], "Larger(a, c)"),
+            (&["Larger(a, b)"], "Smaller(b, a)"),
+            (&["LeftOf(a, b)"], "¬SameCol(a, b)"),
+            (&["SameRow(a, b)", "SameRow(b, c)"], "SameRow(a, c)"),
+            (&["Adjoins(a, b)"], "¬(a = b)"),
+            (&["LeftOf(a, b)", "LeftOf(b, c)"], "RightOf(c, a)"),
+            (&["Adjoins(a, b)"], "Adjoins(b, a)"),
+            (&["Between(a, b, c)"], "Between(a, c, b)"),
+            (&["Between(a, b, c)"], "¬Adjoins(b, c) ∧ ¬(a = b)"),
+            (&["Cube(a)", "Cube(b)"], "SameShape(a, b)"),
+        ];
+        for (premises, conclusion) in cases {
+            assert_eq!(
+                consequence(premises, conclusion),
+                AnalyticResult::Consequence,
+                "{conclusion}"
+            );
+        }
+        assert_eq!(
+            consequence(&[], "∀x (Small(x) ∨ Medium(x) ∨ Large(x))"),
+            AnalyticResult::Consequence
+        );
+        assert_eq!(consequence(&[], "¬∃x LeftOf(x, x)"), AnalyticResult::Consequence);
+        assert_eq!(
+            consequence(&["∀x (Cube(x) → Large(x))", "∃y Cube(y)"], "∃z Large(z)"),
+            AnalyticResult::Consequence
+        );
+    }
+
+    #[test]
+    fn test_more_blocks_than_max_blocks() {
+        // five named blocks in a row: the search may not stop at MAX_BLOCKS blocks
+        assert!(matches!(
+            consequence(&["LeftOf(a, b) ∧ LeftOf(b, c) ∧ LeftOf(c, d) ∧ LeftOf(d, e)"], "⊥"),
+            AnalyticResult::Counterexample(_)
+        ));
+        assert!(matches!(
+            consequence(
+                &[],
+                "¬∃x ∃y ∃z ∃u ∃w (LeftOf(x, y) ∧ LeftOf(y, z) ∧ LeftOf(z, u) ∧ LeftOf(u, w))"
+            ),
+            AnalyticResult::Counterexample(_)
+        ));
+        // this premise is false in every world (nothing is to the right of the rightmost block),
+        // but the search cannot show that
+        assert_eq!(consequence(&["∀x ∃y LeftOf(x, y)"], "⊥"), AnalyticResult::Undecided);
+    }
+
+    #[test]
+    fn test_existentials() {
+        let count = |s: &str| existentials(&wff(s), true, false);
+        assert_eq!(count("Cube(a) ∧ ¬∀x Small(x)"), Some(1));
+        assert_eq!(count("∃x ∀y (LeftOf(x, y) → ∃z Cube(z))"), None);
+        assert_eq!(count("∃x Cube(x) ∨ ∀y ∃z Larger(y, z)"), None);
+        assert_eq!(count("¬∃x ∃y LeftOf(x, y)"), Some(0));
+        assert_eq!(count("(∀x Cube(x)) → Small(a)"), Some(1));
+        assert_eq!(count("∃x Cube(x) ↔ Small(a)"), None);
+    }
+
+    #[test]
+    fn test_counterexamples() {
+        let cases: [(&[&str], &str); 6] = [
+            (&["Cube(a)"], "Small(a)"),
+            (&["Larger(a, b)"], "Large(a)"),
+            (&["LeftOf(a, b)"], "Adjoins(a, b)"),
+            (&["SameRow(a, b)"], "a = b"),
+            (&["Between(a, b, c)"], "SameRow(a, b) ∨ SameCol(a, b)"),
+            (&["P(a)"], "Cube(a)"),
+        ];
+        for (premises, conclusion) in cases {
+            assert!(
+                matches!(consequence(premises, conclusion), AnalyticResult::Counterexample(_)),
+                "{conclusion}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_world_display() {
+        let AnalyticResult::Counterexample(world) =
+            consequence(&["Cube(a)", "Large(a)", "a = b", "P(a)"], "Small(c)")
+        else {
+            panic!("expected a counterexample");
+        };
+        assert_eq!(world.blocks.len(), 1);
+        assert_eq!(world.to_string(), "block 0 is a large cube, named a, b and c; P = {0}");
+    }
+}
@@ -1,49 +1,81 @@
+use crate::blocks::{self, AnalyticResult};
 use crate::data::*;
+use crate::exercise::CheckOptions;
+use crate::fol::{self, FolResult};
 use crate::formatter;
 use crate::proof::*;
+use crate::rule_set::ModalSystem;
+use crate::template::{self, TemplateMatching};
+use crate::truth_table::{self, TruthTableResult};
 use crate::util;
 use std::collections::{HashMap, HashSet};
 use std::iter::zip;
 
-/// This function checks whether a proof is fully correct. It takes in a vector of [ProofLine]s,
-/// which can come straight from the parser (i.e. there are no preconditions about well-formedness
-/// of this vector).
+/// This function checks a proof against everything that its [CheckOptions] ask for: it should be
+/// fully correct, match the template (the premises, followed by the conclusion) if there is one,
+/// as far as the [TemplateMatching] of the options requires, only use the rules of their
+/// [RuleSet](crate::RuleSet), and respect their other limits, such as the number of lines and the
+/// boxed constants that may be introduced. It takes in a vector of [ProofLine]s, which can come straight from the
+/// parser (i.e. there are no preconditions about well-formedness of this vector).
 ///
-/// The second argument is the set of strings that should be seen as a variable.
-/// For example, if this is the set ["x", "y", "z"], then something like ∀x P(x) will be accepted,
-/// but something like ∀a P(a) will not be accepted, because "a" is not listed as a string
-/// that should be seen as a variable.
-pub fn check_proof(
+/// The template and the variable names of the options are passed in parsed. For example, if the
+/// variable names are the set ["x", "y", "z"], then something like ∀x P(x) will be accepted, but
+/// something like ∀a P(a) will not be accepted, because "a" is not listed as a string that should
+/// be seen as a variable.
+///
+/// Besides the result, the relaxations of the [TemplateMatching] that were needed to match the
+/// template are returned.
+pub fn check_proof_with_options(
     proof_lines: Vec<ProofLine>,
+    template: Option<Vec<Wff>>,
     allowed_variable_names: HashSet<String>,
-) -> ProofResult {
-    match Proof::construct(proof_lines, allowed_variable_names) {
-        Err(err) => ProofResult::FatalError(err),
-        Ok(proof) => proof.is_fully_correct(),
-    }
+    options: &CheckOptions,
+) -> (ProofResult, Vec<TemplateRelaxation>) {
+    let first_parse_error = proof_lines.iter().find_map(|l| l.parse_error.clone());
+    let proof =
+        match Proof::construct(proof_lines, allowed_variable_names, options.rule_set.clone()) {
+            Err(err) => {
+                return (
+                    ProofResult::FatalError(first_parse_error_or(err, first_parse_error)),
+                    vec![],
+                )
+            }
+            Ok(proof) => proof,
+        };
+    let (result, relaxations) = match template {
+        Some(template) => {
+            proof.is_fully_correct_and_matches_template(template, &options.template_matching)
+        }
+        None => (proof.is_fully_correct(), vec![]),
+    };
+    let exercise_errors = proof.exercise_errors(options);
+    let result = match result {
+        ProofResult::Correct if exercise_errors.is_empty() => ProofResult::Correct,
+        ProofResult::Correct => ProofResult::Error(exercise_errors),
+        ProofResult::Error(mut errs) => {
+            // sorted again, so that the mistakes against the exercise are in between the others
+            errs.extend(exercise_errors);
+            util::natural_sort(&mut errs);
+            ProofResult::Error(errs)
+        }
+        fatal @ ProofResult::FatalError(_) => fatal,
+    };
+    (result, relaxations)
 }
 
-/// This function checks whether a proof is fully correct, AND that it matches a given proof
-/// template. It takes in a vector of [ProofLine]s,
-/// which can come straight from the parser (i.e. there are no preconditions about well-formedness
-/// of this vector).
-///
-/// The second argument is a vector of [Wff]s. These are the proof template that should be matched
-/// against. These [Wff]s are, in order, the premises, followed by the conclusion that the proof
-/// should lead to.
-///
-/// The third argument is the set of strings that should be seen as a variable.
-/// For example, if this is the set ["x", "y", "z"], then something like ∀x P(x) will be accepted,
-/// but something like ∀a P(a) will not be accepted, because "a" is not listed as a string
-/// that should be seen as a variable.
-pub fn check_proof_with_template(
-    proof_lines: Vec<ProofLine>,
-    template: Vec<Wff>,
-    allowed_variable_names: HashSet<String>,
-) -> ProofResult {
-    match Proof::construct(proof_lines, allowed_variable_names) {
-        Err(err) => ProofResult::FatalError(err),
-        Ok(proof) => proof.is_fully_correct_and_matches_template(template),
+/// If the structure of a proof is broken, this is very likely caused by lines that could not be
+/// parsed. In that case, the first parse error (made fatal) is much more helpful to the user than
+/// the structural error, so this function returns that one. Otherwise, `err` is returned.
+pub(crate) fn first_parse_error_or(
+    err: Diagnostic,
+    first_parse_error: Option<Diagnostic>,
+) -> Diagnostic {
+    match first_parse_error {
+        Some(parse_error) => Diagnostic {
+            severity: Severity::Fatal,
+            ..parse_error
+        },
+        None => err,
     }
 }
 
@@ -54,34 +86,48 @@ impl Proof {
     /// proof template. A template is a vector of [Wff]s, containing (in order) all the premises,
     /// followed by the final conclusion that the proof should lead to.
     ///
+    /// How strictly the proof is compared to the template is set by `matching`. Besides the
+    /// result, the relaxations of `matching` that were needed to match the template are returned.
+    ///
     /// When you want to fully assess the validity of a proof, and
     /// check that it matches the template, you should first
     /// [Proof::construct] the proof, and then run this function.
-    fn is_fully_correct_and_matches_template(&self, template: Vec<Wff>) -> ProofResult {
+    pub(crate) fn is_fully_correct_and_matches_template(
+        &self,
+        template: Vec<Wff>,
+        matching: &TemplateMatching,
+    ) -> (ProofResult, Vec<TemplateRelaxation>) {
         // Note: don't remove this check on the length of `template`. It would cause some panics
         // below if the length is zero.
         if template.is_empty() {
-            return ProofResult::FatalError("The proof template is empty. This should not be! If you see this on Themis as a student, please contact the course staff as soon as possible. Something is wrong on our side. Thanks!".to_owned());
+            return (ProofResult::FatalError(Diagnostic::fatal(ErrorCode::TemplateEmpty, "The proof template is empty. This should not be! If you see this on Themis as a student, please contact the course staff as soon as possible. Something is wrong on our side. Thanks!".to_owned())), vec![]);
         }
 
         // template matching errors that we will be accumulating.
-        let mut template_errors: Vec<String> = vec![];
+        let mut template_errors: Vec<Diagnostic> = vec![];
+        // the relaxations of `matching` that were needed, and the lines whose sentence only
+        // matches after renaming bound variables
+        let mut relaxations: Vec<TemplateRelaxation> = vec![];
+        let mut renamed: Vec<usize> = vec![];
 
         // check premises
         {
-            let premises_in_proof: Vec<Wff> = self
+            let premises_in_proof: Vec<(usize, Wff)> = self
                 .lines
                 .iter()
                 .take_while(|l| !l.is_fitch_bar_line)
-                .filter_map(|l| l.sentence.clone())
+                .filter_map(|l| Some((l.line_num?, l.sentence.clone()?)))
                 .collect();
 
             // index is within bounds
-            if premises_in_proof != template[0..template.len() - 1] {
-                template_errors.push(
-                    "The premises of your proof do not match the premises in the proof template."
-                        .to_owned(),
-                );
+            let premises_in_template = &template[0..template.len() - 1];
+            match matching.match_premises(&premises_in_proof, premises_in_template) {
+                Some((renamed_premises, premise_relaxations)) => {
+                    renamed.extend(renamed_premises);
+                    relaxations.extend(premise_relaxations);
+                }
+                None => template_errors
+                    .extend(template::compare_premises(&premises_in_proof, premises_in_template)),
             }
         }
 
@@ -90,23 +136,48 @@ impl Proof {
             let conclusion_in_proof = self.lines.iter().rev().find(|l| l.sentence.is_some());
             match conclusion_in_proof {
                 None => {
-                    template_errors
-                        .push("It seems that your proof has no sentences in it.".to_owned());
+                    template_errors.push(Diagnostic::error(
+                        ErrorCode::TemplateNoSentences,
+                        None,
+                        "It seems that your proof has no sentences in it.".to_owned(),
+                    ));
                 }
                 Some(concl) => {
                     // both unwraps work (note that we checked the length of `template`)
-                    if concl.sentence.as_ref().unwrap() != template.last().unwrap() {
-                        template_errors.push("The conclusion of your proof does not match the conclusion in the proof template.".to_owned());
+                    let (concl_wff, template_wff) =
+                        (concl.sentence.as_ref().unwrap(), template.last().unwrap());
+                    match matching.sentences_match(concl_wff, template_wff) {
+                        Some(false) => {}
+                        Some(true) => renamed.extend(concl.line_num),
+                        None => template_errors.push(
+                            Diagnostic::error(
+                                ErrorCode::TemplateConclusion,
+                                None,
+                                format!(
+                                    "The conclusion of your proof, '{}', does not match the \
+                                    conclusion in the proof template, '{}'{}.",
+                                    formatter::format_wff(concl_wff),
+                                    formatter::format_wff(template_wff),
+                                    template::explain_difference(concl_wff, template_wff)
+                                        .map(|e| format!("; {e}"))
+                                        .unwrap_or_default()
+                                ),
+                            )
+                            .with_related_lines(concl.line_num.into_iter().collect()),
+                        ),
                     }
                 }
             }
         }
+        if !renamed.is_empty() {
+            relaxations.insert(0, TemplateRelaxation::AlphaEquivalence(renamed));
+        }
 
         let result_without_template_check = self.is_fully_correct();
-        match result_without_template_check {
+        let result = match result_without_template_check {
             // If the proof generates a fatal error by itself, the user is not interested in
             // template matching errors.
-            ProofResult::FatalError(_) => result_without_template_check,
+            ProofResult::FatalError(_) => return (result_without_template_check, vec![]),
             // If there were already errors, just append any template matching errors.
             ProofResult::Error(mut errs) => {
                 errs.append(&mut template_errors);
@@ -121,23 +192,27 @@ impl Proof {
                     ProofResult::Error(template_errors)
                 }
             }
-        }
+        };
+        (result, relaxations)
     }
     /// Given a [Proof], this function checks if it is fully correct.
     ///
     /// When you want to fully assess the validity of a proof, you should first [Proof::construct] the proof, and then run this function.
-    fn is_fully_correct(&self) -> ProofResult {
-        let mut errors: Vec<String> = vec![]; // here we accumulate all errors
+    pub(crate) fn is_fully_correct(&self) -> ProofResult {
+        let mut errors: Vec<Diagnostic> = vec![]; // here we accumulate all errors
+
+        // report the lines that could not be parsed
+        errors.extend(self.lines.iter().filter_map(|line| line.parse_error.clone()));
 
         // check that user applied proof rule correctly everywhere
         for line in &self.lines {
             if let Err(err) = self.check_line(line) {
-                errors.push(err.to_string());
+                errors.push(self.with_repair_suggestions(line, err));
             }
         }
 
         // check that proof starts with zero or more premises, followed by a Fitch bar
-        if !self.units.iter().any(|u| *u == ProofUnit::FitchBarLine)
+        if !self.units.contains(&ProofUnit::FitchBarLine)
             || !self.units.iter().take_while(|u| **u != ProofUnit::FitchBarLine).all(|u| {
                 matches!(
                     *u,
@@ -145,18 +220,22 @@ impl Proof {
                 )
             })
         {
-            errors.push(
+            errors.push(Diagnostic::error(
+                ErrorCode::MissingFitchBar,
+                None,
                 "Each proof should start start with zero or more premises, followed by a Fitch bar"
                     .to_string(),
-            );
+            ));
         }
 
         // check that all inferences have justification
-        errors.extend(
-            self.line_numbers_missing_justification()
-                .iter()
-                .map(|n| format!("Line {n}: missing justification").to_string()),
-        );
+        errors.extend(self.line_numbers_missing_justification().iter().map(|&n| {
+            Diagnostic::error(
+                ErrorCode::MissingJustification,
+                Some(n),
+                "missing justification".to_string(),
+            )
+        }));
 
         // check that all variables are bound, that user doesn't have nested quantifiers over the
         // same variable and that users don't quantify over a constant, and that the user does not make
@@ -189,7 +268,11 @@ impl Proof {
         // check that last line is top-level
         if self.last_line_is_inside_subproof() {
             let lln = self.last_line_num();
-            errors.push(format!("Line {lln}: last line of proof should not be inside subproof"));
+            errors.push(Diagnostic::error(
+                ErrorCode::LastLineInSubproof,
+                Some(lln),
+                "last line of proof should not be inside subproof".to_string(),
+            ));
         }
 
         if errors.is_empty() {
@@ -203,12 +286,15 @@ impl Proof {
     /// This function returns a vector containing all line numbers which correspond to "premises"
     /// that are found between a Fitch bar line and a SubproofOpen.
     /// (these would be the inferences with missing justification, but they are parsed as premises)
-    fn line_numbers_missing_justification(&self) -> Vec<usize> {
+    ///
+    /// Lines that could not be parsed are not included, since we cannot know whether the user
+    /// wrote a justification there.
+    pub(crate) fn line_numbers_missing_justification(&self) -> Vec<usize> {
         let mut res = vec![]; // store what we're going to return
         let mut expect_justification = false;
         for i in 0..self.units.len() {
             match self.units[i] {
-                ProofUnit::FitchBarLine => {
+                ProofUnit::FitchBarLine | ProofUnit::StrictFitchBarLine => {
                     expect_justification = true;
                 }
                 ProofUnit::SubproofOpen => {
@@ -218,7 +304,9 @@ impl Proof {
                 ProofUnit::NumberedProofLineWithJustification(_) => {}
                 ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(num)
                 | ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(num) => {
-                    if expect_justification {
+                    if expect_justification
+                        && self.get_proofline_at_line_unsafe(num).parse_error.is_none()
+                    {
                         res.push(num);
                     }
                 }
@@ -230,13 +318,12 @@ impl Proof {
 
     /// This function returns true if and only if the last line (that has a line number) is inside a subproof
     fn last_line_is_inside_subproof(&self) -> bool {
-        // unwrap should work, since this proof is half-well-structured, so it should contain some
-        // line that contains a logical sentence or boxed constant (i.e. it has a line number).
-        self.lines.iter().rev().find(|&pl| pl.sentence.is_some()).unwrap().depth > 1
+        // a proof that is only a Fitch bar (see `Proof::construct_unfinished`) has no such line
+        self.lines.iter().rev().find(|&pl| pl.line_num.is_some()).is_some_and(|pl| pl.depth > 1)
     }
 
     /// This function returns the line number of the last sentence of the proof.
-    fn last_line_num(&self) -> usize {
+    pub(crate) fn last_line_num(&self) -> usize {
         // unwrap should work, since this proof is half-well-structured, so it should contain some
         // line that contains a logical sentence or boxed constant (i.e. it has a line number).
         self.lines.iter().rev().find(|&pl| pl.line_num.is_some()).unwrap().line_num.unwrap()
@@ -252,8 +339,8 @@ impl Proof {
     /// This function checks that no boxed constants are used outside the subproof. If no boxed
     /// constants are used outside the corresponding subproof, `Ok(())` is returned. Otherwise, a
     /// vector or relevant error messages will be returned, wrapped in an `Err`.
-    fn check_boxed_constant_outside_subproof(&self) -> Result<(), Vec<String>> {
-        let mut errors: Vec<String> = vec![];
+    fn check_boxed_constant_outside_subproof(&self) -> Result<(), Vec<Diagnostic>> {
+        let mut errors: Vec<Diagnostic> = vec![];
 
         // step 1: check which boxed constants exist within the proof
         let boxed_consts: HashSet<_> = self
@@ -273,7 +360,7 @@ impl Proof {
                     ) {
                         None
                     } else {
-                        Some(format!("Line {}: a boxed constant cannot be a variable (should not have the name of a variable).", line.line_num.unwrap()))
+                        Some(Diagnostic::error(ErrorCode::BoxedVariable, line.line_num, "a boxed constant cannot be a variable (should not have the name of a variable).".to_string()))
                     }
                 }),
         );
@@ -284,6 +371,8 @@ impl Proof {
         for i in 0..self.units.len() {
             match self.units[i] {
                 ProofUnit::FitchBarLine => {}
+                // a strict subproof has no premise, so nothing else opens its level of the stack
+                ProofUnit::StrictFitchBarLine => currently_in_scope.push(None),
                 ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(num) => {
                     let new_boxed_const =
                         &self.get_proofline_at_line_unsafe(num).constant_between_square_brackets;
@@ -291,7 +380,7 @@ impl Proof {
                     // before we add the new variable to current scope,
                     // test that it was not already in scope:
                     if currently_in_scope.contains(new_boxed_const) {
-                        errors.push(format!("Line {num}: you cannot introduce the same boxed constant twice in nested subproofs"));
+                        errors.push(Diagnostic::error(ErrorCode::BoxedConstantTwice, Some(num), "you cannot introduce the same boxed constant twice in nested subproofs".to_string()));
                     }
 
                     currently_in_scope.push(new_boxed_const.clone());
@@ -311,14 +400,16 @@ impl Proof {
                 ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(num) => {
                     currently_in_scope.push(None);
 
-                    let prfln = self.get_proofline_at_line_unsafe(num);
-                    if let Err(err) = check_wff_not_contain_out_of_scope_boxed_consts(
-                        prfln.sentence.as_ref().unwrap(),
-                        &currently_in_scope,
-                        &boxed_consts,
-                        num,
-                    ) {
-                        errors.push(err);
+                    // (unparsed lines have no sentence)
+                    if let Some(wff) = &self.get_proofline_at_line_unsafe(num).sentence {
+                        if let Err(err) = check_wff_not_contain_out_of_scope_boxed_consts(
+                            wff,
+                            &currently_in_scope,
+                            &boxed_consts,
+                            num,
+                        ) {
+                            errors.push(err);
+                        }
                     }
                 }
                 ProofUnit::NumberedProofLineWithJustification(num) => {
@@ -346,19 +437,19 @@ impl Proof {
             curr_scope: &[Option<Term>],
             all_boxeds: &HashSet<Term>,
             line_num: usize,
-        ) -> Result<(), String> {
+        ) -> Result<(), Diagnostic> {
             fn check_term_not_contain_out_of_scope_boxed_consts(
                 term: &Term,
                 curr_scope: &[Option<Term>],
                 all_boxeds: &HashSet<Term>,
                 line_num: usize,
-            ) -> Result<(), String> {
+            ) -> Result<(), Diagnostic> {
                 match term {
                     Term::Atomic(_) => {
                         if all_boxeds.contains(term)
                             && !curr_scope.iter().filter_map(|x| x.as_ref()).any(|t| t == term)
                         {
-                            Err(format!("Line {line_num}: it is not allowed to use a boxed constant outside the subproof that defines it"))
+                            Err(Diagnostic::error(ErrorCode::BoxedConstantOutsideSubproof, Some(line_num), "it is not allowed to use a boxed constant outside the subproof that defines it".to_string()))
                         } else {
                             Ok(())
                         }
@@ -385,9 +476,11 @@ impl Proof {
                         b, curr_scope, all_boxeds, line_num,
                     ))
                 }
-                Wff::Not(w) => check_wff_not_contain_out_of_scope_boxed_consts(
-                    w, curr_scope, all_boxeds, line_num,
-                ),
+                Wff::Not(w) | Wff::Necessarily(w) | Wff::Possibly(w) => {
+                    check_wff_not_contain_out_of_scope_boxed_consts(
+                        w, curr_scope, all_boxeds, line_num,
+                    )
+                }
                 Wff::Forall(_, w) | Wff::Exists(_, w) => {
                     check_wff_not_contain_out_of_scope_boxed_consts(
                         w, curr_scope, all_boxeds, line_num,
@@ -423,13 +516,13 @@ impl Proof {
         &self,
         wff: &Wff,
         line_num: usize,
-    ) -> Result<(), String> {
+    ) -> Result<(), Diagnostic> {
         fn check_variable_scoping_naming_issues_helper(
             proof: &Proof,
             wff: &Wff,
             line_num: usize,
             bound_vars_in_scope: &mut Vec<String>,
-        ) -> Result<(), String> {
+        ) -> Result<(), Diagnostic> {
             match wff {
                 Wff::Bottom => Ok(()),
                 Wff::Atomic(_) => Ok(()),
@@ -463,12 +556,14 @@ impl Proof {
                         bound_vars_in_scope,
                     ))
                 }
-                Wff::Not(w) => check_variable_scoping_naming_issues_helper(
-                    proof,
-                    w,
-                    line_num,
-                    bound_vars_in_scope,
-                ),
+                Wff::Not(w) | Wff::Necessarily(w) | Wff::Possibly(w) => {
+                    check_variable_scoping_naming_issues_helper(
+                        proof,
+                        w,
+                        line_num,
+                        bound_vars_in_scope,
+                    )
+                }
                 Wff::Equals(t1, t2) => check_variable_scoping_naming_issues_helper_term(
                     proof,
                     t1,
@@ -483,11 +578,19 @@ impl Proof {
                 )),
                 Wff::Forall(var, wff) | Wff::Exists(var, wff) => {
                     if !proof.allowed_variable_names.contains(var) {
-                        Err(format!("Line {line_num}: you can only quantify over a variable, not over a constant."))
+                        Err(Diagnostic::error(
+                            ErrorCode::QuantifiedConstant,
+                            Some(line_num),
+                            "you can only quantify over a variable, not over a constant."
+                                .to_string(),
+                        ))
                     } else if bound_vars_in_scope.contains(var) {
-                        Err(format!(
-                            "Line {line_num}: this line contains \
+                        Err(Diagnostic::error(
+                            ErrorCode::NestedQuantifier,
+                            Some(line_num),
+                            "this line contains \
                                        two nested quantifiers over the same variable."
+                                .to_string(),
                         ))
                     } else {
                         bound_vars_in_scope.push(var.to_string());
@@ -509,13 +612,17 @@ impl Proof {
             term: &Term,
             line_num: usize,
             bound_vars_in_scope: &mut Vec<String>,
-        ) -> Result<(), String> {
+        ) -> Result<(), Diagnostic> {
             match term {
                 Term::Atomic(str) => {
                     if proof.allowed_variable_names.contains(str)
                         && !bound_vars_in_scope.contains(str)
                     {
-                        Err(format!("Line {line_num}: this line contains unbound variables."))
+                        Err(Diagnostic::error(
+                            ErrorCode::UnboundVariable,
+                            Some(line_num),
+                            "this line contains unbound variables.".to_string(),
+                        ))
                     } else {
                         Ok(())
                     }
@@ -531,9 +638,13 @@ impl Proof {
                         )
                     })
                     .and(if proof.allowed_variable_names.contains(name) {
-                        Err(format!(
-                            "Line {line_num}: you cannot have a function called \
+                        Err(Diagnostic::error(
+                            ErrorCode::FunctionNamedLikeVariable,
+                            Some(line_num),
+                            format!(
+                                "you cannot have a function called \
                                      {name}, because {name} is a reserved name for variables."
+                            ),
                         ))
                     } else {
                         Ok(())
@@ -547,8 +658,11 @@ impl Proof {
     /// error messages, if for example from the arity set it can be determined that the user has
     /// functions of inconsistent arity throughout the proof (e.g. they use both f(x) and f(x,x)) or
     /// if for example the user uses some letter both as a constant name and a function name.
-    fn generate_arity_errors(&self) -> Vec<String> {
-        let mut errors: Vec<String> = vec![];
+    ///
+    /// The returned diagnostics are not tied to a single line, but they do mention all lines in
+    /// which the offending name occurs.
+    fn generate_arity_errors(&self) -> Vec<Diagnostic> {
+        let mut errors: Vec<Diagnostic> = vec![];
         let mut arity_map: HashMap<String, Vec<usize>> = HashMap::from([]);
         for (name, arity) in self.get_arity_set() {
             if !arity_map.contains_key(&name) {
@@ -564,17 +678,26 @@ impl Proof {
             }
             if arities.len() > 1 {
                 arities.sort();
-                if arities.contains(&0) {
+                let (code, message) = if arities.contains(&0) {
                     if name.chars().next().unwrap().is_lowercase() {
-                        errors.push(format!("Error: it seems like you use the name \'{name}\' both to denote a constant, and to denote a function symbol"));
+                        (ErrorCode::SymbolKindClash, format!("Error: it seems like you use the name \'{name}\' both to denote a constant, and to denote a function symbol"))
                     } else {
-                        errors.push(format!("Error: it seems like you use the name \'{name}\' both to denote a nullary predicate (\'no inputs\'), and to denote a non-nullary predicate"));
+                        (ErrorCode::SymbolKindClash, format!("Error: it seems like you use the name \'{name}\' both to denote a nullary predicate (\'no inputs\'), and to denote a non-nullary predicate"))
                     }
                 } else if name.chars().next().unwrap().is_lowercase() {
-                    errors.push(format!("Error: it seems like \'{name}\' is meant to denote a function symbol, but throughout the proof, its arity is inconsistent. The found arities are {arities:?}"))
+                    (ErrorCode::ArityMismatch, format!("Error: it seems like \'{name}\' is meant to denote a function symbol, but throughout the proof, its arity is inconsistent. The found arities are {arities:?}"))
                 } else {
-                    errors.push(format!("Error: it seems like \'{name}\' is meant to denote a predicate, but throughout the proof, its arity is inconsistent. The found arities are {arities:?}"))
-                }
+                    (ErrorCode::ArityMismatch, format!("Error: it seems like \'{name}\' is meant to denote a predicate, but throughout the proof, its arity is inconsistent. The found arities are {arities:?}"))
+                };
+                let lines_using_name = self
+                    .lines
+                    .iter()
+                    .filter(|line| self.get_arity_set_of_line(line).iter().any(|(n, _)| *n == name))
+                    .filter_map(|line| line.line_num)
+                    .collect();
+                errors.push(
+                    Diagnostic::error(code, None, message).with_related_lines(lines_using_name),
+                );
             }
         }
         errors
@@ -588,6 +711,27 @@ impl Proof {
     /// Note that if you find for example both f(x,x) and f(x,x,x) in the same proof, then BOTH the
     /// entries ("f", 2) and ("f", 3) will be included in the arity set.
     fn get_arity_set(&self) -> HashSet<(String, usize)> {
+        self.lines.iter().flat_map(|line| self.get_arity_set_of_line(line)).collect()
+    }
+
+    /// This function returns the "arity set" (see [Proof::get_arity_set]) of a single [ProofLine],
+    /// taking into account both its sentence and its boxed constant.
+    fn get_arity_set_of_line(&self, line: &ProofLine) -> HashSet<(String, usize)> {
+        line.sentence
+            .iter()
+            .flat_map(|t| self.get_arity_set_of_wff(t))
+            .chain(
+                // also include boxed constants in arity set!
+                line.constant_between_square_brackets.iter().map(|c| match c {
+                    Term::Atomic(str) => (str.to_owned(), 0),
+                    Term::FuncApp(..) => panic!("boxed constant cannot be FuncApp"),
+                }),
+            )
+            .collect()
+    }
+
+    /// This function returns the "arity set" (see [Proof::get_arity_set]) of a single [Wff].
+    fn get_arity_set_of_wff(&self, wff: &Wff) -> HashSet<(String, usize)> {
         fn get_arity_set_term(proof: &Proof, term: &Term) -> HashSet<(String, usize)> {
             match term {
                 Term::Atomic(str) => {
@@ -611,7 +755,11 @@ impl Proof {
                 Wff::And(li) | Wff::Or(li) => {
                     li.iter().flat_map(|t| get_arity_set_wff(proof, t)).collect()
                 }
-                Wff::Forall(_, w) | Wff::Exists(_, w) | Wff::Not(w) => get_arity_set_wff(proof, w),
+                Wff::Forall(_, w)
+                | Wff::Exists(_, w)
+                | Wff::Not(w)
+                | Wff::Necessarily(w)
+                | Wff::Possibly(w) => get_arity_set_wff(proof, w),
                 Wff::Bicond(w1, w2) | Wff::Implies(w1, w2) => get_arity_set_wff(proof, w1)
                     .into_iter()
                     .chain(get_arity_set_wff(proof, w2))
@@ -629,23 +777,7 @@ impl Proof {
                 Wff::Atomic(str) => HashSet::from([(str.to_owned(), 0)]),
             }
         }
-        self.lines
-            .iter()
-            .by_ref()
-            .filter_map(|line| line.sentence.as_ref())
-            .flat_map(|t| get_arity_set_wff(self, t))
-            .chain(
-                // also include boxed constants in arity set!
-                self.lines
-                    .iter()
-                    .by_ref()
-                    .filter_map(|line| line.constant_between_square_brackets.as_ref())
-                    .map(|c| match c {
-                        Term::Atomic(str) => (str.to_owned(), 0),
-                        Term::FuncApp(..) => panic!("boxed constant cannot be FuncApp"),
-                    }),
-            )
-            .collect()
+        get_arity_set_wff(self, wff)
     }
 
     /// This function returns whether line n1 can reference line n2.
@@ -666,22 +798,68 @@ impl Proof {
         &self,
         referencing_line: usize,
         requested_line: usize,
-    ) -> Result<&Wff, String> {
+    ) -> Result<&Wff, Diagnostic> {
+        let error = |code, message| {
+            Diagnostic::error(code, Some(referencing_line), message)
+                .with_related_lines(vec![requested_line])
+        };
         let li = self.lines.iter().find(|l| l.line_num == Some(requested_line));
         if let Some(l) = li {
             if let Some(wff) = &l.sentence {
                 if self.can_reference(referencing_line, requested_line) {
                     Ok(wff)
+                } else if self.scope[referencing_line].2.contains(&requested_line) {
+                    Err(error(ErrorCode::StrictSubproof, format!("line {requested_line} is outside the strict subproof that line {referencing_line} is in, so it can only be brought in with □ Elim{}.", if self.rule_set.modal_system() >= ModalSystem::S4 { " or R□" } else { "" })))
+                } else if requested_line < referencing_line
+                    && self.is_across_strict_boundary(referencing_line, requested_line)
+                {
+                    Err(error(ErrorCode::StrictSubproof, format!("line {requested_line} is referenced in the justification, but this is not allowed, because line {requested_line} is outside the strict subproof that line {referencing_line} is in, and in {:?} its sentence cannot be brought into it.", self.rule_set.modal_system())))
                 } else if requested_line < referencing_line {
-                    Err(format!("Line {referencing_line}: line {requested_line} is referenced in the justification, but this is not allowed, because line {requested_line} is inside an already closed subproof."))
+                    Err(error(ErrorCode::RefClosedSubproof, format!("line {requested_line} is referenced in the justification, but this is not allowed, because line {requested_line} is inside an already closed subproof.")))
                 } else {
-                    Err(format!("Line {referencing_line}: line {requested_line} is referenced in the justification, but this is not allowed, because line {requested_line} does not come before line {referencing_line}."))
+                    Err(error(ErrorCode::RefNotBefore, format!("line {requested_line} is referenced in the justification, but this is not allowed, because line {requested_line} does not come before line {referencing_line}.")))
                 }
             } else {
-                Err(format!("Line {referencing_line}: line {requested_line} is being referenced in the justification, but that line does not contain a sentence."))
+                Err(error(ErrorCode::RefNoSentence, format!("line {requested_line} is being referenced in the justification, but that line does not contain a sentence.")))
+            }
+        } else {
+            Err(error(ErrorCode::RefNonexistent, format!("line {requested_line} is being referenced in the justification, but that line does not exist.")))
+        }
+    }
+
+    /// Returns whether line `n1` is inside a strict subproof that line `n2`, which comes before it,
+    /// is not inside.
+    fn is_across_strict_boundary(&self, n1: usize, n2: usize) -> bool {
+        // going back from line n1, a strict Fitch bar that is not deeper than anything in between
+        // opens a strict subproof around line n1
+        let mut min_depth = usize::MAX;
+        for l in self
+            .lines
+            .iter()
+            .rev()
+            .skip_while(|l| l.line_num != Some(n1))
+            .take_while(|l| l.line_num != Some(n2))
+        {
+            if l.is_strict_subproof_bar && l.depth <= min_depth {
+                return true;
             }
+            min_depth = min_depth.min(l.depth);
+        }
+        false
+    }
+
+    /// Gets the [Wff] at line `requested_line` to bring into the strict subproof that
+    /// `referencing_line` is in (see [Scope]). If that is not possible, the error of
+    /// [Proof::get_wff_at_line] is returned.
+    fn get_wff_to_import(
+        &self,
+        referencing_line: usize,
+        requested_line: usize,
+    ) -> Result<&Wff, Diagnostic> {
+        if self.scope[referencing_line].2.contains(&requested_line) {
+            Ok(self.get_proofline_at_line_unsafe(requested_line).sentence.as_ref().unwrap())
         } else {
-            Err(format!("Line {referencing_line}: line {requested_line} is being referenced in the justification, but that line does not exist."))
+            self.get_wff_at_line(referencing_line, requested_line)
         }
     }
 
@@ -700,19 +878,35 @@ impl Proof {
         &self,
         referencing_line: usize,
         (subproof_begin, subproof_end): (usize, usize),
-    ) -> Result<(&ProofLine, &ProofLine), String> {
+    ) -> Result<(&ProofLine, &ProofLine), Diagnostic> {
+        if self.strict_subproofs.contains(&(subproof_begin, subproof_end)) {
+            return Err(Diagnostic::error(
+                ErrorCode::StrictSubproof,
+                Some(referencing_line),
+                format!(
+                    "the referenced subproof {subproof_begin}-{subproof_end} is a strict \
+                    subproof, and only □ Intro can cite a strict subproof."
+                ),
+            )
+            .with_related_lines(vec![subproof_begin, subproof_end]));
+        }
         if self.scope[referencing_line].1.contains(&(subproof_begin, subproof_end)) {
             let s_begin = self.lines.iter().find(|l| l.line_num == Some(subproof_begin)).unwrap();
             // the unwrap should work, since `scope` should refer only to valid line numbers
             let s_end = self.lines.iter().find(|l| l.line_num == Some(subproof_end)).unwrap();
             Ok((s_begin, s_end))
         } else {
-            Err(format!(
-                "Line {referencing_line}: the referenced \
+            Err(Diagnostic::error(
+                ErrorCode::SubproofNotInScope,
+                Some(referencing_line),
+                format!(
+                    "the referenced \
                         subproof {subproof_begin}-{subproof_end} is \
                         not in the scope of line {referencing_line}, \
                         or it does not exist."
-            ))
+                ),
+            )
+            .with_related_lines(vec![subproof_begin, subproof_end]))
         }
     }
 
@@ -723,13 +917,118 @@ impl Proof {
     /// correctly. It will also return `Ok(())` if the given line is a premise, or a Fitch bar
     /// line, or an empty line, since in those cases there is no justification to check.
     ///
+    /// A rule that is not allowed by the [RuleSet](crate::RuleSet) of the proof is rejected without looking at
+    /// how it was applied.
+    ///
     /// Note that the provided [ProofLine] should exist in the proof!
-    fn check_line(&self, line: &ProofLine) -> Result<(), String> {
+    ///
+    /// The returned [Diagnostic] always mentions the rule that was used. Unless a more specific
+    /// line is known to be involved, its related lines are all lines cited by the justification.
+    pub(crate) fn check_line(&self, line: &ProofLine) -> Result<(), Diagnostic> {
         // this function only checks lines that have a justification...
-        if line.justification.is_none() {
+        let Some(just) = &line.justification else {
+            return Ok(());
+        };
+        // ... and that do not cite lines that could not be parsed (those lines are reported
+        // already, and we cannot know what the user meant to write there)
+        if self.cites_unparsed_line(just) {
             return Ok(());
         }
+        let result = if !self.rule_set.is_allowed(just.rule()) {
+            Err(Diagnostic::error(
+                ErrorCode::RuleNotAllowed,
+                line.line_num,
+                format!(
+                    "rule {} is not allowed in this exercise.",
+                    self.rule_set.name(just.rule())
+                ),
+            ))
+        } else if self.rule_set.is_intuitionistic()
+            && matches!(just.rule(), Rule::TautCon | Rule::FoCon | Rule::AnaCon)
+        {
+            Err(Diagnostic::error(
+                ErrorCode::RuleNotAllowed,
+                line.line_num,
+                format!(
+                    "rule {} is not available in intuitionistic mode, because it checks consequence in classical logic.",
+                    self.rule_set.name(just.rule())
+                ),
+            ))
+        } else {
+            self.check_justification(line).and_then(|()| match self.classical_principle(line) {
+                Some(principle) if self.rule_set.is_intuitionistic() => Err(Diagnostic::error(
+                    ErrorCode::ClassicalStep,
+                    line.line_num,
+                    format!(
+                        "this step relies on {principle}, which is not valid in intuitionistic logic."
+                    ),
+                )),
+                _ => Ok(()),
+            })
+        };
+        result.map_err(|mut diagnostic| {
+            diagnostic.rule = Some(just.rule());
+            if diagnostic.related_lines.is_empty() {
+                diagnostic.related_lines = just.cited_lines();
+            }
+            diagnostic
+        })
+    }
+
+    /// Whether a justification cites a line that could not be parsed. [Proof::check_line] does
+    /// not check such a justification, so it does not say whether the justification is correct.
+    pub(crate) fn cites_unparsed_line(&self, just: &Justification) -> bool {
+        just.cited_lines()
+            .iter()
+            .any(|n| self.lines.iter().any(|l| l.line_num == Some(*n) && l.parse_error.is_some()))
+    }
+
+    /// Returns a description of the principle of classical logic that the (correct) step in a
+    /// [ProofLine] relies on, or [None] if the step is also valid in intuitionistic logic.
+    fn classical_principle(&self, line: &ProofLine) -> Option<String> {
+        let curr_line_num = line.line_num?;
+        let just = line.justification.as_ref()?;
+        match just {
+            Justification::NotElim(_) | Justification::DoubleNegationElim(_) => {
+                Some("double negation elimination (going from ¬¬P to P)".to_owned())
+            }
+            Justification::IndirectProof(_) => Some(
+                "indirect proof (concluding P because ¬P leads to ⊥, which only gives ¬¬P)"
+                    .to_owned(),
+            ),
+            Justification::ExcludedMiddle(..) => {
+                Some("the law of the excluded middle (P ∨ ¬P)".to_owned())
+            }
+            Justification::DeMorgan(n) => match self.get_wff_at_line(curr_line_num, *n).ok()? {
+                Wff::Not(inner) if matches!(**inner, Wff::And(_)) => {
+                    Some("the De Morgan law from ¬(P ∧ Q) to ¬P ∨ ¬Q".to_owned())
+                }
+                _ => None,
+            },
+            Justification::ConversionOfQuantifiers(n) => {
+                match (self.get_wff_at_line(curr_line_num, *n).ok()?, line.sentence.as_ref()?) {
+                    (Wff::Not(inner), _) if matches!(**inner, Wff::Forall(..)) => Some(
+                        "the conversion from ¬∀x P to ∃x ¬P, which claims a witness that the proof does not provide"
+                            .to_owned(),
+                    ),
+                    // ¬∃x ¬P gives ∀x ¬¬P, but ∀x P needs double negation elimination
+                    (Wff::Not(inner), Wff::Forall(_, body)) => match &**inner {
+                        Wff::Exists(_, inner_body) if **body != Wff::Not(inner_body.clone()) => Some(
+                            "double negation elimination (going from ¬∃x ¬P to ∀x P)".to_owned(),
+                        ),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
 
+    /// This function does the actual work for [Proof::check_line].
+    ///
+    /// Precondition: the provided [ProofLine] has a justification.
+    fn check_justification(&self, line: &ProofLine) -> Result<(), Diagnostic> {
         let mut curr_line_num: usize = usize::MAX;
         if let Some(line_num) = line.line_num {
             curr_line_num = line_num;
@@ -743,43 +1042,198 @@ impl Proof {
                 if curr_wff == ref_wff {
                     Ok(())
                 } else {
-                    Err(format!(
-                        "Line {curr_line_num}: the \
+                    Err(Diagnostic::error(
+                        ErrorCode::RuleMisapplied,
+                        Some(curr_line_num),
+                        "the \
                                            proof rule Reit is used, but the sentence \
                                            in this line is not the same as the sentence \
                                            in the referenced line."
+                            .to_string(),
                     ))
                 }
             }
+            Justification::TautCon(ns) => {
+                let ref_wffs = ns
+                    .iter()
+                    .map(|n| self.get_wff_at_line(curr_line_num, *n))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match truth_table::tautological_consequence(&ref_wffs, curr_wff) {
+                    TruthTableResult::Consequence => Ok(()),
+                    TruthTableResult::Counterexample(valuation) => {
+                        let valuation = truth_table::describe_valuation(&valuation);
+                        Err(Diagnostic::error(
+                            ErrorCode::NotConsequence,
+                            Some(curr_line_num),
+                            if ns.is_empty() {
+                                format!(
+                                    "the rule Taut Con is used without referenced lines, but the \
+                                    sentence in this line is not a tautology: it is false \
+                                    {valuation}."
+                                )
+                            } else {
+                                format!(
+                                    "the rule Taut Con is used, but the sentence in this line is \
+                                    not a tautological consequence of the referenced lines: \
+                                    {valuation}, all referenced lines are true, but the sentence \
+                                    in this line is false."
+                                )
+                            },
+                        ))
+                    }
+                    TruthTableResult::TooManyAtoms(n) => Err(Diagnostic::error(
+                        ErrorCode::Undecided,
+                        Some(curr_line_num),
+                        format!(
+                            "the rule Taut Con is used, but the sentences involved contain {n} \
+                            different atomic sentences, which is too many to build a truth table \
+                            for (the maximum is {}). Try to split this step up.",
+                            truth_table::MAX_ATOMS
+                        ),
+                    )),
+                }
+            }
+            Justification::FoCon(ns) => {
+                let ref_wffs = ns
+                    .iter()
+                    .map(|n| self.get_wff_at_line(curr_line_num, *n))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if ref_wffs.iter().copied().chain([curr_wff]).any(contains_modal_operator) {
+                    return Err(Diagnostic::error(ErrorCode::ModalConsequence, Some(curr_line_num), "the rule FO Con is used, but it cannot deal with sentences that contain □ or ◇. Use Taut Con or the modal rules instead.".to_string()));
+                }
+                let arity_set: HashSet<(String, usize)> = ref_wffs
+                    .iter()
+                    .copied()
+                    .chain([curr_wff])
+                    .flat_map(|w| self.get_arity_set_of_wff(w))
+                    .collect();
+                let Some(signature) = fol::Signature::from_arity_set(&arity_set) else {
+                    // the inconsistent arities are reported by Proof::generate_arity_errors
+                    return Ok(());
+                };
+                match fol::fo_consequence(&ref_wffs, curr_wff, &signature) {
+                    FolResult::Consequence => Ok(()),
+                    FolResult::Counterexample(model) => Err(Diagnostic::error(
+                        ErrorCode::NotConsequence,
+                        Some(curr_line_num),
+                        if ns.is_empty() {
+                            format!(
+                                "the rule FO Con is used without referenced lines, but the \
+                                sentence in this line is not logically true: it is false in this \
+                                model: {model}."
+                            )
+                        } else {
+                            format!(
+                                "the rule FO Con is used, but the sentence in this line does not \
+                                follow from the referenced lines: in this model, all referenced \
+                                lines are true, but the sentence in this line is false: {model}."
+                            )
+                        },
+                    )),
+                    FolResult::Undecided => Err(Diagnostic::error(
+                        ErrorCode::Undecided,
+                        Some(curr_line_num),
+                        format!(
+                            "the rule FO Con is used, but it could not be decided whether the \
+                            sentence in this line follows from the referenced lines: no \
+                            counter-model with at most {} objects was found, but no proof either. \
+                            Try to split this step up.",
+                            fol::MAX_DOMAIN_SIZE
+                        ),
+                    )),
+                }
+            }
+            Justification::AnaCon(ns) => {
+                let ref_wffs = ns
+                    .iter()
+                    .map(|n| self.get_wff_at_line(curr_line_num, *n))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if ref_wffs.iter().copied().chain([curr_wff]).any(contains_modal_operator) {
+                    return Err(Diagnostic::error(ErrorCode::ModalConsequence, Some(curr_line_num), "the rule Ana Con is used, but it cannot deal with sentences that contain □ or ◇. Use Taut Con or the modal rules instead.".to_string()));
+                }
+                let arity_set: HashSet<(String, usize)> = ref_wffs
+                    .iter()
+                    .copied()
+                    .chain([curr_wff])
+                    .flat_map(|w| self.get_arity_set_of_wff(w))
+                    .collect();
+                let Some(signature) = fol::Signature::from_arity_set(&arity_set) else {
+                    // the inconsistent arities are reported by Proof::generate_arity_errors
+                    return Ok(());
+                };
+                match blocks::analytic_consequence(&ref_wffs, curr_wff, &signature) {
+                    AnalyticResult::Consequence => Ok(()),
+                    AnalyticResult::Counterexample(world) => Err(Diagnostic::error(
+                        ErrorCode::NotConsequence,
+                        Some(curr_line_num),
+                        if ns.is_empty() {
+                            format!(
+                                "the rule Ana Con is used without referenced lines, but the \
+                                sentence in this line is not analytically true: it is false in \
+                                this world: {world}."
+                            )
+                        } else {
+                            format!(
+                                "the rule Ana Con is used, but the sentence in this line does not \
+                                follow from the referenced lines: in this world, all referenced \
+                                lines are true, but the sentence in this line is false: {world}."
+                            )
+                        },
+                    )),
+                    AnalyticResult::Undecided => Err(Diagnostic::error(
+                        ErrorCode::Undecided,
+                        Some(curr_line_num),
+                        format!(
+                            "the rule Ana Con is used, but it could not be decided whether the \
+                            sentence in this line follows from the referenced lines: there is no \
+                            counterexample among the worlds with at most {} blocks, but there may \
+                            be one with more blocks, or the search took too long. Try to split \
+                            this step up.",
+                            blocks::MAX_BLOCKS
+                        ),
+                    )),
+                }
+            }
             Justification::AndIntro(ns) => {
                 if let Wff::And(conjs) = curr_wff {
                     if ns.len() != conjs.len() {
-                        return Err(format!(
-                            "Line {curr_line_num}: the rule ∧Intro is used, but the number of \
+                        return Err(Diagnostic::error(
+                            ErrorCode::CitationCount,
+                            Some(curr_line_num),
+                            format!(
+                                "the rule ∧Intro is used, but the number of \
                             conjuncts ({}) of the sentence in line {curr_line_num} is not equal \
                             to the number of referenced proof lines ({}).",
-                            conjs.len(),
-                            ns.len()
+                                conjs.len(),
+                                ns.len()
+                            ),
                         ));
                     }
                     for i in 0..ns.len() {
                         if &conjs[i] != self.get_wff_at_line(curr_line_num, ns[i])? {
-                            return Err(format!(
-                                "Line {curr_line_num}: the rule ∧Intro is used, but the {}\'th \
+                            return Err(Diagnostic::error(
+                                ErrorCode::RuleMisapplied,
+                                Some(curr_line_num),
+                                format!(
+                                    "the rule ∧Intro is used, but the {}\'th \
                                 conjunct of the sentence in that line is not the same as \
                                 the sentence found in line {} (the {}\'th \
                                 line referenced in the justification).",
-                                i + 1,
-                                ns[i],
-                                i + 1
+                                    i + 1,
+                                    ns[i],
+                                    i + 1
+                                ),
                             ));
                         }
                     }
                     Ok(())
                 } else {
-                    Err(format!(
-                        "Line {curr_line_num}: the justification ∧Intro is \
+                    Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        "the justification ∧Intro is \
                                 used, but the top-level connective of this line is not ∧."
+                            .to_string(),
                     ))
                 }
             }
@@ -789,18 +1243,26 @@ impl Proof {
                     if conjs.iter().any(|conj| conj == curr_wff) {
                         Ok(())
                     } else {
-                        Err(format!(
-                            "Line {curr_line_num}: the justification \
+                        Err(Diagnostic::error(
+                            ErrorCode::RuleMisapplied,
+                            Some(curr_line_num),
+                            format!(
+                                "the justification \
                             ∧Elim: {n} is used, but none of the \
                             conjuncts in line {n} is identical \
                             to the sentence found in line {curr_line_num}."
+                            ),
                         ))
                     }
                 } else {
-                    Err(format!(
-                        "Line {curr_line_num}: the justification \
+                    Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        format!(
+                            "the justification \
                         ∧Elim: {n} is used, but the top-level \
                         connective of line {n} is not a conjunction."
+                        ),
                     ))
                 }
             }
@@ -810,37 +1272,52 @@ impl Proof {
                     if disjs.iter().any(|disj| disj == ref_wff) {
                         Ok(())
                     } else {
-                        Err(format!(
-                            "Line {curr_line_num}: the justification \
+                        Err(Diagnostic::error(
+                            ErrorCode::RuleMisapplied,
+                            Some(curr_line_num),
+                            format!(
+                                "the justification \
                             ∨Intro: {n} is used, but none of the \
                             disjuncts in line {curr_line_num} is identical \
                             to the sentence found in line {n}."
+                            ),
                         ))
                     }
                 } else {
-                    Err(format!(
-                        "Line {curr_line_num}: the justification \
+                    Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        "the justification \
                         ∨Intro is used, but the top-level \
                         connective of this line is not a disjunction."
+                            .to_string(),
                     ))
                 }
             }
             Justification::OrElim(n, subproofs) => {
                 let Wff::Or(disjs) = self.get_wff_at_line(curr_line_num, *n)? else {
-                    return Err(format!(
-                        "Line {curr_line_num}: ∨Elim: {n}, ..... \
+                    return Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        format!(
+                            "∨Elim: {n}, ..... \
                         is used, but the top-level connective of \
                         the sentence at line {n} is not ∨."
+                        ),
                     ));
                 };
                 if disjs.len() != subproofs.len() {
-                    return Err(format!(
-                        "Line {curr_line_num}: the rule ∨Elim: {n}, ..... \
+                    return Err(Diagnostic::error(
+                        ErrorCode::CitationCount,
+                        Some(curr_line_num),
+                        format!(
+                            "the rule ∨Elim: {n}, ..... \
                             is used, but the number of disjuncts ({}) \
                             of the sentence in line {n} is not equal to \
                             the number of referenced subproofs ({}).",
-                        disjs.len(),
-                        subproofs.len()
+                            disjs.len(),
+                            subproofs.len()
+                        ),
                     ));
                 }
                 for (disj, subprf) in zip(disjs, subproofs) {
@@ -850,28 +1327,39 @@ impl Proof {
                         &s_end.sentence,
                         &s_begin.constant_between_square_brackets,
                     ) else {
-                        return Err(format!(
-                            "Line {curr_line_num}: when using ∨Elim, \
+                        return Err(Diagnostic::error(
+                            ErrorCode::SubproofKind,
+                            Some(curr_line_num),
+                            "when using ∨Elim, \
                             you cannot reference subproofs which \
                             introduce a boxed constant."
+                                .to_string(),
                         ));
                     };
                     if disj != s_begin_wff {
-                        return Err(format!(
-                            "Line {curr_line_num}: ∨Elim: {n}, ..... \
+                        return Err(Diagnostic::error(
+                            ErrorCode::RuleMisapplied,
+                            Some(curr_line_num),
+                            format!(
+                                "∨Elim: {n}, ..... \
                             is used, but the premise of one of the \
                             referenced subproofs does not match the \
                             corresponding disjunct of the sentence at line {n}. \
                             Note that the subproofs should be referenced in the \
                             order in which their corresponding premises \
                             appear as disjuncts in the sentence at line {n}."
+                            ),
                         ));
                     }
                     if s_end_wff != curr_wff {
-                        return Err(format!(
-                            "Line {curr_line_num}: ∨Elim \
+                        return Err(Diagnostic::error(
+                            ErrorCode::RuleMisapplied,
+                            Some(curr_line_num),
+                            format!(
+                                "∨Elim \
                             is used, but not all referenced subproofs end with \
                             the same sentence as the sentence in line {curr_line_num}."
+                            ),
                         ));
                     }
                 }
@@ -879,10 +1367,13 @@ impl Proof {
             }
             Justification::ImpliesIntro((n, m)) => {
                 let Wff::Implies(a, b) = curr_wff else {
-                    return Err(format!(
-                        "Line {curr_line_num}: →Intro is used, but \
+                    return Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        "→Intro is used, but \
                             the top-level connective of the sentence at this line \
                             is not an implication."
+                            .to_string(),
                     ));
                 };
                 let (s_begin, s_end) = self.get_subproof_at_lines(curr_line_num, (*n, *m))?;
@@ -890,31 +1381,46 @@ impl Proof {
                     (&s_begin.sentence, &s_end.sentence, &s_begin.constant_between_square_brackets)
                 {
                     if **a != *s_begin_wff && **b == *s_end_wff {
-                        Err(format!(
-                            "Line {curr_line_num}: →Intro is used, but \
+                        Err(Diagnostic::error(
+                            ErrorCode::RuleMisapplied,
+                            Some(curr_line_num),
+                            format!(
+                                "→Intro is used, but \
                                 the premise of the referenced subproof does not match the \
                                 antecedent of the implication found in line {curr_line_num}."
+                            ),
                         ))
                     } else if **a == *s_begin_wff && **b != *s_end_wff {
-                        Err(format!(
-                            "Line {curr_line_num}: →Intro is used, but \
+                        Err(Diagnostic::error(
+                            ErrorCode::RuleMisapplied,
+                            Some(curr_line_num),
+                            format!(
+                                "→Intro is used, but \
                                 the last sentence of the referenced subproof does not match the \
                                 consequent of the implication found in line {curr_line_num}."
+                            ),
                         ))
                     } else if **a != *s_begin_wff && **b != *s_end_wff {
-                        Err(format!(
-                            "Line {curr_line_num}: →Intro is used, but \
+                        Err(Diagnostic::error(
+                            ErrorCode::RuleMisapplied,
+                            Some(curr_line_num),
+                            format!(
+                                "→Intro is used, but \
                                 the premise and last sentence of the referenced subproof \
                                 do not match the antecedent and the consequent, respectively, \
                                 of the implication found in line {curr_line_num}."
+                            ),
                         ))
                     } else {
                         Ok(())
                     }
                 } else {
-                    Err(format!(
-                        "Line {curr_line_num}: when using →Intro, you \
+                    Err(Diagnostic::error(
+                        ErrorCode::SubproofKind,
+                        Some(curr_line_num),
+                        "when using →Intro, you \
                         cannot reference a subproof that introduces a boxed constant."
+                            .to_string(),
                     ))
                 }
             }
@@ -924,16 +1430,23 @@ impl Proof {
                     if *wff_m == **wff1 && **wff2 == *curr_wff {
                         Ok(())
                     } else {
-                        Err(format!(
-                            "Line {curr_line_num}: the rule \
+                        Err(Diagnostic::error(
+                            ErrorCode::RuleMisapplied,
+                            Some(curr_line_num),
+                            "the rule \
                                                →Elim is wrongly used."
+                                .to_string(),
                         ))
                     }
                 } else {
-                    Err(format!(
-                        "Line {curr_line_num}: the rule \
+                    Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        format!(
+                            "the rule \
                                            →Elim: {n}, {m} is used, but the top-level \
                                            connective of line {n} is not an implication."
+                        ),
                     ))
                 }
             }
@@ -965,13 +1478,13 @@ impl Proof {
                         {
                             Ok(())
                         } else {
-                            Err(format!("Line {curr_line_num}: when using ↔Intro to infer P↔Q, you must first cite the subproof that proves P→Q, and then the subproof that proves Q→P."))
+                            Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), "when using ↔Intro to infer P↔Q, you must first cite the subproof that proves P→Q, and then the subproof that proves Q→P.".to_string()))
                         }
                     } else {
-                        Err(format!("Line {curr_line_num}: when using ↔Intro, you cannot reference a subproof that introduces a boxed constant."))
+                        Err(Diagnostic::error(ErrorCode::SubproofKind, Some(curr_line_num), "when using ↔Intro, you cannot reference a subproof that introduces a boxed constant.".to_string()))
                     }
                 } else {
-                    Err(format!("Line {curr_line_num}: ↔Intro is used, but the top-level connective of this sentence is not a bi-implication."))
+                    Err(Diagnostic::error(ErrorCode::WrongConnective, Some(curr_line_num), "↔Intro is used, but the top-level connective of this sentence is not a bi-implication.".to_string()))
                 }
             }
             Justification::BicondElim(n, m) => {
@@ -982,10 +1495,14 @@ impl Proof {
                     {
                         Ok(())
                     } else {
-                        Err(format!("Line {curr_line_num}: the rule ↔Elim is wrongly used."))
+                        Err(Diagnostic::error(
+                            ErrorCode::RuleMisapplied,
+                            Some(curr_line_num),
+                            "the rule ↔Elim is wrongly used.".to_string(),
+                        ))
                     }
                 } else {
-                    Err(format!("Line {curr_line_num}: the rule ↔Elim: {n}, {m} is used, but the top-level connective of line {n} is not a bi-implication."))
+                    Err(Diagnostic::error(ErrorCode::WrongConnective, Some(curr_line_num), format!("the rule ↔Elim: {n}, {m} is used, but the top-level connective of line {n} is not a bi-implication.")))
                 }
             }
             Justification::NotIntro((n, m)) => {
@@ -998,32 +1515,44 @@ impl Proof {
                             if *s_end_wff == Wff::Bottom {
                                 Ok(())
                             } else {
-                                Err(format!(
-                                    "Line {curr_line_num}: ¬Intro is used, \
+                                Err(Diagnostic::error(
+                                    ErrorCode::WrongConnective,
+                                    Some(curr_line_num),
+                                    "¬Intro is used, \
                                     but the last sentence in the referenced \
                                     subproof is not ⊥."
+                                        .to_string(),
                                 ))
                             }
                         } else {
-                            Err(format!(
-                                "Line {curr_line_num}: ¬Intro is \
+                            Err(Diagnostic::error(
+                                ErrorCode::RuleMisapplied,
+                                Some(curr_line_num),
+                                "¬Intro is \
                                             used, but the negation of the premise \
                                             of the referenced subproof does \
                                             not match this line."
+                                    .to_string(),
                             ))
                         }
                     } else {
-                        Err(format!(
-                            "Line {curr_line_num}: ¬Intro is \
+                        Err(Diagnostic::error(
+                            ErrorCode::SubproofKind,
+                            Some(curr_line_num),
+                            "¬Intro is \
                             used, but the referenced subproof is not \
                             of the proper form. You cannot use ¬Intro \
                             on a subproof that introduces a boxed constant."
+                                .to_string(),
                         ))
                     }
                 } else {
-                    Err(format!(
-                        "Line {curr_line_num}: ¬Intro is used, \
+                    Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        "¬Intro is used, \
                         but the top-level connective of the sentence in this line is not ¬."
+                            .to_string(),
                     ))
                 }
             }
@@ -1038,11 +1567,15 @@ impl Proof {
                 if let Wff::Not(negd_wff) = curr_wff {
                     if let Wff::Not(negd_negd_wff) = &**negd_wff {
                         if *self.get_wff_at_line(curr_line_num, *n)? == **negd_negd_wff {
-                            return Err(format!("Line {curr_line_num}: ¬Elim can only be used to go from ¬¬P to P, not the other way around"));
+                            return Err(Diagnostic::error(ErrorCode::WrongDirection, Some(curr_line_num), "¬Elim can only be used to go from ¬¬P to P, not the other way around".to_string()));
                         }
                     }
                 }
-                Err(format!("Line {curr_line_num}: ¬Elim is used improperly"))
+                Err(Diagnostic::error(
+                    ErrorCode::RuleMisapplied,
+                    Some(curr_line_num),
+                    "¬Elim is used improperly".to_string(),
+                ))
             }
             Justification::BottomIntro(n, m) => {
                 let wff1 = self.get_wff_at_line(curr_line_num, *n)?;
@@ -1052,19 +1585,27 @@ impl Proof {
                         return Ok(());
                     }
                 }
-                Err(format!(
-                    "Line {curr_line_num}: ⊥Intro: {n}, {m} is used, \
+                Err(Diagnostic::error(
+                    ErrorCode::RuleMisapplied,
+                    Some(curr_line_num),
+                    format!(
+                        "⊥Intro: {n}, {m} is used, \
                     but the sentence at line {m} is not the negation \
                     of the sentence at line {n}"
+                    ),
                 ))
             }
             Justification::BottomElim(n) => {
                 if let Wff::Bottom = self.get_wff_at_line(curr_line_num, *n)? {
                     Ok(())
                 } else {
-                    Err(format!(
-                        "Line {curr_line_num}: ⊥Elim: {n} is \
+                    Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        format!(
+                            "⊥Elim: {n} is \
                         used, but the sentence at line {n} is not ⊥."
+                        ),
                     ))
                 }
             }
@@ -1074,14 +1615,22 @@ impl Proof {
                         return Ok(());
                     }
                 }
-                Err(format!("Line {curr_line_num}: =Intro is wrongly used"))
+                Err(Diagnostic::error(
+                    ErrorCode::RuleMisapplied,
+                    Some(curr_line_num),
+                    "=Intro is wrongly used".to_string(),
+                ))
             }
             Justification::EqualsElim(n, m) => {
                 let Wff::Equals(subst_old, subst_new) = self.get_wff_at_line(curr_line_num, *m)?
                 else {
-                    return Err(format!(
-                        "Line {curr_line_num}: the rule =Elim:{n},{m} \
+                    return Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        format!(
+                            "the rule =Elim:{n},{m} \
                         is used, but line {m} is not of the form (term1) = (term2)"
+                        ),
                     ));
                 };
 
@@ -1092,35 +1641,48 @@ impl Proof {
                 ) {
                     Ok(())
                 } else {
-                    Err(format!(
-                        "Line {curr_line_num}: the rule =Elim:{n},{m} \
+                    Err(Diagnostic::error(
+                        ErrorCode::BadSubstitution,
+                        Some(curr_line_num),
+                        format!(
+                            "the rule =Elim:{n},{m} \
                             is used, but is is impossible to obtain line {curr_line_num} \
                             from line {n} by changing one or more occurrences of {} to {}",
-                        formatter::format_term(subst_old),
-                        formatter::format_term(subst_new),
+                            formatter::format_term(subst_old),
+                            formatter::format_term(subst_new),
+                        ),
                     ))
                 }
             }
             Justification::ForallIntro((sb, se)) => {
                 let Wff::Forall(var, forall_curr_wff) = curr_wff else {
-                    return Err(format!(
-                        "Line {curr_line_num}: the rule ∀Intro is used, \
+                    return Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        "the rule ∀Intro is used, \
                     but the sentence at this line is not universally quantified at the top-level"
+                            .to_string(),
                     ));
                 };
                 let (s_begin, s_end) = self.get_subproof_at_lines(curr_line_num, (*sb, *se))?;
                 let Some(boxed_const @ Term::Atomic(bc)) =
                     &s_begin.constant_between_square_brackets
                 else {
-                    return Err(format!(
-                        "Line {curr_line_num}: the rule ∀Intro is used, but the \
+                    return Err(Diagnostic::error(
+                        ErrorCode::SubproofKind,
+                        Some(curr_line_num),
+                        "the rule ∀Intro is used, but the \
                         referenced subproof does not introduce a boxed constant"
+                            .to_string(),
                     ));
                 };
                 if s_begin.sentence.is_some() {
-                    return Err(format!(
-                            "Line {curr_line_num}: when using ∀Intro, the premise of the referenced subproof \
+                    return Err(Diagnostic::error(
+                        ErrorCode::SubproofKind,
+                        Some(curr_line_num),
+                        "when using ∀Intro, the premise of the referenced subproof \
                             should consist of solely a boxed constant, without a sentence"
+                            .to_string(),
                     ));
                 }
                 if apply_trivial_substitution_everywhere_to_wff(
@@ -1128,17 +1690,21 @@ impl Proof {
                     (&Term::Atomic(var.to_string()), boxed_const),
                 ) != *s_end.sentence.as_ref().unwrap()
                 {
-                    return Err(format!("Line {curr_line_num}: the rule ∀Intro:{sb}-{se} is used, but if all occurrences of {var} in the quantified part of line {curr_line_num} are replaced by {bc}, one does not obtain the sentence in line {se}"));
+                    return Err(Diagnostic::error(ErrorCode::BadSubstitution, Some(curr_line_num), format!("the rule ∀Intro:{sb}-{se} is used, but if all occurrences of {var} in the quantified part of line {curr_line_num} are replaced by {bc}, one does not obtain the sentence in line {se}")));
                 }
 
                 Ok(())
             }
             Justification::ForallElim(n) => {
                 let Wff::Forall(var, ref_wff) = self.get_wff_at_line(curr_line_num, *n)? else {
-                    return Err(format!(
-                        "Line {curr_line_num}: the justification \
+                    return Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        format!(
+                            "the justification \
                         ∀Elim:{n} is used, but the sentence at line {n} is not a \
                         universally quantified sentence at the top level"
+                        ),
                     ));
                 };
                 if let Some((term1, term2)) =
@@ -1151,13 +1717,17 @@ impl Proof {
                         return if self.is_closed_term(&term2) {
                             Ok(())
                         } else {
-                            Err(format!(
-                                "Line {curr_line_num}: the rule ∀Elim:{n} is used, \
+                            Err(Diagnostic::error(
+                                ErrorCode::NotClosedTerm,
+                                Some(curr_line_num),
+                                format!(
+                                    "the rule ∀Elim:{n} is used, \
                                  but {} is not a closed term (so you cannot substitute {}
                                  for all occurences of {var} in line {})",
-                                formatter::format_term(&term2),
-                                formatter::format_term(&term2),
-                                *n
+                                    formatter::format_term(&term2),
+                                    formatter::format_term(&term2),
+                                    *n
+                                ),
                             ))
                         };
                     }
@@ -1165,17 +1735,25 @@ impl Proof {
                 if &**ref_wff == curr_wff {
                     return Ok(());
                 }
-                Err(format!(
-                    "Line {curr_line_num}: the rule ∀Elim:{n} is used, but there is no \
+                Err(Diagnostic::error(
+                    ErrorCode::BadSubstitution,
+                    Some(curr_line_num),
+                    format!(
+                        "the rule ∀Elim:{n} is used, but there is no \
                     appropriate substitution between line {n} and line {curr_line_num}"
+                    ),
                 ))
             }
             Justification::ExistsIntro(n) => {
                 let Wff::Exists(var, exists_curr_wff) = curr_wff else {
-                    return Err(format!(
-                        "Line {curr_line_num}: the justification \
+                    return Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        format!(
+                            "the justification \
                         ∃Intro:{n} is used, but the sentence at line {curr_line_num} is not an \
                         existentially quantified sentence at the top level"
+                        ),
                     ));
                 };
                 let ref_wff = self.get_wff_at_line(curr_line_num, *n)?;
@@ -1191,11 +1769,15 @@ impl Proof {
                         return if self.is_closed_term(&term2) {
                             Ok(())
                         } else {
-                            Err(format!(
-                                "Line {curr_line_num}: the rule ∃Intro:{n} is \
+                            Err(Diagnostic::error(
+                                ErrorCode::NotClosedTerm,
+                                Some(curr_line_num),
+                                format!(
+                                    "the rule ∃Intro:{n} is \
                                 used, but {} in line {} is not a closed term",
-                                formatter::format_term(&term2),
-                                *n
+                                    formatter::format_term(&term2),
+                                    *n
+                                ),
                             ))
                         };
                     }
@@ -1203,30 +1785,38 @@ impl Proof {
                 if **exists_curr_wff == *ref_wff {
                     return Ok(());
                 }
-                Err(format!(
-                    "Line {curr_line_num}: the rule ∃Intro:{n} is used, but there is no \
+                Err(Diagnostic::error(
+                    ErrorCode::BadSubstitution,
+                    Some(curr_line_num),
+                    format!(
+                        "the rule ∃Intro:{n} is used, but there is no \
                     appropriate substitution between line {n} and line {curr_line_num}"
+                    ),
                 ))
             }
             Justification::ExistsElim(n, (sb, se)) => {
                 let ref_wff = self.get_wff_at_line(curr_line_num, *n)?;
                 let (s_begin, s_end) = self.get_subproof_at_lines(curr_line_num, (*sb, *se))?;
                 let Wff::Exists(var, exists_ref_wff) = ref_wff else {
-                    return Err(format!(
-                        "Line {curr_line_num}: the rule ∃Elim:{n},{sb}-{se} \
+                    return Err(Diagnostic::error(
+                        ErrorCode::WrongConnective,
+                        Some(curr_line_num),
+                        format!(
+                            "the rule ∃Elim:{n},{sb}-{se} \
                     is used, but the sentence at line {n} ({}) is not an existentially \
                     quantified sentence at the top-level",
-                        formatter::format_wff(ref_wff)
+                            formatter::format_wff(ref_wff)
+                        ),
                     ));
                 };
 
                 let Some(bc_term @ Term::Atomic(bc)) = &s_begin.constant_between_square_brackets
                 else {
-                    return Err(format!("Line {curr_line_num}: the rule ∃Elim:{n},{sb}-{se} is used, but the referenced subproof does not introduce a boxed constant in line {sb}."));
+                    return Err(Diagnostic::error(ErrorCode::SubproofKind, Some(curr_line_num), format!("the rule ∃Elim:{n},{sb}-{se} is used, but the referenced subproof does not introduce a boxed constant in line {sb}.")));
                 };
 
                 if s_begin.sentence.is_none() {
-                    return Err(format!("Line {curr_line_num}: the rule ∃Elim:{n},{sb}-{se} is used, but line {sb} contains only a boxed constant; when using ∃Elim, it should contain both a boxed constant and a sentence"));
+                    return Err(Diagnostic::error(ErrorCode::SubproofKind, Some(curr_line_num), format!("the rule ∃Elim:{n},{sb}-{se} is used, but line {sb} contains only a boxed constant; when using ∃Elim, it should contain both a boxed constant and a sentence")));
                 }
                 if apply_trivial_substitution_everywhere_to_wff(
                     exists_ref_wff,
@@ -1236,30 +1826,245 @@ impl Proof {
                     if s_end.sentence.as_ref().unwrap() == curr_wff {
                         Ok(())
                     } else {
-                        Err(format!(
-                            "Line {curr_line_num}: the rule ∃Elim:{n},{sb}-{se} \
+                        Err(Diagnostic::error(
+                            ErrorCode::RuleMisapplied,
+                            Some(curr_line_num),
+                            format!(
+                                "the rule ∃Elim:{n},{sb}-{se} \
                         is used, but the sentence in line {se} ({}) is not the same as \
                         the sentence in line {curr_line_num} ({})",
-                            formatter::format_wff(s_end.sentence.as_ref().unwrap()),
-                            formatter::format_wff(curr_wff),
+                                formatter::format_wff(s_end.sentence.as_ref().unwrap()),
+                                formatter::format_wff(curr_wff),
+                            ),
                         ))
                     }
                 } else {
-                    Err(format!(
-                        "Line {curr_line_num}: the rule ∃Elim:{n},{sb}-{se} \
+                    Err(Diagnostic::error(
+                        ErrorCode::BadSubstitution,
+                        Some(curr_line_num),
+                        format!(
+                            "the rule ∃Elim:{n},{sb}-{se} \
                         is used, but if one substitutes {bc} for all free \
                         occurences of {var} in the quantified part of the sentence \
                         in line {n} ({}), one obtains {}, but this is not equal to the \
                         sentence found in line {sb} ({})",
-                        formatter::format_wff(ref_wff),
-                        formatter::format_wff(&apply_trivial_substitution_everywhere_to_wff(
-                            exists_ref_wff,
-                            (&Term::Atomic(var.to_string()), bc_term)
-                        )),
-                        formatter::format_wff(s_begin.sentence.as_ref().unwrap())
+                            formatter::format_wff(ref_wff),
+                            formatter::format_wff(&apply_trivial_substitution_everywhere_to_wff(
+                                exists_ref_wff,
+                                (&Term::Atomic(var.to_string()), bc_term)
+                            )),
+                            formatter::format_wff(s_begin.sentence.as_ref().unwrap())
+                        ),
                     ))
                 }
             }
+            Justification::DisjunctiveSyllogism(n, m) => {
+                let (wff_n, wff_m) = (
+                    self.get_wff_at_line(curr_line_num, *n)?,
+                    self.get_wff_at_line(curr_line_num, *m)?,
+                );
+                let mut problem = None;
+                // the disjunction may be cited first or second
+                for ((disjunction, k), (negation, l)) in
+                    [((wff_n, n), (wff_m, m)), ((wff_m, m), (wff_n, n))]
+                {
+                    let Wff::Or(disjuncts) = disjunction else {
+                        continue;
+                    };
+                    let Some(i) =
+                        disjuncts.iter().position(|d| *negation == Wff::Not(Box::new(d.clone())))
+                    else {
+                        problem.get_or_insert(format!("the sentence in line {l} is not the negation of one of the disjuncts of line {k}."));
+                        continue;
+                    };
+                    let mut rest = disjuncts.clone();
+                    rest.remove(i);
+                    let expected = if rest.len() == 1 {
+                        rest.pop().unwrap()
+                    } else {
+                        Wff::Or(rest)
+                    };
+                    if *curr_wff == expected {
+                        return Ok(());
+                    }
+                    problem = Some(format!(
+                        "the sentence in this line should be {}, which is what remains of line {k} without the disjunct that is negated in line {l}.",
+                        formatter::format_wff(&expected)
+                    ));
+                }
+                match problem {
+                    None => Err(Diagnostic::error(ErrorCode::WrongConnective, Some(curr_line_num), format!("the rule DS: {n}, {m} is used, but neither of the referenced lines is a disjunction."))),
+                    Some(problem) => Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule DS: {n}, {m} is used, but {problem}"))),
+                }
+            }
+            Justification::ModusTollens(n, m) => {
+                let (wff_n, wff_m) = (
+                    self.get_wff_at_line(curr_line_num, *n)?,
+                    self.get_wff_at_line(curr_line_num, *m)?,
+                );
+                let mut problem = None;
+                // the conditional may be cited first or second
+                for ((conditional, k), (negation, l)) in
+                    [((wff_n, n), (wff_m, m)), ((wff_m, m), (wff_n, n))]
+                {
+                    let Wff::Implies(antecedent, consequent) = conditional else {
+                        continue;
+                    };
+                    if *negation != Wff::Not(consequent.clone()) {
+                        problem.get_or_insert(format!("the sentence in line {l} is not the negation of the consequent of line {k}."));
+                        continue;
+                    }
+                    if *curr_wff == Wff::Not(antecedent.clone()) {
+                        return Ok(());
+                    }
+                    problem = Some(format!("the sentence in this line is not the negation of the antecedent of line {k}."));
+                }
+                match problem {
+                    None => Err(Diagnostic::error(ErrorCode::WrongConnective, Some(curr_line_num), format!("the rule MT: {n}, {m} is used, but neither of the referenced lines is a conditional."))),
+                    Some(problem) => Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule MT: {n}, {m} is used, but {problem}"))),
+                }
+            }
+            Justification::DoubleNegationElim(n) => {
+                let ref_wff = self.get_wff_at_line(curr_line_num, *n)?;
+                if *ref_wff == Wff::Not(Box::new(Wff::Not(Box::new(curr_wff.clone())))) {
+                    Ok(())
+                } else if *curr_wff == Wff::Not(Box::new(Wff::Not(Box::new(ref_wff.clone())))) {
+                    Err(Diagnostic::error(
+                        ErrorCode::WrongDirection,
+                        Some(curr_line_num),
+                        "DNE can only be used to go from ¬¬P to P, not the other way around"
+                            .to_string(),
+                    ))
+                } else {
+                    Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule DNE: {n} is used, but the sentence in line {n} is not the double negation of the sentence in this line.")))
+                }
+            }
+            Justification::ExcludedMiddle(sub1, sub2) => {
+                let mut subproofs = vec![];
+                for sub in [sub1, sub2] {
+                    let (s_begin, s_end) = self.get_subproof_at_lines(curr_line_num, *sub)?;
+                    let (Some(assumption), Some(conclusion)) = (&s_begin.sentence, &s_end.sentence)
+                    else {
+                        return Err(Diagnostic::error(ErrorCode::SubproofKind, Some(curr_line_num), format!("the rule LEM is used, but the referenced subproof {}-{} is not of the proper form. You cannot use LEM on a subproof that introduces a boxed constant.", sub.0, sub.1)));
+                    };
+                    if conclusion != curr_wff {
+                        return Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule LEM is used, but the last sentence of the referenced subproof {}-{} is not the sentence in this line.", sub.0, sub.1)));
+                    }
+                    subproofs.push(assumption);
+                }
+                let (a, b) = (subproofs[0], subproofs[1]);
+                if *a == Wff::Not(Box::new(b.clone())) || *b == Wff::Not(Box::new(a.clone())) {
+                    Ok(())
+                } else {
+                    Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), "the rule LEM is used, but the assumption of one referenced subproof is not the negation of the assumption of the other.".to_string()))
+                }
+            }
+            Justification::IndirectProof((n, m)) => {
+                let (s_begin, s_end) = self.get_subproof_at_lines(curr_line_num, (*n, *m))?;
+                let (Some(assumption), Some(conclusion)) = (&s_begin.sentence, &s_end.sentence)
+                else {
+                    return Err(Diagnostic::error(ErrorCode::SubproofKind, Some(curr_line_num), "the rule IP is used, but the referenced subproof is not of the proper form. You cannot use IP on a subproof that introduces a boxed constant.".to_string()));
+                };
+                if *assumption != Wff::Not(Box::new(curr_wff.clone())) {
+                    Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), "the rule IP is used, but the premise of the referenced subproof is not the negation of the sentence in this line.".to_string()))
+                } else if *conclusion != Wff::Bottom {
+                    Err(Diagnostic::error(ErrorCode::WrongConnective, Some(curr_line_num), "the rule IP is used, but the last sentence in the referenced subproof is not ⊥.".to_string()))
+                } else {
+                    Ok(())
+                }
+            }
+            Justification::DeMorgan(n) => {
+                let ref_wff = self.get_wff_at_line(curr_line_num, *n)?;
+                if de_morgan(ref_wff).as_ref() == Some(curr_wff) {
+                    Ok(())
+                } else {
+                    Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule DeM: {n} is used, but the sentence in this line is not the result of applying one of De Morgan's laws to the sentence in line {n} ({}).", formatter::format_wff(ref_wff))))
+                }
+            }
+            Justification::ConversionOfQuantifiers(n) => {
+                let ref_wff = self.get_wff_at_line(curr_line_num, *n)?;
+                if quantifier_conversions(ref_wff).contains(curr_wff) {
+                    Ok(())
+                } else {
+                    Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule CQ: {n} is used, but the sentence in this line is not the result of moving a negation through the outermost quantifier of the sentence in line {n} ({}).", formatter::format_wff(ref_wff))))
+                }
+            }
+            Justification::BoxIntro((sb, se)) => {
+                if !self.strict_subproofs.contains(&(*sb, *se)) {
+                    return Err(Diagnostic::error(ErrorCode::SubproofKind, Some(curr_line_num), format!("the rule □ Intro: {sb}-{se} is used, but {sb}-{se} is not a strict subproof (a subproof that starts with |□----).")).with_related_lines(vec![*sb, *se]));
+                }
+                if !self.scope[curr_line_num].1.contains(&(*sb, *se)) {
+                    return Err(Diagnostic::error(ErrorCode::SubproofNotInScope, Some(curr_line_num), format!("the referenced subproof {sb}-{se} is not in the scope of line {curr_line_num}.")).with_related_lines(vec![*sb, *se]));
+                }
+                let conclusion = self.get_proofline_at_line_unsafe(*se).sentence.as_ref().unwrap();
+                if *curr_wff == Wff::Necessarily(Box::new(conclusion.clone())) {
+                    Ok(())
+                } else {
+                    Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule □ Intro: {sb}-{se} is used, but the sentence in this line should be □ applied to the last sentence of the strict subproof, so {}.", formatter::format_wff(&Wff::Necessarily(Box::new(conclusion.clone()))))))
+                }
+            }
+            Justification::BoxElim(n) => {
+                // in K, □A only gives A inside a strict subproof right inside the line with □A
+                if self.rule_set.modal_system() == ModalSystem::K
+                    && self.can_reference(curr_line_num, *n)
+                {
+                    return Err(Diagnostic::error(ErrorCode::StrictSubproof, Some(curr_line_num), format!("the rule □ Elim: {n} is used, but in K, □ Elim can only bring a sentence into a strict subproof from right outside it, and line {n} is not right outside a strict subproof that this line is in.")));
+                }
+                let ref_wff = self.get_wff_to_import(curr_line_num, *n)?;
+                match ref_wff {
+                    Wff::Necessarily(inner) if **inner == *curr_wff => Ok(()),
+                    Wff::Necessarily(inner) => Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule □ Elim: {n} is used, but the sentence in this line should be {}.", formatter::format_wff(inner)))),
+                    _ => Err(Diagnostic::error(ErrorCode::WrongConnective, Some(curr_line_num), format!("the rule □ Elim: {n} is used, but the sentence in line {n} does not start with □."))),
+                }
+            }
+            Justification::DiamondIntro(n) => {
+                let ref_wff = self.get_wff_at_line(curr_line_num, *n)?;
+                let Wff::Possibly(inner) = curr_wff else {
+                    return Err(Diagnostic::error(ErrorCode::WrongConnective, Some(curr_line_num), format!("the rule ◇ Intro: {n} is used, but the sentence in this line does not start with ◇.")));
+                };
+                let dual = Wff::Not(Box::new(Wff::Necessarily(Box::new(Wff::Not(inner.clone())))));
+                if *ref_wff == dual {
+                    Ok(())
+                } else if ref_wff == &**inner {
+                    if self.rule_set.modal_system() >= ModalSystem::T {
+                        Ok(())
+                    } else {
+                        Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule ◇ Intro: {n} is used to go from A to ◇A, but that is only allowed in T, S4 and S5; in K, ◇ Intro needs {}.", formatter::format_wff(&dual))))
+                    }
+                } else {
+                    Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule ◇ Intro: {n} is used, but the sentence in line {n} should be {}.", formatter::format_wff(&dual))))
+                }
+            }
+            Justification::DiamondElim(n) => {
+                let ref_wff = self.get_wff_at_line(curr_line_num, *n)?;
+                let Wff::Possibly(inner) = ref_wff else {
+                    return Err(Diagnostic::error(ErrorCode::WrongConnective, Some(curr_line_num), format!("the rule ◇ Elim: {n} is used, but the sentence in line {n} does not start with ◇.")));
+                };
+                let dual = Wff::Not(Box::new(Wff::Necessarily(Box::new(Wff::Not(inner.clone())))));
+                if *curr_wff == dual {
+                    Ok(())
+                } else {
+                    Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule ◇ Elim: {n} is used, but the sentence in this line should be {}.", formatter::format_wff(&dual))))
+                }
+            }
+            Justification::BoxReit(n) => {
+                let modal_system = self.rule_set.modal_system();
+                if modal_system < ModalSystem::S4 {
+                    return Err(Diagnostic::error(ErrorCode::StrictSubproof, Some(curr_line_num), format!("the rule R□: {n} is used, but R□ is not available in {modal_system:?}; use □ Elim instead.")));
+                }
+                if !self.scope[curr_line_num].2.contains(n) {
+                    if self.can_reference(curr_line_num, *n) {
+                        return Err(Diagnostic::error(ErrorCode::StrictSubproof, Some(curr_line_num), format!("the rule R□: {n} is used, but R□ can only bring a sentence into a strict subproof from right outside it; use Reit instead.")));
+                    }
+                    self.get_wff_at_line(curr_line_num, *n)?;
+                }
+                let ref_wff = self.get_wff_to_import(curr_line_num, *n)?;
+                if ref_wff == curr_wff {
+                    Ok(())
+                } else {
+                    Err(Diagnostic::error(ErrorCode::RuleMisapplied, Some(curr_line_num), format!("the rule R□: {n} is used, but the sentence in this line is not the same as the sentence in line {n}.")))
+                }
+            }
         }
     }
 
@@ -1411,6 +2216,80 @@ fn find_possible_trivial_substitution_wff(wff1: &Wff, wff2: &Wff) -> Option<(Ter
     zip(terms1, terms2).find_map(|(t1, t2)| find_possible_trivial_substitution_term(t1, t2))
 }
 
+/// Returns whether a [Wff] contains □ or ◇ anywhere.
+pub(crate) fn contains_modal_operator(wff: &Wff) -> bool {
+    match wff {
+        Wff::Necessarily(_) | Wff::Possibly(_) => true,
+        Wff::Bottom | Wff::Atomic(_) | Wff::PredApp(..) | Wff::Equals(..) => false,
+        Wff::And(li) | Wff::Or(li) => li.iter().any(contains_modal_operator),
+        Wff::Implies(w1, w2) | Wff::Bicond(w1, w2) => {
+            contains_modal_operator(w1) || contains_modal_operator(w2)
+        }
+        Wff::Not(w) | Wff::Forall(_, w) | Wff::Exists(_, w) => contains_modal_operator(w),
+    }
+}
+
+/// Applies one of De Morgan's laws to the outermost connectives of a [Wff]: ¬(A∧B) becomes ¬A∨¬B,
+/// ¬(A∨B) becomes ¬A∧¬B, and the other way around. This also works for more than two conjuncts
+/// or disjuncts. Returns [None] if no law applies.
+fn de_morgan(wff: &Wff) -> Option<Wff> {
+    fn negate_all(li: &[Wff]) -> Vec<Wff> {
+        li.iter().map(|w| Wff::Not(Box::new(w.clone()))).collect()
+    }
+    fn unnegate_all(li: &[Wff]) -> Option<Vec<Wff>> {
+        li.iter()
+            .map(|w| match w {
+                Wff::Not(inner) => Some((**inner).clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    match wff {
+        Wff::Not(inner) => match &**inner {
+            Wff::And(li) => Some(Wff::Or(negate_all(li))),
+            Wff::Or(li) => Some(Wff::And(negate_all(li))),
+            _ => None,
+        },
+        Wff::Or(li) => Some(Wff::Not(Box::new(Wff::And(unnegate_all(li)?)))),
+        Wff::And(li) => Some(Wff::Not(Box::new(Wff::Or(unnegate_all(li)?)))),
+        _ => None,
+    }
+}
+
+/// Returns all [Wff]s that follow from a [Wff] by moving a negation through its outermost
+/// quantifier: ∀xA and ¬∃x¬A are interchangeable, as are ∃xA and ¬∀x¬A, and double negations in
+/// front of A are removed (so ∀x¬A and ¬∃xA are interchangeable too).
+fn quantifier_conversions(wff: &Wff) -> Vec<Wff> {
+    fn not(wff: Wff) -> Wff {
+        Wff::Not(Box::new(wff))
+    }
+    let (negated, quantified) = match wff {
+        Wff::Not(inner) => (true, &**inner),
+        _ => (false, wff),
+    };
+    // the quantifier always turns into the other one
+    let (converted, var, body): (fn(String, Box<Wff>) -> Wff, _, _) = match quantified {
+        Wff::Forall(x, body) => (Wff::Exists, x, body),
+        Wff::Exists(x, body) => (Wff::Forall, x, body),
+        _ => return vec![],
+    };
+    let mut bodies = vec![not((**body).clone())];
+    if let Wff::Not(inner) = &**body {
+        bodies.push((**inner).clone());
+    }
+    bodies
+        .into_iter()
+        .map(|body| {
+            let converted = converted(var.clone(), Box::new(body));
+            if negated {
+                converted
+            } else {
+                not(converted)
+            }
+        })
+        .collect()
+}
+
 /// Consider the abstract syntax trees of two well-formed formulas `wff1` and `wff2`. Now remove
 /// from that tree all the nodes which are a [Term]. This function returns `true` if the syntax
 /// trees are then equal (in which case we call the original wffs s-equivalent), `false` otherwise.
@@ -1443,6 +2322,11 @@ fn wffs_are_syntactically_equal_except_possibly_the_terms(wff1: &Wff, wff2: &Wff
         (Wff::Not(w1), Wff::Not(w2)) => s_eq(w1, w2),
         (Wff::Not(..), _) => false,
 
+        (Wff::Necessarily(w1), Wff::Necessarily(w2)) | (Wff::Possibly(w1), Wff::Possibly(w2)) => {
+            s_eq(w1, w2)
+        }
+        (Wff::Necessarily(..) | Wff::Possibly(..), _) => false,
+
         (Wff::Forall(x1, w1), Wff::Forall(x2, w2)) | (Wff::Exists(x1, w1), Wff::Exists(x2, w2)) => {
             x1 == x2 && s_eq(w1, w2)
         }
@@ -1482,7 +2366,7 @@ macro_rules! terms_from_wff_macro {
                     }
                     Wff::PredApp(_, args) => ts.extend(args),
                     Wff::Forall(_, w) | Wff::Exists(_, w) => helper(w, ts),
-                    Wff::Not(w) => helper(w, ts),
+                    Wff::Not(w) | Wff::Necessarily(w) | Wff::Possibly(w) => helper(w, ts),
                     Wff::Bottom | Wff::Atomic(_) => {}
                 }
             }
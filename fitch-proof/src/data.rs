@@ -3,7 +3,7 @@
 /// If a user types in a proof (which is a string), then this proof is split into lines (which are
 /// also strings), and each of these lines correspond directly to one [ProofLine].
 ///
-/// There are exactly 6 possible types of lines that the user can make:
+/// There are exactly 7 possible types of lines that the user can make:
 /// 1. an empty line, consisting of only a positive number of vertical bars
 /// 2. a Fitch bar line, which consists of a positive number of vertical bars followed by a
 ///    positive number of minuses.
@@ -11,11 +11,14 @@
 /// 4. a premise that contains only a logical sentence, no boxed constant
 /// 5. a premise that contains both a boxed constant introduction and a logical sentence
 /// 6. an inference: a line that contains a sentence and justification
+/// 7. a comment line, consisting of only a comment (see [ProofLine::comment])
+///
+/// Lines of the types 1-6 may also end with a comment.
 ///
 /// Each line of the user's input must correspond to exactly one of the above types. If the user
-/// writes garbage, then it is not possible to convert it into [ProofLine]s and a fatal error will
-/// be given to the user.
-#[derive(PartialEq, Debug)]
+/// writes garbage, then the line becomes an "unparsed" [ProofLine] (see
+/// [ProofLine::parse_error]), so that the rest of the proof can still be checked.
+#[derive(PartialEq, Debug, Clone)]
 pub struct ProofLine {
     /// The line number of the proof line. This is *not* the index at which the current line
     /// occured in the input string that the user gave, but it is the line number inside a Fitch
@@ -23,9 +26,10 @@ pub struct ProofLine {
     ///
     /// `42 | | | | P(a,b,c,d)  =Elim:137,108`
     ///
-    /// this field in the struct would be `Some(42)`. This field must be [None] if and only if the
-    /// corresponding line was an empty line or a Fitch bar line. In all other cases, the line
-    /// number must be [Some(_)].
+    /// this field in the struct would be `Some(42)`. This field must be [None] if the corresponding
+    /// line was an empty line, a Fitch bar line or a comment line, and it is also [None] for an
+    /// unparsed line whose line number could not be recovered. In all other cases, the line number
+    /// must be [Some(_)].
     pub line_num: Option<usize>,
     /// The number of vertical bars on the left side. This indicates in how many nested subproofs
     /// this proof line is.
@@ -36,6 +40,13 @@ pub struct ProofLine {
     pub depth: usize,
     // This field is `true` if and only if the corresponding proof line is a Fitch bar line.
     pub is_fitch_bar_line: bool,
+    /// This field is `true` if and only if the corresponding proof line is a Fitch bar line with
+    /// the strict-subproof marker `□` on it, like this:
+    ///
+    /// `  | |□----`
+    ///
+    /// Such a bar opens a strict subproof, which has no premise.
+    pub is_strict_subproof_bar: bool,
     // The logical sentence ([Wff]) that this proof line contains.
     //
     // This field must be [None] if this proof line does not contain a sentence, which is
@@ -51,6 +62,26 @@ pub struct ProofLine {
     /// If the current proof line is a premise that introduces a constant in a box, then this field
     /// contains it.
     pub constant_between_square_brackets: Option<Term>,
+    /// If this line could not be lexed or parsed, then this field contains the corresponding error
+    /// and the line is an "unparsed" line. Of an unparsed line, only the line number and the depth
+    /// are known (as far as they could be recovered from the start of the line); it contains no
+    /// sentence, justification or boxed constant.
+    pub parse_error: Option<Diagnostic>,
+    /// The comment at the end of the line, if any. A comment starts with `#` or `//` and runs until
+    /// the end of the line; it is stored here verbatim, including the `#` or `//`. The checker
+    /// ignores comments.
+    ///
+    /// A line that consists of only a comment (without vertical bars) is a comment line; it has a
+    /// `depth` of `0` and is not part of the structure of the proof. See
+    /// [ProofLine::is_comment_line].
+    pub comment: Option<String>,
+}
+
+impl ProofLine {
+    /// Returns whether this line consists of only a comment, without any vertical bars.
+    pub fn is_comment_line(&self) -> bool {
+        self.depth == 0
+    }
 }
 
 /// This a logical term. A term can be either a constant, a variable, or a function application
@@ -63,7 +94,7 @@ pub enum Term {
     FuncApp(String, Vec<Term>),
 }
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 /// A logical sentence. "Wff" stands for "well-formed formula", but this is a slightly incorrect
 /// name, since for example, a logical sentence that has predicate ariy mismatches is still
 /// expressable in this [Wff]. A [Wff] is a core element of a proof. For example, each proof line
@@ -80,6 +111,10 @@ pub enum Wff {
     Bicond(Box<Wff>, Box<Wff>),
     /// Negation.
     Not(Box<Wff>),
+    /// Necessity (□).
+    Necessarily(Box<Wff>),
+    /// Possibility (◇).
+    Possibly(Box<Wff>),
     /// Bottom / contradiction.
     Bottom,
     /// Universal quantification.
@@ -106,7 +141,7 @@ pub enum Wff {
 
 /// This enum represents the justification rules for an inference. The associated [usize]s denote
 /// the line numbers being represented.
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum Justification {
     AndIntro(Vec<usize>),
     AndElim(usize),
@@ -127,21 +162,825 @@ pub enum Justification {
     ExistsIntro(usize),
     ExistsElim(usize, (usize, usize)),
     Reit(usize),
+    /// Tautological consequence: the sentence follows from the cited sentences by truth tables
+    /// alone. The list of cited lines may be empty, in which case the sentence must be a
+    /// tautology.
+    TautCon(Vec<usize>),
+    /// First-order consequence: the sentence follows from the cited sentences in first-order logic
+    /// with identity. The list of cited lines may be empty, in which case the sentence must be
+    /// logically true.
+    FoCon(Vec<usize>),
+    /// Analytic consequence: the sentence follows from the cited sentences, given the meaning of
+    /// the predicates of the blocks language. The list of cited lines may be empty.
+    AnaCon(Vec<usize>),
+    /// Disjunctive syllogism, a derived rule: from a disjunction and the negation of one of its
+    /// disjuncts, infer the other disjunct(s). The lines may be cited in either order.
+    DisjunctiveSyllogism(usize, usize),
+    /// Modus tollens, a derived rule: from `A → B` and `¬B`, infer `¬A`. The lines may be cited in
+    /// either order.
+    ModusTollens(usize, usize),
+    /// Double negation elimination, a derived rule: from `¬¬A`, infer `A`.
+    DoubleNegationElim(usize),
+    /// The law of excluded middle, a derived rule: from a subproof that assumes `A` and a subproof
+    /// that assumes `¬A`, both ending in `B`, infer `B`.
+    ExcludedMiddle((usize, usize), (usize, usize)),
+    /// Indirect proof, a derived rule: from a subproof that assumes `¬A` and ends in ⊥, infer `A`.
+    IndirectProof((usize, usize)),
+    /// De Morgan's laws, a derived rule (in both directions).
+    DeMorgan(usize),
+    /// Conversion of quantifiers, a derived rule: moves a negation through a quantifier (in both
+    /// directions).
+    ConversionOfQuantifiers(usize),
+    /// □ Intro: from a strict subproof that ends in `A`, infer `□A`.
+    BoxIntro((usize, usize)),
+    /// □ Elim: from `□A` outside the strict subproof that this line is in, infer `A` inside it.
+    /// From T on, `□A` may also be in scope in the ordinary way.
+    BoxElim(usize),
+    /// ◇ Intro: from `¬□¬A`, infer `◇A`. From T on, `◇A` may also be inferred from `A`.
+    DiamondIntro(usize),
+    /// ◇ Elim: from `◇A`, infer `¬□¬A`.
+    DiamondElim(usize),
+    /// R□: reiterate a sentence into a strict subproof. Which sentences may be reiterated depends
+    /// on the modal system (see [crate::rule_set::ModalSystem]).
+    BoxReit(usize),
+}
+
+impl Justification {
+    /// Returns the [Rule] that this justification applies.
+    pub fn rule(&self) -> Rule {
+        match self {
+            Justification::AndIntro(..) => Rule::AndIntro,
+            Justification::AndElim(..) => Rule::AndElim,
+            Justification::OrIntro(..) => Rule::OrIntro,
+            Justification::OrElim(..) => Rule::OrElim,
+            Justification::NotIntro(..) => Rule::NotIntro,
+            Justification::NotElim(..) => Rule::NotElim,
+            Justification::BottomIntro(..) => Rule::BottomIntro,
+            Justification::BottomElim(..) => Rule::BottomElim,
+            Justification::ImpliesIntro(..) => Rule::ImpliesIntro,
+            Justification::ImpliesElim(..) => Rule::ImpliesElim,
+            Justification::BicondIntro(..) => Rule::BicondIntro,
+            Justification::BicondElim(..) => Rule::BicondElim,
+            Justification::EqualsIntro => Rule::EqualsIntro,
+            Justification::EqualsElim(..) => Rule::EqualsElim,
+            Justification::ForallIntro(..) => Rule::ForallIntro,
+            Justification::ForallElim(..) => Rule::ForallElim,
+            Justification::ExistsIntro(..) => Rule::ExistsIntro,
+            Justification::ExistsElim(..) => Rule::ExistsElim,
+            Justification::Reit(..) => Rule::Reit,
+            Justification::TautCon(..) => Rule::TautCon,
+            Justification::FoCon(..) => Rule::FoCon,
+            Justification::AnaCon(..) => Rule::AnaCon,
+            Justification::DisjunctiveSyllogism(..) => Rule::DisjunctiveSyllogism,
+            Justification::ModusTollens(..) => Rule::ModusTollens,
+            Justification::DoubleNegationElim(..) => Rule::DoubleNegationElim,
+            Justification::ExcludedMiddle(..) => Rule::ExcludedMiddle,
+            Justification::IndirectProof(..) => Rule::IndirectProof,
+            Justification::DeMorgan(..) => Rule::DeMorgan,
+            Justification::ConversionOfQuantifiers(..) => Rule::ConversionOfQuantifiers,
+            Justification::BoxIntro(..) => Rule::BoxIntro,
+            Justification::BoxElim(..) => Rule::BoxElim,
+            Justification::DiamondIntro(..) => Rule::DiamondIntro,
+            Justification::DiamondElim(..) => Rule::DiamondElim,
+            Justification::BoxReit(..) => Rule::BoxReit,
+        }
+    }
+
+    /// Returns all line numbers that are cited by this justification, in the order in which they
+    /// are written. For a cited subproof `i-j`, both `i` and `j` are included.
+    pub fn cited_lines(&self) -> Vec<usize> {
+        match self {
+            Justification::AndIntro(ns)
+            | Justification::TautCon(ns)
+            | Justification::FoCon(ns)
+            | Justification::AnaCon(ns) => ns.clone(),
+            Justification::AndElim(n)
+            | Justification::OrIntro(n)
+            | Justification::NotElim(n)
+            | Justification::BottomElim(n)
+            | Justification::ForallElim(n)
+            | Justification::ExistsIntro(n)
+            | Justification::Reit(n)
+            | Justification::DoubleNegationElim(n)
+            | Justification::DeMorgan(n)
+            | Justification::ConversionOfQuantifiers(n)
+            | Justification::BoxElim(n)
+            | Justification::DiamondIntro(n)
+            | Justification::DiamondElim(n)
+            | Justification::BoxReit(n) => vec![*n],
+            Justification::OrElim(n, subs) => {
+                std::iter::once(*n).chain(subs.iter().flat_map(|(a, b)| [*a, *b])).collect()
+            }
+            Justification::NotIntro((a, b))
+            | Justification::ImpliesIntro((a, b))
+            | Justification::ForallIntro((a, b))
+            | Justification::IndirectProof((a, b))
+            | Justification::BoxIntro((a, b))
+            | Justification::BottomIntro(a, b)
+            | Justification::ImpliesElim(a, b)
+            | Justification::BicondElim(a, b)
+            | Justification::EqualsElim(a, b)
+            | Justification::DisjunctiveSyllogism(a, b)
+            | Justification::ModusTollens(a, b) => vec![*a, *b],
+            Justification::BicondIntro((a, b), (c, d))
+            | Justification::ExcludedMiddle((a, b), (c, d)) => vec![*a, *b, *c, *d],
+            Justification::ExistsElim(n, (a, b)) => vec![*n, *a, *b],
+            Justification::EqualsIntro => vec![],
+        }
+    }
+
+    /// Splits the citations of this justification into the cited single lines and the cited
+    /// subproofs, both in the order in which they are written.
+    pub fn citations(&self) -> (Vec<usize>, Vec<(usize, usize)>) {
+        match self {
+            Justification::AndIntro(ns)
+            | Justification::TautCon(ns)
+            | Justification::FoCon(ns)
+            | Justification::AnaCon(ns) => (ns.clone(), vec![]),
+            Justification::OrElim(n, subproofs) => (vec![*n], subproofs.clone()),
+            Justification::ExistsElim(n, subproof) => (vec![*n], vec![*subproof]),
+            Justification::NotIntro(s)
+            | Justification::ImpliesIntro(s)
+            | Justification::ForallIntro(s)
+            | Justification::IndirectProof(s)
+            | Justification::BoxIntro(s) => (vec![], vec![*s]),
+            Justification::BicondIntro(s1, s2) | Justification::ExcludedMiddle(s1, s2) => {
+                (vec![], vec![*s1, *s2])
+            }
+            // all other rules only cite single lines
+            _ => (self.cited_lines(), vec![]),
+        }
+    }
+}
+
+/// The proof rules that can be used in a [Justification], without the line numbers that they cite.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum Rule {
+    AndIntro,
+    AndElim,
+    OrIntro,
+    OrElim,
+    NotIntro,
+    NotElim,
+    BottomIntro,
+    BottomElim,
+    ImpliesIntro,
+    ImpliesElim,
+    BicondIntro,
+    BicondElim,
+    EqualsIntro,
+    EqualsElim,
+    ForallIntro,
+    ForallElim,
+    ExistsIntro,
+    ExistsElim,
+    Reit,
+    TautCon,
+    FoCon,
+    AnaCon,
+    DisjunctiveSyllogism,
+    ModusTollens,
+    DoubleNegationElim,
+    ExcludedMiddle,
+    IndirectProof,
+    DeMorgan,
+    ConversionOfQuantifiers,
+    BoxIntro,
+    BoxElim,
+    DiamondIntro,
+    DiamondElim,
+    BoxReit,
+}
+
+impl Rule {
+    /// All rules.
+    pub const ALL: [Rule; 34] = [
+        Rule::AndIntro,
+        Rule::AndElim,
+        Rule::OrIntro,
+        Rule::OrElim,
+        Rule::NotIntro,
+        Rule::NotElim,
+        Rule::BottomIntro,
+        Rule::BottomElim,
+        Rule::ImpliesIntro,
+        Rule::ImpliesElim,
+        Rule::BicondIntro,
+        Rule::BicondElim,
+        Rule::EqualsIntro,
+        Rule::EqualsElim,
+        Rule::ForallIntro,
+        Rule::ForallElim,
+        Rule::ExistsIntro,
+        Rule::ExistsElim,
+        Rule::Reit,
+        Rule::TautCon,
+        Rule::FoCon,
+        Rule::AnaCon,
+        Rule::DisjunctiveSyllogism,
+        Rule::ModusTollens,
+        Rule::DoubleNegationElim,
+        Rule::ExcludedMiddle,
+        Rule::IndirectProof,
+        Rule::DeMorgan,
+        Rule::ConversionOfQuantifiers,
+        Rule::BoxIntro,
+        Rule::BoxElim,
+        Rule::DiamondIntro,
+        Rule::DiamondElim,
+        Rule::BoxReit,
+    ];
+
+    /// The name of the rule, as it is written in a proof (e.g. "∧ Intro").
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::AndIntro => "∧ Intro",
+            Rule::AndElim => "∧ Elim",
+            Rule::OrIntro => "∨ Intro",
+            Rule::OrElim => "∨ Elim",
+            Rule::NotIntro => "¬ Intro",
+            Rule::NotElim => "¬ Elim",
+            Rule::BottomIntro => "⊥ Intro",
+            Rule::BottomElim => "⊥ Elim",
+            Rule::ImpliesIntro => "→ Intro",
+            Rule::ImpliesElim => "→ Elim",
+            Rule::BicondIntro => "↔ Intro",
+            Rule::BicondElim => "↔ Elim",
+            Rule::EqualsIntro => "= Intro",
+            Rule::EqualsElim => "= Elim",
+            Rule::ForallIntro => "∀ Intro",
+            Rule::ForallElim => "∀ Elim",
+            Rule::ExistsIntro => "∃ Intro",
+            Rule::ExistsElim => "∃ Elim",
+            Rule::Reit => "Reit",
+            Rule::TautCon => "Taut Con",
+            Rule::FoCon => "FO Con",
+            Rule::AnaCon => "Ana Con",
+            Rule::DisjunctiveSyllogism => "DS",
+            Rule::ModusTollens => "MT",
+            Rule::DoubleNegationElim => "DNE",
+            Rule::ExcludedMiddle => "LEM",
+            Rule::IndirectProof => "IP",
+            Rule::DeMorgan => "DeM",
+            Rule::ConversionOfQuantifiers => "CQ",
+            Rule::BoxIntro => "□ Intro",
+            Rule::BoxElim => "□ Elim",
+            Rule::DiamondIntro => "◇ Intro",
+            Rule::DiamondElim => "◇ Elim",
+            Rule::BoxReit => "R□",
+        }
+    }
+
+    /// Whether the rule is a derived rule: one that can be replaced by a number of applications of
+    /// the basic introduction and elimination rules.
+    pub fn is_derived(&self) -> bool {
+        matches!(
+            self,
+            Rule::DisjunctiveSyllogism
+                | Rule::ModusTollens
+                | Rule::DoubleNegationElim
+                | Rule::ExcludedMiddle
+                | Rule::IndirectProof
+                | Rule::DeMorgan
+                | Rule::ConversionOfQuantifiers
+        )
+    }
+}
+
+/// How bad the problem described by a [Diagnostic] is.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Severity {
+    /// A mistake that makes the proof wrong, but still allows the checker to go on.
+    Error,
+    /// A mistake that is so severe that the checker cannot continue its analysis.
+    Fatal,
+}
+
+/// A stable identifier for the kind of problem that a [Diagnostic] describes. Unlike the message
+/// of a [Diagnostic], these codes do not change between versions, so tools can rely on them.
+///
+/// Use [ErrorCode::as_str] to get the textual form (e.g. `E_REF_CLOSED_SUBPROOF`).
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum ErrorCode {
+    /// A line of the proof could not be split into tokens.
+    Lexer,
+    /// A line of the proof could not be parsed.
+    Parser,
+    /// The list of allowed variable names could not be parsed.
+    VariableNames,
+    /// The proof contains no lines at all.
+    EmptyProof,
+    /// A subproof is opened or closed in a way that is not allowed.
+    Structure,
+    /// The line numbers do not start at 1 or do not increase by one at a time.
+    LineNumbers,
+    /// The proof does not start with zero or more premises followed by a Fitch bar.
+    MissingFitchBar,
+    /// An inference has no justification.
+    MissingJustification,
+    /// The last line of the proof is inside a subproof.
+    LastLineInSubproof,
+    /// A justification cites a line that does not exist.
+    RefNonexistent,
+    /// A justification cites a line that contains no sentence.
+    RefNoSentence,
+    /// A justification cites a line that is inside an already closed subproof.
+    RefClosedSubproof,
+    /// A justification cites a line that does not come before the citing line.
+    RefNotBefore,
+    /// A justification cites a subproof that does not exist or that is not in scope.
+    SubproofNotInScope,
+    /// A justification cites a subproof which does (not) introduce a boxed constant, while the
+    /// rule requires the opposite.
+    SubproofKind,
+    /// The sentence in a line or a cited line does not have the main connective that the rule
+    /// requires.
+    WrongConnective,
+    /// The number of cited lines or subproofs does not match the sentence.
+    CitationCount,
+    /// A rule is applied in the wrong direction (e.g. ¬Elim from P to ¬¬P).
+    WrongDirection,
+    /// The sentences do not relate to each other in the way that the rule requires.
+    RuleMisapplied,
+    /// The sentence in a line does not follow from the cited lines; the message describes a
+    /// counterexample.
+    NotConsequence,
+    /// It could not be decided whether the sentence in a line follows from the cited lines,
+    /// because the search for a counterexample was too large.
+    Undecided,
+    /// There is no substitution of terms that relates the sentences in the way that the rule
+    /// requires.
+    BadSubstitution,
+    /// A term that is substituted for a variable is not closed.
+    NotClosedTerm,
+    /// A quantifier quantifies over a constant instead of over a variable.
+    QuantifiedConstant,
+    /// There are two nested quantifiers over the same variable.
+    NestedQuantifier,
+    /// A sentence contains a variable that is not bound by a quantifier.
+    UnboundVariable,
+    /// A function symbol has the name of a variable.
+    FunctionNamedLikeVariable,
+    /// A name is used both for a constant and a function symbol (or both for a nullary and a
+    /// non-nullary predicate).
+    SymbolKindClash,
+    /// A function symbol or predicate is used with inconsistent arities.
+    ArityMismatch,
+    /// A boxed constant has the name of a variable.
+    BoxedVariable,
+    /// The same boxed constant is introduced twice in nested subproofs.
+    BoxedConstantTwice,
+    /// A boxed constant is used outside the subproof that introduces it.
+    BoxedConstantOutsideSubproof,
+    /// The proof template is empty.
+    TemplateEmpty,
+    /// The proof template contains sentences that could not be parsed.
+    TemplateUnparsable,
+    /// The proof contains no sentences to match against the template.
+    TemplateNoSentences,
+    /// The premises of the proof do not match the premises of the template.
+    TemplatePremises,
+    /// The conclusion of the proof does not match the conclusion of the template.
+    TemplateConclusion,
+    /// A rule is used that the rule set of the exercise does not allow.
+    RuleNotAllowed,
+    /// The name of a rule set is not known.
+    UnknownRuleSet,
+    /// The name of a template matching policy is not known.
+    UnknownTemplateMatching,
+    /// A step relies on a principle of classical logic, while the proof is checked
+    /// intuitionistically.
+    ClassicalStep,
+    /// A line outside a strict subproof is used inside it in a way that the modal system does not
+    /// allow, or a strict subproof is cited by a rule other than □ Intro.
+    StrictSubproof,
+    /// FO Con or Ana Con is used on a sentence with □ or ◇.
+    ModalConsequence,
+    /// An exercise file could not be parsed, or contains a setting that does not exist.
+    ExerciseUnparsable,
+    /// The proof has more lines than the exercise allows.
+    TooManyLines,
+    /// A boxed constant is introduced that the exercise does not allow.
+    BoxedConstantNotAllowed,
+}
+
+impl ErrorCode {
+    /// Returns the stable textual form of the error code.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Lexer => "E_LEX",
+            ErrorCode::Parser => "E_PARSE",
+            ErrorCode::VariableNames => "E_VARIABLE_NAMES",
+            ErrorCode::EmptyProof => "E_EMPTY_PROOF",
+            ErrorCode::Structure => "E_STRUCTURE",
+            ErrorCode::LineNumbers => "E_LINE_NUMBERS",
+            ErrorCode::MissingFitchBar => "E_MISSING_FITCH_BAR",
+            ErrorCode::MissingJustification => "E_MISSING_JUSTIFICATION",
+            ErrorCode::LastLineInSubproof => "E_LAST_LINE_IN_SUBPROOF",
+            ErrorCode::RefNonexistent => "E_REF_NONEXISTENT",
+            ErrorCode::RefNoSentence => "E_REF_NO_SENTENCE",
+            ErrorCode::RefClosedSubproof => "E_REF_CLOSED_SUBPROOF",
+            ErrorCode::RefNotBefore => "E_REF_NOT_BEFORE",
+            ErrorCode::SubproofNotInScope => "E_SUBPROOF_NOT_IN_SCOPE",
+            ErrorCode::SubproofKind => "E_SUBPROOF_KIND",
+            ErrorCode::WrongConnective => "E_WRONG_CONNECTIVE",
+            ErrorCode::CitationCount => "E_CITATION_COUNT",
+            ErrorCode::WrongDirection => "E_WRONG_DIRECTION",
+            ErrorCode::RuleMisapplied => "E_RULE_MISAPPLIED",
+            ErrorCode::NotConsequence => "E_NOT_CONSEQUENCE",
+            ErrorCode::Undecided => "E_UNDECIDED",
+            ErrorCode::BadSubstitution => "E_BAD_SUBSTITUTION",
+            ErrorCode::NotClosedTerm => "E_NOT_CLOSED_TERM",
+            ErrorCode::QuantifiedConstant => "E_QUANTIFIED_CONSTANT",
+            ErrorCode::NestedQuantifier => "E_NESTED_QUANTIFIER",
+            ErrorCode::UnboundVariable => "E_UNBOUND_VARIABLE",
+            ErrorCode::FunctionNamedLikeVariable => "E_FUNCTION_NAMED_LIKE_VARIABLE",
+            ErrorCode::SymbolKindClash => "E_SYMBOL_KIND_CLASH",
+            ErrorCode::ArityMismatch => "E_ARITY_MISMATCH",
+            ErrorCode::BoxedVariable => "E_BOXED_VARIABLE",
+            ErrorCode::BoxedConstantTwice => "E_BOXED_CONSTANT_TWICE",
+            ErrorCode::BoxedConstantOutsideSubproof => "E_BOXED_CONSTANT_OUTSIDE_SUBPROOF",
+            ErrorCode::TemplateEmpty => "E_TEMPLATE_EMPTY",
+            ErrorCode::TemplateUnparsable => "E_TEMPLATE_UNPARSABLE",
+            ErrorCode::TemplateNoSentences => "E_TEMPLATE_NO_SENTENCES",
+            ErrorCode::TemplatePremises => "E_TEMPLATE_PREMISES",
+            ErrorCode::TemplateConclusion => "E_TEMPLATE_CONCLUSION",
+            ErrorCode::RuleNotAllowed => "E_RULE_NOT_ALLOWED",
+            ErrorCode::UnknownRuleSet => "E_UNKNOWN_RULE_SET",
+            ErrorCode::UnknownTemplateMatching => "E_UNKNOWN_TEMPLATE_MATCHING",
+            ErrorCode::ClassicalStep => "E_CLASSICAL_STEP",
+            ErrorCode::StrictSubproof => "E_STRICT_SUBPROOF",
+            ErrorCode::ModalConsequence => "E_MODAL_CONSEQUENCE",
+            ErrorCode::ExerciseUnparsable => "E_EXERCISE_UNPARSABLE",
+            ErrorCode::TooManyLines => "E_TOO_MANY_LINES",
+            ErrorCode::BoxedConstantNotAllowed => "E_BOXED_CONSTANT_NOT_ALLOWED",
+        }
+    }
+}
+
+/// A range of characters within one line of the user's input. Both `start` and `end` are counted
+/// in characters (not bytes) from the start of the line, starting at 0; `end` is exclusive.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The place in the user's input where a problem was found, together with the text of that line
+/// so that an excerpt can be shown.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct SourceLocation {
+    /// The physical line in the input (the first line is line 1). Note that this is usually not
+    /// the same as the line number that the user wrote in front of the proof line.
+    pub line: usize,
+    pub span: Span,
+    /// The full text of the physical line.
+    pub source_line: String,
+}
+
+impl SourceLocation {
+    /// Returns something like "column 4" or "columns 4-9" (counting from 1, as editors do).
+    pub fn columns_description(&self) -> String {
+        if self.span.end <= self.span.start + 1 {
+            format!("column {}", self.span.start + 1)
+        } else {
+            format!("columns {}-{}", self.span.start + 1, self.span.end)
+        }
+    }
+
+    /// Returns the source line followed by a line of carets that point at the span, e.g.
+    ///
+    /// ```notrust
+    ///     3 | H(s*ocrates)
+    ///            ^
+    /// ```
+    pub fn excerpt(&self) -> String {
+        // tabs would mess up the alignment of the carets, so they are shown as a single space
+        let text: String = self
+            .source_line
+            .chars()
+            .map(|c| {
+                if c == '\t' {
+                    ' '
+                } else {
+                    c
+                }
+            })
+            .collect();
+        format!(
+            "    {}\n    {}{}",
+            text.trim_end(),
+            " ".repeat(self.span.start),
+            "^".repeat((self.span.end.max(self.span.start + 1)) - self.span.start)
+        )
+    }
+}
+
+/// A single problem that was found in a proof.
+///
+/// The `message` is meant for humans; all other fields are meant for tools (e.g. to highlight
+/// lines in an editor). Note that the `message` does not repeat the primary line number: use the
+/// [std::fmt::Display] implementation to get the full text that is shown to the user, which is
+/// of the form `Line 7: <message>` if there is a primary line.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: ErrorCode,
+    /// The line number (as written in the proof) of the line that contains the mistake, if the
+    /// mistake can be attributed to one line.
+    pub line: Option<usize>,
+    /// Other line numbers that are involved in the mistake, for example the lines that are cited
+    /// by the justification of the primary line.
+    pub related_lines: Vec<usize>,
+    /// The proof rule that was involved, if any.
+    pub rule: Option<Rule>,
+    pub message: String,
+    /// Where exactly in the input the problem is. This is only known for problems that are found
+    /// while lexing and parsing. It is boxed to keep diagnostics small, since they are passed
+    /// around in `Result`s.
+    pub location: Option<Box<SourceLocation>>,
+    /// Justifications that would make the primary line correct, like `⊥ Intro: 3, 2` when the
+    /// student wrote `⊥ Intro: 2, 3`. They are shown as "Did you mean ...?".
+    pub suggestions: Vec<String>,
+}
+
+impl Diagnostic {
+    /// Returns the suggestions as a question, like "Did you mean '→ Elim: 2,1'?", or [None] if
+    /// there are none.
+    pub fn suggestions_text(&self) -> Option<String> {
+        let (last, init) = self.suggestions.split_last()?;
+        let quoted = |s: &String| format!("'{s}'");
+        Some(match init {
+            [] => format!("Did you mean {}?", quoted(last)),
+            _ => format!(
+                "Did you mean {} or {}?",
+                init.iter().map(quoted).collect::<Vec<_>>().join(", "),
+                quoted(last)
+            ),
+        })
+    }
+
+    /// Creates a [Severity::Error] diagnostic without related lines and without a rule.
+    pub fn error(code: ErrorCode, line: Option<usize>, message: String) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            line,
+            related_lines: vec![],
+            rule: None,
+            message,
+            location: None,
+            suggestions: vec![],
+        }
+    }
+
+    /// Creates a [Severity::Fatal] diagnostic without a primary line, related lines or rule.
+    pub fn fatal(code: ErrorCode, message: String) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Fatal,
+            code,
+            line: None,
+            related_lines: vec![],
+            rule: None,
+            message,
+            location: None,
+            suggestions: vec![],
+        }
+    }
+
+    /// Returns the diagnostic with its related lines set to `related_lines`.
+    pub fn with_related_lines(mut self, related_lines: Vec<usize>) -> Diagnostic {
+        self.related_lines = related_lines;
+        self
+    }
+
+    /// Returns the diagnostic with its location in the input set to `location`.
+    pub fn with_location(mut self, location: SourceLocation) -> Diagnostic {
+        self.location = Some(Box::new(location));
+        self
+    }
+
+    /// Returns the diagnostic with its suggestions set to `suggestions`.
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Diagnostic {
+        self.suggestions = suggestions;
+        self
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line_num) => write!(f, "Line {line_num}: {}", self.message)?,
+            None => write!(f, "{}", self.message)?,
+        }
+        if let Some(location) = &self.location {
+            write!(f, "\n\n{}", location.excerpt())?;
+        }
+        if let Some(suggestions) = self.suggestions_text() {
+            write!(f, "\n{suggestions}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A suggestion for the next step in a proof that is not finished yet. See [crate::hint].
+///
+/// The same suggestion is given at three levels of detail (see [HintLevel]), so that a student can
+/// first try to find the step themselves, and only ask for more help when they are stuck.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Hint {
+    /// Only says where to look, e.g. "Line 4 is a disjunction."
+    pub nudge: String,
+    /// Also says which rule to use, e.g. "Line 4 is a disjunction; consider ∨ Elim."
+    pub rule: String,
+    /// Says exactly which step to take.
+    pub step: String,
+    /// The lines of the proof that the hint is about.
+    pub lines: Vec<usize>,
+}
+
+impl Hint {
+    /// Returns the hint at the given level of detail.
+    pub fn at_level(&self, level: HintLevel) -> &str {
+        match level {
+            HintLevel::Nudge => &self.nudge,
+            HintLevel::Rule => &self.rule,
+            HintLevel::Step => &self.step,
+        }
+    }
 }
 
+/// How much detail a [Hint] gives.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum HintLevel {
+    /// Only where to look.
+    Nudge,
+    /// Which rule to use.
+    Rule,
+    /// Exactly which step to take.
+    Step,
+}
+
+impl HintLevel {
+    /// Returns the level with the given number: 1 is [HintLevel::Nudge], 2 is [HintLevel::Rule]
+    /// and 3 is [HintLevel::Step].
+    pub fn from_number(level: usize) -> Option<HintLevel> {
+        match level {
+            1 => Some(HintLevel::Nudge),
+            2 => Some(HintLevel::Rule),
+            3 => Some(HintLevel::Step),
+            _ => None,
+        }
+    }
+}
+
+/// The outcome of looking for a justification for a line that has none, or a wrong one. See
+/// [crate::infer_justifications].
+#[derive(PartialEq, Debug, Clone)]
+pub struct InferredJustification {
+    /// The line number of the line.
+    pub line: usize,
+    /// A justification that makes the line correct (e.g. `→ Elim: 1,2`), or [None] if no such
+    /// justification was found.
+    pub justification: Option<String>,
+    /// Whether the line had no justification at all (otherwise, it had a wrong one).
+    pub was_missing: bool,
+}
+
+/// What a proof establishes, found by following the citations from its conclusion back to the
+/// premises. See [crate::proven_sequent].
+#[derive(PartialEq, Debug, Clone)]
+pub struct ProvenSequent {
+    /// For every line with a sentence: its line number, and the line numbers of the premises and
+    /// open assumptions that it depends on.
+    pub dependencies: Vec<(usize, Vec<usize>)>,
+    /// The line number of the conclusion (the last sentence of the proof).
+    pub conclusion: usize,
+    /// The premises of the proof that the conclusion depends on.
+    pub used_premises: Vec<usize>,
+    /// The premises of the proof that the conclusion does not depend on.
+    pub unused_premises: Vec<usize>,
+    /// The other lines that the conclusion depends on: lines below the Fitch bar that have no
+    /// justification, and assumptions of subproofs that were never closed.
+    pub open_assumptions: Vec<usize>,
+    /// The sequent that the proof establishes, like `P, R ⊢ P ∧ R`. Its left-hand side contains
+    /// the used premises and the open assumptions, in the order of the proof.
+    pub sequent: String,
+    /// The premises of the template that do not occur among the used premises, if the proof was
+    /// compared to a template.
+    pub unneeded_template_premises: Vec<String>,
+}
+
+/// A line or subproof that a justification cites, with its sentences. See
+/// [crate::cited_sentences].
+#[derive(PartialEq, Debug, Clone)]
+pub struct CitedSentence {
+    /// The citation as it would be written, like `3` or `2-4`.
+    pub citation: String,
+    /// The sentence of a cited line, or the first and the last sentence of a cited subproof (just
+    /// one if the subproof has one line). A line that introduces a boxed constant starts with it,
+    /// like `[a] P(a)`.
+    pub sentences: Vec<String>,
+}
+
+/// What a line of a proof may cite, sorted from top to bottom. See [crate::scope_at_line].
+#[derive(PartialEq, Debug, Clone)]
+pub struct LineScope {
+    /// The lines that may be cited.
+    pub lines: Vec<usize>,
+    /// The subproofs that may be cited, as (first line, last line).
+    pub subproofs: Vec<(usize, usize)>,
+    /// The lines outside the innermost strict subproof whose sentences may be brought into it
+    /// (with □ Elim or R□).
+    pub imports: Vec<usize>,
+}
+
+/// The grade of one submission for an exercise. See [crate::grade_proof].
+#[derive(PartialEq, Debug)]
+pub struct Grade {
+    /// The result of checking the proof against the exercise.
+    pub result: ProofResult,
+    /// The relaxations of the template matching policy that were needed.
+    pub relaxations: Vec<TemplateRelaxation>,
+    /// The number of numbered lines of the proof.
+    pub line_count: usize,
+    /// The score, from 0 to 100. A correct proof gets 100, a proof with a fatal error gets 0, and
+    /// any other proof gets at most 50: `50 * good_lines / line_count`, rounded down, where
+    /// `good_lines` is the number of numbered lines that could be parsed and have no error.
+    pub score: u32,
+}
+
+/// Whether the conclusion of a proof template follows from its premises. See
+/// [crate::validate_template].
+#[derive(PartialEq, Debug, Clone)]
+pub enum TemplateValidity {
+    /// The conclusion follows from the premises, so the exercise can be done.
+    Valid,
+    /// The conclusion does not follow from the premises. The string describes a counter-model: a
+    /// row of the truth table, or a structure, in which the premises are true and the conclusion
+    /// is false.
+    Invalid(String),
+    /// It could not be decided whether the conclusion follows from the premises. The string says
+    /// why.
+    Undecided(String),
+}
+
+/// A way in which a proof was allowed to differ from its template. Which ways are allowed is set
+/// by a [TemplateMatching](crate::TemplateMatching).
+#[derive(PartialEq, Debug, Clone)]
+pub enum TemplateRelaxation {
+    /// The sentences on these lines only match the template after renaming bound variables.
+    AlphaEquivalence(Vec<usize>),
+    /// The premises of the proof are in another order than in the template.
+    PremiseOrder,
+    /// These premises of the template are not in the proof.
+    OmittedPremises(Vec<String>),
+}
+
+impl std::fmt::Display for TemplateRelaxation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TemplateRelaxation::AlphaEquivalence(lines) => {
+                if let [n] = lines[..] {
+                    return write!(
+                        f,
+                        "The sentence at line {n} only matches the proof template after renaming \
+                        bound variables."
+                    );
+                }
+                let lines: Vec<String> = lines.iter().map(|n| n.to_string()).collect();
+                write!(
+                    f,
+                    "The sentences at lines {} only match the proof template after renaming bound \
+                    variables.",
+                    lines.join(", ")
+                )
+            }
+            TemplateRelaxation::PremiseOrder => {
+                write!(f, "The premises are in another order than in the proof template.")
+            }
+            TemplateRelaxation::OmittedPremises(premises) => {
+                let premises: Vec<String> = premises.iter().map(|p| format!("'{p}'")).collect();
+                write!(
+                    f,
+                    "These premises of the proof template do not appear in your proof: {}.",
+                    premises.join(", ")
+                )
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
 pub enum ProofResult {
     /// No mistakes; proof is correct.
     Correct,
     /// An 'error' is a mistake that makes the proof wrong, but still allows
     /// the checker to go on and find other mistakes. This [ProofResult::Error]
     /// variant denotes the list of errors that was obtained during analysis.
-    Error(Vec<String>),
+    Error(Vec<Diagnostic>),
     /// A mistake that is so severe that the checker cannot continue its analysis.
     /// When a fatal error occurs, this fatal error will be returned to the user,
     /// with no other error messages along it.
-    ///
-    /// Note that when the user checks some proof that should match to some proof template,
-    /// a [ProofResult::FatalError] will be returned if the proof does
-    /// not match the template.
-    FatalError(String),
+    FatalError(Diagnostic),
 }
@@ -0,0 +1,184 @@
+use std::collections::BTreeSet;
+
+use crate::data::*;
+use crate::formatter::format_wff;
+use crate::proof::*;
+
+impl Proof {
+    /// Computes, for every line, the line numbers of the premises and open assumptions that it
+    /// depends on. The result is indexed by line number, like [Scope] (so index 0 is unused).
+    ///
+    /// A line with a sentence but without justification (a premise, the assumption of a
+    /// subproof, or an inference whose justification is missing) depends on itself. A justified
+    /// line depends on everything that the lines it cites depend on, and on everything that the
+    /// last line of each cited subproof depends on, except the assumption of that subproof: it is
+    /// discharged. A strict subproof has no assumption, so nothing is discharged there.
+    ///
+    /// The citations are followed as they are written, whether or not the justification is
+    /// correct. A line that cites itself or a later line cannot depend on what it cites, so it
+    /// depends on itself, like a line without justification.
+    pub fn dependencies(&self) -> Vec<BTreeSet<usize>> {
+        let mut deps: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); self.last_line_num() + 1];
+        for line in &self.lines {
+            let Some(n) = line.line_num else {
+                continue;
+            };
+            let Some(just) = &line.justification else {
+                if line.sentence.is_some() {
+                    deps[n].insert(n);
+                }
+                continue;
+            };
+            let (lines, subproofs) = just.citations();
+            if lines.iter().any(|&m| m >= n) || subproofs.iter().any(|&(_, end)| end >= n) {
+                deps[n].insert(n);
+                continue;
+            }
+            let mut line_deps = BTreeSet::new();
+            for m in lines {
+                line_deps.extend(deps[m].iter().copied());
+            }
+            for (begin, end) in subproofs {
+                let strict = self.strict_subproofs.contains(&(begin, end));
+                line_deps.extend(deps[end].iter().copied().filter(|&m| strict || m != begin));
+            }
+            deps[n] = line_deps;
+        }
+        deps
+    }
+
+    /// Works out which sequent the proof establishes, from the [Proof::dependencies] of its
+    /// conclusion (its last sentence). If a template is given, its premises that the proof does
+    /// not need are reported as well.
+    ///
+    /// Returns [None] if the proof contains no sentences.
+    pub fn proven_sequent(&self, template: Option<&[Wff]>) -> Option<ProvenSequent> {
+        let conclusion = self.lines.iter().rev().find(|l| l.sentence.is_some())?;
+        // both unwraps work: lines with a sentence have a line number
+        let conclusion_num = conclusion.line_num.unwrap();
+        let deps = self.dependencies();
+        let sentence_at = |n: usize| {
+            self.lines.iter().find(|l| l.line_num == Some(n)).and_then(|l| l.sentence.as_ref())
+        };
+
+        let premises: Vec<usize> = self
+            .lines
+            .iter()
+            .take_while(|l| !l.is_fitch_bar_line)
+            .filter(|l| l.sentence.is_some())
+            .filter_map(|l| l.line_num)
+            .collect();
+        let (used_premises, unused_premises): (Vec<usize>, Vec<usize>) =
+            premises.iter().partition(|n| deps[conclusion_num].contains(n));
+        let open_assumptions: Vec<usize> =
+            deps[conclusion_num].iter().copied().filter(|n| !premises.contains(n)).collect();
+
+        let antecedent: Vec<String> =
+            deps[conclusion_num].iter().filter_map(|&n| sentence_at(n)).map(format_wff).collect();
+        let conclusion_wff = format_wff(conclusion.sentence.as_ref().unwrap());
+        let sequent = if antecedent.is_empty() {
+            format!("⊢ {conclusion_wff}")
+        } else {
+            format!("{} ⊢ {conclusion_wff}", antecedent.join(", "))
+        };
+
+        let unneeded_template_premises = match template {
+            Some(template) if !template.is_empty() => template[..template.len() - 1]
+                .iter()
+                .filter(|wff| !used_premises.iter().any(|&n| sentence_at(n) == Some(*wff)))
+                .map(format_wff)
+                .collect(),
+            _ => vec![],
+        };
+
+        let dependencies = self
+            .lines
+            .iter()
+            .filter(|l| l.sentence.is_some())
+            .filter_map(|l| l.line_num)
+            .map(|n| (n, deps[n].iter().copied().collect()))
+            .collect();
+
+        Some(ProvenSequent {
+            dependencies,
+            conclusion: conclusion_num,
+            used_premises,
+            unused_premises,
+            open_assumptions,
+            sequent,
+            unneeded_template_premises,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse_fitch_proof;
+    use crate::proof::Proof;
+    use crate::rule_set::RuleSet;
+
+    fn construct(proof: &str, rule_set: RuleSet) -> Proof {
+        let lines = parse_fitch_proof(proof).unwrap();
+        let variables = ["x", "y", "z"].into_iter().map(String::from).collect();
+        Proof::construct(lines, variables, rule_set).unwrap()
+    }
+
+    #[test]
+    fn test_dependencies() {
+        // the assumption of the subproof is discharged by → Intro
+        let proof = construct(
+            "1 | P\n2 | Q\n  |---\n3 | | R\n  | |---\n4 | | P   Reit: 1\n5 | R → P   → Intro: 3-4",
+            RuleSet::lpl(),
+        );
+        let deps = proof.dependencies();
+        assert_eq!(deps[4].iter().copied().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(deps[3].iter().copied().collect::<Vec<_>>(), vec![3]);
+        assert_eq!(deps[5].iter().copied().collect::<Vec<_>>(), vec![1]);
+
+        // a line without justification is an open assumption
+        let proof = construct("1 | P\n  |---\n2 | Q\n3 | P ∧ Q   ∧ Intro: 1, 2", RuleSet::lpl());
+        assert_eq!(proof.dependencies()[3].iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+
+        // nothing is discharged by a strict subproof
+        let proof = construct(
+            "1 | □P\n  |---\n  | |□----\n2 | | P   □ Elim: 1\n3 | □P   □ Intro: 2-2",
+            RuleSet::lpl().with_modal_system(crate::rule_set::ModalSystem::K),
+        );
+        assert_eq!(proof.dependencies()[3].iter().copied().collect::<Vec<_>>(), vec![1]);
+
+        // a line that cites itself or a later line depends on itself
+        let proof = construct(
+            "1 | P → Q\n2 | P\n  |---\n3 | Q   → Elim: 1, 2\n4 | Q ∨ R   ∨ Intro: 4\n5 | R   Reit: 6\n6 | R   Reit: 5",
+            RuleSet::lpl(),
+        );
+        let deps = proof.dependencies();
+        assert_eq!(deps[4].iter().copied().collect::<Vec<_>>(), vec![4]);
+        assert_eq!(deps[5].iter().copied().collect::<Vec<_>>(), vec![5]);
+        assert_eq!(deps[6].iter().copied().collect::<Vec<_>>(), vec![5]);
+    }
+
+    #[test]
+    fn test_proven_sequent() {
+        let proof =
+            construct("1 | P\n2 | Q\n3 | R\n  |---\n4 | P ∧ R   ∧ Intro: 1, 3", RuleSet::lpl());
+        let sequent = proof.proven_sequent(None).unwrap();
+        assert_eq!(sequent.sequent, "P, R ⊢ P ∧ R");
+        assert_eq!(sequent.used_premises, vec![1, 3]);
+        assert_eq!(sequent.unused_premises, vec![2]);
+        assert!(sequent.open_assumptions.is_empty());
+
+        let template = [
+            crate::parser::parse_logical_expression_string("P").unwrap(),
+            crate::parser::parse_logical_expression_string("Q").unwrap(),
+            crate::parser::parse_logical_expression_string("R").unwrap(),
+            crate::parser::parse_logical_expression_string("P ∧ R").unwrap(),
+        ];
+        let sequent = proof.proven_sequent(Some(&template)).unwrap();
+        assert_eq!(sequent.unneeded_template_premises, vec!["Q".to_string()]);
+
+        let proof = construct("  |---\n1 | P\n2 | P ∨ Q   ∨ Intro: 1", RuleSet::lpl());
+        let sequent = proof.proven_sequent(None).unwrap();
+        assert_eq!(sequent.sequent, "P ⊢ P ∨ Q");
+        assert_eq!(sequent.open_assumptions, vec![1]);
+    }
+}
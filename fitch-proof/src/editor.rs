@@ -0,0 +1,107 @@
+use crate::data::*;
+use crate::formatter::{format_term, format_wff};
+use crate::proof::*;
+
+/// Returns, for every physical line of `proof` (the first one has index 0), the line number that
+/// is written in front of it, if any. Editors need this to put a [Diagnostic] about a numbered
+/// line on the right row.
+pub(crate) fn line_numbers_by_source_line(proof: &str) -> Vec<Option<usize>> {
+    // the recovering parser skips empty lines, and gives one line for every other physical line
+    let mut parsed = crate::parser::parse_fitch_proof_recovering(proof).into_iter();
+    proof
+        .lines()
+        .map(|s| {
+            if s.is_empty() {
+                None
+            } else {
+                parsed.next().and_then(|l| l.line_num)
+            }
+        })
+        .collect()
+}
+
+/// Returns the lines and subproofs that the justification of line `line_num` cites, with their
+/// sentences, in the order in which they are written. Citations of lines that do not exist are
+/// left out.
+///
+/// Returns [None] if there is no line `line_num` with a justification.
+pub(crate) fn cited_sentences(lines: &[ProofLine], line_num: usize) -> Option<Vec<CitedSentence>> {
+    let line_at = |n: usize| lines.iter().find(|l| l.line_num == Some(n));
+    let just = line_at(line_num)?.justification.as_ref()?;
+    let (cited_lines, cited_subproofs) = just.citations();
+    let single = cited_lines.into_iter().filter_map(|n| {
+        Some(CitedSentence {
+            citation: n.to_string(),
+            sentences: vec![line_text(line_at(n)?)],
+        })
+    });
+    let subproofs = cited_subproofs.into_iter().filter_map(|(begin, end)| {
+        let mut sentences = vec![line_text(line_at(begin)?)];
+        if end != begin {
+            sentences.push(line_text(line_at(end)?));
+        }
+        Some(CitedSentence {
+            citation: format!("{begin}-{end}"),
+            sentences,
+        })
+    });
+    Some(single.chain(subproofs).collect())
+}
+
+/// Returns the boxed constant and the sentence of a line, as they would be written.
+fn line_text(line: &ProofLine) -> String {
+    let constant =
+        line.constant_between_square_brackets.as_ref().map(|c| format!("[{}]", format_term(c)));
+    let sentence = line.sentence.as_ref().map(format_wff);
+    constant.into_iter().chain(sentence).collect::<Vec<_>>().join(" ")
+}
+
+impl Proof {
+    /// Returns what line `line_num` may cite (see [Proof::scope_of_line]), sorted from top to
+    /// bottom, or [None] if the proof has no line `line_num`.
+    pub(crate) fn line_scope(&self, line_num: usize) -> Option<LineScope> {
+        if !self.units.iter().any(|u| u.line_num() == Some(line_num)) {
+            return None;
+        }
+        let (mut lines, mut subproofs, mut imports) = self.scope_of_line(line_num);
+        lines.sort();
+        subproofs.sort();
+        imports.sort();
+        Some(LineScope {
+            lines,
+            subproofs,
+            imports,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_fitch_proof;
+
+    #[test]
+    fn test_line_numbers_by_source_line() {
+        let proof = "1 | P\n  |---\n\n2 | P ∧\n3 | P   Reit: 1";
+        assert_eq!(line_numbers_by_source_line(proof), vec![Some(1), None, None, Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn test_cited_sentences() {
+        let proof = "1 | P\n  |---\n2 | | Q\n  | |---\n3 | | P   Reit: 1\n4 | Q → P   → Intro: 2-3\n5 | P ∧ (Q → P)   ∧ Intro: 1, 4, 9";
+        let lines = parse_fitch_proof(proof).unwrap();
+        let cited = |n| {
+            cited_sentences(&lines, n)
+                .map(|cs| cs.into_iter().map(|c| (c.citation, c.sentences)).collect::<Vec<_>>())
+        };
+        assert_eq!(cited(4), Some(vec![("2-3".to_owned(), vec!["Q".to_owned(), "P".to_owned()])]));
+        assert_eq!(
+            cited(5),
+            Some(vec![
+                ("1".to_owned(), vec!["P".to_owned()]),
+                ("4".to_owned(), vec!["Q → P".to_owned()])
+            ])
+        );
+        assert_eq!(cited(1), None);
+    }
+}
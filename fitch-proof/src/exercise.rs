@@ -0,0 +1,528 @@
+use crate::data::*;
+use crate::parser;
+use crate::proof::*;
+use crate::rule_set::{ModalSystem, RuleSet};
+use crate::template::TemplateMatching;
+
+/// How much a student is told about a proof that is not correct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Feedback {
+    /// All mistakes, with their explanations.
+    #[default]
+    Full,
+    /// Only the first mistake.
+    FirstMistake,
+    /// Only whether the proof is correct.
+    Verdict,
+}
+
+impl Feedback {
+    /// Returns the level of feedback with the given name ("full", "first-mistake" or "verdict"),
+    /// if there is one.
+    pub fn from_name(name: &str) -> Option<Feedback> {
+        match name {
+            "full" => Some(Feedback::Full),
+            "first-mistake" => Some(Feedback::FirstMistake),
+            "verdict" => Some(Feedback::Verdict),
+            _ => None,
+        }
+    }
+}
+
+/// Everything that the proof of an exercise is checked against: the sentences that it should
+/// start and end with, and the settings under which it should be written.
+///
+/// An exercise is usually read from an exercise file (see [Exercise::from_toml]), which looks like
+/// this. Only the conclusion is required.
+/// ```toml
+/// premises = ["∀x (H(x) → M(x))", "H(socrates)"]
+/// conclusion = "M(socrates)"
+/// variables = ["x", "y", "z"]     # the default is x, y, z, u, v and w
+/// rule_set = "forallx"            # "lpl" (the default), "forallx", "lpl-intuitionistic", ...
+/// modal_system = "S4"             # "K" (the default), "T", "S4" or "S5"
+/// template_matching = "lenient"   # see TemplateMatching::from_names
+/// max_lines = 10
+/// boxed_constants = ["a", "b"]
+/// feedback = "first-mistake"      # "full" (the default), "first-mistake" or "verdict"
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exercise {
+    /// The premises that the proof should start with.
+    pub premises: Vec<String>,
+    /// The sentence that the proof should end with.
+    pub conclusion: String,
+    /// The names that count as variables, separated by commas.
+    pub allowed_variable_names: String,
+    /// The rules that may be used.
+    pub rule_set: RuleSet,
+    /// How strictly the proof is compared to the premises and the conclusion.
+    pub template_matching: TemplateMatching,
+    /// The largest number of numbered lines that the proof may have, if there is a limit.
+    pub max_lines: Option<usize>,
+    /// The names that may be introduced as boxed constants, if they are restricted.
+    pub boxed_constants: Option<Vec<String>>,
+    /// How much the student is told about a proof that is not correct.
+    pub feedback: Feedback,
+}
+
+impl Exercise {
+    /// Reads an exercise from an exercise file (see [Exercise] for what it looks like).
+    ///
+    /// Exercise files are written in the part of TOML that consists of `key = value` lines, where a
+    /// value is a string, a non-negative integer, a boolean or an array of values (which may be
+    /// spread over several lines), and comments that start with `#`. Tables are not supported.
+    ///
+    /// If the file cannot be parsed, contains a setting that does not exist, or contains a
+    /// sentence, variable name, rule set or other setting that is not valid, a fatal [Diagnostic]
+    /// is returned.
+    pub fn from_toml(text: &str) -> Result<Exercise, Diagnostic> {
+        let error = |message: String| Diagnostic::fatal(ErrorCode::ExerciseUnparsable, message);
+        let settings = TomlParser::new(text)
+            .parse()
+            .map_err(|e| error(format!("the exercise file could not be parsed: {e}.")))?;
+
+        let mut premises = vec![];
+        let mut conclusion = None;
+        let mut allowed_variable_names = default_variable_names!().to_owned();
+        let mut rule_set = RuleSet::lpl();
+        let mut modal_system = None;
+        let mut template_matching = TemplateMatching::strict();
+        let mut max_lines = None;
+        let mut boxed_constants = None;
+        let mut feedback = Feedback::Full;
+        for (key, value) in settings {
+            let invalid = |name: &str, what: &str| error(format!("'{name}' is not {what}."));
+            match (key.as_str(), value) {
+                ("premises", Value::Array(values)) => premises = strings(&key, values)?,
+                ("conclusion", Value::String(s)) => conclusion = Some(s),
+                ("variables", Value::Array(values)) => {
+                    allowed_variable_names = strings(&key, values)?.join(",");
+                }
+                ("rule_set", Value::String(s)) => {
+                    rule_set = RuleSet::from_name(&s).ok_or_else(|| invalid(&s, "a rule set"))?;
+                }
+                ("modal_system", Value::String(s)) => {
+                    modal_system = Some(
+                        ModalSystem::from_name(&s).ok_or_else(|| invalid(&s, "a modal system"))?,
+                    );
+                }
+                ("template_matching", Value::String(s)) => {
+                    template_matching = TemplateMatching::from_names(&s)
+                        .ok_or_else(|| invalid(&s, "a template matching policy"))?;
+                }
+                ("max_lines", Value::Integer(n)) => max_lines = Some(n),
+                ("boxed_constants", Value::Array(values)) => {
+                    boxed_constants = Some(strings(&key, values)?);
+                }
+                ("feedback", Value::String(s)) => {
+                    feedback =
+                        Feedback::from_name(&s).ok_or_else(|| invalid(&s, "a feedback level"))?;
+                }
+                (_, _) => {
+                    return Err(error(match expected_type(&key) {
+                        Some(expected) => format!("the setting '{key}' should be {expected}."),
+                        None => format!("there is no setting called '{key}'."),
+                    }))
+                }
+            }
+        }
+        if let Some(modal_system) = modal_system {
+            rule_set = rule_set.with_modal_system(modal_system);
+        }
+        let Some(conclusion) = conclusion else {
+            return Err(error("the exercise file has no conclusion.".to_owned()));
+        };
+
+        // check the sentences and names now, so that a broken exercise is noticed right away
+        for (i, premise) in premises.iter().enumerate() {
+            if parser::parse_logical_expression_string(premise).is_none() {
+                return Err(error(format!("premise {} ('{premise}') could not be parsed.", i + 1)));
+            }
+        }
+        if parser::parse_logical_expression_string(&conclusion).is_none() {
+            return Err(error(format!("the conclusion ('{conclusion}') could not be parsed.")));
+        }
+        parser::parse_allowed_variable_names(&allowed_variable_names).map_err(error)?;
+
+        Ok(Exercise {
+            premises,
+            conclusion,
+            allowed_variable_names,
+            rule_set,
+            template_matching,
+            max_lines,
+            boxed_constants,
+            feedback,
+        })
+    }
+
+    /// Returns the proof template of the exercise: the premises, followed by the conclusion.
+    pub fn template(&self) -> Vec<String> {
+        self.premises.iter().chain([&self.conclusion]).cloned().collect()
+    }
+}
+
+/// The settings that a proof is checked with (see [crate::check_proof_to_proofresult]). The
+/// defaults check a proof on its own, with the LPL rules and the variables `x,y,z,u,v,w`; the
+/// other settings are changed like this:
+/// ```notrust
+/// CheckOptions::default().with_template(template).with_rule_set(RuleSet::forallx())
+/// ```
+/// An [Exercise] converts into the settings that its proofs are checked with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckOptions {
+    /// The premises followed by the conclusion that the proof should match, if there is a
+    /// template.
+    pub template: Option<Vec<String>>,
+    /// The names that count as variables, separated by commas.
+    pub allowed_variable_names: String,
+    /// The rules that may be used.
+    pub rule_set: RuleSet,
+    /// How strictly the proof is compared to the template.
+    pub template_matching: TemplateMatching,
+    /// The largest number of numbered lines that the proof may have, if there is a limit.
+    pub max_lines: Option<usize>,
+    /// The names that may be introduced as boxed constants, if they are restricted.
+    pub boxed_constants: Option<Vec<String>>,
+}
+
+impl Default for CheckOptions {
+    fn default() -> CheckOptions {
+        CheckOptions {
+            template: None,
+            allowed_variable_names: default_variable_names!().to_owned(),
+            rule_set: RuleSet::lpl(),
+            template_matching: TemplateMatching::strict(),
+            max_lines: None,
+            boxed_constants: None,
+        }
+    }
+}
+
+impl CheckOptions {
+    /// Sets the template that the proof should match: the premises, followed by the conclusion.
+    pub fn with_template(mut self, template: Vec<String>) -> CheckOptions {
+        self.template = Some(template);
+        self
+    }
+
+    /// Sets the names that count as variables, separated by commas.
+    pub fn with_allowed_variable_names(mut self, allowed_variable_names: &str) -> CheckOptions {
+        self.allowed_variable_names = allowed_variable_names.to_owned();
+        self
+    }
+
+    /// Sets the rules that may be used.
+    pub fn with_rule_set(mut self, rule_set: RuleSet) -> CheckOptions {
+        self.rule_set = rule_set;
+        self
+    }
+
+    /// Sets how strictly the proof is compared to the template.
+    pub fn with_template_matching(mut self, template_matching: TemplateMatching) -> CheckOptions {
+        self.template_matching = template_matching;
+        self
+    }
+}
+
+impl From<&Exercise> for CheckOptions {
+    fn from(exercise: &Exercise) -> CheckOptions {
+        CheckOptions {
+            template: Some(exercise.template()),
+            allowed_variable_names: exercise.allowed_variable_names.clone(),
+            rule_set: exercise.rule_set.clone(),
+            template_matching: exercise.template_matching,
+            max_lines: exercise.max_lines,
+            boxed_constants: exercise.boxed_constants.clone(),
+        }
+    }
+}
+
+impl Proof {
+    /// Returns the mistakes against the settings of an exercise that are not about the rules or
+    /// the template: a proof that is too long, and boxed constants that may not be used.
+    pub(crate) fn exercise_errors(&self, options: &CheckOptions) -> Vec<Diagnostic> {
+        let mut errors = vec![];
+        if let Some(max_lines) = options.max_lines {
+            let num_lines = self.lines.iter().filter(|l| l.line_num.is_some()).count();
+            if num_lines > max_lines {
+                errors.push(Diagnostic::error(
+                    ErrorCode::TooManyLines,
+                    None,
+                    format!(
+                        "Your proof has {num_lines} lines, but this exercise allows at most \
+                        {max_lines}."
+                    ),
+                ));
+            }
+        }
+        if let Some(allowed) = &options.boxed_constants {
+            for line in &self.lines {
+                let Some(Term::Atomic(name)) = &line.constant_between_square_brackets else {
+                    continue;
+                };
+                if !allowed.contains(name) {
+                    let message = match &allowed[..] {
+                        [] => format!("the boxed constant {name} is used, but this exercise does not allow boxed constants."),
+                        _ => format!(
+                            "the boxed constant {name} is used, but this exercise only allows {}.",
+                            allowed.join(", ")
+                        ),
+                    };
+                    errors.push(Diagnostic::error(
+                        ErrorCode::BoxedConstantNotAllowed,
+                        line.line_num,
+                        message,
+                    ));
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// Returns what the value of a setting of an exercise file should be, or [None] if there is no
+/// such setting.
+fn expected_type(key: &str) -> Option<&'static str> {
+    match key {
+        "premises" => Some("a list of sentences"),
+        "conclusion" => Some("a sentence"),
+        "variables" | "boxed_constants" => Some("a list of names"),
+        "rule_set" | "modal_system" | "template_matching" | "feedback" => Some("a name"),
+        "max_lines" => Some("a number"),
+        _ => None,
+    }
+}
+
+/// Returns the strings in an array of an exercise file, or an error if it contains something else.
+fn strings(key: &str, values: Vec<Value>) -> Result<Vec<String>, Diagnostic> {
+    values
+        .into_iter()
+        .map(|value| match value {
+            Value::String(s) => Ok(s),
+            _ => Err(Diagnostic::fatal(
+                ErrorCode::ExerciseUnparsable,
+                format!("the setting '{key}' should only contain strings."),
+            )),
+        })
+        .collect()
+}
+
+/// A value in an exercise file.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Integer(usize),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+/// Parses the part of TOML that exercise files are written in (see [Exercise::from_toml]).
+struct TomlParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    /// The line that the parser is in, counting from 1.
+    line: usize,
+}
+
+impl TomlParser<'_> {
+    fn new(text: &str) -> TomlParser<'_> {
+        TomlParser {
+            chars: text.chars().peekable(),
+            line: 1,
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {message}", self.line)
+    }
+
+    /// Returns the `key = value` pairs of the file, in order.
+    fn parse(mut self) -> Result<Vec<(String, Value)>, String> {
+        let mut pairs: Vec<(String, Value)> = vec![];
+        loop {
+            self.skip_whitespace(true);
+            if self.chars.peek().is_none() {
+                return Ok(pairs);
+            }
+            let key = self.word();
+            if key.is_empty() {
+                return Err(self.error("expected a setting, like 'conclusion = \"P\"'"));
+            }
+            self.skip_whitespace(false);
+            if self.chars.next() != Some('=') {
+                return Err(self.error(&format!("expected '=' after '{key}'")));
+            }
+            self.skip_whitespace(false);
+            let value = self.value()?;
+            self.skip_whitespace(false);
+            if self.chars.peek().is_some_and(|&c| c != '\n') {
+                return Err(self.error(&format!("expected the end of the line after '{key}'")));
+            }
+            if pairs.iter().any(|(k, _)| *k == key) {
+                return Err(self.error(&format!("'{key}' is set twice")));
+            }
+            pairs.push((key, value));
+        }
+    }
+
+    /// Skips spaces, tabs and comments, and also line breaks if `newlines` is true.
+    fn skip_whitespace(&mut self, newlines: bool) {
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                ' ' | '\t' | '\r' => {}
+                '\n' if newlines => self.line += 1,
+                '#' => {
+                    while self.chars.next_if(|&c| c != '\n').is_some() {}
+                    continue;
+                }
+                _ => return,
+            }
+            self.chars.next();
+        }
+    }
+
+    /// Reads a bare word: a key, a number, `true` or `false`.
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) =
+            self.chars.next_if(|&c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            word.push(c);
+        }
+        word
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.chars.peek() {
+            Some(&quote @ ('"' | '\'')) => {
+                self.chars.next();
+                self.string(quote).map(Value::String)
+            }
+            Some('[') => {
+                self.chars.next();
+                self.array().map(Value::Array)
+            }
+            _ => match self.word().as_str() {
+                "true" => Ok(Value::Boolean(true)),
+                "false" => Ok(Value::Boolean(false)),
+                word => match word.parse::<usize>() {
+                    Ok(n) => Ok(Value::Integer(n)),
+                    Err(_) => Err(self.error("expected a string, a number, a boolean or a list")),
+                },
+            },
+        }
+    }
+
+    /// Reads the rest of a string that starts with `quote`. In a string in double quotes, `\"`,
+    /// `\\`, `\n` and `\t` are escapes; a string in single quotes has no escapes.
+    fn string(&mut self, quote: char) -> Result<String, String> {
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                None | Some('\n') => return Err(self.error("a string is not closed")),
+                Some(c) if c == quote => return Ok(s),
+                Some('\\') if quote == '"' => match self.chars.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    _ => return Err(self.error("unknown escape in a string")),
+                },
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    /// Reads the rest of an array, after its `[`.
+    fn array(&mut self) -> Result<Vec<Value>, String> {
+        let mut values = vec![];
+        loop {
+            self.skip_whitespace(true);
+            if self.chars.next_if_eq(&']').is_some() {
+                return Ok(values);
+            }
+            values.push(self.value()?);
+            self.skip_whitespace(true);
+            match self.chars.next() {
+                Some(',') => {}
+                Some(']') => return Ok(values),
+                _ => return Err(self.error("expected ',' or ']' in a list")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_parser() {
+        let text = "# an exercise\nconclusion = \"P \\\"Q\\\"\"  # comment\nlist = [\n  'a', # first\n  \"b\",\n]\nn = 12\nb = false\n";
+        assert_eq!(
+            TomlParser::new(text).parse(),
+            Ok(vec![
+                ("conclusion".to_owned(), Value::String("P \"Q\"".to_owned())),
+                (
+                    "list".to_owned(),
+                    Value::Array(vec![
+                        Value::String("a".to_owned()),
+                        Value::String("b".to_owned())
+                    ])
+                ),
+                ("n".to_owned(), Value::Integer(12)),
+                ("b".to_owned(), Value::Boolean(false)),
+            ])
+        );
+        assert_eq!(
+            TomlParser::new("a = 1\nb = \"x\ny\"").parse(),
+            Err("line 2: a string is not closed".to_owned())
+        );
+        assert_eq!(
+            TomlParser::new("a = [1, 2\nb = 3").parse(),
+            Err("line 2: expected ',' or ']' in a list".to_owned())
+        );
+        assert_eq!(
+            TomlParser::new("a = 1 2").parse(),
+            Err("line 1: expected the end of the line after 'a'".to_owned())
+        );
+        assert_eq!(
+            TomlParser::new("a = 1\na = 2").parse(),
+            Err("line 2: 'a' is set twice".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_exercise_from_toml() {
+        let exercise = Exercise::from_toml(
+            "premises = [\"P\", \"P → Q\"]\nconclusion = \"Q\"\nrule_set = \"forallx\"\nmodal_system = \"S4\"\nmax_lines = 4\nfeedback = \"verdict\"",
+        )
+        .unwrap();
+        assert_eq!(exercise.template(), vec!["P", "P → Q", "Q"]);
+        assert_eq!(exercise.rule_set, RuleSet::forallx().with_modal_system(ModalSystem::S4));
+        assert_eq!(exercise.max_lines, Some(4));
+        assert_eq!(exercise.boxed_constants, None);
+        assert_eq!(exercise.feedback, Feedback::Verdict);
+        assert_eq!(exercise.allowed_variable_names, default_variable_names!());
+
+        let message = |text: &str| Exercise::from_toml(text).unwrap_err().message;
+        assert_eq!(message("premises = [\"P\"]"), "the exercise file has no conclusion.");
+        assert_eq!(message("conclusion = \"P ∧\""), "the conclusion ('P ∧') could not be parsed.");
+        assert_eq!(
+            message("conclusion = \"P\"\nmax_lines = \"4\""),
+            "the setting 'max_lines' should be a number."
+        );
+        assert_eq!(
+            message("conclusion = \"P\"\nrules = \"lpl\""),
+            "there is no setting called 'rules'."
+        );
+        assert_eq!(
+            message("conclusion = \"P\"\nrule_set = \"fitch\""),
+            "'fitch' is not a rule set."
+        );
+        assert_eq!(
+            message("conclusion = \"P\"\nvariables = [\"x\", 1]"),
+            "the setting 'variables' should only contain strings."
+        );
+    }
+}
@@ -7,13 +7,22 @@ pub fn proof_to_latex(proof: &[ProofLine]) -> String {
     let mut prev_depth = 1;
     let mut is_hypo = true;
     let proof_str = proof.iter().fold(String::new(), |mut output, l| {
+        if l.is_comment_line() {
+            let _ = writeln!(output, "{}", comment_to_latex(l));
+            return output;
+        }
         if l.is_fitch_bar_line {
             is_hypo = false;
         }
         let part1 = if l.depth == prev_depth + 1 {
             prev_depth += 1;
-            is_hypo = true;
-            "\\open\n"
+            // a strict subproof has no hypothesis
+            is_hypo = !l.is_strict_subproof_bar;
+            if l.is_strict_subproof_bar {
+                "\\open % strict subproof\n"
+            } else {
+                "\\open\n"
+            }
         } else if l.depth == prev_depth - 1 {
             prev_depth -= 1;
             "\\close\n"
@@ -22,6 +31,9 @@ pub fn proof_to_latex(proof: &[ProofLine]) -> String {
         };
         if l.line_num.is_none() {
             let _ = write!(output, "{}", part1);
+            if l.comment.is_some() {
+                let _ = writeln!(output, "{}", comment_to_latex(l));
+            }
             return output;
         }
         let part2 = format!(
@@ -45,28 +57,47 @@ pub fn proof_to_latex(proof: &[ProofLine]) -> String {
             Some(just) => justification_to_latex(just),
             _ => "".to_string(),
         };
-        let _ = writeln!(output, "{}{}{}", part1, part2, part3);
+        let part4 = match &l.comment {
+            Some(_) => format!(" {}", comment_to_latex(l)),
+            None => "".to_string(),
+        };
+        let _ =
+            writeln!(output, "{}{}{}", part1, remove_redundant_spaces(&(part2 + &part3)), part4);
         output
     });
     format!("{}{}{}", "$\n\\begin{nd}\n", proof_str, "\\end{nd}\n$")
-        .replace("  ", " ")
-        .replace("{ ", "{")
-        .replace(" }", "}")
-        .replace(" \\", "\\")
 }
 
 /* ------------------ PRIVATE -------------------- */
 
+/// Removes the spaces that are not needed from a line of LaTeX output. This is not done on the
+/// whole output at once, because the text in comments should be left alone.
+fn remove_redundant_spaces(latex: &str) -> String {
+    latex.replace("  ", " ").replace("{ ", "{").replace(" }", "}").replace(" \\", "\\")
+}
+
+/// Turns the comment of a [ProofLine] into a LaTeX comment. The `#` or `//` that starts the comment
+/// is replaced by a `%`.
+fn comment_to_latex(line: &ProofLine) -> String {
+    let comment = line.comment.as_deref().unwrap_or_default();
+    let text = comment.strip_prefix('#').or_else(|| comment.strip_prefix("//")).unwrap_or(comment);
+    format!("%{text}")
+}
+
 /// Converts a [Wff] to a LaTeX string. This uses [format_wff] under the hood.
 fn wff_to_latex(wff: &Wff) -> String {
     let formatted = format_wff(wff);
 
     // better too many spaces then not enough...
     // we will eliminate duplicate spaces later
+    // underscores in names are not meant as subscripts
     formatted
+        .replace('_', "\\_")
         .replace('∧', " \\land ")
         .replace('∨', " \\lor ")
         .replace('¬', " \\neg ")
+        .replace('□', " \\Box ")
+        .replace('◇', " \\Diamond ")
         .replace('→', " \\rightarrow ")
         .replace('↔', " \\leftrightarrow ")
         .replace('⊥', " \\bot ")
@@ -105,5 +136,29 @@ fn justification_to_latex(just: &Justification) -> String {
         Justification::ForallElim(n) => format!("\\Ae{{{n}}}"),
         Justification::ExistsIntro(n) => format!("\\Ei{{{n}}}"),
         Justification::ExistsElim(n, (a, b)) => format!("\\Ee{{{n},{a}-{b}}}"),
+        Justification::TautCon(ns) => format!(
+            "\\tc{{{}}}",
+            ns.iter().map(|n| n.to_string()).collect::<Vec<String>>().join(",")
+        ),
+        Justification::FoCon(ns) => format!(
+            "\\fc{{{}}}",
+            ns.iter().map(|n| n.to_string()).collect::<Vec<String>>().join(",")
+        ),
+        Justification::AnaCon(ns) => format!(
+            "\\ac{{{}}}",
+            ns.iter().map(|n| n.to_string()).collect::<Vec<String>>().join(",")
+        ),
+        Justification::DisjunctiveSyllogism(n, m) => format!("\\ds{{{n},{m}}}"),
+        Justification::ModusTollens(n, m) => format!("\\mt{{{n},{m}}}"),
+        Justification::DoubleNegationElim(n) => format!("\\dne{{{n}}}"),
+        Justification::ExcludedMiddle((a, b), (c, d)) => format!("\\lem{{{a}-{b},{c}-{d}}}"),
+        Justification::IndirectProof((a, b)) => format!("\\ip{{{a}-{b}}}"),
+        Justification::DeMorgan(n) => format!("\\dem{{{n}}}"),
+        Justification::ConversionOfQuantifiers(n) => format!("\\cq{{{n}}}"),
+        Justification::BoxIntro((a, b)) => format!("\\nci{{{a}-{b}}}"),
+        Justification::BoxElim(n) => format!("\\nce{{{n}}}"),
+        Justification::DiamondIntro(n) => format!("\\dmi{{{n}}}"),
+        Justification::DiamondElim(n) => format!("\\dme{{{n}}}"),
+        Justification::BoxReit(n) => format!("\\rbox{{{n}}}"),
     }
 }
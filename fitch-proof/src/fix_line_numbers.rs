@@ -36,6 +36,13 @@ pub fn fix_line_numbers(proof_lines: &mut [ProofLine]) {
                 Justification::AndIntro(ns) => {
                     Justification::AndIntro(ns.iter().map(new_val).collect())
                 }
+                Justification::TautCon(ns) => {
+                    Justification::TautCon(ns.iter().map(new_val).collect())
+                }
+                Justification::FoCon(ns) => Justification::FoCon(ns.iter().map(new_val).collect()),
+                Justification::AnaCon(ns) => {
+                    Justification::AnaCon(ns.iter().map(new_val).collect())
+                }
                 Justification::AndElim(n) => Justification::AndElim(new_val(n)),
                 Justification::OrIntro(n) => Justification::OrIntro(new_val(n)),
                 Justification::OrElim(n, subs) => Justification::OrElim(
@@ -74,6 +81,33 @@ pub fn fix_line_numbers(proof_lines: &mut [ProofLine]) {
                 Justification::ImpliesElim(n, m) => {
                     Justification::ImpliesElim(new_val(n), new_val(m))
                 }
+                Justification::DisjunctiveSyllogism(n, m) => {
+                    Justification::DisjunctiveSyllogism(new_val(n), new_val(m))
+                }
+                Justification::ModusTollens(n, m) => {
+                    Justification::ModusTollens(new_val(n), new_val(m))
+                }
+                Justification::DoubleNegationElim(n) => {
+                    Justification::DoubleNegationElim(new_val(n))
+                }
+                Justification::ExcludedMiddle((a, b), (c, d)) => Justification::ExcludedMiddle(
+                    (new_val(a), new_val(b)),
+                    (new_val(c), new_val(d)),
+                ),
+                Justification::IndirectProof((n, m)) => {
+                    Justification::IndirectProof((new_val(n), new_val(m)))
+                }
+                Justification::DeMorgan(n) => Justification::DeMorgan(new_val(n)),
+                Justification::ConversionOfQuantifiers(n) => {
+                    Justification::ConversionOfQuantifiers(new_val(n))
+                }
+                Justification::BoxIntro((n, m)) => {
+                    Justification::BoxIntro((new_val(n), new_val(m)))
+                }
+                Justification::BoxElim(n) => Justification::BoxElim(new_val(n)),
+                Justification::DiamondIntro(n) => Justification::DiamondIntro(new_val(n)),
+                Justification::DiamondElim(n) => Justification::DiamondElim(new_val(n)),
+                Justification::BoxReit(n) => Justification::BoxReit(new_val(n)),
             });
         }
     }
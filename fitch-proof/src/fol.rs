@@ -0,0 +1,844 @@
+use crate::data::*;
+use crate::sat::{self, Prop, SatResult};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// The largest domain in which [fo_consequence] looks for a counter-model.
+pub const MAX_DOMAIN_SIZE: usize = 4;
+
+/// The number of times that the terms in the Herbrand universe may be nested by [fo_consequence]
+/// when it tries to prove a consequence.
+const MAX_INSTANTIATION_DEPTH: usize = 2;
+
+/// The maximum number of ground terms that [fo_consequence] is willing to instantiate
+/// quantifiers with.
+const MAX_GROUND_TERMS: usize = 30;
+
+/// The maximum number of instances of quantified subformulas that [fo_consequence] is willing
+/// to generate.
+const MAX_INSTANCES: usize = 20000;
+
+/// The number of conflicts after which a single search for a model is given up.
+const MAX_CONFLICTS: usize = 20000;
+
+/// The constants, function symbols and predicates that a number of sentences talk about.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Signature {
+    pub constants: Vec<String>,
+    /// The function symbols, with their arities.
+    pub functions: Vec<(String, usize)>,
+    /// The predicates, with their arities. An atomic proposition is a predicate of arity 0.
+    pub predicates: Vec<(String, usize)>,
+}
+
+impl Signature {
+    /// Makes a [Signature] out of an "arity set", as collected by the checker. Names that start
+    /// with an uppercase letter are predicates; the other names are constants (if their arity is
+    /// 0) or function symbols.
+    ///
+    /// If some name occurs with more than one arity, [None] is returned.
+    pub fn from_arity_set(arity_set: &HashSet<(String, usize)>) -> Option<Signature> {
+        let mut arity_set: Vec<&(String, usize)> = arity_set.iter().collect();
+        arity_set.sort();
+        if arity_set.windows(2).any(|w| w[0].0 == w[1].0) {
+            return None;
+        }
+        let mut signature = Signature::default();
+        for (name, arity) in arity_set {
+            signature.add(name, *arity, name.chars().next()?.is_uppercase());
+        }
+        Some(signature)
+    }
+
+    /// Adds the symbols of a [Wff] that are not in the signature yet. A name that is not bound by
+    /// a quantifier is seen as a constant.
+    pub fn add_symbols_of(&mut self, wff: &Wff) {
+        fn add_term(signature: &mut Signature, term: &Term, bound: &[String]) {
+            match term {
+                Term::Atomic(name) if !bound.contains(name) => signature.add(name, 0, false),
+                Term::Atomic(_) => {}
+                Term::FuncApp(name, args) => {
+                    signature.add(name, args.len(), false);
+                    args.iter().for_each(|t| add_term(signature, t, bound));
+                }
+            }
+        }
+        fn add_wff(signature: &mut Signature, wff: &Wff, bound: &mut Vec<String>) {
+            match wff {
+                Wff::Bottom => {}
+                Wff::And(li) | Wff::Or(li) => li.iter().for_each(|w| add_wff(signature, w, bound)),
+                Wff::Not(w) | Wff::Necessarily(w) | Wff::Possibly(w) => {
+                    add_wff(signature, w, bound)
+                }
+                Wff::Implies(w1, w2) | Wff::Bicond(w1, w2) => {
+                    add_wff(signature, w1, bound);
+                    add_wff(signature, w2, bound);
+                }
+                Wff::Forall(var, w) | Wff::Exists(var, w) => {
+                    bound.push(var.to_owned());
+                    add_wff(signature, w, bound);
+                    bound.pop();
+                }
+                Wff::Atomic(name) => signature.add(name, 0, true),
+                Wff::PredApp(name, args) => {
+                    signature.add(name, args.len(), true);
+                    args.iter().for_each(|t| add_term(signature, t, bound));
+                }
+                Wff::Equals(t1, t2) => {
+                    add_term(signature, t1, bound);
+                    add_term(signature, t2, bound);
+                }
+            }
+        }
+        add_wff(self, wff, &mut vec![]);
+    }
+
+    fn add(&mut self, name: &str, arity: usize, is_predicate: bool) {
+        let list = if is_predicate {
+            &mut self.predicates
+        } else if arity == 0 {
+            if !self.constants.iter().any(|c| c == name) {
+                self.constants.push(name.to_owned());
+            }
+            return;
+        } else {
+            &mut self.functions
+        };
+        if !list.iter().any(|(n, a)| n == name && *a == arity) {
+            list.push((name.to_owned(), arity));
+        }
+    }
+}
+
+/// The value of a function for each tuple of arguments.
+pub type FunctionTable = Vec<(Vec<usize>, usize)>;
+
+/// A finite model (structure) for a [Signature]. Its domain consists of the numbers
+/// `0..size`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Model {
+    pub size: usize,
+    /// The object that each constant refers to.
+    pub constants: Vec<(String, usize)>,
+    /// For each function symbol, its value for each tuple of arguments.
+    pub functions: Vec<(String, FunctionTable)>,
+    /// For each predicate, the tuples of which it is true. For an atomic proposition, this is
+    /// either the empty tuple (if it is true) or nothing.
+    pub predicates: Vec<(String, usize, Vec<Vec<usize>>)>,
+}
+
+impl Model {
+    /// Describes the interpretation of each symbol, like `a = 0`, `f(0) = 1, f(1) = 0`, `P = {1}`
+    /// or `A is true`.
+    pub fn describe_symbols(&self) -> Vec<String> {
+        let tuple = |t: &[usize]| match t {
+            [x] => x.to_string(),
+            _ => format!("({})", t.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")),
+        };
+        let mut parts: Vec<String> =
+            self.constants.iter().map(|(c, x)| format!("{c} = {x}")).collect();
+        parts.extend(self.functions.iter().map(|(name, table)| {
+            table
+                .iter()
+                .map(|(args, x)| format!("{name}{} = {x}", tuple_in_brackets(args)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }));
+        parts.extend(self.predicates.iter().map(|(name, arity, tuples)| {
+            if *arity == 0 {
+                format!("{name} is {}", !tuples.is_empty())
+            } else {
+                format!(
+                    "{name} = {{{}}}",
+                    tuples.iter().map(|t| tuple(t)).collect::<Vec<_>>().join(", ")
+                )
+            }
+        }));
+        parts
+    }
+}
+
+impl fmt::Display for Model {
+    /// Writes the model like this: `domain {0, 1}; a = 0; f(0) = 1, f(1) = 0; P = {1}; A is true`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = vec![format!(
+            "domain {{{}}}",
+            (0..self.size).map(|x| x.to_string()).collect::<Vec<_>>().join(", ")
+        )];
+        parts.extend(self.describe_symbols());
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// The outcome of [fo_consequence].
+#[derive(Debug, PartialEq)]
+pub enum FolResult {
+    /// The conclusion follows from the premises.
+    Consequence,
+    /// The conclusion does not follow from the premises: in this model, all premises are true
+    /// but the conclusion is false.
+    Counterexample(Model),
+    /// It could not be decided whether the conclusion follows from the premises.
+    Undecided,
+}
+
+/// Decides whether `conclusion` follows from `premises` in first-order logic with identity, as
+/// far as possible.
+///
+/// This is done by two bounded searches, so the answer may be [FolResult::Undecided] (after all,
+/// first-order consequence is undecidable in general):
+/// - a search for a proof: the premises and the negation of the conclusion are Skolemized, and
+///   their quantifiers are instantiated with the terms of the Herbrand universe (up to a certain
+///   nesting depth). If the resulting instances, together with the axioms of identity, are
+///   propositionally unsatisfiable, then the conclusion follows.
+/// - a search for a counter-model with at most [MAX_DOMAIN_SIZE] objects. The sentences are
+///   translated into propositional formulas about the interpretation of the symbols, which are
+///   satisfiable if and only if there is a counter-model of that size.
+///
+/// The `signature` should contain the symbols of the sentences; symbols that are missing from it
+/// are added.
+pub fn fo_consequence(premises: &[&Wff], conclusion: &Wff, signature: &Signature) -> FolResult {
+    let negated_conclusion = Wff::Not(Box::new(conclusion.clone()));
+    let sentences: Vec<&Wff> = premises.iter().copied().chain([&negated_conclusion]).collect();
+    let mut signature = signature.clone();
+    sentences.iter().for_each(|w| signature.add_symbols_of(w));
+
+    if refute_by_instantiation(&sentences, &signature, 0) == SatResult::Unsat {
+        return FolResult::Consequence;
+    }
+    if let Some(model) = find_model(&sentences, &signature) {
+        return FolResult::Counterexample(model);
+    }
+    for depth in 1..=MAX_INSTANTIATION_DEPTH {
+        if refute_by_instantiation(&sentences, &signature, depth) == SatResult::Unsat {
+            return FolResult::Consequence;
+        }
+    }
+    FolResult::Undecided
+}
+
+/// All tuples of length `len` of the numbers `0..size`.
+pub fn tuples(size: usize, len: usize) -> Vec<Vec<usize>> {
+    (0..len).fold(vec![vec![]], |acc, _| {
+        acc.iter().flat_map(|t| (0..size).map(move |x| [t.clone(), vec![x]].concat())).collect()
+    })
+}
+
+/* ------------------ PRIVATE -------------------- */
+
+/// Looks for a model of the sentences with at most [MAX_DOMAIN_SIZE] objects.
+fn find_model(sentences: &[&Wff], signature: &Signature) -> Option<Model> {
+    (1..=MAX_DOMAIN_SIZE).find_map(|size| {
+        let encoding = ModelEncoding::new(signature, size);
+        let mut formulas: Vec<Prop> =
+            sentences.iter().map(|w| encoding.wff(w, &mut vec![])).collect();
+        formulas.extend(encoding.constraints());
+        match sat::satisfy(&formulas, encoding.num_vars(), MAX_CONFLICTS) {
+            SatResult::Sat(assignment) => Some(encoding.model(&assignment)),
+            _ => None,
+        }
+    })
+}
+
+/// A key for a propositional variable in a [ModelEncoding].
+#[derive(PartialEq, Eq, Hash)]
+enum Key {
+    /// The constant refers to the object.
+    Constant(String, usize),
+    /// The function symbol maps the tuple of objects to the object.
+    Function(String, Vec<usize>, usize),
+    /// The predicate is true of the tuple of objects.
+    Predicate(String, Vec<usize>),
+}
+
+/// The translation of sentences into propositional formulas that are satisfiable if and only if
+/// the sentences have a model of a given size.
+///
+/// The variables of the encoding are numbered from 0 to [ModelEncoding::num_vars], so other
+/// restrictions on the models (like the meaning of the blocks language in [crate::blocks]) can use
+/// the variables after that.
+pub struct ModelEncoding<'a> {
+    signature: &'a Signature,
+    size: usize,
+    vars: HashMap<Key, usize>,
+}
+
+impl<'a> ModelEncoding<'a> {
+    pub fn new(signature: &'a Signature, size: usize) -> ModelEncoding<'a> {
+        let mut keys = vec![];
+        for c in &signature.constants {
+            keys.extend((0..size).map(|x| Key::Constant(c.to_owned(), x)));
+        }
+        for (f, arity) in &signature.functions {
+            for args in tuples(size, *arity) {
+                keys.extend((0..size).map(|x| Key::Function(f.to_owned(), args.clone(), x)));
+            }
+        }
+        for (p, arity) in &signature.predicates {
+            keys.extend(
+                tuples(size, *arity).into_iter().map(|args| Key::Predicate(p.to_owned(), args)),
+            );
+        }
+        ModelEncoding {
+            signature,
+            size,
+            vars: keys.into_iter().enumerate().map(|(i, k)| (k, i)).collect(),
+        }
+    }
+
+    fn var(&self, key: Key) -> Prop {
+        Prop::Var(self.vars[&key])
+    }
+
+    /// The number of variables of the encoding.
+    pub fn num_vars(&self) -> usize {
+        self.vars.len()
+    }
+
+    /// The variable saying that the predicate is true of the tuple of objects, if the predicate
+    /// (with this arity) is in the signature.
+    pub fn predicate(&self, name: &str, args: &[usize]) -> Option<Prop> {
+        let key = Key::Predicate(name.to_owned(), args.to_vec());
+        self.vars.get(&key).map(|v| Prop::Var(*v))
+    }
+
+    /// The formulas saying that each constant and each function value refers to exactly one
+    /// object. The first constant refers to object 0, which is no restriction, since the objects
+    /// can always be renumbered.
+    pub fn constraints(&self) -> Vec<Prop> {
+        let mut groups: Vec<Vec<Prop>> = vec![];
+        for c in &self.signature.constants {
+            groups.push((0..self.size).map(|x| self.var(Key::Constant(c.to_owned(), x))).collect());
+        }
+        for (f, arity) in &self.signature.functions {
+            for args in tuples(self.size, *arity) {
+                groups.push(
+                    (0..self.size)
+                        .map(|x| self.var(Key::Function(f.to_owned(), args.clone(), x)))
+                        .collect(),
+                );
+            }
+        }
+        let mut constraints = vec![];
+        if let Some(c) = self.signature.constants.first() {
+            constraints.push(self.var(Key::Constant(c.to_owned(), 0)));
+        }
+        for group in groups {
+            for i in 0..group.len() {
+                for j in i + 1..group.len() {
+                    constraints.push(Prop::not(Prop::and([group[i].clone(), group[j].clone()])));
+                }
+            }
+            constraints.push(Prop::or(group));
+        }
+        constraints
+    }
+
+    /// The formula saying that `term` refers to object `x`, where the variables in `env` refer to
+    /// the given objects.
+    fn term_is(&self, term: &Term, x: usize, env: &[(String, usize)]) -> Prop {
+        match term {
+            Term::Atomic(name) => match env.iter().rev().find(|(v, _)| v == name) {
+                Some((_, y)) if *y == x => Prop::t(),
+                Some(_) => Prop::f(),
+                None => self.var(Key::Constant(name.to_owned(), x)),
+            },
+            Term::FuncApp(f, args) => {
+                Prop::or(tuples(self.size, args.len()).into_iter().map(|ys| {
+                    Prop::and(
+                        zip_terms(args, &ys)
+                            .map(|(t, y)| self.term_is(t, y, env))
+                            .chain([self.var(Key::Function(f.to_owned(), ys.clone(), x))]),
+                    )
+                }))
+            }
+        }
+    }
+
+    /// The formula saying that `wff` is true, where the variables in `env` refer to the given
+    /// objects.
+    pub fn wff(&self, wff: &Wff, env: &mut Vec<(String, usize)>) -> Prop {
+        match wff {
+            Wff::Bottom => Prop::f(),
+            Wff::And(li) => Prop::and(li.iter().map(|w| self.wff(w, env)).collect::<Vec<_>>()),
+            Wff::Or(li) => Prop::or(li.iter().map(|w| self.wff(w, env)).collect::<Vec<_>>()),
+            Wff::Not(w) => Prop::not(self.wff(w, env)),
+            // the checker does not let FO Con and Ana Con look at modal sentences
+            Wff::Necessarily(_) | Wff::Possibly(_) => unreachable!(),
+            Wff::Implies(w1, w2) => Prop::implies(self.wff(w1, env), self.wff(w2, env)),
+            Wff::Bicond(w1, w2) => Prop::iff(self.wff(w1, env), self.wff(w2, env)),
+            Wff::Forall(var, w) | Wff::Exists(var, w) => {
+                let instances: Vec<Prop> = (0..self.size)
+                    .map(|x| {
+                        env.push((var.to_owned(), x));
+                        let instance = self.wff(w, env);
+                        env.pop();
+                        instance
+                    })
+                    .collect();
+                if matches!(wff, Wff::Forall(..)) {
+                    Prop::and(instances)
+                } else {
+                    Prop::or(instances)
+                }
+            }
+            Wff::Atomic(p) => self.var(Key::Predicate(p.to_owned(), vec![])),
+            Wff::PredApp(p, args) => {
+                Prop::or(tuples(self.size, args.len()).into_iter().map(|xs| {
+                    Prop::and(
+                        zip_terms(args, &xs)
+                            .map(|(t, x)| self.term_is(t, x, env))
+                            .chain([self.var(Key::Predicate(p.to_owned(), xs.clone()))]),
+                    )
+                }))
+            }
+            Wff::Equals(t1, t2) => Prop::or(
+                (0..self.size)
+                    .map(|x| Prop::and([self.term_is(t1, x, env), self.term_is(t2, x, env)])),
+            ),
+        }
+    }
+
+    /// Reads the model off a satisfying assignment of the variables.
+    pub fn model(&self, assignment: &[bool]) -> Model {
+        let holds = |key: Key| assignment[self.vars[&key]];
+        Model {
+            size: self.size,
+            constants: self
+                .signature
+                .constants
+                .iter()
+                .map(|c| {
+                    let x = (0..self.size).find(|x| holds(Key::Constant(c.to_owned(), *x)));
+                    (c.to_owned(), x.unwrap())
+                })
+                .collect(),
+            functions: self
+                .signature
+                .functions
+                .iter()
+                .map(|(f, arity)| {
+                    let table = tuples(self.size, *arity)
+                        .into_iter()
+                        .map(|args| {
+                            let x = (0..self.size)
+                                .find(|x| holds(Key::Function(f.to_owned(), args.clone(), *x)));
+                            (args, x.unwrap())
+                        })
+                        .collect();
+                    (f.to_owned(), table)
+                })
+                .collect(),
+            predicates: self
+                .signature
+                .predicates
+                .iter()
+                .map(|(p, arity)| {
+                    let extension = tuples(self.size, *arity)
+                        .into_iter()
+                        .filter(|args| holds(Key::Predicate(p.to_owned(), args.clone())))
+                        .collect();
+                    (p.to_owned(), *arity, extension)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Tries to show that the sentences have no model at all, by instantiating their quantifiers (see
+/// [fo_consequence]) with the terms that are nested at most `depth` times. Returns
+/// [SatResult::Unsat] if that worked.
+fn refute_by_instantiation(sentences: &[&Wff], signature: &Signature, depth: usize) -> SatResult {
+    let mut skolem_functions: Vec<(String, usize)> = vec![];
+    let skolemized: Vec<Wff> = sentences
+        .iter()
+        .map(|w| skolemize(&nnf(w, true), &mut vec![], &mut skolem_functions))
+        .collect();
+
+    // the Herbrand universe, up to the given depth
+    let mut constants: Vec<Term> =
+        signature.constants.iter().map(|c| Term::Atomic(c.to_owned())).collect();
+    let mut functions = signature.functions.clone();
+    for (f, arity) in skolem_functions {
+        if arity == 0 {
+            constants.push(Term::Atomic(f));
+        } else {
+            functions.push((f, arity));
+        }
+    }
+    if constants.is_empty() {
+        constants.push(Term::Atomic("#c".to_string()));
+    }
+    let mut universe = constants;
+    for _ in 0..depth {
+        let mut next = universe.clone();
+        for (f, arity) in &functions {
+            for args in tuples(universe.len(), *arity) {
+                let term = Term::FuncApp(
+                    f.to_owned(),
+                    args.iter().map(|i| universe[*i].clone()).collect(),
+                );
+                if !next.contains(&term) {
+                    next.push(term);
+                }
+            }
+        }
+        if next.len() > MAX_GROUND_TERMS {
+            return SatResult::Unknown;
+        }
+        universe = next;
+    }
+
+    let mut grounding = Grounding {
+        universe,
+        atoms: vec![],
+        instances: 0,
+    };
+    let mut formulas = vec![];
+    for wff in &skolemized {
+        match grounding.ground(wff, &mut vec![]) {
+            Some(p) => formulas.push(p),
+            None => return SatResult::Unknown,
+        }
+    }
+    match grounding.identity_axioms() {
+        Some(axioms) => formulas.extend(axioms),
+        None => return SatResult::Unknown,
+    }
+    sat::satisfy(&formulas, grounding.atoms.len(), MAX_CONFLICTS)
+}
+
+/// The translation of quantifier-free instances of sentences to propositional formulas, in which
+/// each ground atom becomes a propositional variable.
+struct Grounding {
+    universe: Vec<Term>,
+    /// The ground atoms; the index of an atom is its propositional variable.
+    atoms: Vec<Wff>,
+    instances: usize,
+}
+
+impl Grounding {
+    fn atom(&mut self, atom: Wff) -> Prop {
+        // an identity and its mirror image are the same variable
+        let mirror = match &atom {
+            Wff::Equals(t1, t2) => Some(Wff::Equals(t2.clone(), t1.clone())),
+            _ => None,
+        };
+        match self.atoms.iter().position(|a| a == &atom || Some(a) == mirror.as_ref()) {
+            Some(i) => Prop::Var(i),
+            None => {
+                self.atoms.push(atom);
+                Prop::Var(self.atoms.len() - 1)
+            }
+        }
+    }
+
+    /// Grounds a Skolemized sentence in negation normal form: each universal quantifier becomes
+    /// the conjunction of its instances for all terms in the universe. Returns [None] if there are
+    /// too many instances.
+    fn ground(&mut self, wff: &Wff, env: &mut Vec<(String, Term)>) -> Option<Prop> {
+        Some(match wff {
+            Wff::Bottom => Prop::f(),
+            Wff::And(li) => {
+                Prop::and(li.iter().map(|w| self.ground(w, env)).collect::<Option<Vec<_>>>()?)
+            }
+            Wff::Or(li) => {
+                Prop::or(li.iter().map(|w| self.ground(w, env)).collect::<Option<Vec<_>>>()?)
+            }
+            Wff::Not(w) => Prop::not(self.ground(w, env)?),
+            Wff::Forall(var, w) => {
+                let mut instances = vec![];
+                for t in self.universe.clone() {
+                    self.instances += 1;
+                    if self.instances > MAX_INSTANCES {
+                        return None;
+                    }
+                    env.push((var.to_owned(), t));
+                    instances.push(self.ground(w, env));
+                    env.pop();
+                }
+                Prop::and(instances.into_iter().collect::<Option<Vec<_>>>()?)
+            }
+            Wff::Atomic(_) => self.atom(wff.clone()),
+            Wff::PredApp(p, args) => self.atom(Wff::PredApp(
+                p.to_owned(),
+                args.iter().map(|t| substitute(t, env)).collect(),
+            )),
+            Wff::Equals(t1, t2) => {
+                let (t1, t2) = (substitute(t1, env), substitute(t2, env));
+                if t1 == t2 {
+                    Prop::t()
+                } else {
+                    self.atom(Wff::Equals(t1, t2))
+                }
+            }
+            // these do not occur in a Skolemized sentence in negation normal form
+            Wff::Implies(..) | Wff::Bicond(..) | Wff::Exists(..) => unreachable!(),
+            // and the checker does not let FO Con look at modal sentences
+            Wff::Necessarily(_) | Wff::Possibly(_) => unreachable!(),
+        })
+    }
+
+    /// The instances of the axioms of identity (symmetry is built into [Grounding::atom] and
+    /// reflexivity into [Grounding::ground]) for the ground terms that occur in the atoms. Returns
+    /// [None] if there are too many of those terms.
+    fn identity_axioms(&mut self) -> Option<Vec<Prop>> {
+        if !self.atoms.iter().any(|a| matches!(a, Wff::Equals(..))) {
+            return Some(vec![]);
+        }
+        let mut terms: Vec<Term> = vec![];
+        for atom in &self.atoms {
+            match atom {
+                Wff::PredApp(_, args) => args.iter().for_each(|t| add_subterms(t, &mut terms)),
+                Wff::Equals(t1, t2) => {
+                    add_subterms(t1, &mut terms);
+                    add_subterms(t2, &mut terms);
+                }
+                _ => {}
+            }
+        }
+        if terms.len() > MAX_GROUND_TERMS {
+            return None;
+        }
+        let eq = |g: &mut Grounding, s: &Term, t: &Term| {
+            if s == t {
+                Prop::t()
+            } else {
+                g.atom(Wff::Equals(s.clone(), t.clone()))
+            }
+        };
+        let mut axioms = vec![];
+        // transitivity
+        for s in &terms {
+            for t in &terms {
+                for u in &terms {
+                    if s != t && t != u && s != u {
+                        axioms.push(Prop::implies(
+                            Prop::and([eq(self, s, t), eq(self, t, u)]),
+                            eq(self, s, u),
+                        ));
+                    }
+                }
+            }
+        }
+        // congruence for function symbols
+        for s in &terms {
+            for t in &terms {
+                if let (Term::FuncApp(f, args1), Term::FuncApp(g, args2)) = (s, t) {
+                    if f == g && args1.len() == args2.len() && s != t {
+                        let premise: Vec<Prop> =
+                            args1.iter().zip(args2).map(|(a, b)| eq(self, a, b)).collect();
+                        axioms.push(Prop::implies(Prop::and(premise), eq(self, s, t)));
+                    }
+                }
+            }
+        }
+        // congruence for predicates
+        let pred_apps: Vec<Wff> =
+            self.atoms.iter().filter(|a| matches!(a, Wff::PredApp(..))).cloned().collect();
+        for a1 in &pred_apps {
+            for a2 in &pred_apps {
+                if let (Wff::PredApp(p, args1), Wff::PredApp(q, args2)) = (a1, a2) {
+                    if p == q && args1.len() == args2.len() && a1 != a2 {
+                        let mut premise: Vec<Prop> =
+                            args1.iter().zip(args2).map(|(a, b)| eq(self, a, b)).collect();
+                        premise.push(self.atom(a1.clone()));
+                        axioms.push(Prop::implies(Prop::and(premise), self.atom(a2.clone())));
+                    }
+                }
+            }
+        }
+        Some(axioms)
+    }
+}
+
+/// Turns a [Wff] into negation normal form, in which negations only occur in front of atoms and
+/// there are no implications and biconditionals. If `positive` is false, the negation of the
+/// [Wff] is turned into negation normal form instead.
+fn nnf(wff: &Wff, positive: bool) -> Wff {
+    // a conjunction under a negation becomes a disjunction, and vice versa
+    let and = |li| {
+        if positive {
+            Wff::And(li)
+        } else {
+            Wff::Or(li)
+        }
+    };
+    let or = |li| {
+        if positive {
+            Wff::Or(li)
+        } else {
+            Wff::And(li)
+        }
+    };
+    match wff {
+        Wff::Bottom if positive => Wff::Bottom,
+        Wff::Bottom => Wff::And(vec![]),
+        Wff::Not(w) => nnf(w, !positive),
+        Wff::And(li) => and(li.iter().map(|w| nnf(w, positive)).collect()),
+        Wff::Or(li) => or(li.iter().map(|w| nnf(w, positive)).collect()),
+        Wff::Implies(w1, w2) => or(vec![nnf(w1, !positive), nnf(w2, positive)]),
+        Wff::Bicond(w1, w2) => Wff::Or(vec![
+            Wff::And(vec![nnf(w1, true), nnf(w2, positive)]),
+            Wff::And(vec![nnf(w1, false), nnf(w2, !positive)]),
+        ]),
+        Wff::Forall(var, w) if positive => Wff::Forall(var.to_owned(), Box::new(nnf(w, true))),
+        Wff::Forall(var, w) => Wff::Exists(var.to_owned(), Box::new(nnf(w, false))),
+        Wff::Exists(var, w) if positive => Wff::Exists(var.to_owned(), Box::new(nnf(w, true))),
+        Wff::Exists(var, w) => Wff::Forall(var.to_owned(), Box::new(nnf(w, false))),
+        atom if positive => atom.clone(),
+        atom => Wff::Not(Box::new(atom.clone())),
+    }
+}
+
+/// Removes the existential quantifiers from a [Wff] in negation normal form, by replacing each
+/// existentially quantified variable by a new Skolem function applied to the universally
+/// quantified variables around it. The new function symbols (with their arities) are added to
+/// `skolem_functions`; their names start with `#`, so they cannot clash with the names in a proof.
+fn skolemize(
+    wff: &Wff,
+    universals: &mut Vec<String>,
+    skolem_functions: &mut Vec<(String, usize)>,
+) -> Wff {
+    match wff {
+        Wff::And(li) => {
+            Wff::And(li.iter().map(|w| skolemize(w, universals, skolem_functions)).collect())
+        }
+        Wff::Or(li) => {
+            Wff::Or(li.iter().map(|w| skolemize(w, universals, skolem_functions)).collect())
+        }
+        Wff::Forall(var, w) => {
+            universals.push(var.to_owned());
+            let w = skolemize(w, universals, skolem_functions);
+            universals.pop();
+            Wff::Forall(var.to_owned(), Box::new(w))
+        }
+        Wff::Exists(var, w) => {
+            let name = format!("#sk{}", skolem_functions.len());
+            skolem_functions.push((name.to_owned(), universals.len()));
+            let term = if universals.is_empty() {
+                Term::Atomic(name)
+            } else {
+                Term::FuncApp(name, universals.iter().map(|v| Term::Atomic(v.to_owned())).collect())
+            };
+            let w = substitute_in_wff(w, var, &term);
+            skolemize(&w, universals, skolem_functions)
+        }
+        literal => literal.clone(),
+    }
+}
+
+/// Replaces the free occurrences of variable `var` in a [Wff] by `term`.
+pub(crate) fn substitute_in_wff(wff: &Wff, var: &str, term: &Term) -> Wff {
+    let env = [(var.to_owned(), term.clone())];
+    let sub = |w: &Wff| substitute_in_wff(w, var, term);
+    match wff {
+        Wff::Bottom | Wff::Atomic(_) => wff.clone(),
+        Wff::And(li) => Wff::And(li.iter().map(sub).collect()),
+        Wff::Or(li) => Wff::Or(li.iter().map(sub).collect()),
+        Wff::Not(w) => Wff::Not(Box::new(sub(w))),
+        Wff::Necessarily(w) => Wff::Necessarily(Box::new(sub(w))),
+        Wff::Possibly(w) => Wff::Possibly(Box::new(sub(w))),
+        Wff::Implies(w1, w2) => Wff::Implies(Box::new(sub(w1)), Box::new(sub(w2))),
+        Wff::Bicond(w1, w2) => Wff::Bicond(Box::new(sub(w1)), Box::new(sub(w2))),
+        Wff::Forall(v, _) | Wff::Exists(v, _) if v == var => wff.clone(),
+        Wff::Forall(v, w) => Wff::Forall(v.to_owned(), Box::new(sub(w))),
+        Wff::Exists(v, w) => Wff::Exists(v.to_owned(), Box::new(sub(w))),
+        Wff::PredApp(p, args) => {
+            Wff::PredApp(p.to_owned(), args.iter().map(|t| substitute(t, &env)).collect())
+        }
+        Wff::Equals(t1, t2) => Wff::Equals(substitute(t1, &env), substitute(t2, &env)),
+    }
+}
+
+/// Replaces the variables in a [Term] by the terms that `env` assigns to them (the last
+/// assignment counts).
+fn substitute(term: &Term, env: &[(String, Term)]) -> Term {
+    match term {
+        Term::Atomic(name) => match env.iter().rev().find(|(v, _)| v == name) {
+            Some((_, t)) => t.clone(),
+            None => term.clone(),
+        },
+        Term::FuncApp(f, args) => {
+            Term::FuncApp(f.to_owned(), args.iter().map(|t| substitute(t, env)).collect())
+        }
+    }
+}
+
+/// Adds a [Term] and all its subterms to `terms`, without duplicates.
+fn add_subterms(term: &Term, terms: &mut Vec<Term>) {
+    if let Term::FuncApp(_, args) = term {
+        args.iter().for_each(|t| add_subterms(t, terms));
+    }
+    if !terms.contains(term) {
+        terms.push(term.clone());
+    }
+}
+
+fn zip_terms<'b>(
+    terms: &'b [Term],
+    objects: &'b [usize],
+) -> impl Iterator<Item = (&'b Term, usize)> {
+    terms.iter().zip(objects.iter().copied())
+}
+
+/// Writes a tuple of arguments like `(0, 1)`.
+fn tuple_in_brackets(args: &[usize]) -> String {
+    format!("({})", args.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_logical_expression_string;
+
+    fn wff(s: &str) -> Wff {
+        parse_logical_expression_string(s).unwrap()
+    }
+
+    fn consequence(premises: &[&str], conclusion: &str) -> FolResult {
+        let premises: Vec<Wff> = premises.iter().map(|s| wff(s)).collect();
+        fo_consequence(
+            &premises.iter().collect::<Vec<_>>(),
+            &wff(conclusion),
+            &Signature::default(),
+        )
+    }
+
+    #[test]
+    fn test_consequences() {
+        assert_eq!(consequence(&["∀x P(x)"], "P(a)"), FolResult::Consequence);
+        assert_eq!(consequence(&["∀x (P(x) → Q(x))", "P(a)"], "∃y Q(y)"), FolResult::Consequence);
+        assert_eq!(consequence(&["∃x ∀y R(x, y)"], "∀y ∃x R(x, y)"), FolResult::Consequence);
+        assert_eq!(consequence(&["a = b", "P(f(a))"], "P(f(b))"), FolResult::Consequence);
+        assert_eq!(consequence(&["a = b", "b = c"], "c = a"), FolResult::Consequence);
+        assert_eq!(consequence(&[], "∃x (P(x) → ∀y P(y))"), FolResult::Consequence);
+        assert_eq!(consequence(&["¬∀x P(x)"], "∃x ¬P(x)"), FolResult::Consequence);
+    }
+
+    #[test]
+    fn test_counterexamples() {
+        let FolResult::Counterexample(model) = consequence(&["∀y ∃x R(x, y)"], "∃x ∀y R(x, y)")
+        else {
+            panic!("expected a counter-model")
+        };
+        assert_eq!(model.size, 2);
+        let FolResult::Counterexample(model) = consequence(&["P(a)"], "P(b)") else {
+            panic!("expected a counter-model")
+        };
+        assert_eq!(model.to_string(), "domain {0, 1}; a = 0; b = 1; P = {0}");
+        let FolResult::Counterexample(model) = consequence(&["f(a) = b", "A"], "f(b) = a") else {
+            panic!("expected a counter-model")
+        };
+        assert_eq!(model.size, 2);
+    }
+
+    #[test]
+    fn test_undecided() {
+        // every model of the premises is infinite
+        assert_eq!(
+            consequence(&["∀x ∀y (s(x) = s(y) → x = y)", "∀x ¬(s(x) = c)"], "P"),
+            FolResult::Undecided
+        );
+    }
+}
@@ -2,7 +2,8 @@ use std::iter::zip;
 
 use crate::data::*;
 
-/// Formats a proof.
+/// Formats a proof. Lines that could not be parsed (see [ProofLine::parse_error]) are left as
+/// they are.
 ///
 /// PRECONDITION (panics otherwise): !proof_lines.is_empty()
 pub fn format_proof(proof_lines: Vec<ProofLine>) -> String {
@@ -10,8 +11,8 @@ pub fn format_proof(proof_lines: Vec<ProofLine>) -> String {
     let mut line_strings: Vec<String> = proof_lines
         .iter()
         .map(|pl| {
-            if pl.line_num.is_some() {
-                pl.line_num.unwrap().to_string()
+            if let Some(num) = pl.line_num {
+                num.to_string()
             } else {
                 "".to_string()
             }
@@ -21,19 +22,24 @@ pub fn format_proof(proof_lines: Vec<ProofLine>) -> String {
     pad_to_same_length(&mut line_strings, 1);
 
     for (line, line_string) in zip(&proof_lines, &mut line_strings) {
-        line_string.push_str(format!("|{}", " |".repeat(line.depth - 1)).as_str());
+        if !line.is_comment_line() {
+            line_string.push_str(format!("|{}", " |".repeat(line.depth - 1)).as_str());
+        }
     }
 
     for (line, line_string) in zip(&proof_lines, &mut line_strings) {
+        if line.is_strict_subproof_bar {
+            line_string.push('□');
+        }
         if line.is_fitch_bar_line {
             line_string.push_str("----");
         }
     }
 
     for (line, line_string) in zip(&proof_lines, &mut line_strings) {
-        if line.constant_between_square_brackets.is_some() {
+        if let Some(constant) = &line.constant_between_square_brackets {
             line_string.push_str(" [");
-            line_string.push_str(match line.constant_between_square_brackets.as_ref().unwrap() {
+            line_string.push_str(match constant {
                 Term::Atomic(str) => str,
                 _ => panic!(),
             });
@@ -42,22 +48,44 @@ pub fn format_proof(proof_lines: Vec<ProofLine>) -> String {
     }
 
     for (line, line_string) in zip(&proof_lines, &mut line_strings) {
-        if line.sentence.is_some() {
+        if let Some(wff) = &line.sentence {
             line_string.push(' ');
-            line_string.push_str(&format_wff(line.sentence.as_ref().unwrap()));
+            line_string.push_str(&format_wff(wff));
         }
     }
 
     pad_to_same_length(&mut line_strings, 9);
 
     for (line, line_string) in zip(&proof_lines, &mut line_strings) {
-        if line.justification.is_some() {
-            line_string.push_str(&format_justification(line.justification.as_ref().unwrap()));
+        if let Some(just) = &line.justification {
+            line_string.push_str(&format_justification(just));
+        }
+    }
+
+    for (line, line_string) in zip(&proof_lines, &mut line_strings) {
+        if line.is_comment_line() {
+            line_string.clear();
+        } else {
+            remove_whitespace_at_end(line_string);
+        }
+    }
+
+    // trailing comments are aligned, a bit to the right of the longest line
+    let comment_column = line_strings.iter().map(|x| x.chars().count()).max().unwrap() + 4;
+    for (line, line_string) in zip(&proof_lines, &mut line_strings) {
+        if let Some(comment) = &line.comment {
+            if !line.is_comment_line() {
+                line_string.push_str(&" ".repeat(comment_column - line_string.chars().count()));
+            }
+            line_string.push_str(comment);
         }
     }
 
-    for line_string in &mut line_strings {
-        remove_whitespace_at_end(line_string);
+    // a line that could not be parsed is kept as it was written, comment and all
+    for (line, line_string) in zip(&proof_lines, &mut line_strings) {
+        if let Some(location) = line.parse_error.as_ref().and_then(|e| e.location.as_ref()) {
+            *line_string = location.source_line.trim_end().to_owned();
+        }
     }
 
     line_strings.join("\n")
@@ -77,6 +105,8 @@ pub fn format_wff(wff: &Wff) -> String {
                 format!("({})", li.iter().map(wff_with_brackets).collect::<Vec<_>>().join(" ∧ "))
             }
             Wff::Not(w) => format!("¬{}", wff_with_brackets(w)),
+            Wff::Necessarily(w) => format!("□{}", wff_with_brackets(w)),
+            Wff::Possibly(w) => format!("◇{}", wff_with_brackets(w)),
             Wff::Implies(w1, w2) => {
                 format!("({} → {})", wff_with_brackets(w1), wff_with_brackets(w2))
             }
@@ -132,7 +162,7 @@ fn pad_to_same_length(strings: &mut [String], extra: usize) {
 }
 
 /// Makes a [String] out of a [Justification].
-fn format_justification(just: &Justification) -> String {
+pub(crate) fn format_justification(just: &Justification) -> String {
     match just {
         Justification::Reit(n) => format!("Reit: {n}"),
         Justification::AndIntro(ns) => {
@@ -158,6 +188,27 @@ fn format_justification(just: &Justification) -> String {
         Justification::ForallElim(n) => format!("∀ Elim: {n}"),
         Justification::ExistsIntro(n) => format!("∃ Intro: {n}"),
         Justification::ExistsElim(n, (a, b)) => format!("∃ Elim: {n}, {a}-{b}"),
+        Justification::TautCon(ns) => {
+            format!("Taut Con: {}", ns.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", "))
+        }
+        Justification::FoCon(ns) => {
+            format!("FO Con: {}", ns.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", "))
+        }
+        Justification::AnaCon(ns) => {
+            format!("Ana Con: {}", ns.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", "))
+        }
+        Justification::DisjunctiveSyllogism(n, m) => format!("DS: {n}, {m}"),
+        Justification::ModusTollens(n, m) => format!("MT: {n}, {m}"),
+        Justification::DoubleNegationElim(n) => format!("DNE: {n}"),
+        Justification::ExcludedMiddle((a, b), (c, d)) => format!("LEM: {a}-{b}, {c}-{d}"),
+        Justification::IndirectProof((a, b)) => format!("IP: {a}-{b}"),
+        Justification::DeMorgan(n) => format!("DeM: {n}"),
+        Justification::ConversionOfQuantifiers(n) => format!("CQ: {n}"),
+        Justification::BoxIntro((a, b)) => format!("□ Intro: {a}-{b}"),
+        Justification::BoxElim(n) => format!("□ Elim: {n}"),
+        Justification::DiamondIntro(n) => format!("◇ Intro: {n}"),
+        Justification::DiamondElim(n) => format!("◇ Elim: {n}"),
+        Justification::BoxReit(n) => format!("R□: {n}"),
     }
 }
 
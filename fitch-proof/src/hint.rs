@@ -0,0 +1,1013 @@
+use crate::data::*;
+use crate::fol::substitute_in_wff;
+use crate::formatter::format_wff;
+use crate::proof::*;
+use crate::template::{self, TemplateMatching};
+use crate::truth_table::{self, TruthTableResult};
+
+/// The sentences and subproofs that a new line at some place in the proof may cite.
+struct Context<'a> {
+    lines: Vec<(usize, &'a Wff)>,
+    subproofs: Vec<(usize, usize)>,
+}
+
+impl Context<'_> {
+    /// Returns the first line in the context that contains `wff`, if there is one.
+    fn find(&self, wff: &Wff) -> Option<usize> {
+        self.lines.iter().find(|(_, w)| *w == wff).map(|(n, _)| *n)
+    }
+}
+
+/// One application of a rule: the rule, and the lines and subproofs that it cites (like `3` or
+/// `3-5`).
+struct Step {
+    rule: Rule,
+    cited: Vec<(usize, Option<usize>)>,
+}
+
+impl Step {
+    fn new(rule: Rule, lines: &[usize]) -> Step {
+        Step {
+            rule,
+            cited: lines.iter().map(|&n| (n, None)).collect(),
+        }
+    }
+
+    /// All line numbers that are cited, including the first and last lines of cited subproofs.
+    fn lines(&self) -> Vec<usize> {
+        self.cited.iter().flat_map(|&(a, b)| std::iter::once(a).chain(b)).collect()
+    }
+
+    /// The cited lines and subproofs, in words, like "lines 2 and 3-5".
+    fn describe_cited(&self) -> String {
+        let cited: Vec<String> = self.cited.iter().map(|&c| format_citation(c)).collect();
+        match cited.as_slice() {
+            [] => "nothing".to_owned(),
+            [one] => format!("line {one}"),
+            [init @ .., last] => format!("lines {} and {last}", init.join(", ")),
+        }
+    }
+}
+
+/// A subproof that is still open at the end of the proof.
+struct OpenSubproof {
+    /// The first line of the subproof: its premise, or the line that introduces its boxed
+    /// constant, or (for a strict subproof) the first line after its Fitch bar.
+    first_line: usize,
+    is_strict: bool,
+}
+
+/// What the proof is working towards at the end: the sentence, and, if it is the goal of an open
+/// subproof, how that subproof will be used.
+struct Goal {
+    wff: Wff,
+    purpose: Option<Purpose>,
+}
+
+/// How an open subproof is used once it is finished: it is cited by `rule` to get `result`.
+struct Purpose {
+    first_line: usize,
+    rule: Rule,
+    result: Wff,
+}
+
+impl Proof {
+    /// Suggests the next productive step in this proof, which is usually not finished yet.
+    ///
+    /// If some line is wrong, the hint is about the first such line, since that should be fixed
+    /// first. A line without justification is seen as a sentence that the student still wants to
+    /// prove, so the hint is about how to get there. Otherwise, the hint is about the goal at the
+    /// end of the proof: the conclusion of the template, or, inside a subproof that is still open,
+    /// what that subproof is meant to prove (e.g. `B` in a subproof that assumes `A`, when the
+    /// proof is working towards `A → B`).
+    ///
+    /// The hint looks for, in this order: a rule that gives the goal in one step, an introduction
+    /// rule for the main connective of the goal, a step that derives something new from the lines
+    /// in scope, an elimination rule (∨ Elim, ∃ Elim, → Elim) that can lead to the goal, and finally
+    /// an indirect proof.
+    ///
+    /// A proof that ends with a Fitch bar (see [Proof::construct_unfinished]) is not finished: the
+    /// hint is about the goal below that Fitch bar. If the conclusion of the template does not
+    /// follow from its premises, the hint says so instead, since the proof cannot be finished.
+    pub fn hint(&self, template: Option<&[Wff]>) -> Hint {
+        if let Some(template @ [.., conclusion]) = template {
+            if let TemplateValidity::Invalid(counter_model) = template::template_validity(template)
+            {
+                return same_at_all_levels(
+                    format!(
+                        "The conclusion of the template, {}, does not follow from its premises, so it cannot be proved: {counter_model}",
+                        format_wff(conclusion)
+                    ),
+                    vec![],
+                );
+            }
+        }
+        let result = match template {
+            Some(template) => {
+                self.is_fully_correct_and_matches_template(
+                    template.to_vec(),
+                    &TemplateMatching::strict(),
+                )
+                .0
+            }
+            None => self.is_fully_correct(),
+        };
+        let errors = match result {
+            // the proof is correct so far, but the line below its Fitch bar is still to be written
+            ProofResult::Correct if self.ends_with_fitch_bar() => vec![],
+            ProofResult::Correct => {
+                return same_at_all_levels(
+                    "The proof is complete: there is nothing left to do.".to_owned(),
+                    vec![],
+                )
+            }
+            ProofResult::FatalError(err) => {
+                return same_at_all_levels(format!("First fix this fatal error: {err}"), vec![])
+            }
+            ProofResult::Error(errors) => errors,
+        };
+
+        if let Some(err) = errors
+            .iter()
+            .filter(|e| e.line.is_some() && e.code != ErrorCode::LastLineInSubproof)
+            .min_by_key(|e| e.line)
+        {
+            return self.hint_for_wrong_line(err);
+        }
+        if let Some(err) = errors.iter().find(|e| {
+            !matches!(
+                e.code,
+                ErrorCode::LastLineInSubproof
+                    | ErrorCode::TemplateConclusion
+                    | ErrorCode::TemplateNoSentences
+            )
+        }) {
+            return same_at_all_levels(format!("First fix this: {err}"), err.related_lines.clone());
+        }
+        self.hint_at_end(template.and_then(|t| t.last()))
+    }
+
+    /// Gives a hint about a line that has a mistake (a line without justification counts as a
+    /// mistake too).
+    fn hint_for_wrong_line(&self, err: &Diagnostic) -> Hint {
+        // the unwrap works: only diagnostics with a line are passed to this function
+        let n = err.line.unwrap();
+        let Some(wff) = self.sentence_at(n) else {
+            return self.hint_for_mistake(n, err);
+        };
+        let ctx = self.context(self.scope_of_line(n));
+        if err.code == ErrorCode::MissingJustification {
+            return self.goal_hint(wff, &ctx, Some(n));
+        }
+        match self.one_step(wff, &ctx) {
+            Some(step) => hint(
+                format!("Line {n} is not correct; it does follow in one step, though."),
+                format!("Line {n} is not correct, but it follows by {}.", self.name(step.rule)),
+                format!("Justify line {n} with '{}'.", self.justification(&step)),
+                std::iter::once(n).chain(step.lines()).collect(),
+            ),
+            None => self.hint_for_mistake(n, err),
+        }
+    }
+
+    /// Gives a hint that just points out a mistake on line `n`.
+    fn hint_for_mistake(&self, n: usize, err: &Diagnostic) -> Hint {
+        hint(
+            format!("Line {n} is not correct."),
+            format!("Line {n} is not correct: {}", err.message),
+            format!("Line {n} is not correct: {} Fix this before going on.", err.message),
+            std::iter::once(n).chain(err.related_lines.iter().copied()).collect(),
+        )
+    }
+
+    /// Gives a hint about the goal at the end of the proof. `conclusion` is what the whole proof
+    /// is meant to prove, if that is known.
+    fn hint_at_end(&self, conclusion: Option<&Wff>) -> Hint {
+        let ctx = self.context(self.scope_at_end());
+        let mut goal = conclusion.map(|wff| Goal {
+            wff: wff.clone(),
+            purpose: None,
+        });
+        let mut unknown_subproof = None;
+        for subproof in self.open_subproofs() {
+            goal = goal.and_then(|goal| self.subproof_goal(&goal.wff, &subproof, &ctx));
+            if goal.is_none() {
+                unknown_subproof = Some(subproof.first_line);
+                break;
+            }
+        }
+
+        let Some(goal) = goal else {
+            if let Some((step, wff)) = self.forward_step(&ctx) {
+                return self.forward_step_hint(&step, &wff, None);
+            }
+            return match unknown_subproof {
+                Some(first_line) => same_at_all_levels(
+                    format!("It is not clear what the subproof that starts on line {first_line} is meant to prove, so there is no hint."),
+                    vec![first_line],
+                ),
+                None => same_at_all_levels(
+                    "Without a proof template, it is not clear what the proof is meant to prove, so there is no hint.".to_owned(),
+                    vec![],
+                ),
+            };
+        };
+
+        match &goal.purpose {
+            Some(purpose) if self.sentence_at(self.last_line_num()) == Some(&goal.wff) => {
+                self.closing_hint(purpose, self.last_line_num())
+            }
+            _ => self.goal_hint(&goal.wff, &ctx, None),
+        }
+    }
+
+    /// Gives a hint for how to finish an open subproof whose goal is on its last line.
+    fn closing_hint(&self, purpose: &Purpose, last_line: usize) -> Hint {
+        let first_line = purpose.first_line;
+        let name = self.name(purpose.rule);
+        let result = format_wff(&purpose.result);
+        let step = match purpose.rule {
+            Rule::OrElim => format!("End the subproof after line {last_line}; it is one of the cases of {name}. Once every disjunct has a subproof that ends in {result}, cite the disjunction and these subproofs with {name}."),
+            Rule::BicondIntro => format!("End the subproof after line {last_line}. Together with a subproof for the other direction, cite it with {name} to get {result}."),
+            Rule::ExistsElim => format!("End the subproof after line {last_line}, and cite the existential sentence and the subproof {first_line}-{last_line} with {name} to get {result}."),
+            _ => format!("End the subproof after line {last_line}, and cite it ({first_line}-{last_line}) with {name} to get {result}."),
+        };
+        hint(
+            format!("The subproof that starts on line {first_line} has reached its goal."),
+            format!("The subproof that starts on line {first_line} is done; use it for {name}."),
+            step,
+            vec![first_line, last_line],
+        )
+    }
+
+    /// Gives a hint for proving `goal` from `ctx`. If `line` is given, the goal is the sentence on
+    /// that line, which still needs a justification.
+    fn goal_hint(&self, goal: &Wff, ctx: &Context, line: Option<usize>) -> Hint {
+        let (prefix, suffix) = match line {
+            Some(n) => (
+                format!("Line {n} has no justification yet. "),
+                format!(" Put the new lines above line {n}."),
+            ),
+            None => (String::new(), String::new()),
+        };
+        let g = format_wff(goal);
+
+        if let Some(step) = self.one_step(goal, ctx) {
+            let name = self.name(step.rule);
+            let justification = self.justification(&step);
+            let mut lines = step.lines();
+            lines.extend(line);
+            return match line {
+                Some(n) => hint(
+                    format!("{prefix}It follows in one step from lines that it can cite."),
+                    format!("{prefix}It follows by {name}."),
+                    format!("You can get {g} from {} by {name}: justify line {n} with '{justification}'.", step.describe_cited()),
+                    lines,
+                ),
+                None => hint(
+                    format!("{g} follows in one step from lines that you already have."),
+                    format!("You can get {g} by {name}."),
+                    format!("You can get {g} from {} by {name}: add it as a new line with '{justification}'.", step.describe_cited()),
+                    lines,
+                ),
+            };
+        }
+
+        if let Some(hint) = self.intro_hint(goal, ctx, &prefix, &suffix) {
+            return hint;
+        }
+        if let Some((step, wff)) = self.forward_step(ctx) {
+            return self.forward_step_hint(&step, &wff, line);
+        }
+        if let Some(hint) = self.elim_hint(goal, ctx, &prefix, &suffix) {
+            return hint;
+        }
+        if let Wff::Exists(x, wff) = goal {
+            if self.rule_set.is_allowed(Rule::ExistsIntro) {
+                let c = self.names_in(ctx).into_iter().next().unwrap_or_else(|| self.fresh_name());
+                let instance = format_wff(&substitute_in_wff(wff, x, &Term::Atomic(c.clone())));
+                let name = self.name(Rule::ExistsIntro);
+                return hint(
+                    format!("{prefix}{g} is an existential sentence."),
+                    format!("{prefix}To prove {g}, use {name}."),
+                    format!("Prove {g} for a specific name instead of {x}, for example {instance} (with {c} for {x}), and then use {name}.{suffix}"),
+                    line.into_iter().collect(),
+                );
+            }
+        }
+        if let Some(hint) = self.indirect_proof_hint(goal, &prefix, &suffix) {
+            return hint;
+        }
+        same_at_all_levels(
+            format!("{prefix}Look at the sentences in scope, and think about how {g} could follow from them."),
+            line.into_iter().collect(),
+        )
+    }
+
+    /// Gives a hint that uses the introduction rule for the main connective of `goal`, if that is
+    /// a good idea.
+    fn intro_hint(&self, goal: &Wff, ctx: &Context, prefix: &str, suffix: &str) -> Option<Hint> {
+        let g = format_wff(goal);
+        let (rule, kind, step) = match goal {
+            Wff::Implies(a, b) => (
+                Rule::ImpliesIntro,
+                "a conditional",
+                format!(
+                    "Open a subproof assuming {} to prove {g}: derive {} in it, and cite the subproof with {}.",
+                    format_wff(a),
+                    format_wff(b),
+                    self.name(Rule::ImpliesIntro)
+                ),
+            ),
+            Wff::Not(a) => (
+                Rule::NotIntro,
+                "a negation",
+                format!(
+                    "Open a subproof assuming {} and derive ⊥ in it; then cite the subproof with {} to get {g}.",
+                    format_wff(a),
+                    self.name(Rule::NotIntro)
+                ),
+            ),
+            Wff::Bicond(a, b) => (
+                Rule::BicondIntro,
+                "a biconditional",
+                format!(
+                    "Open a subproof assuming {a} that ends in {b}, and another one assuming {b} that ends in {a}; then cite both subproofs with {}.",
+                    self.name(Rule::BicondIntro),
+                    a = format_wff(a),
+                    b = format_wff(b),
+                ),
+            ),
+            Wff::And(conjuncts) => {
+                let missing: Vec<String> = conjuncts
+                    .iter()
+                    .filter(|c| ctx.find(c).is_none())
+                    .map(format_wff)
+                    .collect();
+                (
+                    Rule::AndIntro,
+                    "a conjunction",
+                    format!(
+                        "Prove each conjunct on a line of its own ({} still missing: {}), and then cite all of them with {}.",
+                        if missing.len() == 1 { "this one is" } else { "these are" },
+                        missing.join(", "),
+                        self.name(Rule::AndIntro)
+                    ),
+                )
+            }
+            Wff::Forall(x, wff) => {
+                let c = self.fresh_name();
+                (
+                    Rule::ForallIntro,
+                    "a universal sentence",
+                    format!(
+                        "Open a subproof that introduces a new boxed constant [{c}], prove {} in it, and cite the subproof with {}.",
+                        format_wff(&substitute_in_wff(wff, x, &Term::Atomic(c.clone()))),
+                        self.name(Rule::ForallIntro)
+                    ),
+                )
+            }
+            Wff::Necessarily(a) => (
+                Rule::BoxIntro,
+                "a necessity",
+                format!(
+                    "Open a strict subproof (a subproof without premise, with □ on its Fitch bar), prove {} in it, and cite the subproof with {}.",
+                    format_wff(a),
+                    self.name(Rule::BoxIntro)
+                ),
+            ),
+            _ => return None,
+        };
+        if !self.rule_set.is_allowed(rule) {
+            return None;
+        }
+        Some(hint(
+            format!("{prefix}{g} is {kind}; look at its main connective."),
+            format!("{prefix}To prove {g}, use {}.", self.name(rule)),
+            format!("{step}{suffix}"),
+            vec![],
+        ))
+    }
+
+    /// Gives a hint that works towards `goal` with an elimination rule, if there is one.
+    fn elim_hint(&self, goal: &Wff, ctx: &Context, prefix: &str, suffix: &str) -> Option<Hint> {
+        let g = format_wff(goal);
+
+        // if one of the disjuncts of the goal already follows, prove that one
+        if let Wff::Or(disjuncts) = goal {
+            let premises: Vec<&Wff> = ctx.lines.iter().map(|(_, w)| *w).collect();
+            let provable = disjuncts.iter().find(|d| {
+                truth_table::tautological_consequence(&premises, d) == TruthTableResult::Consequence
+            });
+            if let (Some(d), true) = (provable, self.rule_set.is_allowed(Rule::OrIntro)) {
+                let name = self.name(Rule::OrIntro);
+                return Some(hint(
+                    format!("{prefix}It is enough to prove one of the disjuncts of {g}."),
+                    format!("{prefix}Prove one of the disjuncts of {g}, and then use {name}."),
+                    format!("Prove {}, and then use {name} to get {g}.{suffix}", format_wff(d)),
+                    vec![],
+                ));
+            }
+        }
+
+        for &(n, wff) in &ctx.lines {
+            match wff {
+                Wff::Or(disjuncts)
+                    if self.rule_set.is_allowed(Rule::OrElim)
+                        && disjuncts.iter().all(|d| ctx.find(d).is_none()) =>
+                {
+                    let name = self.name(Rule::OrElim);
+                    return Some(hint(
+                        format!("{prefix}Line {n} is a disjunction."),
+                        format!("{prefix}Line {n} is a disjunction; consider {name}."),
+                        format!(
+                            "For each disjunct of line {n} ({}), open a subproof assuming it and prove {g} in it; then cite line {n} and these subproofs with {name}.{suffix}",
+                            disjuncts.iter().map(format_wff).collect::<Vec<_>>().join(", ")
+                        ),
+                        vec![n],
+                    ));
+                }
+                Wff::Exists(x, body) if self.rule_set.is_allowed(Rule::ExistsElim) => {
+                    let name = self.name(Rule::ExistsElim);
+                    let c = self.fresh_name();
+                    let instance = substitute_in_wff(body, x, &Term::Atomic(c.clone()));
+                    return Some(hint(
+                        format!("{prefix}Line {n} is an existential sentence."),
+                        format!("{prefix}Line {n} is an existential sentence; consider {name}."),
+                        format!(
+                            "Open a subproof that introduces a new boxed constant [{c}] with premise {}, prove {g} in it, and then cite line {n} and the subproof with {name}.{suffix}",
+                            format_wff(&instance)
+                        ),
+                        vec![n],
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        for &(n, wff) in &ctx.lines {
+            match wff {
+                Wff::Implies(a, b)
+                    if **b == *goal && self.rule_set.is_allowed(Rule::ImpliesElim) =>
+                {
+                    let name = self.name(Rule::ImpliesElim);
+                    let a = format_wff(a);
+                    return Some(hint(
+                        format!("{prefix}Line {n} has {g} as its consequent."),
+                        format!("{prefix}You can get {g} from line {n} by {name}, once you have its antecedent."),
+                        format!("Prove {a}; then cite line {n} and the line with {a} with {name} to get {g}.{suffix}"),
+                        vec![n],
+                    ));
+                }
+                Wff::Not(a)
+                    if *goal == Wff::Bottom && self.rule_set.is_allowed(Rule::BottomIntro) =>
+                {
+                    let name = self.name(Rule::BottomIntro);
+                    let a = format_wff(a);
+                    return Some(hint(
+                        format!("{prefix}Line {n} is a negation; ⊥ follows from it together with what it negates."),
+                        format!("{prefix}Prove {a}, to use {name} with line {n}."),
+                        format!("Prove {a}; then cite the line with {a} and line {n} with {name} to get ⊥.{suffix}"),
+                        vec![n],
+                    ));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Gives a hint to prove `goal` indirectly, if that is allowed.
+    fn indirect_proof_hint(&self, goal: &Wff, prefix: &str, suffix: &str) -> Option<Hint> {
+        if *goal == Wff::Bottom || self.rule_set.is_intuitionistic() {
+            return None;
+        }
+        let g = format_wff(goal);
+        let not_g = format_wff(&Wff::Not(Box::new(goal.clone())));
+        let not_not_g = format_wff(&Wff::Not(Box::new(Wff::Not(Box::new(goal.clone())))));
+        let (rule, step) = if self.rule_set.is_allowed(Rule::NotIntro)
+            && self.rule_set.is_allowed(Rule::NotElim)
+        {
+            (
+                Rule::NotIntro,
+                format!(
+                    "Open a subproof assuming {not_g} and derive ⊥ in it. Then cite the subproof with {} to get {not_not_g}, and use {} to get {g}.",
+                    self.name(Rule::NotIntro),
+                    self.name(Rule::NotElim)
+                ),
+            )
+        } else if self.rule_set.is_allowed(Rule::IndirectProof) {
+            (
+                Rule::IndirectProof,
+                format!(
+                    "Open a subproof assuming {not_g} and derive ⊥ in it. Then cite the subproof with {} to get {g}.",
+                    self.name(Rule::IndirectProof)
+                ),
+            )
+        } else {
+            return None;
+        };
+        Some(hint(
+            format!(
+                "{prefix}There is no direct way to prove {g}; try to show that {not_g} is impossible."
+            ),
+            format!("{prefix}Prove {g} indirectly, with {}.", self.name(rule)),
+            format!("{step}{suffix}"),
+            vec![],
+        ))
+    }
+
+    /// Gives a hint to take a step that derives `wff` with `step`.
+    fn forward_step_hint(&self, step: &Step, wff: &Wff, line: Option<usize>) -> Hint {
+        let w = format_wff(wff);
+        let name = self.name(step.rule);
+        let suffix = match line {
+            Some(n) => format!(" Put it above line {n}."),
+            None => String::new(),
+        };
+        hint(
+            "Something new follows in one step from the lines in scope.".to_owned(),
+            format!("You can get {w} by {name}."),
+            format!(
+                "You can get {w} from {} by {name}: add it as a new line with '{}'.{suffix}",
+                step.describe_cited(),
+                self.justification(step)
+            ),
+            step.lines(),
+        )
+    }
+
+    /// Returns a step that gives `goal` in one step from `ctx`, if there is one.
+    fn one_step(&self, goal: &Wff, ctx: &Context) -> Option<Step> {
+        let allowed = |rule| self.rule_set.is_allowed(rule);
+        let classical = !self.rule_set.is_intuitionistic();
+
+        if let (Some(n), true) = (ctx.find(goal), allowed(Rule::Reit)) {
+            return Some(Step::new(Rule::Reit, &[n]));
+        }
+        for &(n, wff) in &ctx.lines {
+            let step = match wff {
+                Wff::And(conjuncts) if allowed(Rule::AndElim) && conjuncts.contains(goal) => {
+                    Some(Step::new(Rule::AndElim, &[n]))
+                }
+                Wff::Implies(a, b) if allowed(Rule::ImpliesElim) && **b == *goal => {
+                    ctx.find(a).map(|m| Step::new(Rule::ImpliesElim, &[n, m]))
+                }
+                Wff::Bicond(a, b) if allowed(Rule::BicondElim) && **b == *goal => {
+                    ctx.find(a).map(|m| Step::new(Rule::BicondElim, &[n, m]))
+                }
+                Wff::Bicond(a, b) if allowed(Rule::BicondElim) && **a == *goal => {
+                    ctx.find(b).map(|m| Step::new(Rule::BicondElim, &[n, m]))
+                }
+                Wff::Not(w) if classical && matches!(&**w, Wff::Not(w) if **w == *goal) => {
+                    [Rule::NotElim, Rule::DoubleNegationElim]
+                        .into_iter()
+                        .find(|&rule| allowed(rule))
+                        .map(|rule| Step::new(rule, &[n]))
+                }
+                Wff::Or(disjuncts)
+                    if allowed(Rule::DisjunctiveSyllogism)
+                        && disjuncts.len() == 2
+                        && disjuncts.contains(goal) =>
+                {
+                    let other = disjuncts.iter().find(|d| *d != goal).unwrap_or(goal);
+                    ctx.find(&Wff::Not(Box::new(other.clone())))
+                        .map(|m| Step::new(Rule::DisjunctiveSyllogism, &[n, m]))
+                }
+                Wff::Forall(x, body) if allowed(Rule::ForallElim) => self
+                    .names_in_wff(goal)
+                    .into_iter()
+                    .any(|c| substitute_in_wff(body, x, &Term::Atomic(c)) == *goal)
+                    .then(|| Step::new(Rule::ForallElim, &[n])),
+                _ => None,
+            };
+            if step.is_some() {
+                return step;
+            }
+        }
+
+        let step = match goal {
+            Wff::Or(disjuncts) if allowed(Rule::OrIntro) => {
+                disjuncts.iter().find_map(|d| ctx.find(d)).map(|n| Step::new(Rule::OrIntro, &[n]))
+            }
+            Wff::And(conjuncts) if allowed(Rule::AndIntro) => conjuncts
+                .iter()
+                .map(|c| ctx.find(c))
+                .collect::<Option<Vec<usize>>>()
+                .map(|ns| Step::new(Rule::AndIntro, &ns)),
+            Wff::Bottom if allowed(Rule::BottomIntro) => {
+                ctx.lines.iter().find_map(|&(n, wff)| match wff {
+                    Wff::Not(a) => ctx.find(a).map(|m| Step::new(Rule::BottomIntro, &[m, n])),
+                    _ => None,
+                })
+            }
+            Wff::Not(a) if allowed(Rule::ModusTollens) => {
+                ctx.lines.iter().find_map(|&(n, wff)| match wff {
+                    Wff::Implies(b, c) if b == a => ctx
+                        .find(&Wff::Not(c.clone()))
+                        .map(|m| Step::new(Rule::ModusTollens, &[n, m])),
+                    _ => None,
+                })
+            }
+            Wff::Exists(x, body) if allowed(Rule::ExistsIntro) => {
+                self.names_in(ctx).into_iter().find_map(|c| {
+                    ctx.find(&substitute_in_wff(body, x, &Term::Atomic(c)))
+                        .map(|n| Step::new(Rule::ExistsIntro, &[n]))
+                })
+            }
+            Wff::Equals(t1, t2) if allowed(Rule::EqualsIntro) && t1 == t2 => {
+                Some(Step::new(Rule::EqualsIntro, &[]))
+            }
+            _ => None,
+        };
+        if step.is_some() {
+            return step;
+        }
+
+        if let Some(step) = self.subproof_step(goal, ctx) {
+            return Some(step);
+        }
+        match ctx.find(&Wff::Bottom) {
+            Some(n) if allowed(Rule::BottomElim) => Some(Step::new(Rule::BottomElim, &[n])),
+            _ => None,
+        }
+    }
+
+    /// Returns a step that gives `goal` by citing subproofs in `ctx`, if there is one.
+    fn subproof_step(&self, goal: &Wff, ctx: &Context) -> Option<Step> {
+        let allowed = |rule| self.rule_set.is_allowed(rule);
+        let find_subproof = |premise: &Wff, conclusion: &Wff| {
+            ctx.subproofs.iter().copied().find(|&(a, b)| {
+                !self.strict_subproofs.contains(&(a, b))
+                    && self.sentence_at(a) == Some(premise)
+                    && self.sentence_at(b) == Some(conclusion)
+            })
+        };
+        let (rule, subproofs) = match goal {
+            Wff::Implies(a, b) if allowed(Rule::ImpliesIntro) => {
+                (Rule::ImpliesIntro, vec![find_subproof(a, b)?])
+            }
+            Wff::Not(a) if allowed(Rule::NotIntro) => {
+                (Rule::NotIntro, vec![find_subproof(a, &Wff::Bottom)?])
+            }
+            Wff::Bicond(a, b) if allowed(Rule::BicondIntro) => {
+                (Rule::BicondIntro, vec![find_subproof(a, b)?, find_subproof(b, a)?])
+            }
+            Wff::Forall(x, body) if allowed(Rule::ForallIntro) => {
+                let subproof = ctx.subproofs.iter().copied().find(|&(a, b)| {
+                    self.sentence_at(a).is_none()
+                        && self.constant_at(a).is_some_and(|c| {
+                            self.sentence_at(b) == Some(&substitute_in_wff(body, x, c))
+                        })
+                })?;
+                (Rule::ForallIntro, vec![subproof])
+            }
+            Wff::Necessarily(a) if allowed(Rule::BoxIntro) => {
+                let subproof = ctx.subproofs.iter().copied().find(|&(first, last)| {
+                    self.strict_subproofs.contains(&(first, last))
+                        && self.sentence_at(last) == Some(&**a)
+                })?;
+                (Rule::BoxIntro, vec![subproof])
+            }
+            _ => return None,
+        };
+        Some(Step {
+            rule,
+            cited: subproofs.into_iter().map(|(a, b)| (a, Some(b))).collect(),
+        })
+    }
+
+    /// Returns a step that derives a sentence from `ctx` that is not in it yet, together with that
+    /// sentence, if there is one. Only steps that take sentences apart are considered (∧ Elim,
+    /// → Elim and ↔ Elim), since those are nearly always useful.
+    fn forward_step(&self, ctx: &Context) -> Option<(Step, Wff)> {
+        let allowed = |rule| self.rule_set.is_allowed(rule);
+        for &(n, wff) in &ctx.lines {
+            match wff {
+                Wff::And(conjuncts) if allowed(Rule::AndElim) => {
+                    if let Some(c) = conjuncts.iter().find(|c| ctx.find(c).is_none()) {
+                        return Some((Step::new(Rule::AndElim, &[n]), c.clone()));
+                    }
+                }
+                Wff::Implies(a, b) if allowed(Rule::ImpliesElim) && ctx.find(b).is_none() => {
+                    if let Some(m) = ctx.find(a) {
+                        return Some((Step::new(Rule::ImpliesElim, &[n, m]), (**b).clone()));
+                    }
+                }
+                Wff::Bicond(a, b) if allowed(Rule::BicondElim) => {
+                    for (from, to) in [(a, b), (b, a)] {
+                        if let (Some(m), None) = (ctx.find(from), ctx.find(to)) {
+                            return Some((Step::new(Rule::BicondElim, &[n, m]), (**to).clone()));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Works out what an open subproof is meant to prove, given that the proof around it is
+    /// meant to prove `outer`.
+    fn subproof_goal(&self, outer: &Wff, subproof: &OpenSubproof, ctx: &Context) -> Option<Goal> {
+        let first_line = subproof.first_line;
+        let goal = |wff: Wff, rule: Rule, result: Wff| {
+            Some(Goal {
+                wff,
+                purpose: Some(Purpose {
+                    first_line,
+                    rule,
+                    result,
+                }),
+            })
+        };
+        if subproof.is_strict {
+            return match outer {
+                Wff::Necessarily(a) => goal((**a).clone(), Rule::BoxIntro, outer.clone()),
+                _ => None,
+            };
+        }
+
+        let Some(premise) = self.sentence_at(first_line) else {
+            // a subproof that only introduces a boxed constant
+            let c = self.constant_at(first_line)?;
+            return match outer {
+                Wff::Forall(x, body) => {
+                    goal(substitute_in_wff(body, x, c), Rule::ForallIntro, outer.clone())
+                }
+                _ => None,
+            };
+        };
+        if let Some(c) = self.constant_at(first_line) {
+            let instantiates_existential = ctx.lines.iter().any(|(_, wff)| {
+                matches!(wff, Wff::Exists(x, body) if substitute_in_wff(body, x, c) == *premise)
+            });
+            return instantiates_existential
+                .then(|| goal(outer.clone(), Rule::ExistsElim, outer.clone()))
+                .flatten();
+        }
+
+        match outer {
+            Wff::Implies(a, b) if **a == *premise => {
+                return goal((**b).clone(), Rule::ImpliesIntro, outer.clone())
+            }
+            Wff::Not(a) if **a == *premise => {
+                return goal(Wff::Bottom, Rule::NotIntro, outer.clone())
+            }
+            Wff::Bicond(a, b) if **a == *premise => {
+                return goal((**b).clone(), Rule::BicondIntro, outer.clone())
+            }
+            Wff::Bicond(a, b) if **b == *premise => {
+                return goal((**a).clone(), Rule::BicondIntro, outer.clone())
+            }
+            _ => {}
+        }
+        if matches!(premise, Wff::Not(w) if **w == *outer) {
+            return if self.rule_set.is_allowed(Rule::IndirectProof)
+                && !self.rule_set.is_allowed(Rule::NotElim)
+            {
+                goal(Wff::Bottom, Rule::IndirectProof, outer.clone())
+            } else {
+                goal(Wff::Bottom, Rule::NotIntro, Wff::Not(Box::new(premise.clone())))
+            };
+        }
+        let is_case = ctx
+            .lines
+            .iter()
+            .any(|(_, wff)| matches!(wff, Wff::Or(disjuncts) if disjuncts.contains(premise)));
+        is_case.then(|| goal(outer.clone(), Rule::OrElim, outer.clone())).flatten()
+    }
+
+    /// Returns the subproofs that are still open at the end of the proof, from the outermost to
+    /// the innermost one.
+    fn ends_with_fitch_bar(&self) -> bool {
+        self.units.last() == Some(&ProofUnit::FitchBarLine)
+    }
+
+    fn open_subproofs(&self) -> Vec<OpenSubproof> {
+        let mut open: Vec<usize> = vec![];
+        for (i, unit) in self.units.iter().enumerate() {
+            match unit {
+                ProofUnit::SubproofOpen => open.push(i),
+                ProofUnit::SubproofClose => {
+                    open.pop();
+                }
+                _ => {}
+            }
+        }
+        open.into_iter()
+            .filter_map(|i| {
+                let first_line = self.units[i + 1..].iter().find_map(ProofUnit::line_num)?;
+                let is_strict = self.units[i + 1] == ProofUnit::StrictFitchBarLine;
+                Some(OpenSubproof {
+                    first_line,
+                    is_strict,
+                })
+            })
+            .collect()
+    }
+
+    /// Turns an entry of a [Scope] into a [Context]. The sentences that could only be brought
+    /// into a strict subproof are left out.
+    fn context(&self, scope: (Vec<usize>, Vec<(usize, usize)>, Vec<usize>)) -> Context<'_> {
+        let (mut lines, mut subproofs, _) = scope;
+        lines.sort();
+        subproofs.sort();
+        Context {
+            lines: lines.into_iter().filter_map(|n| Some((n, self.sentence_at(n)?))).collect(),
+            subproofs,
+        }
+    }
+
+    fn sentence_at(&self, line_num: usize) -> Option<&Wff> {
+        self.line_at(line_num)?.sentence.as_ref()
+    }
+
+    fn constant_at(&self, line_num: usize) -> Option<&Term> {
+        self.line_at(line_num)?.constant_between_square_brackets.as_ref()
+    }
+
+    fn line_at(&self, line_num: usize) -> Option<&ProofLine> {
+        self.lines.iter().find(|l| l.line_num == Some(line_num))
+    }
+
+    fn name(&self, rule: Rule) -> &str {
+        self.rule_set.name(rule)
+    }
+
+    /// The justification that a line derived by `step` should get, like `→ Elim: 2, 3`.
+    fn justification(&self, step: &Step) -> String {
+        if step.cited.is_empty() {
+            return self.name(step.rule).to_owned();
+        }
+        let cited: Vec<String> = step.cited.iter().map(|&c| format_citation(c)).collect();
+        format!("{}: {}", self.name(step.rule), cited.join(", "))
+    }
+
+    /// The names (constants) that occur in the sentences of `ctx`, in order of appearance.
+    fn names_in(&self, ctx: &Context) -> Vec<String> {
+        let mut names = vec![];
+        for (_, wff) in &ctx.lines {
+            for name in self.names_in_wff(wff) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    /// The names (constants) that occur in `wff`.
+    fn names_in_wff(&self, wff: &Wff) -> Vec<String> {
+        fn add_term(proof: &Proof, term: &Term, names: &mut Vec<String>) {
+            match term {
+                Term::Atomic(name) => {
+                    if !proof.allowed_variable_names.contains(name) && !names.contains(name) {
+                        names.push(name.clone());
+                    }
+                }
+                Term::FuncApp(_, args) => args.iter().for_each(|t| add_term(proof, t, names)),
+            }
+        }
+        fn add_wff(proof: &Proof, wff: &Wff, names: &mut Vec<String>) {
+            match wff {
+                Wff::And(li) | Wff::Or(li) => li.iter().for_each(|w| add_wff(proof, w, names)),
+                Wff::Implies(w1, w2) | Wff::Bicond(w1, w2) => {
+                    add_wff(proof, w1, names);
+                    add_wff(proof, w2, names);
+                }
+                Wff::Not(w)
+                | Wff::Necessarily(w)
+                | Wff::Possibly(w)
+                | Wff::Forall(_, w)
+                | Wff::Exists(_, w) => add_wff(proof, w, names),
+                Wff::PredApp(_, args) => args.iter().for_each(|t| add_term(proof, t, names)),
+                Wff::Equals(t1, t2) => {
+                    add_term(proof, t1, names);
+                    add_term(proof, t2, names);
+                }
+                Wff::Bottom | Wff::Atomic(_) => {}
+            }
+        }
+        let mut names = vec![];
+        add_wff(self, wff, &mut names);
+        names
+    }
+
+    /// Returns a one-letter name that is not used anywhere in the proof yet, for a new boxed
+    /// constant.
+    fn fresh_name(&self) -> String {
+        let mut used: Vec<String> = vec![];
+        for line in &self.lines {
+            used.extend(line.sentence.iter().flat_map(|wff| self.names_in_wff(wff)));
+            if let Some(Term::Atomic(name)) = &line.constant_between_square_brackets {
+                used.push(name.clone());
+            }
+        }
+        ('c'..='z')
+            .chain('a'..='b')
+            .map(|c| c.to_string())
+            .find(|c| !used.contains(c) && !self.allowed_variable_names.contains(c))
+            .unwrap_or_else(|| "c0".to_owned())
+    }
+}
+
+/// Formats a cited line (`3`) or subproof (`3-5`).
+fn format_citation((first, last): (usize, Option<usize>)) -> String {
+    match last {
+        Some(last) => format!("{first}-{last}"),
+        None => first.to_string(),
+    }
+}
+
+fn hint(nudge: String, rule: String, step: String, lines: Vec<usize>) -> Hint {
+    Hint {
+        nudge,
+        rule,
+        step,
+        lines,
+    }
+}
+
+/// A hint that is the same at every level of detail, for when there is only one thing to say.
+fn same_at_all_levels(text: String, lines: Vec<usize>) -> Hint {
+    hint(text.clone(), text.clone(), text, lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_fitch_proof, parse_logical_expression_string};
+    use crate::rule_set::RuleSet;
+
+    fn hint_for(proof: &str, template: &[&str]) -> Hint {
+        let lines = parse_fitch_proof(proof).unwrap();
+        let variables = ["x", "y", "z"].into_iter().map(String::from).collect();
+        let proof = Proof::construct(lines, variables, RuleSet::lpl()).unwrap();
+        let template: Vec<Wff> =
+            template.iter().map(|s| parse_logical_expression_string(s).unwrap()).collect();
+        proof.hint(Some(&template))
+    }
+
+    #[test]
+    fn test_goal_hints() {
+        let h = hint_for("1 | P\n  |---\n2 | P   Reit: 1", &["P", "Q → P"]);
+        assert_eq!(h.rule, "To prove Q → P, use → Intro.");
+        assert!(h.step.starts_with("Open a subproof assuming Q to prove Q → P"));
+
+        let h = hint_for("1 | P ∨ Q\n  |---\n2 | P ∨ Q   Reit: 1", &["P ∨ Q", "Q ∨ P"]);
+        assert_eq!(h.rule, "Line 1 is a disjunction; consider ∨ Elim.");
+        assert_eq!(h.lines, vec![1]);
+
+        let h = hint_for(
+            "1 | P → Q\n2 | Q → R\n3 | P\n  |---\n4 | P   Reit: 3",
+            &["P → Q", "Q → R", "P", "R"],
+        );
+        assert_eq!(h.nudge, "Something new follows in one step from the lines in scope.");
+        assert_eq!(
+            h.step,
+            "You can get Q from lines 1 and 3 by → Elim: add it as a new line with '→ Elim: 1, 3'."
+        );
+
+        let h = hint_for("1 | ¬¬P\n  |---\n2 | ¬¬P   Reit: 1", &["¬¬P", "P"]);
+        assert_eq!(h.rule, "You can get P by ¬ Elim.");
+    }
+
+    #[test]
+    fn test_subproof_goals() {
+        // inside the subproof, the goal is Q; it has been reached, so the subproof can be closed
+        let h = hint_for("1 | Q\n  |---\n2 | | P\n  | |---\n3 | | Q   Reit: 1", &["Q", "P → Q"]);
+        assert_eq!(
+            h.step,
+            "End the subproof after line 3, and cite it (2-3) with → Intro to get P → Q."
+        );
+
+        // an indirect proof: the goal inside the subproof is ⊥
+        let h = hint_for(
+            "1 | P\n2 | ¬P ∨ Q\n  |---\n3 | | ¬Q\n  | |---\n4 | | ¬Q   Reit: 3",
+            &["P", "¬P ∨ Q", "Q"],
+        );
+        assert_eq!(h.rule, "Line 2 is a disjunction; consider ∨ Elim.");
+        let h = hint_for("1 | P\n  |---\n2 | | ¬P\n  | |---\n3 | | ¬P   Reit: 2", &["P", "P"]);
+        assert_eq!(h.rule, "You can get ⊥ by ⊥ Intro.");
+    }
+
+    #[test]
+    fn test_line_hints() {
+        let h = hint_for("1 | P ∧ Q\n  |---\n2 | Q", &["P ∧ Q", "Q"]);
+        assert_eq!(
+            h.nudge,
+            "Line 2 has no justification yet. It follows in one step from lines that it can cite."
+        );
+        assert_eq!(h.step, "You can get Q from line 1 by ∧ Elim: justify line 2 with '∧ Elim: 1'.");
+
+        let h = hint_for("1 | P ∧ Q\n  |---\n2 | Q   ∨ Intro: 1", &["P ∧ Q", "Q"]);
+        assert_eq!(h.step, "Justify line 2 with '∧ Elim: 1'.");
+
+        let h = hint_for("1 | P\n  |---\n2 | ∀x (P ∨ R(x))", &["P", "∀x (P ∨ R(x))"]);
+        assert_eq!(h.step, "Open a subproof that introduces a new boxed constant [c], prove P ∨ R(c) in it, and cite the subproof with ∀ Intro. Put the new lines above line 2.");
+
+        let h = hint_for("1 | P\n  |---\n2 | P   Reit: 1", &["P", "P"]);
+        assert_eq!(h.step, "The proof is complete: there is nothing left to do.");
+    }
+}
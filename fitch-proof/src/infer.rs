@@ -0,0 +1,228 @@
+use crate::data::*;
+use crate::formatter;
+use crate::proof::*;
+
+impl Proof {
+    /// Looks for a justification for every inference in the proof that has no justification or a
+    /// wrong one, and fills in the first one that makes the step correct (so afterwards,
+    /// `self.lines` contains the repaired proof). The lines are done from top to bottom.
+    ///
+    /// For a line, every rule that the [RuleSet](crate::rule_set::RuleSet) allows is tried with
+    /// the lines and subproofs in the scope of that line, in the order of [Rule::ALL]. Taut Con,
+    /// FO Con and Ana Con are never filled in: they would justify nearly every step, and teach the
+    /// student nothing.
+    ///
+    /// A line for which no justification is found keeps what it had. The returned vector contains
+    /// an [InferredJustification] for every line that was looked at.
+    pub fn infer_justifications(&mut self) -> Vec<InferredJustification> {
+        let missing = self.line_numbers_missing_justification();
+        let wrong: Vec<usize> = self
+            .lines
+            .iter()
+            .filter(|line| line.sentence.is_some() && self.check_line(line).is_err())
+            .filter_map(|line| line.line_num)
+            .collect();
+        let mut to_do: Vec<usize> = missing.iter().chain(&wrong).copied().collect();
+        to_do.sort();
+        to_do.dedup();
+
+        let mut inferred = vec![];
+        for n in to_do {
+            if self.lines.iter().any(|l| l.line_num == Some(n) && l.sentence.is_some()) {
+                let justification = self.infer_justification(n);
+                inferred.push(InferredJustification {
+                    line: n,
+                    justification: justification.map(|j| formatter::format_justification(&j)),
+                    was_missing: missing.contains(&n),
+                });
+            }
+        }
+        inferred
+    }
+
+    /// Looks for a justification that makes line `line_num` correct. If one is found, it is filled
+    /// in, and a copy is returned. Otherwise, the line keeps its original justification.
+    ///
+    /// PRECONDITION (panics otherwise): line `line_num` exists and contains a sentence.
+    fn infer_justification(&mut self, line_num: usize) -> Option<Justification> {
+        // A line without justification has no scope yet; it gets the scope that it would have if
+        // it had one. This does not change the scope of any other line.
+        let scope = self.scope_of_line(line_num);
+        if self.scope.len() <= line_num {
+            self.scope.resize(line_num + 1, (vec![], vec![], vec![]));
+        }
+        self.scope[line_num] = scope.clone();
+        // the candidates cite lines from top to bottom
+        let (mut lines, mut subproofs, mut imports) = scope;
+        lines.sort();
+        subproofs.sort();
+        imports.sort();
+        let scope = (lines, subproofs, imports);
+
+        let i = self.lines.iter().position(|l| l.line_num == Some(line_num)).unwrap();
+        let original = self.lines[i].justification.take();
+        for rule in Rule::ALL {
+            if !self.rule_set.is_allowed(rule)
+                || matches!(rule, Rule::TautCon | Rule::FoCon | Rule::AnaCon)
+            {
+                continue;
+            }
+            for candidate in self.candidates(rule, line_num, &scope) {
+                // a line that could not be parsed cannot show that the candidate is correct
+                if self.cites_unparsed_line(&candidate) {
+                    continue;
+                }
+                self.lines[i].justification = Some(candidate);
+                if self.check_line(&self.lines[i]).is_ok() {
+                    return self.lines[i].justification.clone();
+                }
+            }
+        }
+        self.lines[i].justification = original;
+        None
+    }
+
+    /// Returns all justifications with rule `rule` that line `line_num` could have, given the
+    /// lines and subproofs in its scope (sorted from top to bottom).
+    fn candidates(
+        &self,
+        rule: Rule,
+        line_num: usize,
+        (lines, subproofs, imports): &(Vec<usize>, Vec<(usize, usize)>, Vec<usize>),
+    ) -> Vec<Justification> {
+        let sentence_at = |n: usize| {
+            self.lines.iter().find(|l| l.line_num == Some(n)).and_then(|l| l.sentence.as_ref())
+        };
+        // the most recent line with the given sentence
+        let find = |wff: &Wff| lines.iter().rev().copied().find(|&n| sentence_at(n) == Some(wff));
+        let pairs = || {
+            lines.iter().flat_map(|&a| lines.iter().filter(move |&&b| b != a).map(move |&b| (a, b)))
+        };
+        let subproof_pairs = || {
+            subproofs
+                .iter()
+                .flat_map(|&a| subproofs.iter().filter(move |&&b| b != a).map(move |&b| (a, b)))
+        };
+
+        match rule {
+            Rule::AndIntro => match sentence_at(line_num) {
+                Some(Wff::And(conjuncts)) => conjuncts
+                    .iter()
+                    .map(find)
+                    .collect::<Option<Vec<usize>>>()
+                    .map(Justification::AndIntro)
+                    .into_iter()
+                    .collect(),
+                _ => vec![],
+            },
+            Rule::OrElim => lines
+                .iter()
+                .filter_map(|&n| {
+                    let Some(Wff::Or(disjuncts)) = sentence_at(n) else {
+                        return None;
+                    };
+                    let cases = disjuncts
+                        .iter()
+                        .map(|d| {
+                            subproofs.iter().copied().find(|&(a, _)| sentence_at(a) == Some(d))
+                        })
+                        .collect::<Option<Vec<(usize, usize)>>>()?;
+                    Some(Justification::OrElim(n, cases))
+                })
+                .collect(),
+            Rule::AndElim => lines.iter().map(|&n| Justification::AndElim(n)).collect(),
+            Rule::OrIntro => lines.iter().map(|&n| Justification::OrIntro(n)).collect(),
+            Rule::NotElim => lines.iter().map(|&n| Justification::NotElim(n)).collect(),
+            Rule::BottomElim => lines.iter().map(|&n| Justification::BottomElim(n)).collect(),
+            Rule::ForallElim => lines.iter().map(|&n| Justification::ForallElim(n)).collect(),
+            Rule::ExistsIntro => lines.iter().map(|&n| Justification::ExistsIntro(n)).collect(),
+            Rule::Reit => lines.iter().map(|&n| Justification::Reit(n)).collect(),
+            Rule::DoubleNegationElim => {
+                lines.iter().map(|&n| Justification::DoubleNegationElim(n)).collect()
+            }
+            Rule::DeMorgan => lines.iter().map(|&n| Justification::DeMorgan(n)).collect(),
+            Rule::ConversionOfQuantifiers => {
+                lines.iter().map(|&n| Justification::ConversionOfQuantifiers(n)).collect()
+            }
+            Rule::DiamondIntro => lines.iter().map(|&n| Justification::DiamondIntro(n)).collect(),
+            Rule::DiamondElim => lines.iter().map(|&n| Justification::DiamondElim(n)).collect(),
+            Rule::BoxElim => {
+                imports.iter().chain(lines).map(|&n| Justification::BoxElim(n)).collect()
+            }
+            Rule::BoxReit => imports.iter().map(|&n| Justification::BoxReit(n)).collect(),
+            Rule::BottomIntro => pairs().map(|(a, b)| Justification::BottomIntro(a, b)).collect(),
+            Rule::ImpliesElim => pairs().map(|(a, b)| Justification::ImpliesElim(a, b)).collect(),
+            Rule::BicondElim => pairs().map(|(a, b)| Justification::BicondElim(a, b)).collect(),
+            Rule::EqualsElim => pairs().map(|(a, b)| Justification::EqualsElim(a, b)).collect(),
+            Rule::DisjunctiveSyllogism => {
+                pairs().map(|(a, b)| Justification::DisjunctiveSyllogism(a, b)).collect()
+            }
+            Rule::ModusTollens => pairs().map(|(a, b)| Justification::ModusTollens(a, b)).collect(),
+            Rule::EqualsIntro => vec![Justification::EqualsIntro],
+            Rule::NotIntro => subproofs.iter().map(|&s| Justification::NotIntro(s)).collect(),
+            Rule::ImpliesIntro => {
+                subproofs.iter().map(|&s| Justification::ImpliesIntro(s)).collect()
+            }
+            Rule::ForallIntro => subproofs.iter().map(|&s| Justification::ForallIntro(s)).collect(),
+            Rule::IndirectProof => {
+                subproofs.iter().map(|&s| Justification::IndirectProof(s)).collect()
+            }
+            Rule::BoxIntro => subproofs.iter().map(|&s| Justification::BoxIntro(s)).collect(),
+            Rule::BicondIntro => {
+                subproof_pairs().map(|(s1, s2)| Justification::BicondIntro(s1, s2)).collect()
+            }
+            Rule::ExcludedMiddle => {
+                subproof_pairs().map(|(s1, s2)| Justification::ExcludedMiddle(s1, s2)).collect()
+            }
+            Rule::ExistsElim => lines
+                .iter()
+                .flat_map(|&n| subproofs.iter().map(move |&s| Justification::ExistsElim(n, s)))
+                .collect(),
+            Rule::TautCon | Rule::FoCon | Rule::AnaCon => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse_fitch_proof;
+    use crate::proof::Proof;
+    use crate::rule_set::RuleSet;
+
+    fn infer(proof: &str, rule_set: RuleSet) -> Vec<(usize, Option<String>)> {
+        let lines = parse_fitch_proof(proof).unwrap();
+        let variables = ["x", "y", "z"].into_iter().map(String::from).collect();
+        let mut proof = Proof::construct(lines, variables, rule_set).unwrap();
+        proof.infer_justifications().into_iter().map(|i| (i.line, i.justification)).collect()
+    }
+
+    #[test]
+    fn test_infer_justifications() {
+        let proof = "1 | P → Q\n2 | P\n  |---\n3 | Q\n4 | Q ∧ P\n5 | R   ∧ Elim: 4";
+        assert_eq!(
+            infer(proof, RuleSet::lpl()),
+            vec![
+                (3, Some("→ Elim: 1,2".to_owned())),
+                (4, Some("∧ Intro: 3, 2".to_owned())),
+                (5, None)
+            ]
+        );
+
+        let proof = "1 | P ∨ Q\n  |---\n2 | | P\n  | |---\n3 | | Q ∨ P\n  |\n4 | | Q\n  | |---\n5 | | Q ∨ P\n6 | Q ∨ P";
+        assert_eq!(
+            infer(proof, RuleSet::lpl()),
+            vec![
+                (3, Some("∨ Intro: 2".to_owned())),
+                (5, Some("∨ Intro: 4".to_owned())),
+                (6, Some("∨ Elim: 1, 2-3, 4-5".to_owned()))
+            ]
+        );
+
+        // a correct line is left alone, and only the rules of the rule set are used
+        let proof = "1 | ¬¬P\n  |---\n2 | ¬¬P   Reit: 1\n3 | P";
+        assert_eq!(infer(proof, RuleSet::lpl()), vec![(3, Some("¬ Elim: 1".to_owned()))]);
+        assert_eq!(infer(proof, RuleSet::forallx()), vec![(3, Some("DNE: 1".to_owned()))]);
+        let intuitionistic = RuleSet::lpl().with_intuitionistic_logic(true);
+        assert_eq!(infer(proof, intuitionistic), vec![(3, None)]);
+    }
+}
@@ -1,19 +1,42 @@
 use wasm_bindgen::prelude::*;
+
+// defined before the modules, so that they can use it too
+macro_rules! default_variable_names {
+    () => {
+        "x,y,z,u,v,w"
+    };
+}
+
+mod blocks;
 mod checker;
 mod data;
+mod dependencies;
+mod editor;
+mod exercise;
 mod export_to_latex;
 mod fix_line_numbers;
+mod fol;
 mod formatter;
+mod hint;
+mod infer;
 mod parser;
 mod proof;
+mod prover;
+mod repair;
+mod rule_set;
+mod sat;
+mod template;
+mod truth_table;
 mod util;
-use crate::data::{ProofResult, Wff};
-
-macro_rules! default_variable_names {
-    () => {
-        "x,y,z,u,v,w"
-    };
-}
+pub use crate::data::{
+    CitedSentence, Diagnostic, ErrorCode, Grade, Hint, HintLevel, InferredJustification, LineScope,
+    ProofResult, ProvenSequent, Rule, Severity, TemplateRelaxation, TemplateValidity,
+};
+use crate::data::{ProofLine, Wff};
+pub use crate::exercise::{CheckOptions, Exercise, Feedback};
+pub use crate::rule_set::{ModalSystem, RuleSet};
+pub use crate::template::TemplateMatching;
+use std::collections::HashSet;
 
 /// Checks if a string is a fully correct proof.
 ///
@@ -26,12 +49,32 @@ macro_rules! default_variable_names {
 /// This function never panics.
 #[wasm_bindgen]
 pub fn check_proof(proof: &str, allowed_variable_names: &str) -> String {
-    let res = check_proof_to_proofresult(proof, allowed_variable_names);
-    match res {
-        ProofResult::Correct => "The proof is correct!".to_string(),
-        ProofResult::Error(errs) => errs.join("\n\n"),
-        ProofResult::FatalError(err) => format!("Fatal error: {err}"),
-    }
+    let options = CheckOptions::default().with_allowed_variable_names(allowed_variable_names);
+    render_check(check_proof_to_proofresult(proof, &options), Feedback::Full)
+}
+
+/// Checks if a string is a fully correct proof that only uses the rules of a built-in rule set
+/// ("lpl" or "forallx", or "lpl-intuitionistic" or "forallx-intuitionistic"; see [RuleSet]).
+///
+/// The result is a string, just like for [check_proof].
+///
+/// This function never panics.
+#[wasm_bindgen]
+pub fn check_proof_with_rule_set(
+    proof: &str,
+    allowed_variable_names: &str,
+    rule_set: &str,
+) -> String {
+    let Some(rule_set) = RuleSet::from_name(rule_set) else {
+        return render_proof_result(&ProofResult::FatalError(Diagnostic::fatal(
+            ErrorCode::UnknownRuleSet,
+            format!("there is no rule set called '{rule_set}'; use 'lpl' or 'forallx', optionally followed by '-intuitionistic'."),
+        )));
+    };
+    let options = CheckOptions::default()
+        .with_allowed_variable_names(allowed_variable_names)
+        .with_rule_set(rule_set);
+    render_check(check_proof_to_proofresult(proof, &options), Feedback::Full)
 }
 
 /// Checks if a string is a fully correct proof that matches a given proof template.
@@ -49,66 +92,472 @@ pub fn check_proof_with_template(
     template: Vec<String>,
     allowed_variable_names: &str,
 ) -> String {
-    let res = check_proof_to_proofresult_with_template(proof, &template, allowed_variable_names);
-    match res {
+    let options = CheckOptions::default()
+        .with_template(template)
+        .with_allowed_variable_names(allowed_variable_names);
+    render_check(check_proof_to_proofresult(proof, &options), Feedback::Full)
+}
+
+/// Checks if a string is a fully correct proof that matches a given proof template, with a
+/// [TemplateMatching] policy given by name (see [TemplateMatching::from_names]), for example
+/// "alpha-equivalence,any-premise-order".
+///
+/// The message is the same as that of [check_proof_with_template], followed by a note for every
+/// relaxation of the policy that was needed to match the template.
+///
+/// This function never panics.
+#[wasm_bindgen]
+pub fn check_proof_with_template_matching(
+    proof: &str,
+    template: Vec<String>,
+    allowed_variable_names: &str,
+    matching: &str,
+) -> String {
+    let Some(matching) = TemplateMatching::from_names(matching) else {
+        return render_proof_result(&ProofResult::FatalError(Diagnostic::fatal(
+            ErrorCode::UnknownTemplateMatching,
+            format!(
+                "'{matching}' is not a template matching policy; use 'strict', 'lenient', or \
+                some of 'alpha-equivalence', 'any-premise-order' and 'omitted-premises', \
+                separated by commas."
+            ),
+        )));
+    };
+    let options = CheckOptions::default()
+        .with_template(template)
+        .with_allowed_variable_names(allowed_variable_names)
+        .with_template_matching(matching);
+    render_check(check_proof_to_proofresult(proof, &options), Feedback::Full)
+}
+
+/// Checks if a string is a fully correct proof for the exercise in an exercise file (see
+/// [Exercise] for its format), so with the template, variable names, rules and limits that the
+/// exercise sets. How much the message says about the mistakes depends on its [Feedback].
+///
+/// Like for [check_proof_with_template_matching], a note follows for every relaxation of the
+/// template matching policy that was needed.
+///
+/// This function never panics.
+#[wasm_bindgen]
+pub fn check_proof_with_exercise(proof: &str, exercise: &str) -> String {
+    let exercise = match Exercise::from_toml(exercise) {
+        Ok(exercise) => exercise,
+        Err(err) => return render_proof_result(&ProofResult::FatalError(err)),
+    };
+    render_check(check_proof_to_proofresult(proof, &(&exercise).into()), exercise.feedback)
+}
+
+/// Turns a [ProofResult] into the human-readable message that [check_proof] and
+/// [check_proof_with_template] return.
+pub fn render_proof_result(result: &ProofResult) -> String {
+    match result {
         ProofResult::Correct => "The proof is correct!".to_string(),
-        ProofResult::Error(errs) => errs.join("\n\n"),
+        ProofResult::Error(errs) => {
+            errs.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n\n")
+        }
         ProofResult::FatalError(err) => format!("Fatal error: {err}"),
     }
 }
 
-/// Checks if a string is a fully correct proof.
+/// Turns a [ProofResult] into a human-readable message that says as much as a [Feedback] level
+/// allows: everything (like [render_proof_result]), only the first mistake, or only whether the
+/// proof is correct.
+pub fn render_proof_result_with_feedback(result: &ProofResult, feedback: Feedback) -> String {
+    match (result, feedback) {
+        (ProofResult::Correct, _) | (_, Feedback::Full) => render_proof_result(result),
+        (_, Feedback::Verdict) => "The proof is not correct.".to_string(),
+        (ProofResult::Error(errs), Feedback::FirstMistake) => {
+            render_proof_result(&ProofResult::Error(errs.iter().take(1).cloned().collect()))
+        }
+        (ProofResult::FatalError(_), Feedback::FirstMistake) => render_proof_result(result),
+    }
+}
+
+/// Renders the outcome of [check_proof_to_proofresult] for the functions that return a string: the
+/// message for the [ProofResult], followed by a note for every relaxation of the template.
+fn render_check(
+    (result, relaxations): (ProofResult, Vec<TemplateRelaxation>),
+    feedback: Feedback,
+) -> String {
+    std::iter::once(render_proof_result_with_feedback(&result, feedback))
+        .chain(relaxations.iter().map(|r| format!("Note: {r}")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Checks if a string is a fully correct proof, with the template, variable names, rules and
+/// limits of a [CheckOptions]; see [checker::check_proof_with_options] for everything that is
+/// checked. An [Exercise] is checked by converting it into its options:
+/// ```notrust
+/// check_proof_to_proofresult(proof, &CheckOptions::from(&exercise))
+/// ```
 ///
-/// This function returns its evaluation of the proof in a [ProofResult].
+/// This function returns its evaluation of the proof in a [ProofResult], so that the individual
+/// [Diagnostic]s can be inspected. Besides the [ProofResult], the relaxations of the
+/// [TemplateMatching] that were needed to match the template are returned (in the order of
+/// [TemplateRelaxation]), so that they can be reported.
 ///
-/// See also [parser::parse_fitch_proof] and [checker::check_proof].
+/// This function never panics.
+pub fn check_proof_to_proofresult(
+    proof: &str,
+    options: &CheckOptions,
+) -> (ProofResult, Vec<TemplateRelaxation>) {
+    check_proof_lines(parser::parse_fitch_proof_recovering(proof), options)
+}
+
+/// Does the work of [check_proof_to_proofresult] for a proof that is already parsed.
+fn check_proof_lines(
+    proof_lines: Vec<ProofLine>,
+    options: &CheckOptions,
+) -> (ProofResult, Vec<TemplateRelaxation>) {
+    let variable_names = match parser::parse_allowed_variable_names(&options.allowed_variable_names)
+    {
+        Ok(variable_names) => variable_names,
+        Err(err) => {
+            return (
+                ProofResult::FatalError(Diagnostic::fatal(ErrorCode::VariableNames, err)),
+                vec![],
+            )
+        }
+    };
+    let template = match options.template.as_deref().map(parse_template).transpose() {
+        Ok(template) => template,
+        Err(err) => return (ProofResult::FatalError(err), vec![]),
+    };
+    checker::check_proof_with_options(proof_lines, template, variable_names, options)
+}
+
+/// Grades a submission for an exercise: checks it like [check_proof_to_proofresult] does, counts
+/// its numbered lines, and gives it a score (see [Grade::score]). A correct proof scores 100 and a
+/// proof with a fatal error (or without numbered lines) 0. Any other proof scores
+/// `50 * good_lines / line_count`, rounded down, where `good_lines` is the number of numbered lines
+/// that could be parsed and have no error. So a proof whose only mistake is not about a line, like
+/// being longer than the exercise allows, scores 50.
 ///
 /// This function never panics.
-fn check_proof_to_proofresult(proof: &str, allowed_variable_names: &str) -> ProofResult {
-    match (
-        parser::parse_fitch_proof(proof),
-        parser::parse_allowed_variable_names(allowed_variable_names),
-    ) {
-        (Ok(proof_lines), Ok(variable_names)) => checker::check_proof(proof_lines, variable_names),
-        (Err(err), _) | (_, Err(err)) => ProofResult::FatalError(err),
+pub fn grade_proof(proof: &str, exercise: &Exercise) -> Grade {
+    let proof_lines = parser::parse_fitch_proof_recovering(proof);
+    let line_count = proof_lines.iter().filter(|l| l.line_num.is_some()).count();
+    // the numbered lines that could be parsed; the ones without an error are good
+    let parsed_lines: Vec<usize> = proof_lines
+        .iter()
+        .filter(|l| l.sentence.is_some() || l.constant_between_square_brackets.is_some())
+        .filter_map(|l| l.line_num)
+        .collect();
+    let (result, relaxations) = check_proof_lines(proof_lines, &exercise.into());
+    let score = match &result {
+        ProofResult::Correct => 100,
+        ProofResult::FatalError(_) => 0,
+        ProofResult::Error(_) if line_count == 0 => 0,
+        ProofResult::Error(errs) => {
+            let good_lines =
+                parsed_lines.iter().filter(|&&n| !errs.iter().any(|e| e.line == Some(n))).count();
+            (50 * good_lines / line_count) as u32
+        }
+    };
+    Grade {
+        result,
+        relaxations,
+        line_count,
+        score,
     }
 }
 
-/// Checks if a string is a fully correct proof that matches a given proof template.
+/// Decides whether a proof template is valid, i.e. whether its conclusion (the last sentence)
+/// follows from its premises (the other sentences), so that the exercise can be done.
 ///
-/// This function returns its evaluation of the proof in a [ProofResult].
+/// The outcome is returned as a human-readable message. If the template is not valid, the message
+/// describes a counter-model.
 ///
-/// See also [parser::parse_fitch_proof] and [checker::check_proof].
+/// This function never panics.
+#[wasm_bindgen]
+pub fn validate_template(template: Vec<String>) -> String {
+    render_template_validity(&validate_template_to_validity(&template))
+}
+
+/// Turns the outcome of [validate_template_to_validity] into the human-readable message that
+/// [validate_template] returns.
+pub fn render_template_validity(validity: &Result<TemplateValidity, Diagnostic>) -> String {
+    match validity {
+        Ok(TemplateValidity::Valid) => {
+            "The template is valid: its conclusion follows from its premises.".to_string()
+        }
+        Ok(TemplateValidity::Invalid(counter_model)) => {
+            format!("The template is not valid: {counter_model}")
+        }
+        Ok(TemplateValidity::Undecided(reason)) => {
+            format!("It could not be decided whether the template is valid: {reason}")
+        }
+        Err(err) => format!("Fatal error: {err}"),
+    }
+}
+
+/// Decides whether a proof template is valid (see [validate_template] and
+/// [template::template_validity]).
+///
+/// If the template is empty or one of its sentences cannot be parsed, a fatal [Diagnostic] is
+/// returned.
+///
+/// This function never panics.
+pub fn validate_template_to_validity(template: &[String]) -> Result<TemplateValidity, Diagnostic> {
+    if template.is_empty() {
+        return Err(Diagnostic::fatal(
+            ErrorCode::TemplateEmpty,
+            "The proof template is empty.".to_owned(),
+        ));
+    }
+    Ok(template::template_validity(&parse_template(template)?))
+}
+
+/// Suggests the next step in a proof that is not finished yet, at the given level of detail: 1
+/// only says where to look, 2 also names the rule to use, and 3 says exactly which step to take
+/// (see [HintLevel]). The template may be empty, but then only hints about the lines that are
+/// already in the proof can be given.
 ///
 /// This function never panics.
-fn check_proof_to_proofresult_with_template(
+#[wasm_bindgen]
+pub fn hint(
+    proof: &str,
+    template: Vec<String>,
+    allowed_variable_names: &str,
+    level: usize,
+) -> String {
+    let Some(level) = HintLevel::from_number(level) else {
+        return format!("Fatal error: there is no hint level {level}; use 1, 2 or 3.");
+    };
+    match hint_for_proof(proof, &template, allowed_variable_names, &RuleSet::lpl()) {
+        Ok(hint) => hint.at_level(level).to_owned(),
+        Err(err) => format!("Fatal error: {err}"),
+    }
+}
+
+/// Suggests the next step in a proof that is not finished yet (see [hint] and
+/// [proof::Proof::hint]), at all levels of detail. The hint only suggests rules in the
+/// [RuleSet].
+///
+/// The proof may end with a Fitch bar, like at the start of an exercise, or right after a
+/// subproof is opened: the hint is then about what to write below it. If the allowed variable
+/// names or the template cannot be parsed, or the structure of the proof is too broken to
+/// analyse, a fatal [Diagnostic] is returned.
+///
+/// This function never panics.
+pub fn hint_for_proof(
     proof: &str,
     template: &[String],
     allowed_variable_names: &str,
-) -> ProofResult {
-    match (
-        parser::parse_fitch_proof(proof),
-        parser::parse_allowed_variable_names(allowed_variable_names),
-    ) {
-        (Ok(proof_lines), Ok(variable_names)) => {
-            let template_wffs: Vec<Wff> = template
-                .iter()
-                .filter_map(|s| parser::parse_logical_expression_string(s))
-                .collect();
-            if template_wffs.len() != template.len() {
-                return ProofResult::FatalError("Some sentences in the template file could not be parsed. If you see this as a student on Themis, please contact the course staff as soon as possible; something is wrong on our side. Thanks!".to_owned());
-            }
-            checker::check_proof_with_template(proof_lines, template_wffs, variable_names)
-        }
-        (Err(err), _) | (_, Err(err)) => ProofResult::FatalError(err),
+    rule_set: &RuleSet,
+) -> Result<Hint, Diagnostic> {
+    let template_wffs = parse_template(template)?;
+    let proof = construct_recovering_with(
+        proof,
+        allowed_variable_names,
+        rule_set,
+        proof::Proof::construct_unfinished,
+    )?;
+    let template = (!template_wffs.is_empty()).then_some(template_wffs.as_slice());
+    Ok(proof.hint(template))
+}
+
+/// Constructs a [proof::Proof], skipping the lines that cannot be parsed (see
+/// [parser::parse_fitch_proof_recovering]).
+///
+/// If the allowed variable names cannot be parsed, or the structure of the proof is too broken to
+/// analyse, a fatal [Diagnostic] is returned: the first parse error, if there is one, since that
+/// is the likely cause.
+fn construct_recovering(
+    proof: &str,
+    allowed_variable_names: &str,
+    rule_set: &RuleSet,
+) -> Result<proof::Proof, Diagnostic> {
+    construct_recovering_with(proof, allowed_variable_names, rule_set, proof::Proof::construct)
+}
+
+/// Like [construct_recovering], with another way to construct the proof from its lines, such as
+/// [proof::Proof::construct_unfinished].
+fn construct_recovering_with(
+    proof: &str,
+    allowed_variable_names: &str,
+    rule_set: &RuleSet,
+    construct: fn(Vec<ProofLine>, HashSet<String>, RuleSet) -> Result<proof::Proof, Diagnostic>,
+) -> Result<proof::Proof, Diagnostic> {
+    let variable_names = parser::parse_allowed_variable_names(allowed_variable_names)
+        .map_err(|err| Diagnostic::fatal(ErrorCode::VariableNames, err))?;
+    let proof_lines = parser::parse_fitch_proof_recovering(proof);
+    let first_parse_error = proof_lines.iter().find_map(|l| l.parse_error.clone());
+    construct(proof_lines, variable_names, rule_set.clone())
+        .map_err(|err| checker::first_parse_error_or(err, first_parse_error))
+}
+
+/// Parses the sentences of a template, or returns a fatal [Diagnostic] about the first one that
+/// cannot be parsed.
+fn parse_template(template: &[String]) -> Result<Vec<Wff>, Diagnostic> {
+    let mut template_wffs: Vec<Wff> = vec![];
+    for (i, s) in template.iter().enumerate() {
+        let Some(wff) = parser::parse_logical_expression_string(s) else {
+            return Err(Diagnostic::fatal(
+                ErrorCode::TemplateUnparsable,
+                format!("sentence {} of the template ('{s}') could not be parsed.", i + 1),
+            ));
+        };
+        template_wffs.push(wff);
     }
+    Ok(template_wffs)
+}
+
+/// Looks for justifications for the lines of a proof that have no justification or a wrong one
+/// (see [proof::Proof::infer_justifications]), and returns what was found as a human-readable
+/// message, with one line per line of the proof.
+///
+/// This function never panics.
+#[wasm_bindgen]
+pub fn infer_justifications(proof: &str, allowed_variable_names: &str) -> String {
+    let inferred =
+        match infer_justifications_in_proof(proof, allowed_variable_names, &RuleSet::lpl()) {
+            Ok(inferred) => inferred,
+            Err(err) => return format!("Fatal error: {err}"),
+        };
+    if inferred.is_empty() {
+        return "Every line already has a correct justification.".to_string();
+    }
+    inferred
+        .iter()
+        .map(|i| match (&i.justification, i.was_missing) {
+            (Some(just), true) => format!("Line {} can be justified with '{just}'.", i.line),
+            (Some(just), false) => {
+                format!("The justification of line {} is wrong; '{just}' would be correct.", i.line)
+            }
+            (None, true) => format!("No justification was found for line {}.", i.line),
+            (None, false) => format!("No correct justification was found for line {}.", i.line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Looks for justifications for the lines of a proof that have no justification or a wrong one,
+/// using only the rules of a [RuleSet] (see [proof::Proof::infer_justifications]).
+///
+/// If the allowed variable names cannot be parsed, or the structure of the proof is too broken
+/// to analyse, a fatal [Diagnostic] is returned.
+///
+/// This function never panics.
+pub fn infer_justifications_in_proof(
+    proof: &str,
+    allowed_variable_names: &str,
+    rule_set: &RuleSet,
+) -> Result<Vec<InferredJustification>, Diagnostic> {
+    let mut proof = construct_recovering(proof, allowed_variable_names, rule_set)?;
+    Ok(proof.infer_justifications())
+}
+
+/// Works out which sequent a proof establishes and which of its premises it does not use (see
+/// [ProvenSequent]), and returns that as a human-readable message. The template may be empty;
+/// otherwise, the message also says which premises of the template are not needed.
+///
+/// The citations are followed as they are written, so for a proof with mistakes, the message
+/// says what the proof would show if its justifications were correct.
+///
+/// This function never panics.
+#[wasm_bindgen]
+pub fn proven_sequent(proof: &str, template: Vec<String>, allowed_variable_names: &str) -> String {
+    let sequent =
+        match proven_sequent_of_proof(proof, &template, allowed_variable_names, &RuleSet::lpl()) {
+            Ok(sequent) => sequent,
+            Err(err) => return format!("Fatal error: {err}"),
+        };
+    let mut messages = vec![format!("Your proof shows {}.", sequent.sequent)];
+    messages.extend(sequent.unused_premises.iter().map(|n| format!("Premise {n} is not used.")));
+    messages.extend(sequent.open_assumptions.iter().map(|n| {
+        format!("The conclusion depends on line {n}, which is not a premise of the proof.")
+    }));
+    messages.extend(
+        sequent
+            .unneeded_template_premises
+            .iter()
+            .map(|p| format!("The premise {p} of the template is not needed.")),
+    );
+    messages.join("\n")
+}
+
+/// Works out which sequent a proof establishes, which of its premises it does not use, and which
+/// premises of the template (which may be empty) it does not need (see
+/// [proof::Proof::proven_sequent]).
+///
+/// If the allowed variable names or the template cannot be parsed, the structure of the proof is
+/// too broken to analyse, or the proof contains no sentences, a fatal [Diagnostic] is returned.
+///
+/// This function never panics.
+pub fn proven_sequent_of_proof(
+    proof: &str,
+    template: &[String],
+    allowed_variable_names: &str,
+    rule_set: &RuleSet,
+) -> Result<ProvenSequent, Diagnostic> {
+    let proof = construct_recovering(proof, allowed_variable_names, rule_set)?;
+    let template_wffs = parse_template(template)?;
+    let template = (!template_wffs.is_empty()).then_some(template_wffs.as_slice());
+    proof.proven_sequent(template).ok_or_else(|| {
+        Diagnostic::fatal(ErrorCode::EmptyProof, "Your proof has no sentences in it.".to_owned())
+    })
+}
+
+/// Returns, for every physical line of a proof (the first one has index 0), the line number that
+/// is written in front of it, if any. Together with [Diagnostic::line] and
+/// [Diagnostic::related_lines], this tells an editor where to show a [Diagnostic].
+///
+/// This function never panics.
+pub fn source_line_numbers(proof: &str) -> Vec<Option<usize>> {
+    editor::line_numbers_by_source_line(proof)
+}
+
+/// Returns the lines and subproofs that the justification of line `line_num` cites, with their
+/// sentences, so that an editor can show them. Lines that cannot be parsed are skipped, so this
+/// also works while the proof is being written.
+///
+/// Returns [None] if there is no line `line_num` with a justification.
+///
+/// This function never panics.
+pub fn cited_sentences(proof: &str, line_num: usize) -> Option<Vec<CitedSentence>> {
+    editor::cited_sentences(&parser::parse_fitch_proof_recovering(proof), line_num)
+}
+
+/// Returns what line `line_num` of a proof may cite (see [proof::Proof::scope_of_line]), so that
+/// an editor can offer those lines and subproofs while a justification is being written.
+///
+/// Returns [None] if the variable names cannot be parsed, the structure of the proof is too broken
+/// to tell, or there is no line `line_num`.
+///
+/// This function never panics.
+pub fn scope_at_line(
+    proof: &str,
+    allowed_variable_names: &str,
+    rule_set: &RuleSet,
+    line_num: usize,
+) -> Option<LineScope> {
+    construct_recovering(proof, allowed_variable_names, rule_set).ok()?.line_scope(line_num)
 }
 
 /// Returns whether a string is a fully correct proof.
 ///
 /// This function never panics.
 pub fn proof_is_correct(proof: &str) -> bool {
-    matches!(check_proof_to_proofresult(proof, default_variable_names!()), ProofResult::Correct)
+    matches!(check_proof_to_proofresult(proof, &CheckOptions::default()).0, ProofResult::Correct)
+}
+
+/// Searches for a proof of `goal` from `premises` in propositional logic (see [prover::prove]).
+///
+/// If the goal is a tautological consequence of the premises, a formatted proof is returned that
+/// only uses the introduction and elimination rules and Reit. If it is not, or if one of the
+/// sentences cannot be parsed, [None] is returned.
+///
+/// This function never panics.
+#[wasm_bindgen]
+pub fn prove_sequent(premises: Vec<String>, goal: &str) -> Option<String> {
+    let premises = premises
+        .iter()
+        .map(|s| parser::parse_logical_expression_string(s))
+        .collect::<Option<Vec<Wff>>>()?;
+    let goal = parser::parse_logical_expression_string(goal)?;
+    prover::prove(&premises, &goal).map(formatter::format_proof)
 }
 
 /// Takes in a proof string as input, and tries to format that proof.
@@ -119,10 +568,15 @@ pub fn proof_is_correct(proof: &str) -> bool {
 /// This function never panics.
 #[wasm_bindgen]
 pub fn format_proof(proof: &str) -> String {
-    match parser::parse_fitch_proof(proof) {
-        Ok(lines) if !lines.is_empty() => formatter::format_proof(lines),
-        _ => proof.to_owned(),
-    }
+    format_proof_to_result(proof).unwrap_or_else(|_| proof.to_owned())
+}
+
+/// Formats a proof, like [format_proof]. If the proof cannot be parsed or is empty, a fatal
+/// [Diagnostic] says why.
+///
+/// This function never panics.
+pub fn format_proof_to_result(proof: &str) -> Result<String, Diagnostic> {
+    parse_nonempty_proof(proof).map(formatter::format_proof)
 }
 
 /// This function fixes the line numbers in a proof (in case they are not proper).
@@ -133,20 +587,60 @@ pub fn format_proof(proof: &str) -> String {
 /// This function never panics.
 #[wasm_bindgen]
 pub fn fix_line_numbers_in_proof(proof: &str) -> String {
-    match parser::parse_fitch_proof(proof) {
-        Ok(mut lines) if !lines.is_empty() => {
-            fix_line_numbers::fix_line_numbers(&mut lines);
-            formatter::format_proof(lines)
+    fix_line_numbers_to_result(proof).unwrap_or_else(|_| proof.to_owned())
+}
+
+/// Fixes the line numbers in a proof, like [fix_line_numbers_in_proof]. If the proof cannot be
+/// parsed or is empty, a fatal [Diagnostic] says why.
+///
+/// This function never panics.
+pub fn fix_line_numbers_to_result(proof: &str) -> Result<String, Diagnostic> {
+    let mut lines = parse_nonempty_proof(proof)?;
+    fix_line_numbers::fix_line_numbers(&mut lines);
+    Ok(formatter::format_proof(lines))
+}
+
+/// This function fills in the justifications that are missing or wrong in a proof, as far as
+/// they can be found (see [infer_justifications]).
+///
+/// The repaired proof is returned, formatted, with the lines that cannot be parsed left as they
+/// are. If the structure of the proof is too broken to analyse, the original string is returned.
+///
+/// This function never panics.
+#[wasm_bindgen]
+pub fn fill_in_justifications_in_proof(proof: &str, allowed_variable_names: &str) -> String {
+    match construct_recovering(proof, allowed_variable_names, &RuleSet::lpl()) {
+        Ok(mut constructed) => {
+            constructed.infer_justifications();
+            formatter::format_proof(constructed.lines)
         }
-        _ => proof.to_owned(),
+        Err(_) => proof.to_owned(),
     }
 }
 
 #[wasm_bindgen]
 pub fn export_to_latex(proof: &str) -> String {
-    match parser::parse_fitch_proof(proof) {
-        Ok(lines) if !lines.is_empty() => export_to_latex::proof_to_latex(&lines),
-        _ => "Failed to export to latex, because the proof could not be parsed or was empty."
-            .to_string(),
+    export_to_latex_to_result(proof).unwrap_or_else(|_| {
+        "Failed to export to latex, because the proof could not be parsed or was empty.".to_string()
+    })
+}
+
+/// Exports a proof to LaTeX, like [export_to_latex]. If the proof cannot be parsed or is empty, a
+/// fatal [Diagnostic] says why.
+///
+/// This function never panics.
+pub fn export_to_latex_to_result(proof: &str) -> Result<String, Diagnostic> {
+    parse_nonempty_proof(proof).map(|lines| export_to_latex::proof_to_latex(&lines))
+}
+
+/// Parses a proof for the functions that rewrite it, which need every line to be parsed.
+fn parse_nonempty_proof(proof: &str) -> Result<Vec<data::ProofLine>, Diagnostic> {
+    let lines = parser::parse_fitch_proof(proof)?;
+    if lines.is_empty() {
+        return Err(Diagnostic::fatal(
+            ErrorCode::EmptyProof,
+            "Your proof has no lines in it.".to_owned(),
+        ));
     }
+    Ok(lines)
 }
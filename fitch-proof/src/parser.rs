@@ -1,34 +1,148 @@
 use std::collections::HashSet;
 use std::iter;
 use std::iter::from_fn;
+use std::ops::Range;
 
 use crate::data::*;
 
 /// This function takes a string slice and tries to parse it as a full proof.
 ///
-/// If it succeeds, a vector of [ProofLine]s is returned. If it does not succeed, then a nice error
-/// message is returned.
+/// If it succeeds, a vector of [ProofLine]s is returned. If it does not succeed, then a fatal
+/// [Diagnostic] with a nice error message is returned.
 ///
 /// For a specification of the grammar that is used for parsing, see the documentation of the
 /// functions [parse_proof_line] and [parse_logical_expr].
-pub fn parse_fitch_proof(proof: &str) -> Result<Vec<ProofLine>, String> {
-    let mut last_line_num = 0;
+///
+/// See also [parse_fitch_proof_recovering], which does not give up on the first bad line.
+pub fn parse_fitch_proof(proof: &str) -> Result<Vec<ProofLine>, Diagnostic> {
+    let lines = parse_fitch_proof_recovering(proof);
+    match lines.iter().find_map(|l| l.parse_error.as_ref()) {
+        Some(err) => Err(Diagnostic {
+            severity: Severity::Fatal,
+            ..err.clone()
+        }),
+        None => Ok(lines),
+    }
+}
+
+/// This function takes a string slice and parses it as a full proof, like [parse_fitch_proof].
+/// However, if some line cannot be lexed or parsed, this function does not give up. Instead, that
+/// line becomes an unparsed [ProofLine] (see [ProofLine::parse_error]), of which the line number
+/// and depth are recovered from the start of the line if possible. This way, the rest of the
+/// proof can still be checked.
+///
+/// If the depth of an unparsed line cannot be recovered, it is assumed to be the same as the depth
+/// of the line before it.
+pub fn parse_fitch_proof_recovering(proof: &str) -> Vec<ProofLine> {
+    let mut prev_depth = 1;
     proof
         .lines()
-        .filter(|s| !s.is_empty())
-        .map(|x| match lex(x) {
-            Ok(toks) => match parse_proof_line(&toks) {
-                Ok(line) => {
-                    last_line_num = line.line_num.unwrap_or(last_line_num);
-                    Ok(line)
-                }
-                Err(err) => Err(format!("parser failure near line {}: {}", last_line_num + 1, err)),
-            },
-            Err(err) => Err(format!("lexer failure near line {}: {}", last_line_num + 1, err)),
+        .enumerate()
+        .filter(|(_, s)| !s.is_empty())
+        .map(|(i, x)| {
+            let line = parse_physical_line(i + 1, x, prev_depth);
+            if !line.is_comment_line() {
+                prev_depth = line.depth;
+            }
+            line
         })
         .collect()
 }
 
+/// Lexes and parses one line of the input. The second argument is the number of the physical line
+/// (starting at 1), which is used in error messages, and the third argument is the depth of the
+/// line before it.
+///
+/// A comment at the end of the line is split off before lexing, and stored in the resulting
+/// [ProofLine].
+///
+/// If the line cannot be lexed or parsed, an unparsed [ProofLine] is returned. Its line number and
+/// depth are recovered from the tokens at the start of the line (if the lexer failed, from the
+/// tokens before the bad character).
+fn parse_physical_line(physical_line: usize, text: &str, prev_depth: usize) -> ProofLine {
+    let (source, comment) = split_off_comment(text);
+    let comment = comment.map(|c| c.trim_end().to_string());
+    if comment.is_some() && source.trim().is_empty() {
+        return ProofLine {
+            line_num: None,
+            depth: 0,
+            is_fitch_bar_line: false,
+            is_strict_subproof_bar: false,
+            sentence: None,
+            justification: None,
+            constant_between_square_brackets: None,
+            parse_error: None,
+            comment,
+        };
+    }
+    let (code, kind, message, span, toks) = match lex(source) {
+        Ok(toks) => match parse_proof_line(&kinds(&toks)) {
+            Ok(line) => {
+                return ProofLine {
+                    comment,
+                    ..line
+                }
+            }
+            Err(err) => (
+                ErrorCode::Parser,
+                "parser",
+                err.message,
+                span_of_tokens(&toks, err.toks),
+                kinds(&toks),
+            ),
+        },
+        Err(err) => {
+            let prefix: String = source.chars().take(err.span.start).collect();
+            let toks = lex(&prefix).map(|toks| kinds(&toks)).unwrap_or_default();
+            (ErrorCode::Lexer, "lexer", err.message, err.span, toks)
+        }
+    };
+    let location = SourceLocation {
+        line: physical_line,
+        span,
+        source_line: text.to_string(),
+    };
+    let diagnostic = Diagnostic::error(
+        code,
+        None,
+        format!(
+            "{kind} failure on line {}, {}: {message}",
+            location.line,
+            location.columns_description()
+        ),
+    )
+    .with_location(location);
+
+    let (line_num, depth, rest) = match toks.as_slice() {
+        [TokenKind::Number(n), TokenKind::ConseqVertBar(d), rest @ ..] => (Some(*n), *d, rest),
+        [TokenKind::Number(n), rest @ ..] => (Some(*n), prev_depth, rest),
+        [TokenKind::ConseqVertBar(d), rest @ ..] => (None, *d, rest),
+        rest => (None, prev_depth, rest),
+    };
+    ProofLine {
+        line_num,
+        depth,
+        // a broken line without line number that starts with a minus was probably meant to be a
+        // Fitch bar
+        is_fitch_bar_line: line_num.is_none() && rest.first() == Some(&TokenKind::Dash),
+        is_strict_subproof_bar: false,
+        sentence: None,
+        justification: None,
+        constant_between_square_brackets: None,
+        parse_error: Some(diagnostic),
+        comment,
+    }
+}
+
+/// Splits a line of the input into the part before the comment and the comment itself (if there
+/// is one). A comment starts at the first `#` or `//` on the line.
+fn split_off_comment(line: &str) -> (&str, Option<&str>) {
+    match [line.find('#'), line.find("//")].into_iter().flatten().min() {
+        Some(i) => (&line[..i], Some(&line[i..])),
+        None => (line, None),
+    }
+}
+
 /// This function parses the list of strings that should be seen as a variable. This list should
 /// simply be a string slice like this: "x,y,z", which means that "x", "y" and "z" are the strings
 /// that should be seen as a variable.
@@ -37,23 +151,29 @@ pub fn parse_fitch_proof(proof: &str) -> Result<Vec<ProofLine>, String> {
 /// of which the name starts with an uppercase letter. If this happens, then an error message is
 /// returned. An error message is returned in all cases in which the parsing failed.
 ///
+/// The variable names follow the same rules as the names in a logical expression (see
+/// [parse_logical_expr]), so for example "x,x',x_1,x2" is fine as well.
+///
 /// If the parsing is successful, a [HashSet] containing the allowed variable names is returned.
 pub fn parse_allowed_variable_names(allowed_var_names: &str) -> Result<HashSet<String>, String> {
     let toks = match lex(allowed_var_names) {
-        Ok(toks) => toks,
+        Ok(toks) => kinds(&toks),
         Err(err) => {
-            return Err(format!("failure when lexing list of allowed variable names: {err}"))
+            return Err(format!(
+                "failure when lexing list of allowed variable names: {}",
+                err.message
+            ))
         }
     };
     let err_str = "the list of allowed variable names could not be parsed".to_string();
-    if toks.iter().any(|tok| !matches!(tok, Token::Name(_) | Token::Comma)) {
+    if toks.iter().any(|tok| !matches!(tok, TokenKind::Name(_) | TokenKind::Comma)) {
         return Err(err_str);
     }
     let mut allowed_variable_names: HashSet<String> = HashSet::from([]);
     let mut rem_toks = toks.as_slice();
 
     loop {
-        let Some(Token::Name(var_name)) = rem_toks.first() else {
+        let Some(TokenKind::Name(var_name)) = rem_toks.first() else {
             return Err(err_str);
         };
         if !var_name.chars().next().unwrap().is_ascii_lowercase() {
@@ -66,7 +186,7 @@ pub fn parse_allowed_variable_names(allowed_var_names: &str) -> Result<HashSet<S
         if rem_toks.len() == 1 {
             break;
         }
-        if !matches!(rem_toks[1], Token::Comma) {
+        if !matches!(rem_toks[1], TokenKind::Comma) {
             return Err(err_str);
         }
         rem_toks = &rem_toks[2..];
@@ -74,7 +194,6 @@ pub fn parse_allowed_variable_names(allowed_var_names: &str) -> Result<HashSet<S
     Ok(allowed_variable_names)
 }
 
-
 /// This function parses a *logical expression* from a String.
 ///
 /// If it succeeds, a [Wff] is returned. Otherwise, a nice error message is returned.
@@ -100,6 +219,8 @@ pub fn parse_allowed_variable_names(allowed_var_names: &str) -> Result<HashSet<S
 ///          | forall <VariableOrConstantName> <E3>
 ///          | exists <VariableOrConstantName> <E3>
 ///          | not <E3>
+///          | box <E3>
+///          | diamond <E3>
 ///          | bottom
 ///
 /// <Term> ::=
@@ -113,22 +234,37 @@ pub fn parse_allowed_variable_names(allowed_var_names: &str) -> Result<HashSet<S
 /// <PredicateName> : some string starting with an UPPERCASE letter
 /// <AtomicPropositionName> : some string starting with an UPPERCASE letter
 /// ```
+///
+/// All names start with a letter, which may be followed by any number of (ASCII) letters,
+/// digits, underscores and primes, like `a1`, `x_2`, `c'` or `P2`.
+///
+/// Apart from the Unicode symbols, the following ASCII notation is accepted as well:
+///
+/// ```notrust
+/// and     : &   /\
+/// or      : \/  v  |      (v and | only between two formulas, see [is_or])
+/// implies : ->
+/// bicond  : <->
+/// not     : ~   !
+/// forall  : forall x   Ax
+/// exists  : exists x   Ex
+/// bottom  : _|_
+/// box     : []
+/// diamond : <>
+/// ```
 pub fn parse_logical_expression_string(expr: &str) -> Option<Wff> {
     if let Ok(toks) = lex(expr) {
-        return match parse_logical_expr(&toks) {
-            Ok(expr) => Some(expr),
-            _ => None,
-        };
+        return parse_logical_expr(&kinds(&toks)).ok();
     }
     None
 }
 
 /* ----------------- PRIVATE -------------------*/
 
-/// This is an enum containing tokens. The lexer converts a [String] to a vector of [Token]s, which
-/// can then be used by the parser.
-#[derive(PartialEq, Debug)]
-enum Token {
+/// This is an enum containing the different kinds of tokens. The lexer converts a [String] to a
+/// vector of [Token]s, and the parser works on the kinds of those tokens.
+#[derive(PartialEq, Debug, Clone)]
+enum TokenKind {
     Name(String),
     LPar,
     RPar,
@@ -139,6 +275,8 @@ enum Token {
     Implies,
     Bicond,
     Not,
+    Box,
+    Diamond,
     Bottom,
     Comma,
     Equals,
@@ -150,114 +288,267 @@ enum Token {
     RSqBracket,
 }
 
-/// Generate a list of [Token]s from a [String]. If the lexer fails, a nice error message is returned.
-fn lex(input: &str) -> Result<Vec<Token>, String> {
+/// A token, together with the place in the input line where it was found.
+#[derive(PartialEq, Debug)]
+struct Token {
+    kind: TokenKind,
+    span: Span,
+}
+
+/// The error that the lexer gives when it cannot make sense of some characters.
+#[derive(PartialEq, Debug)]
+struct LexError {
+    message: String,
+    span: Span,
+}
+
+/// The error that the parser gives. Since the parser works on [TokenKind]s, the location of the
+/// error is given as the range of indices of the tokens that caused it.
+#[derive(PartialEq, Debug)]
+struct ParseError {
+    message: String,
+    toks: Range<usize>,
+}
+
+impl ParseError {
+    fn new(message: String, toks: Range<usize>) -> ParseError {
+        ParseError {
+            message,
+            toks,
+        }
+    }
+
+    /// For when the error was found in a slice of tokens that starts at index `offset`.
+    fn shifted(self, offset: usize) -> ParseError {
+        ParseError::new(self.message, self.toks.start + offset..self.toks.end + offset)
+    }
+}
+
+fn kinds(toks: &[Token]) -> Vec<TokenKind> {
+    toks.iter().map(|t| t.kind.clone()).collect()
+}
+
+/// Returns the characters that are covered by a range of tokens. If the range is empty, the
+/// position right after the preceding token is returned.
+fn span_of_tokens(toks: &[Token], range: Range<usize>) -> Span {
+    match (toks.get(range.start), range.end.checked_sub(1).and_then(|i| toks.get(i))) {
+        (Some(first), Some(last)) if !range.is_empty() => Span {
+            start: first.span.start,
+            end: last.span.end,
+        },
+        _ => {
+            let pos =
+                range.start.checked_sub(1).and_then(|i| toks.get(i)).map_or(0, |t| t.span.end);
+            Span {
+                start: pos,
+                end: pos + 1,
+            }
+        }
+    }
+}
+
+/// Generate a list of [Token]s from a [String]. If the lexer fails, a nice error message is
+/// returned, together with the location of the offending characters.
+fn lex(input: &str) -> Result<Vec<Token>, LexError> {
     let mut toks: Vec<Token> = Vec::new();
-    let mut input_iter = input.chars().peekable();
-
-    while let Some(ch) = input_iter.next() {
-        match ch {
-            ' ' => {} // ignore spaces
-            '(' => toks.push(Token::LPar),
-            ')' => toks.push(Token::RPar),
-            '\u{2200}' => toks.push(Token::Forall),
-            '\u{2203}' => toks.push(Token::Exists),
-            '\u{2227}' => toks.push(Token::And),
-            '\u{2228}' => toks.push(Token::Or),
-            '\u{2192}' => toks.push(Token::Implies),
-            '\u{2194}' => toks.push(Token::Bicond),
-            '\u{00AC}' => toks.push(Token::Not),
-            ',' => toks.push(Token::Comma),
-            '=' => toks.push(Token::Equals),
+    let chars: Vec<char> = input.chars().collect();
+    let mut input_iter = chars.iter().copied().enumerate().peekable();
+
+    while let Some((start, ch)) = input_iter.next() {
+        let kind = match ch {
+            ' ' => continue, // ignore spaces
+            '(' => TokenKind::LPar,
+            ')' => TokenKind::RPar,
+            '\u{2200}' => TokenKind::Forall,
+            '\u{2203}' => TokenKind::Exists,
+            '\u{2227}' => TokenKind::And,
+            '\u{2228}' => TokenKind::Or,
+            '\u{2192}' => TokenKind::Implies,
+            '\u{2194}' => TokenKind::Bicond,
+            '\u{00AC}' => TokenKind::Not,
+            '\u{25A1}' | '\u{25FB}' => TokenKind::Box,
+            '\u{25C7}' | '\u{25CA}' => TokenKind::Diamond,
+            // ASCII notation for the connectives
+            '&' => TokenKind::And,
+            '~' | '!' => TokenKind::Not,
+            '/' if next_chars_are(&chars, start + 1, "\\") => {
+                input_iter.next();
+                TokenKind::And
+            }
+            '\\' if next_chars_are(&chars, start + 1, "/") => {
+                input_iter.next();
+                TokenKind::Or
+            }
+            '-' if next_chars_are(&chars, start + 1, ">") => {
+                input_iter.next();
+                TokenKind::Implies
+            }
+            '<' if next_chars_are(&chars, start + 1, "->") => {
+                input_iter.nth(1);
+                TokenKind::Bicond
+            }
+            '<' if next_chars_are(&chars, start + 1, ">") => {
+                input_iter.next();
+                TokenKind::Diamond
+            }
+            '[' if next_chars_are(&chars, start + 1, "]") => {
+                input_iter.next();
+                TokenKind::Box
+            }
+            '_' if next_chars_are(&chars, start + 1, "|_") => {
+                input_iter.nth(1);
+                TokenKind::Bottom
+            }
+            ',' => TokenKind::Comma,
+            '=' => TokenKind::Equals,
             'a'..='z' | 'A'..='Z' => {
                 let name = iter::once(ch)
-                    .chain(from_fn(|| input_iter.by_ref().next_if(|c| c.is_ascii_alphabetic())))
+                    .chain(from_fn(|| {
+                        input_iter
+                            .by_ref()
+                            .next_if(|&(i, c)| is_name_char(&chars, i, c))
+                            .map(|(_, c)| c)
+                    }))
                     .collect::<String>();
-                toks.push(Token::Name(name));
+                match name.as_str() {
+                    "forall" => TokenKind::Forall,
+                    "exists" => TokenKind::Exists,
+                    _ => TokenKind::Name(name),
+                }
             }
             '1'..='9' => {
-                let num: Result<usize, _> = iter::once(ch)
-                    .chain(from_fn(|| input_iter.by_ref().next_if(|c| c.is_ascii_digit())))
-                    .collect::<String>()
-                    .parse();
-                let err = "there was an integer bigger than 999999999".to_string();
-                if let Ok(n) = num {
-                    if n > 999999999 {
-                        return Err(err);
+                let digits = iter::once(ch)
+                    .chain(from_fn(|| {
+                        input_iter.by_ref().next_if(|(_, c)| c.is_ascii_digit()).map(|(_, c)| c)
+                    }))
+                    .collect::<String>();
+                let num: Result<usize, _> = digits.parse();
+                match num {
+                    Ok(n) if n <= 999999999 => TokenKind::Number(n),
+                    _ => {
+                        return Err(LexError {
+                            message: "there was an integer bigger than 999999999".to_string(),
+                            span: Span {
+                                start,
+                                end: start + digits.chars().count(),
+                            },
+                        });
                     }
-                    toks.push(Token::Number(n));
-                } else {
-                    return Err(err);
                 }
             }
             '|' => {
                 let num: usize = iter::once(ch)
-                    .chain(from_fn(|| input_iter.by_ref().next_if(|c| c == &'|' || c == &' ')))
+                    .chain(from_fn(|| {
+                        input_iter.by_ref().next_if(|(_, c)| c == &'|' || c == &' ').map(|(_, c)| c)
+                    }))
                     .filter(|c| c == &'|')
-                    .collect::<String>()
-                    .len();
-                toks.push(Token::ConseqVertBar(num));
+                    .count();
+                TokenKind::ConseqVertBar(num)
             }
-            ':' => toks.push(Token::Colon),
-            '-' => toks.push(Token::Dash),
-            '[' => toks.push(Token::LSqBracket),
-            ']' => toks.push(Token::RSqBracket),
-            '⊥' => toks.push(Token::Bottom),
+            ':' => TokenKind::Colon,
+            '-' => TokenKind::Dash,
+            '[' => TokenKind::LSqBracket,
+            ']' => TokenKind::RSqBracket,
+            '⊥' => TokenKind::Bottom,
             _ => {
-                let mut err: String = "invalid character found: ".to_owned();
-                err.push(ch);
-                return Err(err);
+                return Err(LexError {
+                    message: format!("invalid character found: {ch}"),
+                    span: Span {
+                        start,
+                        end: start + 1,
+                    },
+                });
             }
+        };
+        // the token ends where the next one starts (not counting spaces that were eaten, which
+        // happens for vertical bars)
+        let mut end = input_iter.peek().map_or(chars.len(), |(i, _)| *i);
+        while end > start + 1 && chars[end - 1] == ' ' {
+            end -= 1;
         }
+        toks.push(Token {
+            kind,
+            span: Span {
+                start,
+                end,
+            },
+        });
     }
 
     Ok(toks)
 }
 
+/// Returns whether the characters starting at index `pos` are exactly the characters of `rest`.
+fn next_chars_are(chars: &[char], pos: usize, rest: &str) -> bool {
+    rest.chars().enumerate().all(|(i, c)| chars.get(pos + i) == Some(&c))
+}
+
+/// Returns whether `c`, found at position `pos` of `chars`, can be part of a name that started
+/// before it. An underscore that starts an ASCII bottom (`_|_`) cannot.
+fn is_name_char(chars: &[char], pos: usize, c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '\'' || (c == '_' && !next_chars_are(chars, pos + 1, "|_"))
+}
+
+/// Returns whether the token means "or". Apart from the proper symbol, in ASCII notation the
+/// letter `v` and a single vertical bar are used for "or" as well. This is unambiguous, as long as
+/// this function is only used for a token that comes right after a complete `<E2>` (or at the
+/// start of a justification): a variable or constant cannot appear there, and the vertical bars
+/// that indicate the depth of a proof line have already been consumed.
+fn is_or(tok: &TokenKind) -> bool {
+    match tok {
+        TokenKind::Or | TokenKind::ConseqVertBar(1) => true,
+        TokenKind::Name(name) => name == "v",
+        _ => false,
+    }
+}
+
 /// This function parses a *logical expression* from a list of [Token]s.
 ///
 /// If it succeeds, a [Wff] is returned. Otherwise, a nice error message is returned.
 ///
 /// The grammar: see documentation of [parser::parse_logical_expression_string].
 ///
-fn parse_logical_expr(toks: &[Token]) -> Result<Wff, String> {
+/// If only the first part of the tokens can be parsed, then the error points at the rest of the
+/// tokens. Otherwise, it points at all tokens.
+fn parse_logical_expr(toks: &[TokenKind]) -> Result<Wff, ParseError> {
+    let err_str = "failed to parse logical expression".to_string();
     if let Some((wff, rem_toks)) = parse_e1(toks) {
         if rem_toks.is_empty() {
             // there should be no remaining tokens!
             return Ok(wff);
         } else {
-            return Err("failed to parse logical expression".to_string());
+            return Err(ParseError::new(err_str, toks.len() - rem_toks.len()..toks.len()));
         }
     }
-    Err("failed to parse logical expression".to_string())
+    Err(ParseError::new(err_str, 0..toks.len()))
 }
 
 /// Parse an `<E1>` as defined by the grammar specified in the documentation of [parse_logical_expr].
-fn parse_e1(toks: &[Token]) -> Option<(Wff, &[Token])> {
+fn parse_e1(toks: &[TokenKind]) -> Option<(Wff, &[TokenKind])> {
     // always accept the first <E2>
     if let Some((wff, mut rem_toks)) = parse_e2(toks) {
         if rem_toks.is_empty() {
             return Some((wff, rem_toks));
         }
-        return match rem_toks[0] {
+        return match &rem_toks[0] {
             // <E2> implies <E2>
-            Token::Implies => {
+            TokenKind::Implies => {
                 if let Some((wff2, rem_rem_toks)) = parse_e2(rem_toks.get(1..)?) {
                     return Some((Wff::Implies(Box::new(wff), Box::new(wff2)), rem_rem_toks));
                 }
                 None
             }
             // <E2> implies <E2>
-            Token::Bicond => {
+            TokenKind::Bicond => {
                 if let Some((wff2, rem_rem_toks)) = parse_e2(rem_toks.get(1..)?) {
                     return Some((Wff::Bicond(Box::new(wff), Box::new(wff2)), rem_rem_toks));
                 }
                 None
             }
             // <E2> and <E2> {and <E2>}
-            Token::And => {
+            TokenKind::And => {
                 let mut conjs: Vec<Wff> = vec![wff];
-                while !rem_toks.is_empty() && rem_toks[0] == Token::And {
+                while !rem_toks.is_empty() && rem_toks[0] == TokenKind::And {
                     if let Some((new_wff, rem_rem_toks)) = parse_e2(rem_toks.get(1..)?) {
                         rem_toks = rem_rem_toks;
                         conjs.push(new_wff);
@@ -268,9 +559,9 @@ fn parse_e1(toks: &[Token]) -> Option<(Wff, &[Token])> {
                 Some((Wff::And(conjs), rem_toks))
             }
             // <E2> or <E2> {or <E2>}
-            Token::Or => {
+            tok if is_or(tok) => {
                 let mut disjs: Vec<Wff> = vec![wff];
-                while !rem_toks.is_empty() && rem_toks[0] == Token::Or {
+                while !rem_toks.is_empty() && is_or(&rem_toks[0]) {
                     if let Some((new_wff, rem_rem_toks)) = parse_e2(rem_toks.get(1..)?) {
                         rem_toks = rem_rem_toks;
                         disjs.push(new_wff);
@@ -289,7 +580,7 @@ fn parse_e1(toks: &[Token]) -> Option<(Wff, &[Token])> {
 }
 
 /// Parse an `<E2>` as defined by the grammar specified in the documentation of [parse_logical_expr].
-fn parse_e2(toks: &[Token]) -> Option<(Wff, &[Token])> {
+fn parse_e2(toks: &[TokenKind]) -> Option<(Wff, &[TokenKind])> {
     // just <E3>
     if let Some((wff, rem_toks)) = parse_e3(toks) {
         return Some((wff, rem_toks));
@@ -297,7 +588,7 @@ fn parse_e2(toks: &[Token]) -> Option<(Wff, &[Token])> {
 
     // <Term> equals <Term>
     if let Some((term1, rem_toks1)) = parse_term(toks) {
-        if rem_toks1.first()? == &Token::Equals {
+        if rem_toks1.first()? == &TokenKind::Equals {
             if let Some((term2, rem_toks2)) = parse_term(rem_toks1.get(1..)?) {
                 return Some((Wff::Equals(term1, term2), rem_toks2));
             }
@@ -308,32 +599,43 @@ fn parse_e2(toks: &[Token]) -> Option<(Wff, &[Token])> {
 }
 
 /// Parse an `<E3>` as defined by the grammar specified in the documentation of [parse_logical_expr].
-fn parse_e3(toks: &[Token]) -> Option<(Wff, &[Token])> {
+fn parse_e3(toks: &[TokenKind]) -> Option<(Wff, &[TokenKind])> {
     match toks.first()? {
-        Token::Name(name) if name.chars().next()?.is_uppercase() => {
+        TokenKind::Name(name) if name.chars().next()?.is_uppercase() => {
+            if let Some(quantified) = parse_ascii_quantifier(name, &toks[1..]) {
+                return Some(quantified);
+            }
             if let Some((terms, rem_toks)) = parse_arg_list(toks.get(1..)?) {
                 Some((Wff::PredApp(name.to_string(), terms), rem_toks))
             } else {
                 Some((Wff::Atomic(name.to_string()), &toks[1..]))
             }
         }
-        Token::Not => {
+        TokenKind::Not => {
             if let Some((wff, rem_toks)) = parse_e3(&toks[1..]) {
                 Some((Wff::Not(Box::new(wff)), rem_toks))
             } else {
                 None
             }
         }
-        Token::LPar => {
+        TokenKind::Box => {
+            let (wff, rem_toks) = parse_e3(&toks[1..])?;
+            Some((Wff::Necessarily(Box::new(wff)), rem_toks))
+        }
+        TokenKind::Diamond => {
+            let (wff, rem_toks) = parse_e3(&toks[1..])?;
+            Some((Wff::Possibly(Box::new(wff)), rem_toks))
+        }
+        TokenKind::LPar => {
             if let Some((wff, rem_toks)) = parse_e1(&toks[1..]) {
-                if rem_toks.first()? == &Token::RPar {
+                if rem_toks.first()? == &TokenKind::RPar {
                     return Some((wff, &rem_toks[1..]));
                 }
             }
             None
         }
-        Token::Forall => match toks.get(1)? {
-            Token::Name(name) if name.chars().next()?.is_lowercase() => {
+        TokenKind::Forall => match toks.get(1)? {
+            TokenKind::Name(name) if name.chars().next()?.is_lowercase() => {
                 if let Some((wff, rem_toks)) = parse_e3(toks.get(2..)?) {
                     return Some((Wff::Forall(name.to_owned(), Box::new(wff)), rem_toks));
                 }
@@ -341,8 +643,8 @@ fn parse_e3(toks: &[Token]) -> Option<(Wff, &[Token])> {
             }
             _ => None,
         },
-        Token::Exists => match toks.get(1)? {
-            Token::Name(name) if name.chars().next()?.is_lowercase() => {
+        TokenKind::Exists => match toks.get(1)? {
+            TokenKind::Name(name) if name.chars().next()?.is_lowercase() => {
                 if let Some((wff, rem_toks)) = parse_e3(toks.get(2..)?) {
                     return Some((Wff::Exists(name.to_owned(), Box::new(wff)), rem_toks));
                 }
@@ -350,15 +652,39 @@ fn parse_e3(toks: &[Token]) -> Option<(Wff, &[Token])> {
             }
             _ => None,
         },
-        Token::Bottom => Some((Wff::Bottom, &toks[1..])),
+        TokenKind::Bottom => Some((Wff::Bottom, &toks[1..])),
         _ => None,
     }
 }
 
+/// In ASCII notation, `Ax <E3>` means `∀x <E3>` and `Ex <E3>` means `∃x <E3>`. The lexer sees `Ax`
+/// as a single name, so this function tries to read a name as such a quantifier, followed by an
+/// `<E3>` in `rem_toks`. If that does not work, then [None] is returned, and the name should be
+/// read as an atomic proposition or predicate instead.
+fn parse_ascii_quantifier<'a>(
+    name: &str,
+    rem_toks: &'a [TokenKind],
+) -> Option<(Wff, &'a [TokenKind])> {
+    let (is_forall, var) = match name.split_at(1) {
+        ("A", var) => (true, var),
+        ("E", var) => (false, var),
+        _ => return None,
+    };
+    if !var.chars().next()?.is_lowercase() {
+        return None;
+    }
+    let (wff, rem_toks) = parse_e3(rem_toks)?;
+    if is_forall {
+        Some((Wff::Forall(var.to_owned(), Box::new(wff)), rem_toks))
+    } else {
+        Some((Wff::Exists(var.to_owned(), Box::new(wff)), rem_toks))
+    }
+}
+
 /// Parse a `<Term>` as defined by the grammar specified in the documentation of [parse_logical_expr].
-fn parse_term(toks: &[Token]) -> Option<(Term, &[Token])> {
+fn parse_term(toks: &[TokenKind]) -> Option<(Term, &[TokenKind])> {
     match toks.first()? {
-        Token::Name(name) => {
+        TokenKind::Name(name) => {
             if let Some((terms, rem_toks)) = parse_arg_list(&toks[1..]) {
                 Some((Term::FuncApp(name.to_string(), terms), rem_toks))
             } else {
@@ -370,8 +696,8 @@ fn parse_term(toks: &[Token]) -> Option<(Term, &[Token])> {
 }
 
 /// Parse an `<ArgList>` as defined by the grammar specified in the documentation of [parse_logical_expr].
-fn parse_arg_list(toks: &[Token]) -> Option<(Vec<Term>, &[Token])> {
-    if toks.first()? != &Token::LPar {
+fn parse_arg_list(toks: &[TokenKind]) -> Option<(Vec<Term>, &[TokenKind])> {
+    if toks.first()? != &TokenKind::LPar {
         return None;
     }
 
@@ -379,7 +705,7 @@ fn parse_arg_list(toks: &[Token]) -> Option<(Vec<Term>, &[Token])> {
 
     if let Some((term, mut rem_toks)) = parse_term(&toks[1..]) {
         terms.push(term);
-        while rem_toks.first()? == &Token::Comma {
+        while rem_toks.first()? == &TokenKind::Comma {
             if let Some((term2, rem_rem_toks)) = parse_term(rem_toks.get(1..)?) {
                 terms.push(term2);
                 rem_toks = rem_rem_toks;
@@ -388,7 +714,7 @@ fn parse_arg_list(toks: &[Token]) -> Option<(Vec<Term>, &[Token])> {
             }
         }
 
-        if rem_toks.first()? == &Token::RPar {
+        if rem_toks.first()? == &TokenKind::RPar {
             Some((terms, &rem_toks[1..]))
         } else {
             None
@@ -409,6 +735,7 @@ fn parse_arg_list(toks: &[Token]) -> Option<(Vec<Term>, &[Token])> {
 ///                      | <num> '|' { '|' } <E1>                             // premise
 ///                      | <num> '|' { '|' } '[' <ConstantName> ']' [ <E1> ]  // premise with box
 ///                      | '|' { '|' } - { - }                                // fitch bar
+///                      | '|' { '|' } '□' - { - }                            // strict subproof bar
 ///                      | '|' { '|' }                                        // empty line
 ///
 /// <ConstantName> : some string starting with lowercase letter
@@ -438,7 +765,25 @@ fn parse_arg_list(toks: &[Token]) -> Option<(Vec<Term>, &[Token])> {
 ///                      | Forall Elim: <num>
 ///                      | Exists Intro: <num>
 ///                      | Exists Elim: <num>, <numrange>
+///                      | Taut Con: [ <num> {, <num>} ]
+///                      | FO Con: [ <num> {, <num>} ]
+///                      | Ana Con: [ <num> {, <num>} ]
+///                      | DS: <num>, <num>
+///                      | MT: <num>, <num>
+///                      | DNE: <num>
+///                      | LEM: <numrange>, <numrange>
+///                      | IP: <numrange>
+///                      | DeM: <num>
+///                      | CQ: <num>
+///                      | Box Intro: <numrange>
+///                      | Box Elim: <num>
+///                      | Diamond Intro: <num>
+///                      | Diamond Elim: <num>
+///                      | R Box: <num>
 ///
+/// The rules can also be written with their forall x names: 'I' and 'E' instead of 'Intro' and
+/// 'Elim', R for Reit, X for Bottom Elim, TND for LEM, and Not Elim with two lines for Bottom
+/// Intro.
 /// ```
 ///
 /// Note that Fitch proof lines are not very straightforward to parse, because it can be difficult
@@ -446,64 +791,71 @@ fn parse_arg_list(toks: &[Token]) -> Option<(Vec<Term>, &[Token])> {
 /// token only appears in the `<Justification>`, not in `<E1>`, `<num>` or `<ConstantName>`. Hence, if we
 /// want to parse a proof line, we first check whether there is a colon token in it. If there is,
 /// then we parse the justification first. If the line ends with =Intro, then we also parse the
-/// justification first (=Intro is the only justification without colon). For the rest, everything
+/// justification first (=Intro, or =I, is the only justification without colon). For the rest, everything
 /// can just be done normally from left to right.
-
-fn parse_proof_line(toks: &[Token]) -> Result<ProofLine, String> {
-    if toks.contains(&Token::Colon)
-        || (toks.last() == Some(&Token::Name("Intro".to_string())) // special check for =Intro
-            && toks.get(toks.len() - 2) == Some(&Token::Equals))
+fn parse_proof_line(toks: &[TokenKind]) -> Result<ProofLine, ParseError> {
+    if toks.contains(&TokenKind::Colon)
+        || (matches!(toks.last(), Some(TokenKind::Name(name)) if name == "Intro" || name == "I") // special check for =Intro
+            && toks.len().checked_sub(2).and_then(|i| toks.get(i)) == Some(&TokenKind::Equals))
     {
         // we know that in this case, <FitchProofLine> ::= <num> '|' { '|' } <E1> <Justification>
         // since only a Justification can legally contain a colon token or end with =Intro
-        let colon_index: usize = toks.iter().position(|t| t == &Token::Colon).unwrap_or(toks.len());
+        let colon_index: usize =
+            toks.iter().position(|t| t == &TokenKind::Colon).unwrap_or(toks.len());
         // note that we set colon_index to toks.len() in case of =Intro
 
         if colon_index < 4 {
-            return Err("failed to parse proof line. The proof line contains a colon, but this colon appears so early that it cannot possibly be a justification".to_string());
+            return Err(ParseError::new("failed to parse proof line. The proof line contains a colon, but this colon appears so early that it cannot possibly be a justification".to_string(), colon_index..colon_index + 1));
             // colon cannot appear this early in a sentence
         }
-        let toks_before_justification: &[Token];
-        let toks_justification: &[Token];
-        if let Token::Name(name) = &toks[colon_index - 1] {
-            match name.as_str() {
-                "Reit" => {
-                    toks_before_justification = &toks[..colon_index - 1];
-                    toks_justification = &toks[colon_index - 1..];
-                }
-                "Intro" | "Elim" => {
-                    toks_before_justification = &toks[..colon_index - 2];
-                    toks_justification = &toks[colon_index - 2..];
+        // the index at which the justification starts
+        let justification_start = match &toks[colon_index - 1] {
+            TokenKind::Name(name) => match name.as_str() {
+                "Reit" | "R" | "X" | "DS" | "MT" | "DNE" | "LEM" | "TND" | "IP" | "DeM" | "CQ" => {
+                    colon_index - 1
                 }
+                "Intro" | "Elim" | "I" | "E" | "Con" => colon_index - 2,
                 _ => {
-                    return Err(format!("failed to parse justification. Expected \'Reit\', \'Intro\' or \'Elim\', found \'{name}\'. Note that capitalization matters!"));
+                    return Err(ParseError::new(format!("failed to parse justification. Expected \'Reit\', \'Intro\', \'Elim\', \'Con\' or the name of a derived rule, found \'{name}\'. Note that capitalization matters!"), colon_index - 1..colon_index));
                 }
+            },
+            // R□ is the only rule whose name ends in a symbol
+            TokenKind::Box if toks[colon_index - 2] == TokenKind::Name("R".to_owned()) => {
+                colon_index - 2
             }
-
-            if let (
-                Some(Token::Number(line_num)),
-                Some(Token::ConseqVertBar(depth)),
-                justific,
-                wff,
-            ) = (
-                toks_before_justification.first(),
-                toks_before_justification.get(1),
-                parse_justification(toks_justification)?,
-                parse_logical_expr(toks_before_justification.get(2..).unwrap_or(&[]))?,
-            ) {
-                Ok(ProofLine {
-                    line_num: Some(*line_num),
-                    depth: *depth,
-                    is_fitch_bar_line: false,
-                    sentence: Some(wff),
-                    justification: Some(justific),
-                    constant_between_square_brackets: None,
-                })
-            } else {
-                Err("a line with an inference should always start with a line number (integer), followed by at least one vertical bar.".to_string())
+            _ => {
+                return Err(ParseError::new("sentence contains a colon, which was expected to be preceded by \'Intro\', \'Elim\' or \'Reit\' (with that capitalization), but the parser did not find any of these.".to_string(), colon_index - 1..colon_index + 1));
             }
+        };
+        let toks_before_justification = &toks[..justification_start];
+        let toks_justification = &toks[justification_start..];
+
+        if let (
+            Some(TokenKind::Number(line_num)),
+            Some(TokenKind::ConseqVertBar(depth)),
+            justific,
+            wff,
+        ) = (
+            toks_before_justification.first(),
+            toks_before_justification.get(1),
+            parse_justification(toks_justification)
+                .map_err(|err| ParseError::new(err, toks_before_justification.len()..toks.len()))?,
+            parse_logical_expr(toks_before_justification.get(2..).unwrap_or(&[]))
+                .map_err(|err| err.shifted(2))?,
+        ) {
+            Ok(ProofLine {
+                line_num: Some(*line_num),
+                depth: *depth,
+                is_fitch_bar_line: false,
+                is_strict_subproof_bar: false,
+                sentence: Some(wff),
+                justification: Some(justific),
+                constant_between_square_brackets: None,
+                parse_error: None,
+                comment: None,
+            })
         } else {
-            Err("sentence contains a colon, which was expected to be preceded by \'Intro\', \'Elim\' or \'Reit\' (with that capitalization), but the parser did not find any of these.".to_string())
+            Err(ParseError::new("a line with an inference should always start with a line number (integer), followed by at least one vertical bar.".to_string(), 0..2))
         }
     } else {
         // Now we must be in one if these cases:
@@ -511,25 +863,28 @@ fn parse_proof_line(toks: &[Token]) -> Result<ProofLine, String> {
         //  <num> '|' { '|' } '[' <ConstantName> ']' [ <E1> ]
         //  '|' { '|' } - { - }
         //  '|' { '|' }
-        if toks.first().is_none() {
-            return Err("one proof line appears to be empty".to_string());
+        if toks.is_empty() {
+            return Err(ParseError::new("one proof line appears to be empty".to_string(), 0..0));
         }
         match toks.first().unwrap() {
-            Token::Number(num) => {
-                let Some(Token::ConseqVertBar(depth)) = toks.get(1) else {
-                    return Err("after the line number, there should be at least one vertical bar"
-                        .to_string());
+            TokenKind::Number(num) => {
+                let Some(TokenKind::ConseqVertBar(depth)) = toks.get(1) else {
+                    return Err(ParseError::new(
+                        "after the line number, there should be at least one vertical bar"
+                            .to_string(),
+                        1..2,
+                    ));
                 };
                 let mut const_betw_sqbr: Option<Term> = None;
                 let expression_start_index: usize = if let (
-                    Some(Token::LSqBracket),
-                    Some(Token::Name(name)),
-                    Some(Token::RSqBracket),
+                    Some(TokenKind::LSqBracket),
+                    Some(TokenKind::Name(name)),
+                    Some(TokenKind::RSqBracket),
                 ) = (toks.get(2), toks.get(3), toks.get(4))
                 {
                     const_betw_sqbr = Some(Term::Atomic(name.to_string()));
                     if !name.chars().next().unwrap_or('U').is_lowercase() {
-                        return Err("a boxed constant must be a constant; it should start with a lowercase letter".to_string());
+                        return Err(ParseError::new("a boxed constant must be a constant; it should start with a lowercase letter".to_string(), 3..4));
                     }
                     if toks.len() == 5 {
                         // this premise contains only a boxed constant, no further expression:
@@ -538,9 +893,12 @@ fn parse_proof_line(toks: &[Token]) -> Result<ProofLine, String> {
                             line_num: Some(*num),
                             depth: *depth,
                             is_fitch_bar_line: false,
+                            is_strict_subproof_bar: false,
                             sentence: None,
                             justification: None,
                             constant_between_square_brackets: const_betw_sqbr,
+                            parse_error: None,
+                            comment: None,
                         });
                     }
                     5
@@ -549,71 +907,160 @@ fn parse_proof_line(toks: &[Token]) -> Result<ProofLine, String> {
                 };
 
                 // give error if it seems like the user tried to make a boxed constant but failed
-                if (toks.contains(&Token::LSqBracket) || toks.contains(&Token::RSqBracket))
+                if (toks.contains(&TokenKind::LSqBracket) || toks.contains(&TokenKind::RSqBracket))
                     && expression_start_index != 5
                 {
-                    return Err("failed when trying to read boxed constant (if \
+                    let bracket_index = toks
+                        .iter()
+                        .position(|t| t == &TokenKind::LSqBracket || t == &TokenKind::RSqBracket)
+                        .unwrap(); // we just checked that there is one
+                    return Err(ParseError::new(
+                        "failed when trying to read boxed constant (if \
                         you did not intend to introduce a boxed constant in this \
                         proof line, remove the characters \'[\' and \']\' from this line)"
-                        .to_string());
+                            .to_string(),
+                        bracket_index..bracket_index + 1,
+                    ));
                 }
 
-                let wff = parse_logical_expr(toks.get(expression_start_index..).unwrap_or(&[]))?;
+                let wff = parse_logical_expr(toks.get(expression_start_index..).unwrap_or(&[]))
+                    .map_err(|err| err.shifted(expression_start_index))?;
 
                 Ok(ProofLine {
                     line_num: Some(*num),
                     depth: *depth,
                     is_fitch_bar_line: false,
+                    is_strict_subproof_bar: false,
                     sentence: Some(wff),
                     justification: None,
                     constant_between_square_brackets: const_betw_sqbr,
+                    parse_error: None,
+                    comment: None,
                 })
             }
-            Token::ConseqVertBar(depth) => {
-                if toks[1..].iter().all(|t| t == &Token::Dash) {
+            TokenKind::ConseqVertBar(depth) => {
+                // a Fitch bar that opens a strict subproof starts with a box
+                let is_strict_subproof_bar =
+                    toks.get(1) == Some(&TokenKind::Box) && toks.get(2) == Some(&TokenKind::Dash);
+                let dashes_start = if is_strict_subproof_bar {
+                    2
+                } else {
+                    1
+                };
+                if toks[dashes_start..].iter().all(|t| t == &TokenKind::Dash) {
                     Ok(ProofLine {
                         line_num: None,
                         depth: *depth,
                         // if there is a dash, then this is a fitch bar. Otherwise it's an empty line.
-                        is_fitch_bar_line: toks[1..].contains(&Token::Dash),
+                        is_fitch_bar_line: toks[1..].contains(&TokenKind::Dash),
+                        is_strict_subproof_bar,
                         sentence: None,
                         justification: None,
                         constant_between_square_brackets: None,
+                        parse_error: None,
+                        comment: None,
                     })
                 } else {
-                    Err("when you have a line without line number, then that line can only possibly contain some minuses to indicate a Fitch bar, but it may contain no other tokens than minuses after the vertical bar(s)".to_string())
+                    let first_non_dash =
+                        toks.iter().skip(dashes_start).position(|t| t != &TokenKind::Dash).unwrap()
+                            + dashes_start; // exists, since not all are dashes
+                    Err(ParseError::new("when you have a line without line number, then that line can only possibly contain some minuses to indicate a Fitch bar (with a □ in front of them for a strict subproof), but it may contain no other tokens than minuses after the vertical bar(s)".to_string(), first_non_dash..toks.len()))
                 }
             }
-            _ => {
-                Err("each text line must start either with a line number or a vertical bar"
-                    .to_string())
-            }
+            _ => Err(ParseError::new(
+                "each text line must start either with a line number or a vertical bar".to_string(),
+                0..1,
+            )),
         }
     }
 }
 
 /// Parse a justification, as specified by the grammar defined in the documentation for
 /// [parse_proof_line].
-fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
-    if toks.first().is_none() || toks.get(1).is_none() {
+fn parse_justification(toks: &[TokenKind]) -> Result<Justification, String> {
+    if toks.is_empty() || toks.get(1).is_none() {
         return Err("failure when parsing justification; it seems not to be there?".to_string());
     }
-    match (&toks[0], &toks[1], toks.get(2), toks.get(3)) {
-        (Token::Name(name), Token::Colon, Some(Token::Number(num)), None) if name == "Reit" => {
+    // the connective of the rule may also be written in ASCII notation
+    let connective = match &toks[0] {
+        tok if is_or(tok) => TokenKind::Or,
+        TokenKind::Name(name) if name == "A" => TokenKind::Forall,
+        TokenKind::Name(name) if name == "E" => TokenKind::Exists,
+        tok => tok.clone(),
+    };
+    // forall x writes I and E for Intro and Elim
+    let short_name = matches!(&toks[1], TokenKind::Name(name) if name == "I" || name == "E");
+    let toks: &[TokenKind] = &toks
+        .iter()
+        .enumerate()
+        .map(|(i, tok)| match tok {
+            TokenKind::Name(name) if i == 1 && name == "I" => TokenKind::Name("Intro".to_owned()),
+            TokenKind::Name(name) if i == 1 && name == "E" => TokenKind::Name("Elim".to_owned()),
+            tok => tok.clone(),
+        })
+        .collect::<Vec<_>>();
+    match (&connective, &toks[1], toks.get(2), toks.get(3)) {
+        (TokenKind::Name(name), TokenKind::Colon, Some(TokenKind::Number(num)), None) if name == "Reit" || name == "R" => {
             Ok(Justification::Reit(*num))
         }
-        (Token::And, Token::Name(name), Some(Token::Colon), Some(Token::Number(num)))
+        (TokenKind::Name(name), TokenKind::Colon, Some(TokenKind::Number(num)), None) if name == "X" => {
+            Ok(Justification::BottomElim(*num))
+        }
+        (TokenKind::Name(name), TokenKind::Box, Some(TokenKind::Colon), _) if name == "R" => match &toks[3..] {
+            [TokenKind::Number(num)] => Ok(Justification::BoxReit(*num)),
+            _ => Err("failed to parse R□ justification. It should be of this form: R□:<num>".to_string()),
+        },
+        (TokenKind::Box, TokenKind::Name(name), Some(TokenKind::Colon), _) if name == "Intro" => match &toks[3..] {
+            [TokenKind::Number(a), TokenKind::Dash, TokenKind::Number(b)] => Ok(Justification::BoxIntro((*a, *b))),
+            _ => Err("failed to parse □Intro justification. It should be of this form: □Intro:<num>-<num>".to_string()),
+        },
+        (TokenKind::Box, TokenKind::Name(name), Some(TokenKind::Colon), _) if name == "Elim" => match &toks[3..] {
+            [TokenKind::Number(num)] => Ok(Justification::BoxElim(*num)),
+            _ => Err("failed to parse □Elim justification. It should be of this form: □Elim:<num>".to_string()),
+        },
+        (TokenKind::Diamond, TokenKind::Name(name), Some(TokenKind::Colon), _) if name == "Intro" => match &toks[3..] {
+            [TokenKind::Number(num)] => Ok(Justification::DiamondIntro(*num)),
+            _ => Err("failed to parse ◇Intro justification. It should be of this form: ◇Intro:<num>".to_string()),
+        },
+        (TokenKind::Diamond, TokenKind::Name(name), Some(TokenKind::Colon), _) if name == "Elim" => match &toks[3..] {
+            [TokenKind::Number(num)] => Ok(Justification::DiamondElim(*num)),
+            _ => Err("failed to parse ◇Elim justification. It should be of this form: ◇Elim:<num>".to_string()),
+        },
+        (TokenKind::Name(name), TokenKind::Colon, ..) if name == "DS" || name == "MT" => match &toks[2..] {
+            [TokenKind::Number(n), TokenKind::Comma, TokenKind::Number(m)] if name == "DS" => {
+                Ok(Justification::DisjunctiveSyllogism(*n, *m))
+            }
+            [TokenKind::Number(n), TokenKind::Comma, TokenKind::Number(m)] => Ok(Justification::ModusTollens(*n, *m)),
+            _ => Err(format!("failed to parse {name} justification. It should be of this form: {name}:<num>,<num>")),
+        },
+        (TokenKind::Name(name), TokenKind::Colon, ..) if name == "DNE" || name == "DeM" || name == "CQ" => match &toks[2..] {
+            [TokenKind::Number(n)] if name == "DNE" => Ok(Justification::DoubleNegationElim(*n)),
+            [TokenKind::Number(n)] if name == "DeM" => Ok(Justification::DeMorgan(*n)),
+            [TokenKind::Number(n)] => Ok(Justification::ConversionOfQuantifiers(*n)),
+            _ => Err(format!("failed to parse {name} justification. It should be of this form: {name}:<num>")),
+        },
+        (TokenKind::Name(name), TokenKind::Colon, ..) if name == "LEM" || name == "TND" => match &toks[2..] {
+            [TokenKind::Number(a), TokenKind::Dash, TokenKind::Number(b), TokenKind::Comma, TokenKind::Number(c), TokenKind::Dash, TokenKind::Number(d)] => {
+                Ok(Justification::ExcludedMiddle((*a, *b), (*c, *d)))
+            }
+            _ => Err(format!("failed to parse {name} justification. It should be of this form: {name}:<num>-<num>,<num>-<num>")),
+        },
+        (TokenKind::Name(name), TokenKind::Colon, ..) if name == "IP" => match &toks[2..] {
+            [TokenKind::Number(a), TokenKind::Dash, TokenKind::Number(b)] => Ok(Justification::IndirectProof((*a, *b))),
+            _ => Err("failed to parse IP justification. It should be of this form: IP:<num>-<num>".to_string()),
+        },
+        (TokenKind::And, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num)))
             if name == "Intro" =>
         {
             let err_str = "failed to parse ∧Intro justification. It should be of this form: ∧Intro:<num>,<num>{,<num>}".to_string();
             let mut nums: Vec<usize> = vec![*num];
             let mut i = 4;
             while toks.get(i).is_some() {
-                if toks[i] == Token::Comma {
+                if toks[i] == TokenKind::Comma {
                     if toks.get(i + 1).is_none() {
                         return Err(err_str);
                     }
-                    if let Token::Number(next_num) = toks.get(i + 1).unwrap() {
+                    if let TokenKind::Number(next_num) = toks.get(i + 1).unwrap() {
                         nums.push(*next_num);
                     } else {
                         return Err(err_str);
@@ -625,7 +1072,7 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
             }
             Ok(Justification::AndIntro(nums))
         }
-        (Token::And, Token::Name(name), Some(Token::Colon), Some(Token::Number(num)))
+        (TokenKind::And, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num)))
             if name == "Elim" =>
         {
             if toks.get(4).is_none() {
@@ -635,7 +1082,7 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                     .to_string())
             }
         }
-        (Token::Or, Token::Name(name), Some(Token::Colon), Some(Token::Number(num)))
+        (TokenKind::Or, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num)))
             if name == "Intro" =>
         {
             if toks.get(4).is_none() {
@@ -645,7 +1092,7 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                     .to_string())
             }
         }
-        (Token::Or, Token::Name(name), Some(Token::Colon), Some(Token::Number(num)))
+        (TokenKind::Or, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num)))
             if name == "Elim" =>
         {
             let err_str = "failed to parse ∨Elim justification. It should be of this form: ∨Elim:<num>,<num>-<num>,<num>-<num>{,<num>-<num>}".to_string();
@@ -656,14 +1103,14 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                 return Err(err_str);
             };
             while toks.get(i).is_some() {
-                if toks[i] == Token::Comma {
+                if toks[i] == TokenKind::Comma {
                     if toks.get(i + 1).is_none()
                         || toks.get(i + 2).is_none()
                         || toks.get(i + 3).is_none()
                     {
                         return Err(err_str);
                     }
-                    if let (Token::Number(next_num1), Token::Dash, Token::Number(next_num2)) = (
+                    if let (TokenKind::Number(next_num1), TokenKind::Dash, TokenKind::Number(next_num2)) = (
                         toks.get(i + 1).unwrap(),
                         toks.get(i + 2).unwrap(),
                         toks.get(i + 3).unwrap(),
@@ -679,21 +1126,21 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
             }
             Ok(Justification::OrElim(*num, num_pairs))
         }
-        (Token::Implies, Token::Name(name), Some(Token::Colon), Some(Token::Number(num1)))
+        (TokenKind::Implies, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num1)))
             if name == "Intro" =>
         {
             let err_str = "failed to parse →Intro justification. It should be of this form: →Intro:<num>-<num>".to_string();
             if toks.len() != 6 {
                 return Err(err_str);
             }
-            if let (Token::Dash, Token::Number(num2)) = (toks.get(4).unwrap(), toks.get(5).unwrap())
+            if let (TokenKind::Dash, TokenKind::Number(num2)) = (toks.get(4).unwrap(), toks.get(5).unwrap())
             {
                 Ok(Justification::ImpliesIntro((*num1, *num2)))
             } else {
                 Err(err_str)
             }
         }
-        (Token::Implies, Token::Name(name), Some(Token::Colon), Some(Token::Number(num1)))
+        (TokenKind::Implies, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num1)))
             if name == "Elim" =>
         {
             let err_str =
@@ -701,19 +1148,19 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                     .to_string();
             if toks.len() != 6 {
                 Err(err_str)
-            } else if let [Token::Comma, Token::Number(num2)] = &toks[4..6] {
+            } else if let [TokenKind::Comma, TokenKind::Number(num2)] = &toks[4..6] {
                 Ok(Justification::ImpliesElim(*num1, *num2))
             } else {
                 Err(err_str)
             }
         }
-        (Token::Bicond, Token::Name(name), Some(Token::Colon), Some(Token::Number(num1)))
+        (TokenKind::Bicond, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num1)))
             if name == "Intro" =>
         {
             let err_str = "failed to parse ↔Intro justification. It should be of this form: ↔Intro:<num>-<num>,<num>-<num>".to_string();
             if toks.len() != 10 {
                 Err(err_str)
-            } else if let [Token::Dash, Token::Number(num2), Token::Comma, Token::Number(num3), Token::Dash, Token::Number(num4)] =
+            } else if let [TokenKind::Dash, TokenKind::Number(num2), TokenKind::Comma, TokenKind::Number(num3), TokenKind::Dash, TokenKind::Number(num4)] =
                 &toks[4..10]
             {
                 Ok(Justification::BicondIntro((*num1, *num2), (*num3, *num4)))
@@ -721,7 +1168,7 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                 Err(err_str)
             }
         }
-        (Token::Bicond, Token::Name(name), Some(Token::Colon), Some(Token::Number(num1)))
+        (TokenKind::Bicond, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num1)))
             if name == "Elim" =>
         {
             let err_str =
@@ -729,47 +1176,50 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                     .to_string();
             if toks.len() != 6 {
                 Err(err_str)
-            } else if let (Token::Comma, Token::Number(num2)) = (&toks[4], &toks[5]) {
+            } else if let (TokenKind::Comma, TokenKind::Number(num2)) = (&toks[4], &toks[5]) {
                 Ok(Justification::BicondElim(*num1, *num2))
             } else {
                 Err(err_str)
             }
         }
-        (Token::Not, Token::Name(name), Some(Token::Colon), Some(Token::Number(num1)))
+        (TokenKind::Not, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num1)))
             if name == "Intro" =>
         {
             let err_str = "failed to parse ¬Intro justification. It should be of this form: ¬Intro:<num>-<num>".to_string();
             if toks.len() != 6 {
                 Err(err_str)
-            } else if let (Token::Dash, Token::Number(num2)) = (&toks[4], &toks[5]) {
+            } else if let (TokenKind::Dash, TokenKind::Number(num2)) = (&toks[4], &toks[5]) {
                 Ok(Justification::NotIntro((*num1, *num2)))
             } else {
                 Err(err_str)
             }
         }
-        (Token::Not, Token::Name(name), Some(Token::Colon), Some(Token::Number(num)))
+        (TokenKind::Not, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num)))
             if name == "Elim" =>
         {
             if toks.get(4).is_none() {
                 Ok(Justification::NotElim(*num))
+            } else if let (true, [TokenKind::Comma, TokenKind::Number(num2)]) = (short_name, &toks[4..]) {
+                // in forall x, ¬E is what LPL calls ⊥ Intro
+                Ok(Justification::BottomIntro(*num, *num2))
             } else {
                 Err("failed to parse ¬Elim justification. It should be of this form: ¬Elim:<num>"
                     .to_string())
             }
         }
-        (Token::Bottom, Token::Name(name), Some(Token::Colon), Some(Token::Number(num1)))
+        (TokenKind::Bottom, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num1)))
             if name == "Intro" =>
         {
             let err_str = "failed to parse ⊥Intro justification. It should be of this form: ⊥Intro:<num>,<num>".to_string();
             if toks.len() != 6 {
                 Err(err_str)
-            } else if let (Token::Comma, Token::Number(num2)) = (&toks[4], &toks[5]) {
+            } else if let (TokenKind::Comma, TokenKind::Number(num2)) = (&toks[4], &toks[5]) {
                 Ok(Justification::BottomIntro(*num1, *num2))
             } else {
                 Err(err_str)
             }
         }
-        (Token::Bottom, Token::Name(name), Some(Token::Colon), Some(Token::Number(num)))
+        (TokenKind::Bottom, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num)))
             if name == "Elim" =>
         {
             if toks.get(4).is_none() {
@@ -779,20 +1229,20 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                     .to_string())
             }
         }
-        (Token::Equals, Token::Name(name), ..) if name == "Intro" => {
+        (TokenKind::Equals, TokenKind::Name(name), ..) if name == "Intro" => {
             if toks.len() == 2 {
                 Ok(Justification::EqualsIntro)
             } else {
                 Err("failed to parse =Intro justification. This proof rule goes without colon and without line references, so all you write is just \'=Intro\'".to_string())
             }
         }
-        (Token::Equals, Token::Name(name), Some(Token::Colon), Some(Token::Number(num1)))
+        (TokenKind::Equals, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num1)))
             if name == "Elim" =>
         {
             let err_str = "failed to parse =Elim justification. It should be of this form: =Elim:<num>,<num>".to_string();
             if toks.len() != 6 {
                 Err(err_str)
-            } else if let (Token::Comma, Token::Number(num2)) =
+            } else if let (TokenKind::Comma, TokenKind::Number(num2)) =
                 (toks.get(4).unwrap(), toks.get(5).unwrap())
             {
                 Ok(Justification::EqualsElim(*num1, *num2))
@@ -800,13 +1250,13 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                 Err(err_str)
             }
         }
-        (Token::Forall, Token::Name(name), Some(Token::Colon), Some(Token::Number(num1)))
+        (TokenKind::Forall, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num1)))
             if name == "Intro" =>
         {
             let err_str = "failed to parse ∀Intro justification. It should be of this form: ∀Intro:<num>-<num>".to_string();
             if toks.len() != 6 {
                 Err(err_str)
-            } else if let (Token::Dash, Token::Number(num2)) =
+            } else if let (TokenKind::Dash, TokenKind::Number(num2)) =
                 (toks.get(4).unwrap(), toks.get(5).unwrap())
             {
                 Ok(Justification::ForallIntro((*num1, *num2)))
@@ -814,7 +1264,7 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                 Err(err_str)
             }
         }
-        (Token::Forall, Token::Name(name), Some(Token::Colon), Some(Token::Number(num)))
+        (TokenKind::Forall, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num)))
             if name == "Elim" && toks.get(4).is_none() =>
         {
             if toks.get(4).is_none() {
@@ -824,7 +1274,7 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                     .to_string())
             }
         }
-        (Token::Exists, Token::Name(name), Some(Token::Colon), Some(Token::Number(num)))
+        (TokenKind::Exists, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num)))
             if name == "Intro" && toks.get(4).is_none() =>
         {
             if toks.get(4).is_none() {
@@ -834,13 +1284,13 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                     .to_string())
             }
         }
-        (Token::Exists, Token::Name(name), Some(Token::Colon), Some(Token::Number(num1)))
+        (TokenKind::Exists, TokenKind::Name(name), Some(TokenKind::Colon), Some(TokenKind::Number(num1)))
             if name == "Elim" =>
         {
             let err_str = "failed to parse ∃Elim justification. It should be of this form: ∃Elim:<num>,<num>-<num>".to_string();
             if toks.len() != 8 {
                 Err(err_str)
-            } else if let (Token::Comma, Token::Number(num2), Token::Dash, Token::Number(num3)) = (
+            } else if let (TokenKind::Comma, TokenKind::Number(num2), TokenKind::Dash, TokenKind::Number(num3)) = (
                 toks.get(4).unwrap(),
                 toks.get(5).unwrap(),
                 toks.get(6).unwrap(),
@@ -851,23 +1301,60 @@ fn parse_justification(toks: &[Token]) -> Result<Justification, String> {
                 Err(err_str)
             }
         }
+        (TokenKind::Name(name1), TokenKind::Name(name2), Some(TokenKind::Colon), _)
+            if name1 == "Taut" && name2 == "Con" =>
+        {
+            parse_line_list(&toks[3..]).map(Justification::TautCon).ok_or("failed to parse Taut Con justification. It should be of this form: Taut Con:<num>{,<num>} (or just Taut Con: for a tautology)".to_string())
+        }
+        (TokenKind::Name(name1), TokenKind::Name(name2), Some(TokenKind::Colon), _)
+            if name1 == "FO" && name2 == "Con" =>
+        {
+            parse_line_list(&toks[3..]).map(Justification::FoCon).ok_or("failed to parse FO Con justification. It should be of this form: FO Con:<num>{,<num>} (or just FO Con: for a logical truth)".to_string())
+        }
+        (TokenKind::Name(name1), TokenKind::Name(name2), Some(TokenKind::Colon), _)
+            if name1 == "Ana" && name2 == "Con" =>
+        {
+            parse_line_list(&toks[3..]).map(Justification::AnaCon).ok_or("failed to parse Ana Con justification. It should be of this form: Ana Con:<num>{,<num>} (or just Ana Con: for an analytic truth)".to_string())
+        }
         _ => Err("failed to parse justification. Make sure that you have references where necessary, and note that the proper capitalization is \'Intro\'/\'Elim\'/\'Reit\'.".to_string()),
     }
 }
 
+/// Parses a possibly empty list of line numbers, separated by commas, that makes up the whole of
+/// `toks`.
+fn parse_line_list(toks: &[TokenKind]) -> Option<Vec<usize>> {
+    if toks.is_empty() {
+        return Some(vec![]);
+    }
+    let mut nums = vec![];
+    for (i, tok) in toks.iter().enumerate() {
+        match tok {
+            TokenKind::Number(num) if i % 2 == 0 => nums.push(*num),
+            TokenKind::Comma if i % 2 == 1 && i + 1 < toks.len() => {}
+            _ => return None,
+        }
+    }
+    Some(nums)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn lex_kinds(input: &str) -> Result<Vec<TokenKind>, LexError> {
+        lex(input).map(|toks| kinds(&toks))
+    }
+
     #[test]
     fn test_lexer_1() {
         assert_eq!(
-            lex("   THIs is SoMe  SiLLY  test  "),
+            lex_kinds("   THIs is SoMe  SiLLY  test  "),
             Ok(vec![
-                Token::Name("THIs".to_owned()),
-                Token::Name("is".to_owned()),
-                Token::Name("SoMe".to_owned()),
-                Token::Name("SiLLY".to_owned()),
-                Token::Name("test".to_owned())
+                TokenKind::Name("THIs".to_owned()),
+                TokenKind::Name("is".to_owned()),
+                TokenKind::Name("SoMe".to_owned()),
+                TokenKind::Name("SiLLY".to_owned()),
+                TokenKind::Name("test".to_owned())
             ])
         );
     }
@@ -875,81 +1362,120 @@ mod tests {
     #[test]
     fn test_lexer_2() {
         assert_eq!(
-            lex("   THIs is SoMe  S∀LLY  test  "),
+            lex_kinds("   THIs is SoMe  S∀LLY  test  "),
             Ok(vec![
-                Token::Name("THIs".to_owned()),
-                Token::Name("is".to_owned()),
-                Token::Name("SoMe".to_owned()),
-                Token::Name("S".to_owned()),
-                Token::Forall,
-                Token::Name("LLY".to_owned()),
-                Token::Name("test".to_owned())
+                TokenKind::Name("THIs".to_owned()),
+                TokenKind::Name("is".to_owned()),
+                TokenKind::Name("SoMe".to_owned()),
+                TokenKind::Name("S".to_owned()),
+                TokenKind::Forall,
+                TokenKind::Name("LLY".to_owned()),
+                TokenKind::Name("test".to_owned())
             ])
         );
     }
     #[test]
     fn test_lexer_3() {
         assert_eq!(
-            lex("   THIs is SoMe  S∀ ∀∀LLY  test  "),
+            lex_kinds("   THIs is SoMe  S∀ ∀∀LLY  test  "),
             Ok(vec![
-                Token::Name("THIs".to_owned()),
-                Token::Name("is".to_owned()),
-                Token::Name("SoMe".to_owned()),
-                Token::Name("S".to_owned()),
-                Token::Forall,
-                Token::Forall,
-                Token::Forall,
-                Token::Name("LLY".to_owned()),
-                Token::Name("test".to_owned())
+                TokenKind::Name("THIs".to_owned()),
+                TokenKind::Name("is".to_owned()),
+                TokenKind::Name("SoMe".to_owned()),
+                TokenKind::Name("S".to_owned()),
+                TokenKind::Forall,
+                TokenKind::Forall,
+                TokenKind::Forall,
+                TokenKind::Name("LLY".to_owned()),
+                TokenKind::Name("test".to_owned())
             ])
         );
     }
     #[test]
     fn test_lexer_4() {
         assert_eq!(
-            lex("∀x(P(x,a)→P(a,x))∨ (A∧ ItIsSunny∧¬∃y P(y,y))∨a=c"),
+            lex_kinds("∀x(P(x,a)→P(a,x))∨ (A∧ ItIsSunny∧¬∃y P(y,y))∨a=c"),
             Ok(vec![
-                Token::Forall,
-                Token::Name("x".to_owned()),
-                Token::LPar,
-                Token::Name("P".to_owned()),
-                Token::LPar,
-                Token::Name("x".to_owned()),
-                Token::Comma,
-                Token::Name("a".to_owned()),
-                Token::RPar,
-                Token::Implies,
-                Token::Name("P".to_owned()),
-                Token::LPar,
-                Token::Name("a".to_owned()),
-                Token::Comma,
-                Token::Name("x".to_owned()),
-                Token::RPar,
-                Token::RPar,
-                Token::Or,
-                Token::LPar,
-                Token::Name("A".to_owned()),
-                Token::And,
-                Token::Name("ItIsSunny".to_owned()),
-                Token::And,
-                Token::Not,
-                Token::Exists,
-                Token::Name("y".to_owned()),
-                Token::Name("P".to_owned()),
-                Token::LPar,
-                Token::Name("y".to_owned()),
-                Token::Comma,
-                Token::Name("y".to_owned()),
-                Token::RPar,
-                Token::RPar,
-                Token::Or,
-                Token::Name("a".to_owned()),
-                Token::Equals,
-                Token::Name("c".to_owned()),
+                TokenKind::Forall,
+                TokenKind::Name("x".to_owned()),
+                TokenKind::LPar,
+                TokenKind::Name("P".to_owned()),
+                TokenKind::LPar,
+                TokenKind::Name("x".to_owned()),
+                TokenKind::Comma,
+                TokenKind::Name("a".to_owned()),
+                TokenKind::RPar,
+                TokenKind::Implies,
+                TokenKind::Name("P".to_owned()),
+                TokenKind::LPar,
+                TokenKind::Name("a".to_owned()),
+                TokenKind::Comma,
+                TokenKind::Name("x".to_owned()),
+                TokenKind::RPar,
+                TokenKind::RPar,
+                TokenKind::Or,
+                TokenKind::LPar,
+                TokenKind::Name("A".to_owned()),
+                TokenKind::And,
+                TokenKind::Name("ItIsSunny".to_owned()),
+                TokenKind::And,
+                TokenKind::Not,
+                TokenKind::Exists,
+                TokenKind::Name("y".to_owned()),
+                TokenKind::Name("P".to_owned()),
+                TokenKind::LPar,
+                TokenKind::Name("y".to_owned()),
+                TokenKind::Comma,
+                TokenKind::Name("y".to_owned()),
+                TokenKind::RPar,
+                TokenKind::RPar,
+                TokenKind::Or,
+                TokenKind::Name("a".to_owned()),
+                TokenKind::Equals,
+                TokenKind::Name("c".to_owned()),
             ])
         );
     }
 
+    #[test]
+    fn test_lexer_spans() {
+        let toks = lex("12 | | ∀x P(x)").unwrap();
+        let spans: Vec<(usize, usize)> = toks.iter().map(|t| (t.span.start, t.span.end)).collect();
+        assert_eq!(
+            spans,
+            vec![(0, 2), (3, 6), (7, 8), (8, 9), (10, 11), (11, 12), (12, 13), (13, 14)]
+        );
+    }
+
+    #[test]
+    fn test_lexer_error_span() {
+        assert_eq!(
+            lex("A ∧ B$"),
+            Err(LexError {
+                message: "invalid character found: $".to_string(),
+                span: Span {
+                    start: 5,
+                    end: 6
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let err = parse_fitch_proof("1 | A\n\n  |----\n2 | A B   Reit: 1").unwrap_err();
+        let location = err.location.unwrap();
+        assert_eq!(location.line, 4);
+        assert_eq!(
+            location.span,
+            Span {
+                start: 6,
+                end: 7
+            }
+        );
+        assert_eq!(location.source_line, "2 | A B   Reit: 1");
+    }
+
     #[test]
     fn test_parser_1() {
         assert_eq!(
@@ -1158,75 +1684,272 @@ mod tests {
     #[test]
     fn test_justification_parser_or_elim() {
         assert_eq!(
-            parse_justification(&lex("∨Elim:42,43-44").unwrap()),
+            parse_justification(&lex_kinds("∨Elim:42,43-44").unwrap()),
             Ok(Justification::OrElim(42, vec![(43, 44)]))
         );
         assert_eq!(
-            parse_justification(&lex("∨Elim:42,43-44,45-46,47-48").unwrap()),
+            parse_justification(&lex_kinds("∨Elim:42,43-44,45-46,47-48").unwrap()),
             Ok(Justification::OrElim(42, vec![(43, 44), (45, 46), (47, 48)]))
         );
-        assert!(parse_justification(&lex("∨Elim:42,43-44,45-46,47,48").unwrap()).is_err());
-        assert!(parse_justification(&lex("∨Elim:42,43-44,45-46-47-48").unwrap()).is_err());
-        assert!(parse_justification(&lex("∨Elim:42-43-44,45-46,47-48").unwrap()).is_err());
-        assert!(parse_justification(&lex("∨Elim-42,43-44,45-46,47-48").unwrap()).is_err());
-        assert!(parse_justification(&lex("∨Elim:42,43-44,45-46,47-48,").unwrap()).is_err());
-        assert!(parse_justification(&lex("∨Elim:42,43-44,45-46,47-48,49").unwrap()).is_err());
-        assert!(parse_justification(&lex("∨Elim:42,43-44,45-46,47-48,49-").unwrap()).is_err());
-        assert!(parse_justification(&lex("∨Elim:42").unwrap()).is_err());
+        assert!(parse_justification(&lex_kinds("∨Elim:42,43-44,45-46,47,48").unwrap()).is_err());
+        assert!(parse_justification(&lex_kinds("∨Elim:42,43-44,45-46-47-48").unwrap()).is_err());
+        assert!(parse_justification(&lex_kinds("∨Elim:42-43-44,45-46,47-48").unwrap()).is_err());
+        assert!(parse_justification(&lex_kinds("∨Elim-42,43-44,45-46,47-48").unwrap()).is_err());
+        assert!(parse_justification(&lex_kinds("∨Elim:42,43-44,45-46,47-48,").unwrap()).is_err());
+        assert!(parse_justification(&lex_kinds("∨Elim:42,43-44,45-46,47-48,49").unwrap()).is_err());
+        assert!(parse_justification(&lex_kinds("∨Elim:42,43-44,45-46,47-48,49-").unwrap()).is_err());
+        assert!(parse_justification(&lex_kinds("∨Elim:42").unwrap()).is_err());
     }
     #[test]
     fn test_justification_parser_and_intro() {
         assert_eq!(
-            parse_justification(&lex("∧Intro:42,43,44").unwrap()),
+            parse_justification(&lex_kinds("∧Intro:42,43,44").unwrap()),
             Ok(Justification::AndIntro(vec![42, 43, 44]))
         );
         assert_eq!(
-            parse_justification(&lex("∧Intro:42,43").unwrap()),
+            parse_justification(&lex_kinds("∧Intro:42,43").unwrap()),
             Ok(Justification::AndIntro(vec![42, 43]))
         );
         assert_eq!(
-            parse_justification(&lex("∧Intro:42").unwrap()),
+            parse_justification(&lex_kinds("∧Intro:42").unwrap()),
             // TODO: decide whether i want to keep behavior like this (a "unary conjunction")
             Ok(Justification::AndIntro(vec![42]))
         );
-        assert!((parse_justification(&lex("∧Intro:42-43").unwrap()).is_err()));
-        assert!((parse_justification(&lex("∧Intro:").unwrap()).is_err()));
+        assert!((parse_justification(&lex_kinds("∧Intro:42-43").unwrap()).is_err()));
+        assert!((parse_justification(&lex_kinds("∧Intro:").unwrap()).is_err()));
     }
     #[test]
     fn test_justification_parser_exists_elim() {
         assert_eq!(
-            parse_justification(&lex("∃Elim:42,43-44").unwrap()),
+            parse_justification(&lex_kinds("∃Elim:42,43-44").unwrap()),
             Ok(Justification::ExistsElim(42, (43, 44)))
         );
     }
     #[test]
     fn test_justification_parser_implies_elim() {
         assert_eq!(
-            parse_justification(&lex("→Elim:42,43").unwrap()),
+            parse_justification(&lex_kinds("→Elim:42,43").unwrap()),
             Ok(Justification::ImpliesElim(42, 43))
         );
-        assert!((parse_justification(&lex("→Elim:42,43,").unwrap()).is_err()));
+        assert!((parse_justification(&lex_kinds("→Elim:42,43,").unwrap()).is_err()));
     }
 
     #[test]
     fn test_parser_bug_infinite_loop_1() {
-        let toks = lex("(f(g(a),=b)").unwrap();
+        let toks = lex_kinds("(f(g(a),=b)").unwrap();
         let _ = parse_e2(&toks);
     }
 
     #[test]
     fn test_parser_bug_infinite_loop_2() {
-        let toks = lex("f(g(a),=b").unwrap();
+        let toks = lex_kinds("f(g(a),=b").unwrap();
         let _ = parse_e1(&toks);
     }
     #[test]
     fn test_parser_bug_infinite_loop_3() {
-        let toks = lex("f(g(a),=b").unwrap();
+        let toks = lex_kinds("f(g(a),=b").unwrap();
         let _ = parse_term(&toks);
     }
     #[test]
     fn test_parser_bug_infinite_loop_4() {
-        let toks = lex("(g(a),=b").unwrap();
+        let toks = lex_kinds("(g(a),=b").unwrap();
         let _ = parse_arg_list(&toks);
     }
+
+    #[test]
+    fn test_ascii_connectives() {
+        assert_eq!(
+            parse_logical_expression_string("~A & !B /\\ C"),
+            parse_logical_expression_string("¬A ∧ ¬B ∧ C")
+        );
+        assert_eq!(
+            parse_logical_expression_string("(A \\/ B v C | D) -> (_|_ <-> E)"),
+            parse_logical_expression_string("(A ∨ B ∨ C ∨ D) → (⊥ ↔ E)")
+        );
+        assert_eq!(parse_logical_expression_string("A || B"), None);
+        assert_eq!(parse_logical_expression_string("A <- B"), None);
+    }
+
+    #[test]
+    fn test_ascii_quantifiers() {
+        assert_eq!(
+            parse_logical_expression_string("forall x exists y P(x, y)"),
+            parse_logical_expression_string("∀x ∃y P(x, y)")
+        );
+        assert_eq!(
+            parse_logical_expression_string("Ax Ey (P(x, y) -> x = y)"),
+            parse_logical_expression_string("∀x ∃y (P(x, y) → x = y)")
+        );
+        // these should not be read as quantifiers
+        assert_eq!(parse_logical_expression_string("Ex"), Some(Wff::Atomic("Ex".to_string())));
+        assert_eq!(
+            parse_logical_expression_string("Ab(c) & Ab"),
+            Some(Wff::And(vec![
+                Wff::PredApp("Ab".to_string(), vec![Term::Atomic("c".to_string())]),
+                Wff::Atomic("Ab".to_string())
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_ascii_or_in_proof_line() {
+        let line = parse_proof_line(&lex_kinds("3 | | A | B      | Intro: 2").unwrap()).unwrap();
+        assert_eq!(line.depth, 2);
+        assert_eq!(
+            line.sentence,
+            Some(Wff::Or(vec![Wff::Atomic("A".to_string()), Wff::Atomic("B".to_string())]))
+        );
+        assert_eq!(line.justification, Some(Justification::OrIntro(2)));
+        assert_eq!(
+            parse_justification(&lex_kinds("v Elim: 1, 2-3, 4-5").unwrap()),
+            Ok(Justification::OrElim(1, vec![(2, 3), (4, 5)]))
+        );
+        assert_eq!(
+            parse_justification(&lex_kinds("A Intro: 2-3").unwrap()),
+            Ok(Justification::ForallIntro((2, 3)))
+        );
+    }
+
+    #[test]
+    fn test_names_with_digits_underscores_and_primes() {
+        assert_eq!(
+            lex_kinds("P2(a1, x_2, c') & Q_'").unwrap(),
+            vec![
+                TokenKind::Name("P2".to_string()),
+                TokenKind::LPar,
+                TokenKind::Name("a1".to_string()),
+                TokenKind::Comma,
+                TokenKind::Name("x_2".to_string()),
+                TokenKind::Comma,
+                TokenKind::Name("c'".to_string()),
+                TokenKind::RPar,
+                TokenKind::And,
+                TokenKind::Name("Q_'".to_string()),
+            ]
+        );
+        // an underscore that starts a bottom is not part of the name before it
+        assert_eq!(
+            lex_kinds("A_|_").unwrap(),
+            vec![TokenKind::Name("A".to_string()), TokenKind::Bottom]
+        );
+        assert_eq!(
+            parse_logical_expression_string("Ax' P(x')"),
+            Some(Wff::Forall(
+                "x'".to_string(),
+                Box::new(Wff::PredApp("P".to_string(), vec![Term::Atomic("x'".to_string())]))
+            ))
+        );
+        assert_eq!(
+            parse_allowed_variable_names("x, x', x_1, y2"),
+            Ok(HashSet::from(["x", "x'", "x_1", "y2"].map(|s| s.to_string())))
+        );
+        assert!(parse_allowed_variable_names("x, X1").is_err());
+    }
+
+    #[test]
+    fn test_consequence_rules() {
+        assert_eq!(
+            parse_justification(&lex_kinds("Taut Con: 1, 3, 5").unwrap()),
+            Ok(Justification::TautCon(vec![1, 3, 5]))
+        );
+        assert_eq!(
+            parse_justification(&lex_kinds("Taut Con:").unwrap()),
+            Ok(Justification::TautCon(vec![]))
+        );
+        assert!(parse_justification(&lex_kinds("Taut Con: 1,").unwrap()).is_err());
+        assert!(parse_justification(&lex_kinds("Taut Con: 1 2").unwrap()).is_err());
+        let line = parse_proof_line(&lex_kinds("4 | A ∨ ¬A    Taut Con:").unwrap()).unwrap();
+        assert_eq!(line.justification, Some(Justification::TautCon(vec![])));
+        assert_eq!(
+            parse_justification(&lex_kinds("FO Con: 2").unwrap()),
+            Ok(Justification::FoCon(vec![2]))
+        );
+        assert_eq!(
+            parse_justification(&lex_kinds("Ana Con: 1, 2").unwrap()),
+            Ok(Justification::AnaCon(vec![1, 2]))
+        );
+    }
+
+    #[test]
+    fn test_forallx_rules() {
+        let just = |s: &str| parse_justification(&lex_kinds(s).unwrap());
+        assert_eq!(just("DS: 1, 2"), Ok(Justification::DisjunctiveSyllogism(1, 2)));
+        assert_eq!(just("MT: 2, 1"), Ok(Justification::ModusTollens(2, 1)));
+        assert_eq!(just("DNE: 3"), Ok(Justification::DoubleNegationElim(3)));
+        assert_eq!(just("TND: 1-2, 3-4"), Ok(Justification::ExcludedMiddle((1, 2), (3, 4))));
+        assert_eq!(just("IP: 2-5"), Ok(Justification::IndirectProof((2, 5))));
+        assert_eq!(just("DeM: 1"), Ok(Justification::DeMorgan(1)));
+        assert_eq!(just("CQ: 1"), Ok(Justification::ConversionOfQuantifiers(1)));
+        assert!(just("DS: 1").is_err());
+        assert!(just("IP: 2").is_err());
+
+        // the basic rules under their forall x names
+        assert_eq!(just("∧I: 1, 2"), Ok(Justification::AndIntro(vec![1, 2])));
+        assert_eq!(just("→E: 1, 2"), Ok(Justification::ImpliesElim(1, 2)));
+        assert_eq!(just("¬E: 1, 2"), Ok(Justification::BottomIntro(1, 2)));
+        assert_eq!(just("¬Elim: 1"), Ok(Justification::NotElim(1)));
+        assert!(just("¬Elim: 1, 2").is_err());
+        assert_eq!(just("X: 4"), Ok(Justification::BottomElim(4)));
+        assert_eq!(just("R: 1"), Ok(Justification::Reit(1)));
+        let line = parse_proof_line(&lex_kinds("3 | a = a  =I").unwrap()).unwrap();
+        assert_eq!(line.justification, Some(Justification::EqualsIntro));
+        let line = parse_proof_line(&lex_kinds("3 | P ∨ Q  ∨I: 1").unwrap()).unwrap();
+        assert_eq!(line.justification, Some(Justification::OrIntro(1)));
+        // a line that is nothing but the end of =Intro is an error, not a panic
+        assert!(parse_proof_line(&lex_kinds("Intro").unwrap()).is_err());
+        assert!(parse_proof_line(&lex_kinds("I").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_modal() {
+        let p = Box::new(Wff::Atomic("P".to_string()));
+        assert_eq!(
+            parse_logical_expression_string("□P → ◇P"),
+            Some(Wff::Implies(
+                Box::new(Wff::Necessarily(p.clone())),
+                Box::new(Wff::Possibly(p.clone()))
+            ))
+        );
+        assert_eq!(
+            parse_logical_expression_string("¬[]<>P"),
+            Some(Wff::Not(Box::new(Wff::Necessarily(Box::new(Wff::Possibly(p.clone()))))))
+        );
+
+        let lines = parse_fitch_proof(
+            "1 | □P\n  |----\n  | |□----\n2 | | P    □ Elim: 1\n3 | □P   □ Intro: 2-2",
+        )
+        .unwrap();
+        assert!(lines[2].is_fitch_bar_line && lines[2].is_strict_subproof_bar);
+        assert_eq!(lines[2].depth, 2);
+        assert!(!lines[1].is_strict_subproof_bar);
+        assert_eq!(lines[3].justification, Some(Justification::BoxElim(1)));
+        assert_eq!(lines[4].justification, Some(Justification::BoxIntro((2, 2))));
+
+        let just = |s: &str| parse_justification(&lex_kinds(s).unwrap());
+        assert_eq!(just("◇ Intro: 3"), Ok(Justification::DiamondIntro(3)));
+        assert_eq!(just("◇E: 3"), Ok(Justification::DiamondElim(3)));
+        assert_eq!(just("R□: 1"), Ok(Justification::BoxReit(1)));
+        assert!(just("□ Intro: 2").is_err());
+    }
+
+    #[test]
+    fn test_comments() {
+        let lines = parse_fitch_proof("# premises\n1 | P  // given\n  |----  # bar\n").unwrap();
+        assert!(lines[0].is_comment_line());
+        assert_eq!(lines[0].comment.as_deref(), Some("# premises"));
+        assert_eq!(lines[1].sentence, Some(Wff::Atomic("P".to_string())));
+        assert_eq!(lines[1].comment.as_deref(), Some("// given"));
+        assert!(lines[2].is_fitch_bar_line);
+        assert_eq!(lines[2].comment.as_deref(), Some("# bar"));
+
+        // the columns of a parse error still refer to the whole line
+        let err = parse_fitch_proof("1 | P $ // given").unwrap_err();
+        assert_eq!(
+            err.location.unwrap().span,
+            Span {
+                start: 6,
+                end: 7
+            }
+        );
+    }
 }
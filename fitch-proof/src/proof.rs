@@ -1,10 +1,11 @@
 use crate::data::*;
+use crate::rule_set::{ModalSystem, RuleSet};
 use std::collections::HashSet;
 
 /// [Scope] is a type which stores scoping information (like which lines can reference which
 /// lines).
 ///
-/// It is a `Vec<(Vec<usize>,Vec<(usize,usize)>)>`),
+/// It is a `Vec<(Vec<usize>,Vec<(usize,usize)>,Vec<usize>)>`),
 /// such that:
 /// ```notrust
 /// for all i in <line numbers corresponding to inferences (not premises) found in proof>:
@@ -12,10 +13,13 @@ use std::collections::HashSet;
 ///     and
 ///   scope[i].1 == <the set of the subproofs i-j (stored as tuple (i,j))
 ///                   which are referenceable by line i>
+///     and
+///   scope[i].2 == <the set of the line numbers outside the innermost strict subproof around
+///                   line i whose sentence may be brought into it (with □ Elim or R□)>
 ///   ```
 ///
 /// The first index `scope[0]` is unused.
-pub type Scope = Vec<(Vec<usize>, Vec<(usize, usize)>)>;
+pub type Scope = Vec<(Vec<usize>, Vec<(usize, usize)>, Vec<usize>)>;
 
 /// A [Proof] is a fundamental entity in this program. It contains important information that can
 /// be used to assess whether the proof is correct.
@@ -36,6 +40,10 @@ pub struct Proof {
     pub units: Vec<ProofUnit>,
     ///  a field which contains the set of strings that should be seen as a variable.
     pub allowed_variable_names: HashSet<String>,
+    ///  a field which contains the rules that may be used in the proof.
+    pub rule_set: RuleSet,
+    ///  a field that contains the strict subproofs of the proof, as (first line, last line).
+    pub strict_subproofs: Vec<(usize, usize)>,
 }
 
 /// An enum that is useful to look at the structure of a proof. This is useful for example when you
@@ -48,17 +56,31 @@ pub struct Proof {
 /// premise at depth 3 (i.e., a new subproof is opened), then between the corresponding
 /// [ProofUnit]s for the two sentences, there will be a [ProofUnit::SubproofOpen].
 ///
-/// Empty lines are not representable in terms of [ProofUnit]s (and this is also not necessary).
+/// Empty lines and comments are not representable in terms of [ProofUnit]s (and this is also not
+/// necessary).
 #[derive(Debug, PartialEq)]
 pub enum ProofUnit {
     NumberedProofLineWithJustification(usize), // usize is line number
     NumberedProofLineWithoutJustificationWithoutBoxedConstant(usize), // usize is line number
     NumberedProofLineThatIntroducesBoxedConstant(usize), // usize is line number
     FitchBarLine,
+    StrictFitchBarLine,
     SubproofOpen,
     SubproofClose,
 }
 
+impl ProofUnit {
+    /// Returns the line number of this unit, if it is a numbered proof line.
+    pub fn line_num(&self) -> Option<usize> {
+        match self {
+            ProofUnit::NumberedProofLineWithJustification(num)
+            | ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(num)
+            | ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(num) => Some(*num),
+            _ => None,
+        }
+    }
+}
+
 impl Proof {
     /// Given a vector of [ProofLine]s, this method constructs the proof. In case this method fails,
     /// it means a fatal error will need to be given, because if this method already fails then the
@@ -68,45 +90,97 @@ impl Proof {
     pub fn construct(
         proof_lines: Vec<ProofLine>,
         allowed_variable_names: HashSet<String>,
-    ) -> Result<Proof, String> {
+        rule_set: RuleSet,
+    ) -> Result<Proof, Diagnostic> {
+        Self::construct_with(proof_lines, allowed_variable_names, rule_set, false)
+    }
+
+    /// Like [Proof::construct], but also accepts a proof that ends with a Fitch bar, like an
+    /// exercise that has only just been started, or a subproof that has only just been opened.
+    /// Such a proof is not finished, so it is never fully correct, but there is already something
+    /// to say about how to go on.
+    pub fn construct_unfinished(
+        proof_lines: Vec<ProofLine>,
+        allowed_variable_names: HashSet<String>,
+        rule_set: RuleSet,
+    ) -> Result<Proof, Diagnostic> {
+        Self::construct_with(proof_lines, allowed_variable_names, rule_set, true)
+    }
+
+    fn construct_with(
+        proof_lines: Vec<ProofLine>,
+        allowed_variable_names: HashSet<String>,
+        rule_set: RuleSet,
+        allow_trailing_fitch_bar: bool,
+    ) -> Result<Proof, Diagnostic> {
         let units = Self::lines_to_units(&proof_lines)?;
-        Self::is_half_well_structured(&units)?; // check if proof is HALF-well-structured
-        let scope = Self::determine_scope(&units);
+        // check if proof is HALF-well-structured
+        Self::is_half_well_structured(&units, allow_trailing_fitch_bar)?;
+        let scope = Self::determine_scope(&units, &proof_lines, rule_set.modal_system());
+        let strict_subproofs = Self::find_strict_subproofs(&units);
 
         Ok(Proof {
             lines: proof_lines,
             scope,
             units,
             allowed_variable_names,
+            rule_set,
+            strict_subproofs,
         })
     }
 
+    /// Returns what line `line_num` may reference, in the same form as an entry of [Scope]. Unlike
+    /// the [Scope] in `self.scope`, this is also known for lines without justification.
+    ///
+    /// PRECONDITION (panics otherwise): line `line_num` exists in the proof.
+    pub fn scope_of_line(&self, line_num: usize) -> (Vec<usize>, Vec<(usize, usize)>, Vec<usize>) {
+        let i = self.units.iter().position(|u| u.line_num() == Some(line_num)).unwrap();
+        Self::scope_at_unit(&self.units, &self.lines, self.rule_set.modal_system(), i)
+    }
+
+    /// Returns what a line that is added to the end of the proof (at the depth of the last line)
+    /// may reference, in the same form as an entry of [Scope].
+    pub fn scope_at_end(&self) -> (Vec<usize>, Vec<(usize, usize)>, Vec<usize>) {
+        Self::scope_at_unit(
+            &self.units,
+            &self.lines,
+            self.rule_set.modal_system(),
+            self.units.len(),
+        )
+    }
+
     /// From a vector of [ProofLine]s, this function generates a vector of [ProofUnit]s which are useful during analysis.
-    fn lines_to_units(proof_lines: &[ProofLine]) -> Result<Vec<ProofUnit>, String> {
+    fn lines_to_units(proof_lines: &[ProofLine]) -> Result<Vec<ProofUnit>, Diagnostic> {
         let mut units: Vec<ProofUnit> = vec![];
         let mut prev_depth = 1;
         let mut last_line_num = 0;
 
         // translate the proof to `ProofUnit`s
-        for line in proof_lines {
+        for line in proof_lines.iter().filter(|l| !l.is_comment_line()) {
             if line.depth == prev_depth + 1 {
                 units.push(ProofUnit::SubproofOpen);
             } else if line.depth + 1 == prev_depth {
                 units.push(ProofUnit::SubproofClose);
             } else if line.depth != prev_depth {
-                return Err(format!("near line {}, there is an \'indentation/scope jump\' that is too big. You cannot open or close two subproofs in the same line.",last_line_num+1));
+                return Err(Diagnostic::fatal(ErrorCode::Structure, format!("near line {}, there is an \'indentation/scope jump\' that is too big. You cannot open or close two subproofs in the same line.",last_line_num+1)));
             }
             if let Some(line_num) = line.line_num {
                 last_line_num = line_num;
                 if line.justification.is_none() && line.constant_between_square_brackets.is_none() {
-                    units.push(ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(line_num));
+                    units.push(
+                        ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(
+                            line_num,
+                        ),
+                    );
                 } else if line.justification.is_none() {
                     units.push(ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(line_num));
                 } else {
                     units.push(ProofUnit::NumberedProofLineWithJustification(line_num));
                 }
             }
-            if line.is_fitch_bar_line {
+            if line.is_strict_subproof_bar {
+                units.push(ProofUnit::StrictFitchBarLine);
+            } else if line.is_fitch_bar_line {
                 units.push(ProofUnit::FitchBarLine);
             }
             prev_depth = line.depth;
@@ -115,7 +189,16 @@ impl Proof {
     }
 
     /// This function computes the [Scope] of a proof.
-    fn determine_scope(units: &[ProofUnit]) -> Scope {
+    ///
+    /// From inside a strict subproof, nothing outside it can be referenced in the ordinary way.
+    /// The lines right outside it (the ones that the strict subproof itself could reference) are
+    /// only in the scope if `modal_system` allows their sentence to be brought into the strict
+    /// subproof (see [ModalSystem::can_import]), and then in the third part of the [Scope].
+    fn determine_scope(
+        units: &[ProofUnit],
+        lines: &[ProofLine],
+        modal_system: ModalSystem,
+    ) -> Scope {
         let last_line_number: usize = units
             .iter()
             .filter_map(|u| match u {
@@ -123,76 +206,119 @@ impl Proof {
                 | ProofUnit::NumberedProofLineWithJustification(num) => Some(*num),
                 _ => None,
             })
-            .last()
-            .unwrap();
-        let mut scope: Scope = vec![(vec![], vec![]); last_line_number + 1];
+            .next_back()
+            .unwrap_or(0);
+        let mut scope: Scope = vec![(vec![], vec![], vec![]); last_line_number + 1];
         for i in 0..units.len() {
             if let ProofUnit::NumberedProofLineWithJustification(num) = units[i] {
-                // used to find referenceable single lines
-                let mut depth: i32 = 0;
+                scope[num] = Self::scope_at_unit(units, lines, modal_system, i);
+            }
+        }
 
-                // used to find referenceable subproofs
-                let mut stack: Vec<usize> = vec![];
+        scope
+    }
 
-                for j in (0..i).rev() {
-                    match units[j] {
-                        ProofUnit::SubproofOpen => {
-                            if depth > 0 {
-                                depth -= 1;
-                                let subproof_begin;
-                                if let ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(
-                                    s_begin,
-                                ) = units[j + 1]
-                                {
-                                    subproof_begin = s_begin;
-                                } else if let ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(
-                                    s_begin,
-                                ) = units[j + 1]
-                                {
-                                    subproof_begin = s_begin;
-                                } else {
-                                    panic!("This really should not happen. This is a mistake by the developer. Please contact me if you get this.");
-                                }
-                                let subproof_end = stack.pop().expect("This is a mistake by the developer. Please contact me if you get this.");
-                                if stack.is_empty() {
-                                    scope[num].1.push((subproof_begin, subproof_end));
-                                }
-                            }
-                        }
-                        ProofUnit::SubproofClose => {
-                            depth += 1;
-                            if let ProofUnit::NumberedProofLineWithJustification(subproof_end) =
-                                units[j - 1]
-                            {
-                                stack.push(subproof_end);
-                            } else if let ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(
-                                subproof_end,
-                            ) = units[j - 1]
-                            {
-                                stack.push(subproof_end);
-                            } else if let ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(
-                                subproof_end,
-                            ) = units[j - 1]
-                            {
-                                stack.push(subproof_end);
-                            } else {
-                                panic!("This really should not happen. This is a mistake by the developer. Please contact me if you get this.");
-                            }
-                        }
-                        ProofUnit::NumberedProofLineWithJustification(ref_num)
-                        | ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(ref_num)
-                        | ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(ref_num) => {
-                            if depth == 0 {
-                                scope[num].0.push(ref_num);
-                            }
-                        }
-                        _ => {}
+    /// This function determines what a line at position `i` of `units` may reference, as an entry
+    /// of [Scope]. This is also meaningful if `i == units.len()`, which is the position of a line
+    /// that would be added to the end of the proof.
+    fn scope_at_unit(
+        units: &[ProofUnit],
+        lines: &[ProofLine],
+        modal_system: ModalSystem,
+        i: usize,
+    ) -> (Vec<usize>, Vec<(usize, usize)>, Vec<usize>) {
+        let can_import = |num: usize| {
+            lines
+                .iter()
+                .find(|l| l.line_num == Some(num))
+                .and_then(|l| l.sentence.as_ref())
+                .is_some_and(|wff| modal_system.can_import(wff))
+        };
+        let mut res = (vec![], vec![], vec![]);
+        // used to find referenceable single lines
+        let mut depth: i32 = 0;
+
+        // used to find referenceable subproofs
+        let mut stack: Vec<usize> = vec![];
+
+        // the number of strict subproofs around the line that have been left
+        let mut strict_subproofs_left = 0;
+
+        for j in (0..i).rev() {
+            match units[j] {
+                ProofUnit::SubproofOpen if depth > 0 => {
+                    depth -= 1;
+                    // a subproof begins with its premise, or, if it is a strict subproof,
+                    // with the first line after its Fitch bar
+                    let subproof_begin = units[j + 1..].iter().find_map(ProofUnit::line_num).expect("This really should not happen. This is a mistake by the developer. Please contact me if you get this.");
+                    let subproof_end = stack.pop().expect(
+                        "This is a mistake by the developer. Please contact me if you get this.",
+                    );
+                    if stack.is_empty() && strict_subproofs_left == 0 {
+                        res.1.push((subproof_begin, subproof_end));
+                    }
+                }
+                // this opens a strict subproof that the line is in
+                ProofUnit::SubproofOpen if units[j + 1] == ProofUnit::StrictFitchBarLine => {
+                    strict_subproofs_left += 1;
+                    if strict_subproofs_left == 2 {
+                        break;
+                    }
+                }
+                ProofUnit::SubproofClose => {
+                    depth += 1;
+                    if let Some(subproof_end) = units[j - 1].line_num() {
+                        stack.push(subproof_end);
+                    } else {
+                        panic!("This really should not happen. This is a mistake by the developer. Please contact me if you get this.");
                     }
                 }
+                ProofUnit::NumberedProofLineWithJustification(ref_num)
+                | ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(ref_num)
+                | ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(ref_num)
+                    if depth == 0 =>
+                {
+                    if strict_subproofs_left == 0 {
+                        res.0.push(ref_num);
+                    } else if can_import(ref_num) {
+                        res.2.push(ref_num);
+                    }
+                }
+                _ => {}
             }
         }
+        res
+    }
 
-        scope
+    /// This function finds the strict subproofs of a proof that are closed, as (first line, last
+    /// line).
+    fn find_strict_subproofs(units: &[ProofUnit]) -> Vec<(usize, usize)> {
+        let mut strict_subproofs = vec![];
+        // for each open subproof: whether it is strict, and its first and last line so far
+        let mut open: Vec<(bool, Option<usize>, Option<usize>)> = vec![];
+        for (i, unit) in units.iter().enumerate() {
+            match unit {
+                ProofUnit::SubproofOpen => open.push((
+                    units.get(i + 1) == Some(&ProofUnit::StrictFitchBarLine),
+                    None,
+                    None,
+                )),
+                ProofUnit::SubproofClose => {
+                    if let Some((true, Some(begin), Some(end))) = open.pop() {
+                        strict_subproofs.push((begin, end));
+                    }
+                }
+                _ => {
+                    if let Some(num) = unit.line_num() {
+                        for (_, begin, end) in &mut open {
+                            begin.get_or_insert(num);
+                            *end = Some(num);
+                        }
+                    }
+                }
+            }
+        }
+        strict_subproofs
     }
 
     /// This function checks if a proof is HALF-well-structured.
@@ -215,19 +341,31 @@ impl Proof {
     /// basically allow the user to not write a justification for the time being. In that case it
     /// will be parsed as a premise, so that's why we allow premises. This function won't complain
     /// about it, but of course, this will be checked when the proof is assessed for full correctness.
-    fn is_half_well_structured(units: &[ProofUnit]) -> Result<(), String> {
+    ///
+    /// If `allow_trailing_fitch_bar` is true, the proof may end with a Fitch bar (see
+    /// [Proof::construct_unfinished]).
+    fn is_half_well_structured(
+        units: &[ProofUnit],
+        allow_trailing_fitch_bar: bool,
+    ) -> Result<(), Diagnostic> {
         // traverse the `ProofUnit`s to check validity of the proof
         // basically, for each "proof unit", we check that the units after that are allowed.
         if units.is_empty() {
-            return Err("Your proof appears to be empty.".to_string());
+            return Err(Diagnostic::fatal(
+                ErrorCode::EmptyProof,
+                "Your proof appears to be empty.".to_string(),
+            ));
         }
         match units[0] {
             ProofUnit::FitchBarLine => {}
             ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(_) => {}
             _ => {
-                return Err("Error: proof should start with premises (or \
+                return Err(Diagnostic::fatal(
+                    ErrorCode::Structure,
+                    "Error: proof should start with premises (or \
                 Fitch bar, if there are no premises)."
-                    .to_string())
+                        .to_string(),
+                ))
             }
         }
         for i in 0..units.len() {
@@ -239,45 +377,89 @@ impl Proof {
                     //  - a new subproof
                     //    and a proof MUST NOT end with a Fitch bar line.
                     if i + 1 == units.len() {
-                        return Err("The proof ends with a Fitch bar.".to_string());
+                        if !allow_trailing_fitch_bar {
+                            return Err(Diagnostic::fatal(
+                                ErrorCode::Structure,
+                                "The proof ends with a Fitch bar.".to_string(),
+                            ));
+                        }
                     } else {
                         match units[i + 1] {
                             ProofUnit::NumberedProofLineWithJustification(_) => {}
                             ProofUnit::SubproofOpen => {}
                             ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(_) => {}
                             _ => {
-                                return Err("Error: Fitch bars should be followed by \
+                                return Err(Diagnostic::fatal(ErrorCode::Structure, "Error: Fitch bars should be followed by \
                                            either a new subproof or an inference. \
                                            You might be missing a justification."
-                                    .to_string());
+                                    .to_string()));
                             }
                         }
                     }
                 }
+                ProofUnit::StrictFitchBarLine => {
+                    // in HALF-well-structured proofs, a strict Fitch bar line must open a subproof,
+                    // and may be succeeded by:
+                    //  - an inference
+                    //  - a premise without boxed constant (inference for which the user didn't write justification yet)
+                    //  - a new subproof
+                    //    and a strict subproof MUST NOT be empty.
+                    if i == 0 || units[i - 1] != ProofUnit::SubproofOpen {
+                        return Err(Diagnostic::fatal(
+                            ErrorCode::Structure,
+                            "Error: a strict Fitch bar (|□----) can only be the first line of a new \
+                                   subproof."
+                                .to_string(),
+                        ));
+                    }
+                    match units.get(i + 1) {
+                        Some(ProofUnit::NumberedProofLineWithJustification(_))
+                        | Some(ProofUnit::SubproofOpen)
+                        | Some(
+                            ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(_),
+                        ) => {}
+                        _ => {
+                            return Err(Diagnostic::fatal(
+                                ErrorCode::Structure,
+                                "Error: a strict subproof should contain at least one line."
+                                    .to_string(),
+                            ));
+                        }
+                    }
+                }
                 ProofUnit::SubproofOpen => {
                     // in HALF-well-structured proofs, after a subproof is opened, there must be:
                     //  - EXACTLY one numbered premise, FOLLOWED by a Fitch bar
                     if i + 1 == units.len() || i + 2 == units.len() {
-                        return Err("Error: this proof ends with an opened \
+                        return Err(Diagnostic::fatal(
+                            ErrorCode::Structure,
+                            "Error: this proof ends with an opened \
                                    subproof in a way that should not be."
-                            .to_string());
+                                .to_string(),
+                        ));
                     }
                     match units[i + 1] {
+                        // a strict subproof has no premise; its Fitch bar is checked below
+                        ProofUnit::StrictFitchBarLine => continue,
                         ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(_)
                         | ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(_) => {}
                         _ => {
-                            return Err(
+                            return Err(Diagnostic::fatal(
+                                ErrorCode::Structure,
                                 "Error: the first line on any new subproof should be a premise."
                                     .to_string(),
-                            )
+                            ))
                         }
                     }
                     match units[i + 2] {
                         ProofUnit::FitchBarLine => {}
                         _ => {
-                            return Err("Error: a subproof should have exactly one \
+                            return Err(Diagnostic::fatal(
+                                ErrorCode::Structure,
+                                "Error: a subproof should have exactly one \
                                          premise, followed by a Fitch bar."
-                                .to_string())
+                                    .to_string(),
+                            ))
                         }
                     }
                 }
@@ -289,9 +471,12 @@ impl Proof {
                     //    and a proof MAY end directly after a closed subproof.
                     if i + 1 == units.len() {
                         if false {
-                            return Err("Error: the proof ends with the closing of a subproof.\
+                            return Err(Diagnostic::fatal(
+                                ErrorCode::Structure,
+                                "Error: the proof ends with the closing of a subproof.\
                                        The last line of the proof should always be top-level."
-                                .to_string());
+                                    .to_string(),
+                            ));
                         }
                     } else {
                         match units[i + 1] {
@@ -299,10 +484,10 @@ impl Proof {
                             ProofUnit::SubproofOpen => {}
                             ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(_) => {}
                             _ => {
-                                return Err("Error: after closing a subproof, either you \
+                                return Err(Diagnostic::fatal(ErrorCode::Structure, "Error: after closing a subproof, either you \
                                      should open a new subproof or there should be \
                                      an inference. Maybe you are missing some justification."
-                                    .to_string())
+                                    .to_string()))
                             }
                         }
                     }
@@ -320,14 +505,14 @@ impl Proof {
                             | ProofUnit::SubproofOpen
                             | ProofUnit::SubproofClose => {}
                             ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(_) => {}
-                            ProofUnit::FitchBarLine => {
-                                return Err("Error: you cannot have a Fitch bar \
+                            ProofUnit::FitchBarLine | ProofUnit::StrictFitchBarLine => {
+                                return Err(Diagnostic::fatal(ErrorCode::Structure, "Error: you cannot have a Fitch bar \
                                         after an inference. Maybe you are giving \
                                         justification for a premise?"
-                                    .to_string());
+                                    .to_string()));
                             }
                             ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(_) =>{
-                                return Err("Error: a boxed constant can only be introduced in the premise of a subproof".to_owned())
+                                return Err(Diagnostic::fatal(ErrorCode::Structure, "Error: a boxed constant can only be introduced in the premise of a subproof".to_owned()))
                             }
                         }
                     }
@@ -346,8 +531,11 @@ impl Proof {
                     if i + 1 < units.len() {
                         match units[i+1] {
                             ProofUnit::FitchBarLine | ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(_) | ProofUnit::NumberedProofLineWithJustification(_) | ProofUnit::SubproofOpen | ProofUnit::SubproofClose => {}
+                            ProofUnit::StrictFitchBarLine => {
+                                return Err(Diagnostic::fatal(ErrorCode::Structure, "Error: a strict Fitch bar (|□----) can only be the first line of a new subproof.".to_owned()))
+                            }
                             ProofUnit::NumberedProofLineThatIntroducesBoxedConstant(_) => {
-                                return Err("Error: a boxed constant can only be introduced in the premise of a subproof".to_owned())
+                                return Err(Diagnostic::fatal(ErrorCode::Structure, "Error: a boxed constant can only be introduced in the premise of a subproof".to_owned()))
                             }
                         }
                     }
@@ -359,14 +547,18 @@ impl Proof {
                     //    and a proof MUST NOT end directly after a premise with b.c.
 
                     if i + 1 >= units.len() {
-                        return Err("Error: a proof cannot end with a premise.".to_owned());
+                        return Err(Diagnostic::fatal(
+                            ErrorCode::Structure,
+                            "Error: a proof cannot end with a premise.".to_owned(),
+                        ));
                     }
                     match units[i + 1] {
                         ProofUnit::FitchBarLine => {}
                         _ => {
-                            return Err(
-                                "Error: after a premise, there should be a Fitch bar".to_owned()
-                            );
+                            return Err(Diagnostic::fatal(
+                                ErrorCode::Structure,
+                                "Error: after a premise, there should be a Fitch bar".to_owned(),
+                            ));
                         }
                     }
                 }
@@ -382,7 +574,11 @@ impl Proof {
                 | ProofUnit::NumberedProofLineWithoutJustificationWithoutBoxedConstant(num)
                 | ProofUnit::NumberedProofLineWithJustification(num) => {
                     if *num != 1 + prev_num {
-                        return Err(format!("Line numbers are wrong; discrepancy between line {prev_num} and {num}..."));
+                        return Err(Diagnostic::fatal(
+                            ErrorCode::LineNumbers,
+                            format!("Line numbers are wrong; discrepancy between line {prev_num} and {num}..."),
+                        )
+                        .with_related_lines(vec![prev_num, *num]));
                     }
                     prev_num = *num;
                 }
@@ -0,0 +1,641 @@
+use crate::data::*;
+use crate::fix_line_numbers::fix_line_numbers;
+use std::collections::{HashMap, HashSet};
+
+/// Searches for a proof of `goal` from `premises` in propositional logic. If `goal` is a
+/// tautological consequence of `premises`, a proof is returned that only uses the primitive rules
+/// of LPL (the introduction and elimination rules of ∧, ∨, ¬, ⊥, → and ↔, and Reit). The proof
+/// consists of the premises, a Fitch bar, and the lines that lead to `goal` on the last line, so it
+/// can be formatted with [crate::formatter::format_proof] and checked like any other proof. If
+/// `goal` does not follow from `premises`, [None] is returned.
+///
+/// Only the truth-functional connectives are looked at. Every other subformula (a predicate
+/// application, an identity, a quantified or a modal sentence) is treated as an atom, like Taut
+/// Con does.
+///
+/// The goal is broken down by introduction rules, and by ∨ Elim on the premises, as far as that is
+/// possible without losing proofs. What is left is proven indirectly, by [Prover::refute]: that is a tableau, which
+/// derives ⊥ by decomposing the available sentences, and splits into cases (by ∨ Elim, or by
+/// proving a part of a sentence indirectly) when a sentence cannot be decomposed otherwise. A
+/// tableau always terminates, and only fails to find ⊥ if the sentences are consistent. Lines
+/// that the proof turns out not to need are removed at the end.
+pub fn prove(premises: &[Wff], goal: &Wff) -> Option<Vec<ProofLine>> {
+    let mut prover = Prover {
+        lines: vec![],
+        depth: 1,
+        line_count: 0,
+    };
+    let mut available = Available::default();
+    for premise in premises {
+        let num = prover.push(premise.clone(), None);
+        available.insert(premise.clone(), num);
+    }
+    prover.push_fitch_bar();
+    let conclusion = prover.prove(&mut available, goal)?;
+    prover.finish(conclusion);
+    let mut lines = remove_unneeded_lines(prover.lines);
+    fix_line_numbers(&mut lines);
+    Some(lines)
+}
+
+/// The sentences that are available at some point of a proof under construction, with the
+/// numbers of the lines they are on. The order in which they became available is kept, so that
+/// the search is deterministic.
+#[derive(Debug, Clone, Default)]
+struct Available {
+    wffs: Vec<Wff>,
+    line_nums: HashMap<Wff, usize>,
+}
+
+impl Available {
+    fn insert(&mut self, wff: Wff, line_num: usize) {
+        if !self.line_nums.contains_key(&wff) {
+            self.line_nums.insert(wff.clone(), line_num);
+            self.wffs.push(wff);
+        }
+    }
+
+    fn get(&self, wff: &Wff) -> Option<usize> {
+        self.line_nums.get(wff).copied()
+    }
+
+    fn has(&self, wff: &Wff) -> bool {
+        self.line_nums.contains_key(wff)
+    }
+}
+
+/// A way to split a tableau into cases (see [Prover::refute]).
+enum Split {
+    /// ∨ Elim on the disjunction in the given line.
+    Cases(usize, Vec<Wff>),
+    /// Prove the sentence indirectly: first refute its negation, and then go on with the sentence
+    /// itself.
+    Cut(Wff),
+}
+
+/// A proof under construction. Lines are only ever added at the end, at the current depth.
+struct Prover {
+    lines: Vec<ProofLine>,
+    depth: usize,
+    line_count: usize,
+}
+
+impl Prover {
+    /// Adds a line with a sentence, and returns its line number.
+    fn push(&mut self, wff: Wff, justification: Option<Justification>) -> usize {
+        self.line_count += 1;
+        self.lines.push(ProofLine {
+            line_num: Some(self.line_count),
+            depth: self.depth,
+            is_fitch_bar_line: false,
+            is_strict_subproof_bar: false,
+            sentence: Some(wff),
+            justification,
+            constant_between_square_brackets: None,
+            parse_error: None,
+            comment: None,
+        });
+        self.line_count
+    }
+
+    fn push_fitch_bar(&mut self) {
+        self.lines.push(ProofLine {
+            line_num: None,
+            depth: self.depth,
+            is_fitch_bar_line: true,
+            is_strict_subproof_bar: false,
+            sentence: None,
+            justification: None,
+            constant_between_square_brackets: None,
+            parse_error: None,
+            comment: None,
+        });
+    }
+
+    /// Adds an inference and makes its sentence available.
+    fn derive(
+        &mut self,
+        available: &mut Available,
+        wff: Wff,
+        justification: Justification,
+    ) -> usize {
+        let num = self.push(wff.clone(), Some(justification));
+        available.insert(wff, num);
+        num
+    }
+
+    /// Makes sure that the line with number `line_num` is the last line at the current depth, by
+    /// reiterating it if it is not (or if it is a premise, which cannot end a (sub)proof).
+    fn finish(&mut self, line_num: usize) -> usize {
+        match self.lines.last() {
+            Some(l) if l.line_num == Some(line_num) && l.justification.is_some() => line_num,
+            _ => {
+                let wff = self.lines.iter().find(|l| l.line_num == Some(line_num)).unwrap();
+                let wff = wff.sentence.clone().unwrap();
+                self.push(wff, Some(Justification::Reit(line_num)))
+            }
+        }
+    }
+
+    /// Adds a subproof with the given premise, whose body is made by `body`. The line that
+    /// `body` returns becomes the last line of the subproof. Returns the line numbers of the
+    /// premise and the last line, or [None] if `body` fails.
+    fn try_subproof(
+        &mut self,
+        available: &Available,
+        premise: Wff,
+        body: impl FnOnce(&mut Prover, &mut Available) -> Option<usize>,
+    ) -> Option<(usize, usize)> {
+        self.depth += 1;
+        let mut inner = available.clone();
+        let begin = self.push(premise.clone(), None);
+        inner.insert(premise, begin);
+        self.push_fitch_bar();
+        let end = body(self, &mut inner).map(|n| self.finish(n));
+        self.depth -= 1;
+        Some((begin, end?))
+    }
+
+    /// Like [Prover::try_subproof], for a body that cannot fail.
+    fn subproof(
+        &mut self,
+        available: &Available,
+        premise: Wff,
+        body: impl FnOnce(&mut Prover, &mut Available) -> usize,
+    ) -> (usize, usize) {
+        self.try_subproof(available, premise, |p, inner| Some(body(p, inner))).unwrap()
+    }
+
+    /// Derives `target` from ⊥ in the given line.
+    fn explode(&mut self, available: &mut Available, bottom: usize, target: &Wff) -> usize {
+        match target {
+            Wff::Bottom => bottom,
+            _ => self.derive(available, target.clone(), Justification::BottomElim(bottom)),
+        }
+    }
+
+    /// Derives `a ↔ b`, from `b` or `¬a`, and from `a` or `¬b`.
+    fn derive_bicond(&mut self, available: &mut Available, a: &Wff, b: &Wff) -> usize {
+        // in the subproof for one direction, the other side is available, or it follows from a
+        // contradiction with the premise
+        let direction = |p: &mut Prover, inner: &mut Available, premise: &Wff, target: &Wff| {
+            if let Some(n) = inner.get(target) {
+                return n;
+            }
+            let premise_line = inner.get(premise).unwrap();
+            let negation_line = inner.get(&not(premise)).unwrap();
+            let bottom = p.derive(
+                inner,
+                Wff::Bottom,
+                Justification::BottomIntro(premise_line, negation_line),
+            );
+            p.explode(inner, bottom, target)
+        };
+        let first = self.subproof(available, a.clone(), |p, inner| direction(p, inner, a, b));
+        let second = self.subproof(available, b.clone(), |p, inner| direction(p, inner, b, a));
+        let bicond = Wff::Bicond(Box::new(a.clone()), Box::new(b.clone()));
+        self.derive(available, bicond, Justification::BicondIntro(first, second))
+    }
+
+    /// Derives `¬premise` by a subproof that assumes `premise` and derives ⊥ in the way `body`
+    /// does.
+    fn derive_negation(
+        &mut self,
+        available: &mut Available,
+        premise: &Wff,
+        body: impl FnOnce(&mut Prover, &mut Available) -> usize,
+    ) -> usize {
+        let range = self.subproof(available, premise.clone(), body);
+        self.derive(available, not(premise), Justification::NotIntro(range))
+    }
+
+    /// Proves `goal` from the available sentences, and returns the number of a line with `goal`
+    /// on it. Returns [None] if `goal` does not follow.
+    fn prove(&mut self, available: &mut Available, goal: &Wff) -> Option<usize> {
+        if let Some(n) = available.get(goal) {
+            return Some(n);
+        }
+        match goal {
+            Wff::And(conjuncts) => {
+                let nums = conjuncts
+                    .iter()
+                    .map(|c| self.prove(available, c))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(self.derive(available, goal.clone(), Justification::AndIntro(nums)))
+            }
+            Wff::Implies(a, b) => {
+                let range =
+                    self.try_subproof(available, (**a).clone(), |p, inner| p.prove(inner, b))?;
+                Some(self.derive(available, goal.clone(), Justification::ImpliesIntro(range)))
+            }
+            Wff::Bicond(a, b) => {
+                let first =
+                    self.try_subproof(available, (**a).clone(), |p, inner| p.prove(inner, b))?;
+                let second =
+                    self.try_subproof(available, (**b).clone(), |p, inner| p.prove(inner, a))?;
+                Some(self.derive(
+                    available,
+                    goal.clone(),
+                    Justification::BicondIntro(first, second),
+                ))
+            }
+            Wff::Not(a) => {
+                let range =
+                    self.try_subproof(available, (**a).clone(), |p, inner| p.refute(inner))?;
+                Some(self.derive(available, goal.clone(), Justification::NotIntro(range)))
+            }
+            Wff::Bottom => self.refute(available),
+            _ => {
+                // first see whether the goal follows by decomposing the available sentences...
+                loop {
+                    if let Some(n) = available.get(goal) {
+                        return Some(n);
+                    }
+                    if let Wff::Or(disjuncts) = goal {
+                        if let Some(n) = disjuncts.iter().find_map(|d| available.get(d)) {
+                            return Some(self.derive(
+                                available,
+                                goal.clone(),
+                                Justification::OrIntro(n),
+                            ));
+                        }
+                    }
+                    if !self.decompose(available) {
+                        break;
+                    }
+                }
+                // ... or by cases on an available disjunction (which loses no proofs either)...
+                let disjunction = available.wffs.iter().find_map(|wff| match wff {
+                    Wff::Or(disjuncts) if !disjuncts.iter().any(|d| available.has(d)) => {
+                        Some((available.get(wff).unwrap(), disjuncts.clone()))
+                    }
+                    _ => None,
+                });
+                if let Some((n, disjuncts)) = disjunction {
+                    let ranges = disjuncts
+                        .into_iter()
+                        .map(|d| self.try_subproof(available, d, |p, inner| p.prove(inner, goal)))
+                        .collect::<Option<Vec<_>>>()?;
+                    return Some(self.derive(
+                        available,
+                        goal.clone(),
+                        Justification::OrElim(n, ranges),
+                    ));
+                }
+                // ... and otherwise prove it indirectly
+                let range = self.try_subproof(available, not(goal), |p, inner| p.refute(inner))?;
+                let double_negation =
+                    self.derive(available, not(&not(goal)), Justification::NotIntro(range));
+                Some(self.derive(available, goal.clone(), Justification::NotElim(double_negation)))
+            }
+        }
+    }
+
+    /// Derives ⊥ from the available sentences, and returns the number of the line with ⊥ on it.
+    /// Returns [None] if the available sentences are consistent.
+    fn refute(&mut self, available: &mut Available) -> Option<usize> {
+        loop {
+            if let Some(n) = available.get(&Wff::Bottom) {
+                return Some(n);
+            }
+            let contradiction = available.wffs.iter().find_map(|wff| match wff {
+                Wff::Not(negated) => Some((available.get(negated)?, available.get(wff).unwrap())),
+                _ => None,
+            });
+            if let Some((n, m)) = contradiction {
+                return Some(self.derive(available, Wff::Bottom, Justification::BottomIntro(n, m)));
+            }
+            if self.decompose(available) {
+                continue;
+            }
+            match find_split(available)? {
+                Split::Cases(n, disjuncts) => {
+                    let ranges = disjuncts
+                        .into_iter()
+                        .map(|d| self.try_subproof(available, d, |p, inner| p.refute(inner)))
+                        .collect::<Option<Vec<_>>>()?;
+                    return Some(self.derive(
+                        available,
+                        Wff::Bottom,
+                        Justification::OrElim(n, ranges),
+                    ));
+                }
+                Split::Cut(wff) => {
+                    let range =
+                        self.try_subproof(available, not(&wff), |p, inner| p.refute(inner))?;
+                    let double_negation =
+                        self.derive(available, not(&not(&wff)), Justification::NotIntro(range));
+                    self.derive(available, wff, Justification::NotElim(double_negation));
+                }
+            }
+        }
+    }
+
+    /// Derives one new sentence from the available sentences without splitting into cases, if
+    /// that is possible. Returns whether a sentence was derived.
+    ///
+    /// Every sentence that is derived is a part of an available sentence, or the negation of one,
+    /// so this can only be done finitely often.
+    fn decompose(&mut self, available: &mut Available) -> bool {
+        for wff in available.wffs.clone() {
+            let n = available.get(&wff).unwrap();
+            match &wff {
+                Wff::And(conjuncts) => {
+                    if let Some(c) = conjuncts.iter().find(|c| !available.has(c)) {
+                        self.derive(available, c.clone(), Justification::AndElim(n));
+                        return true;
+                    }
+                }
+                Wff::Or(disjuncts) if !disjuncts.iter().any(|d| available.has(d)) => {
+                    // if all disjuncts but one are refuted, that one follows by ∨ Elim
+                    let open: Vec<&Wff> =
+                        disjuncts.iter().filter(|d| !available.has(&not(d))).collect();
+                    if let [target] = open[..] {
+                        let ranges = disjuncts
+                            .iter()
+                            .map(|d| {
+                                self.subproof(available, d.clone(), |p, inner| {
+                                    if d == target {
+                                        return inner.get(d).unwrap();
+                                    }
+                                    let bottom = p.derive(
+                                        inner,
+                                        Wff::Bottom,
+                                        Justification::BottomIntro(
+                                            inner.get(d).unwrap(),
+                                            inner.get(&not(d)).unwrap(),
+                                        ),
+                                    );
+                                    p.explode(inner, bottom, target)
+                                })
+                            })
+                            .collect();
+                        self.derive(available, target.clone(), Justification::OrElim(n, ranges));
+                        return true;
+                    }
+                }
+                Wff::Implies(a, b) => {
+                    if let (Some(m), false) = (available.get(a), available.has(b)) {
+                        self.derive(available, (**b).clone(), Justification::ImpliesElim(n, m));
+                        return true;
+                    }
+                    if let (Some(m), false) = (available.get(&not(b)), available.has(&not(a))) {
+                        self.derive_negation(available, a, |p, inner| {
+                            let a_line = inner.get(a).unwrap();
+                            let b_line = p.derive(
+                                inner,
+                                (**b).clone(),
+                                Justification::ImpliesElim(n, a_line),
+                            );
+                            p.derive(inner, Wff::Bottom, Justification::BottomIntro(b_line, m))
+                        });
+                        return true;
+                    }
+                }
+                Wff::Bicond(a, b) => {
+                    for (x, y) in [(a, b), (b, a)] {
+                        if let (Some(m), false) = (available.get(x), available.has(y)) {
+                            self.derive(available, (**y).clone(), Justification::BicondElim(n, m));
+                            return true;
+                        }
+                        if let (Some(m), false) = (available.get(&not(x)), available.has(&not(y))) {
+                            self.derive_negation(available, y, |p, inner| {
+                                let y_line = inner.get(y).unwrap();
+                                let x_line = p.derive(
+                                    inner,
+                                    (**x).clone(),
+                                    Justification::BicondElim(n, y_line),
+                                );
+                                p.derive(inner, Wff::Bottom, Justification::BottomIntro(x_line, m))
+                            });
+                            return true;
+                        }
+                    }
+                }
+                Wff::Not(negated) if self.decompose_negation(available, n, negated) => {
+                    return true;
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Does the work of [Prover::decompose] for the negation of `negated`, which is in line `n`.
+    fn decompose_negation(&mut self, available: &mut Available, n: usize, negated: &Wff) -> bool {
+        match negated {
+            Wff::Not(a) if !available.has(a) => {
+                self.derive(available, (**a).clone(), Justification::NotElim(n));
+                true
+            }
+            Wff::Or(disjuncts) => {
+                let Some(d) = disjuncts.iter().find(|d| !available.has(&not(d))) else {
+                    return false;
+                };
+                self.derive_negation(available, d, |p, inner| {
+                    let d_line = inner.get(d).unwrap();
+                    let disjunction =
+                        p.derive(inner, negated.clone(), Justification::OrIntro(d_line));
+                    p.derive(inner, Wff::Bottom, Justification::BottomIntro(disjunction, n))
+                });
+                true
+            }
+            Wff::And(conjuncts) if !conjuncts.iter().any(|c| available.has(&not(c))) => {
+                let missing: Vec<&Wff> = conjuncts.iter().filter(|c| !available.has(c)).collect();
+                let conjunction = |p: &mut Prover, inner: &mut Available| {
+                    let nums = conjuncts.iter().map(|c| inner.get(c).unwrap()).collect();
+                    p.derive(inner, negated.clone(), Justification::AndIntro(nums))
+                };
+                match missing[..] {
+                    [] if !available.has(negated) => {
+                        conjunction(self, available);
+                        true
+                    }
+                    [c] => {
+                        self.derive_negation(available, c, |p, inner| {
+                            let conjunction = conjunction(p, inner);
+                            p.derive(inner, Wff::Bottom, Justification::BottomIntro(conjunction, n))
+                        });
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            Wff::Implies(a, b) => {
+                if !available.has(a) {
+                    // ¬¬a, since a → b follows from ¬a
+                    let range = self.subproof(available, not(a), |p, inner| {
+                        let implication = p.subproof(inner, (**a).clone(), |p, inner| {
+                            let bottom = p.derive(
+                                inner,
+                                Wff::Bottom,
+                                Justification::BottomIntro(
+                                    inner.get(a).unwrap(),
+                                    inner.get(&not(a)).unwrap(),
+                                ),
+                            );
+                            p.explode(inner, bottom, b)
+                        });
+                        let implication = p.derive(
+                            inner,
+                            negated.clone(),
+                            Justification::ImpliesIntro(implication),
+                        );
+                        p.derive(inner, Wff::Bottom, Justification::BottomIntro(implication, n))
+                    });
+                    let double_negation =
+                        self.derive(available, not(&not(a)), Justification::NotIntro(range));
+                    self.derive(available, (**a).clone(), Justification::NotElim(double_negation));
+                    return true;
+                }
+                if !available.has(&not(b)) {
+                    // ¬b, since a → b follows from b
+                    self.derive_negation(available, b, |p, inner| {
+                        let b_line = inner.get(b).unwrap();
+                        let implication = p.subproof(inner, (**a).clone(), |_, _| b_line);
+                        let implication = p.derive(
+                            inner,
+                            negated.clone(),
+                            Justification::ImpliesIntro(implication),
+                        );
+                        p.derive(inner, Wff::Bottom, Justification::BottomIntro(implication, n))
+                    });
+                    return true;
+                }
+                false
+            }
+            Wff::Bicond(a, b) => {
+                for (x, y) in [(a, b), (b, a)] {
+                    if available.has(x) && !available.has(&not(y)) {
+                        // ¬y, since x ↔ y follows from x and y
+                        self.derive_negation(available, y, |p, inner| {
+                            let bicond = p.derive_bicond(inner, a, b);
+                            p.derive(inner, Wff::Bottom, Justification::BottomIntro(bicond, n))
+                        });
+                        return true;
+                    }
+                    if available.has(&not(x)) && !available.has(y) {
+                        // ¬¬y, since x ↔ y follows from ¬x and ¬y
+                        let range = self.subproof(available, not(y), |p, inner| {
+                            let bicond = p.derive_bicond(inner, a, b);
+                            p.derive(inner, Wff::Bottom, Justification::BottomIntro(bicond, n))
+                        });
+                        let double_negation =
+                            self.derive(available, not(&not(y)), Justification::NotIntro(range));
+                        self.derive(
+                            available,
+                            (**y).clone(),
+                            Justification::NotElim(double_negation),
+                        );
+                        return true;
+                    }
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Finds a sentence that [Prover::decompose] cannot do anything with, but that is not yet
+/// satisfied by the available sentences, and returns how to split the tableau on it.
+fn find_split(available: &Available) -> Option<Split> {
+    let undecided = |wff: &Wff| !available.has(wff) && !available.has(&not(wff));
+    available.wffs.iter().find_map(|wff| match wff {
+        Wff::Or(disjuncts) if !disjuncts.iter().any(|d| available.has(d)) => {
+            Some(Split::Cases(available.get(wff).unwrap(), disjuncts.clone()))
+        }
+        Wff::Implies(a, b) if !available.has(b) && undecided(a) => Some(Split::Cut((**a).clone())),
+        Wff::Bicond(a, _) if undecided(a) => Some(Split::Cut((**a).clone())),
+        Wff::Not(negated) => match &**negated {
+            Wff::Bicond(a, _) if undecided(a) => Some(Split::Cut((**a).clone())),
+            Wff::And(conjuncts) if !conjuncts.iter().any(|c| available.has(&not(c))) => {
+                conjuncts.iter().find(|c| undecided(c)).map(|c| Split::Cut(c.clone()))
+            }
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+fn not(wff: &Wff) -> Wff {
+    Wff::Not(Box::new(wff.clone()))
+}
+
+/// Removes the lines that the last line does not depend on (except for the premises of the
+/// proof), and adds the empty lines that separate two subproofs that follow each other.
+fn remove_unneeded_lines(lines: Vec<ProofLine>) -> Vec<ProofLine> {
+    let justifications: HashMap<usize, &Justification> =
+        lines.iter().filter_map(|l| Some((l.line_num?, l.justification.as_ref()?))).collect();
+    let mut needed: HashSet<usize> = HashSet::new();
+    let mut todo: Vec<usize> =
+        lines.iter().filter_map(|l| l.line_num).next_back().into_iter().collect();
+    while let Some(n) = todo.pop() {
+        if needed.insert(n) {
+            if let Some(just) = justifications.get(&n) {
+                todo.extend(just.cited_lines());
+            }
+        }
+    }
+
+    let mut result: Vec<ProofLine> = vec![];
+    let mut previous_kept = true;
+    for line in lines {
+        let keep = match line.line_num {
+            Some(n) => needed.contains(&n) || (line.depth == 1 && line.justification.is_none()),
+            // a Fitch bar belongs to the premise before it
+            None => previous_kept || line.depth == 1,
+        };
+        previous_kept = keep;
+        if !keep {
+            continue;
+        }
+        let opens_subproof =
+            line.line_num.is_some() && line.justification.is_none() && line.depth > 1;
+        if opens_subproof && result.last().is_some_and(|l| l.depth >= line.depth) {
+            result.push(ProofLine {
+                line_num: None,
+                depth: line.depth - 1,
+                is_fitch_bar_line: false,
+                is_strict_subproof_bar: false,
+                sentence: None,
+                justification: None,
+                constant_between_square_brackets: None,
+                parse_error: None,
+                comment: None,
+            });
+        }
+        result.push(line);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_logical_expression_string;
+
+    fn wff(s: &str) -> Wff {
+        parse_logical_expression_string(s).unwrap()
+    }
+
+    #[test]
+    fn test_short_proofs() {
+        // a premise is reiterated, rather than proven indirectly
+        let lines = prove(&[wff("P")], &wff("P")).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2].justification, Some(Justification::Reit(1)));
+
+        // the lines of the indirect proof that turn out not to be needed are removed
+        let lines = prove(&[wff("P ∧ Q"), wff("R")], &wff("Q")).unwrap();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3].justification, Some(Justification::AndElim(1)));
+    }
+
+    #[test]
+    fn test_invalid_sequents() {
+        assert_eq!(prove(&[wff("P ∨ Q")], &wff("P")), None);
+        assert_eq!(prove(&[], &wff("(P → Q) → (Q → P)")), None);
+        assert_eq!(prove(&[wff("¬(P ∧ Q)")], &wff("¬P")), None);
+    }
+}
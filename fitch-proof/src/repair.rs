@@ -0,0 +1,170 @@
+use crate::data::*;
+use crate::formatter;
+use crate::proof::*;
+
+impl Proof {
+    /// Re-tests a line that [Proof::check_line] rejected with justifications that are close to
+    /// the one that was written, and adds the ones that make the line correct to `diagnostic` as
+    /// suggestions. Close means: another rule with the same citations (this also catches steps in
+    /// the wrong direction, like ∧ Intro for ∧ Elim), or the same rule with two citations swapped
+    /// or with one citation off by one.
+    ///
+    /// The consequence rules (Taut Con, FO Con and Ana Con) are never suggested, since they would
+    /// make nearly every step correct. Neither are justifications that cite a line that could not
+    /// be parsed.
+    pub(crate) fn with_repair_suggestions(
+        &self,
+        line: &ProofLine,
+        diagnostic: Diagnostic,
+    ) -> Diagnostic {
+        let Some(just) = &line.justification else {
+            return diagnostic;
+        };
+        let mut suggestions: Vec<String> = vec![];
+        for candidate in nearby_justifications(just) {
+            // a line that could not be parsed cannot show that the candidate is correct
+            if !self.rule_set.is_allowed(candidate.rule())
+                || matches!(candidate.rule(), Rule::TautCon | Rule::FoCon | Rule::AnaCon)
+                || self.cites_unparsed_line(&candidate)
+            {
+                continue;
+            }
+            let repaired = ProofLine {
+                justification: Some(candidate),
+                ..line.clone()
+            };
+            if self.check_line(&repaired).is_ok() {
+                // the unwrap works: `repaired` has a justification
+                let suggestion =
+                    self.format_justification(repaired.justification.as_ref().unwrap());
+                if !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+        diagnostic.with_suggestions(suggestions)
+    }
+
+    /// Formats a [Justification] with the name that its rule has in the rule set of the proof.
+    fn format_justification(&self, just: &Justification) -> String {
+        let formatted = formatter::format_justification(just);
+        formatted.replacen(just.rule().name(), self.rule_set.name(just.rule()), 1)
+    }
+}
+
+/// Returns the justifications that are close to `just` (see [Proof::with_repair_suggestions]),
+/// from the most to the least likely mistake.
+fn nearby_justifications(just: &Justification) -> Vec<Justification> {
+    let rule = just.rule();
+    let (lines, subproofs) = just.citations();
+    let mut nearby: Vec<Justification> = vec![];
+
+    // the same rule, with two citations swapped
+    if let [a, b] = lines[..] {
+        nearby.extend(justification_with(rule, &[b, a], &subproofs));
+    }
+    if let [s1, s2] = subproofs[..] {
+        nearby.extend(justification_with(rule, &lines, &[s2, s1]));
+    }
+
+    // another rule, with the same citations
+    for other in Rule::ALL.into_iter().filter(|r| *r != rule) {
+        nearby.extend(justification_with(other, &lines, &subproofs));
+    }
+
+    // the same rule, with one citation off by one
+    let off_by_one = |n: usize| [n.saturating_sub(1), n + 1].into_iter().filter(|&m| m > 0);
+    for i in 0..lines.len() {
+        for n in off_by_one(lines[i]) {
+            let mut lines = lines.clone();
+            lines[i] = n;
+            nearby.extend(justification_with(rule, &lines, &subproofs));
+        }
+    }
+    for i in 0..subproofs.len() {
+        let (begin, end) = subproofs[i];
+        let shifted = off_by_one(begin)
+            .map(|b| (b, end))
+            .chain(off_by_one(end).map(|e| (begin, e)))
+            .filter(|(b, e)| b <= e);
+        for subproof in shifted {
+            let mut subproofs = subproofs.clone();
+            subproofs[i] = subproof;
+            nearby.extend(justification_with(rule, &lines, &subproofs));
+        }
+    }
+
+    nearby.retain(|j| j != just);
+    nearby
+}
+
+/// Builds a [Justification] with rule `rule` that cites `lines` and `subproofs`, if that rule can
+/// cite them.
+fn justification_with(
+    rule: Rule,
+    lines: &[usize],
+    subproofs: &[(usize, usize)],
+) -> Option<Justification> {
+    let just = match (rule, lines, subproofs) {
+        (Rule::AndIntro, ns, []) if !ns.is_empty() => Justification::AndIntro(ns.to_vec()),
+        (Rule::AndElim, [n], []) => Justification::AndElim(*n),
+        (Rule::OrIntro, [n], []) => Justification::OrIntro(*n),
+        (Rule::OrElim, [n], subproofs) if !subproofs.is_empty() => {
+            Justification::OrElim(*n, subproofs.to_vec())
+        }
+        (Rule::NotIntro, [], [s]) => Justification::NotIntro(*s),
+        (Rule::NotElim, [n], []) => Justification::NotElim(*n),
+        (Rule::BottomIntro, [n, m], []) => Justification::BottomIntro(*n, *m),
+        (Rule::BottomElim, [n], []) => Justification::BottomElim(*n),
+        (Rule::ImpliesIntro, [], [s]) => Justification::ImpliesIntro(*s),
+        (Rule::ImpliesElim, [n, m], []) => Justification::ImpliesElim(*n, *m),
+        (Rule::BicondIntro, [], [s1, s2]) => Justification::BicondIntro(*s1, *s2),
+        (Rule::BicondElim, [n, m], []) => Justification::BicondElim(*n, *m),
+        (Rule::EqualsElim, [n, m], []) => Justification::EqualsElim(*n, *m),
+        (Rule::ForallIntro, [], [s]) => Justification::ForallIntro(*s),
+        (Rule::ForallElim, [n], []) => Justification::ForallElim(*n),
+        (Rule::ExistsIntro, [n], []) => Justification::ExistsIntro(*n),
+        (Rule::ExistsElim, [n], [s]) => Justification::ExistsElim(*n, *s),
+        (Rule::Reit, [n], []) => Justification::Reit(*n),
+        (Rule::DisjunctiveSyllogism, [n, m], []) => Justification::DisjunctiveSyllogism(*n, *m),
+        (Rule::ModusTollens, [n, m], []) => Justification::ModusTollens(*n, *m),
+        (Rule::DoubleNegationElim, [n], []) => Justification::DoubleNegationElim(*n),
+        (Rule::ExcludedMiddle, [], [s1, s2]) => Justification::ExcludedMiddle(*s1, *s2),
+        (Rule::IndirectProof, [], [s]) => Justification::IndirectProof(*s),
+        (Rule::DeMorgan, [n], []) => Justification::DeMorgan(*n),
+        (Rule::ConversionOfQuantifiers, [n], []) => Justification::ConversionOfQuantifiers(*n),
+        (Rule::BoxIntro, [], [s]) => Justification::BoxIntro(*s),
+        (Rule::BoxElim, [n], []) => Justification::BoxElim(*n),
+        (Rule::DiamondIntro, [n], []) => Justification::DiamondIntro(*n),
+        (Rule::DiamondElim, [n], []) => Justification::DiamondElim(*n),
+        (Rule::BoxReit, [n], []) => Justification::BoxReit(*n),
+        _ => return None,
+    };
+    Some(just)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearby_justifications() {
+        let nearby = nearby_justifications(&Justification::ImpliesElim(3, 2));
+        assert_eq!(nearby[0], Justification::ImpliesElim(2, 3));
+        assert!(nearby.contains(&Justification::BottomIntro(3, 2)));
+        assert!(nearby.contains(&Justification::ImpliesElim(4, 2)));
+        assert!(nearby.contains(&Justification::ImpliesElim(3, 1)));
+        assert!(!nearby.contains(&Justification::ImpliesElim(3, 2)));
+
+        let nearby = nearby_justifications(&Justification::ImpliesIntro((2, 4)));
+        assert!(nearby.contains(&Justification::NotIntro((2, 4))));
+        assert!(nearby.contains(&Justification::ImpliesIntro((2, 5))));
+        assert!(nearby.contains(&Justification::ImpliesIntro((3, 4))));
+        assert!(!nearby.iter().any(|j| matches!(j, Justification::AndElim(_))));
+
+        // a citation is never off by one into line 0
+        let nearby = nearby_justifications(&Justification::AndElim(1));
+        assert!(!nearby.contains(&Justification::AndElim(0)));
+        assert!(nearby.contains(&Justification::AndIntro(vec![1])));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::data::{Rule, Wff};
+use std::collections::{HashMap, HashSet};
+
+/// The systems of modal logic that proofs with □ and ◇ can be checked in. Each system allows
+/// everything that the systems before it allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ModalSystem {
+    /// Only □ Elim brings sentences into a strict subproof, and only from right outside it.
+    K,
+    /// K, plus □ Elim on a sentence in ordinary scope (□A gives A), and ◇ Intro from A.
+    T,
+    /// T, plus R□ for sentences of the form □A.
+    S4,
+    /// S4, plus R□ for sentences of the form ◇A, ¬□A and ¬◇A.
+    S5,
+}
+
+impl ModalSystem {
+    /// Returns the modal system with the given name ("K", "T", "S4" or "S5"), if there is one.
+    pub fn from_name(name: &str) -> Option<ModalSystem> {
+        match name {
+            "K" => Some(ModalSystem::K),
+            "T" => Some(ModalSystem::T),
+            "S4" => Some(ModalSystem::S4),
+            "S5" => Some(ModalSystem::S5),
+            _ => None,
+        }
+    }
+
+    /// Whether a sentence outside a strict subproof can be brought into it in this system,
+    /// either by □ Elim or by R□.
+    pub fn can_import(&self, wff: &Wff) -> bool {
+        match wff {
+            Wff::Necessarily(_) => true,
+            Wff::Possibly(_) => *self == ModalSystem::S5,
+            Wff::Not(w) => {
+                *self == ModalSystem::S5 && matches!(**w, Wff::Necessarily(_) | Wff::Possibly(_))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The rules that may be used in the proofs of an exercise, and the names under which they are
+/// reported.
+///
+/// A [RuleSet] is made from one of the built-in profiles ([RuleSet::lpl] and [RuleSet::forallx])
+/// and can then be adjusted, like this:
+/// ```notrust
+/// RuleSet::lpl().forbid(Rule::FoCon).with_derived_rules(true)
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleSet {
+    /// The rules that may be used. A derived rule may only be used if `derived_rules` is true as
+    /// well.
+    allowed: HashSet<Rule>,
+    /// The names of the rules whose name differs from [Rule::name].
+    names: HashMap<Rule, String>,
+    /// Whether derived rules (see [Rule::is_derived]) may be used.
+    derived_rules: bool,
+    /// Whether steps that are only valid in classical logic are rejected.
+    intuitionistic: bool,
+    /// The modal system that strict subproofs are checked in.
+    modal_system: ModalSystem,
+}
+
+impl RuleSet {
+    /// The rules of *Language, Proof and Logic*: all introduction and elimination rules, Reit, and
+    /// Taut Con, FO Con and Ana Con. There are no derived rules. The modal rules are allowed as
+    /// well, so that proofs with □ and ◇ can be checked too.
+    pub fn lpl() -> RuleSet {
+        RuleSet {
+            allowed: Rule::ALL.into_iter().filter(|r| !r.is_derived()).collect(),
+            names: HashMap::new(),
+            derived_rules: false,
+            intuitionistic: false,
+            modal_system: ModalSystem::K,
+        }
+    }
+
+    /// The rules of *forall x: Calgary*: the basic rules, with the forall x names (so ⊥ Intro is
+    /// called ¬E and ⊥ Elim is called X), and the derived rules DS, MT, DNE, LEM, IP, DeM and CQ.
+    /// Double negation elimination is not a basic rule there, so ¬ Elim is not allowed (DNE does
+    /// the same), and neither are the consequence rules of LPL.
+    pub fn forallx() -> RuleSet {
+        let names = [
+            (Rule::AndIntro, "∧I"),
+            (Rule::AndElim, "∧E"),
+            (Rule::OrIntro, "∨I"),
+            (Rule::OrElim, "∨E"),
+            (Rule::NotIntro, "¬I"),
+            (Rule::BottomIntro, "¬E"),
+            (Rule::BottomElim, "X"),
+            (Rule::ImpliesIntro, "→I"),
+            (Rule::ImpliesElim, "→E"),
+            (Rule::BicondIntro, "↔I"),
+            (Rule::BicondElim, "↔E"),
+            (Rule::EqualsIntro, "=I"),
+            (Rule::EqualsElim, "=E"),
+            (Rule::ForallIntro, "∀I"),
+            (Rule::ForallElim, "∀E"),
+            (Rule::ExistsIntro, "∃I"),
+            (Rule::ExistsElim, "∃E"),
+            (Rule::Reit, "R"),
+            (Rule::BoxIntro, "□I"),
+            (Rule::BoxElim, "□E"),
+            (Rule::DiamondIntro, "◇I"),
+            (Rule::DiamondElim, "◇E"),
+        ];
+        RuleSet {
+            allowed: Rule::ALL
+                .into_iter()
+                .filter(|r| {
+                    !matches!(r, Rule::NotElim | Rule::TautCon | Rule::FoCon | Rule::AnaCon)
+                })
+                .collect(),
+            names: names.into_iter().map(|(r, name)| (r, name.to_owned())).collect(),
+            derived_rules: true,
+            intuitionistic: false,
+            modal_system: ModalSystem::K,
+        }
+    }
+
+    /// Returns the built-in profile with the given name ("lpl" or "forallx"), if there is one.
+    /// "lpl-intuitionistic" and "forallx-intuitionistic" give these profiles in intuitionistic
+    /// mode.
+    pub fn from_name(name: &str) -> Option<RuleSet> {
+        let (profile, intuitionistic) = match name.strip_suffix("-intuitionistic") {
+            Some(profile) => (profile, true),
+            None => (name, false),
+        };
+        let rule_set = match profile {
+            "lpl" => RuleSet::lpl(),
+            "forallx" => RuleSet::forallx(),
+            _ => return None,
+        };
+        Some(rule_set.with_intuitionistic_logic(intuitionistic))
+    }
+
+    /// Allows a rule. For a derived rule, derived rules must be enabled as well.
+    pub fn allow(mut self, rule: Rule) -> RuleSet {
+        self.allowed.insert(rule);
+        self
+    }
+
+    /// Forbids a rule.
+    pub fn forbid(mut self, rule: Rule) -> RuleSet {
+        self.allowed.remove(&rule);
+        self
+    }
+
+    /// Gives a rule another name.
+    pub fn rename(mut self, rule: Rule, name: &str) -> RuleSet {
+        self.names.insert(rule, name.to_owned());
+        self
+    }
+
+    /// Enables or disables all derived rules.
+    pub fn with_derived_rules(mut self, derived_rules: bool) -> RuleSet {
+        self.derived_rules = derived_rules;
+        self
+    }
+
+    /// Enables or disables intuitionistic mode. In this mode, every step that relies on a
+    /// principle of classical logic is rejected: ¬ Elim, DNE, IP and LEM, and the classical
+    /// directions of DeM and CQ. The consequence rules, which check classical consequence, are not
+    /// available at all.
+    pub fn with_intuitionistic_logic(mut self, intuitionistic: bool) -> RuleSet {
+        self.intuitionistic = intuitionistic;
+        self
+    }
+
+    /// Sets the modal system that strict subproofs are checked in. The default is K.
+    pub fn with_modal_system(mut self, modal_system: ModalSystem) -> RuleSet {
+        self.modal_system = modal_system;
+        self
+    }
+
+    /// The modal system that strict subproofs are checked in.
+    pub fn modal_system(&self) -> ModalSystem {
+        self.modal_system
+    }
+
+    /// Whether steps that are only classically valid are rejected.
+    pub fn is_intuitionistic(&self) -> bool {
+        self.intuitionistic
+    }
+
+    /// Whether a rule may be used.
+    pub fn is_allowed(&self, rule: Rule) -> bool {
+        self.allowed.contains(&rule) && (self.derived_rules || !rule.is_derived())
+    }
+
+    /// The name of a rule in this rule set.
+    pub fn name(&self, rule: Rule) -> &str {
+        self.names.get(&rule).map_or(rule.name(), |name| name.as_str())
+    }
+}
+
+impl Default for RuleSet {
+    /// The LPL rules, which are what the checker has always used.
+    fn default() -> RuleSet {
+        RuleSet::lpl()
+    }
+}
@@ -0,0 +1,477 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// A propositional formula over numbered variables. This is what the first-order searches in
+/// [crate::fol] translate their problems to, before handing them to [satisfy].
+///
+/// The empty conjunction `And(vec![])` is "true" and the empty disjunction `Or(vec![])` is
+/// "false". Use [Prop::and], [Prop::or] and [Prop::not] to build formulas; they simplify these
+/// constants away, which keeps the formulas small.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Prop {
+    Var(usize),
+    Not(Box<Prop>),
+    And(Vec<Prop>),
+    Or(Vec<Prop>),
+}
+
+impl Prop {
+    pub fn t() -> Prop {
+        Prop::And(vec![])
+    }
+
+    pub fn f() -> Prop {
+        Prop::Or(vec![])
+    }
+
+    pub fn is_true(&self) -> bool {
+        matches!(self, Prop::And(li) if li.is_empty())
+    }
+
+    pub fn is_false(&self) -> bool {
+        matches!(self, Prop::Or(li) if li.is_empty())
+    }
+
+    pub fn not(p: Prop) -> Prop {
+        match p {
+            p if p.is_true() => Prop::f(),
+            p if p.is_false() => Prop::t(),
+            Prop::Not(p) => *p,
+            p => Prop::Not(Box::new(p)),
+        }
+    }
+
+    pub fn and(ps: impl IntoIterator<Item = Prop>) -> Prop {
+        let mut conjuncts = vec![];
+        for p in ps {
+            match p {
+                p if p.is_false() => return Prop::f(),
+                Prop::And(li) => conjuncts.extend(li),
+                p => conjuncts.push(p),
+            }
+        }
+        if conjuncts.len() == 1 {
+            conjuncts.pop().unwrap()
+        } else {
+            Prop::And(conjuncts)
+        }
+    }
+
+    pub fn or(ps: impl IntoIterator<Item = Prop>) -> Prop {
+        let mut disjuncts = vec![];
+        for p in ps {
+            match p {
+                p if p.is_true() => return Prop::t(),
+                Prop::Or(li) => disjuncts.extend(li),
+                p => disjuncts.push(p),
+            }
+        }
+        if disjuncts.len() == 1 {
+            disjuncts.pop().unwrap()
+        } else {
+            Prop::Or(disjuncts)
+        }
+    }
+
+    pub fn implies(p: Prop, q: Prop) -> Prop {
+        Prop::or([Prop::not(p), q])
+    }
+
+    pub fn iff(p: Prop, q: Prop) -> Prop {
+        Prop::and([Prop::implies(p.clone(), q.clone()), Prop::implies(q, p)])
+    }
+}
+
+/// The outcome of [satisfy].
+#[derive(Debug, PartialEq)]
+pub enum SatResult {
+    /// The formulas are satisfiable; this is a satisfying assignment of the variables.
+    Sat(Vec<bool>),
+    /// The formulas are not satisfiable.
+    Unsat,
+    /// The search was given up because it took too many steps.
+    Unknown,
+}
+
+/// Decides whether the conjunction of `formulas` is satisfiable. All variables in the formulas must
+/// be smaller than `num_vars`.
+///
+/// The formulas are turned into clauses (with the Tseitin transformation), which are then searched
+/// by a CDCL procedure (DPLL with unit propagation and clause learning). The search gives up after
+/// `max_conflicts` conflicts.
+pub fn satisfy(formulas: &[Prop], num_vars: usize, max_conflicts: usize) -> SatResult {
+    let mut cnf = Cnf {
+        num_vars,
+        clauses: vec![],
+    };
+    for formula in formulas {
+        cnf.add_formula(formula);
+    }
+    match Cdcl::new(cnf).solve(max_conflicts) {
+        SatResult::Sat(mut assignment) => {
+            assignment.truncate(num_vars);
+            SatResult::Sat(assignment)
+        }
+        res => res,
+    }
+}
+
+/* ------------------ PRIVATE -------------------- */
+
+/// A literal: variable `v` is `2 * v` and its negation is `2 * v + 1`.
+type Lit = usize;
+
+fn neg(lit: Lit) -> Lit {
+    lit ^ 1
+}
+
+/// The value of a literal, given the values of the variables.
+fn lit_value(values: &[Option<bool>], lit: Lit) -> Option<bool> {
+    values[lit / 2].map(|v| v == (lit & 1 == 0))
+}
+
+/// A formula in conjunctive normal form.
+struct Cnf {
+    num_vars: usize,
+    clauses: Vec<Vec<Lit>>,
+}
+
+impl Cnf {
+    fn new_var(&mut self) -> usize {
+        self.num_vars += 1;
+        self.num_vars - 1
+    }
+
+    /// Adds clauses that force `formula` to be true.
+    fn add_formula(&mut self, formula: &Prop) {
+        match formula {
+            Prop::And(li) => li.iter().for_each(|p| self.add_formula(p)),
+            Prop::Or(li) => {
+                let clause = li.iter().map(|p| self.literal_for(p)).collect();
+                self.clauses.push(clause);
+            }
+            p => {
+                let lit = self.literal_for(p);
+                self.clauses.push(vec![lit]);
+            }
+        }
+    }
+
+    /// Returns a literal that is equivalent to `formula`, adding the clauses that define it.
+    fn literal_for(&mut self, formula: &Prop) -> Lit {
+        match formula {
+            Prop::Var(v) => 2 * v,
+            Prop::Not(p) => neg(self.literal_for(p)),
+            Prop::And(li) | Prop::Or(li) => {
+                let is_and = matches!(formula, Prop::And(_));
+                let lits: Vec<Lit> = li.iter().map(|p| self.literal_for(p)).collect();
+                let x = 2 * self.new_var();
+                // for "and": x → l (for each l), and (all l) → x; "or" is the dual
+                let (x, lits): (Lit, Vec<Lit>) = if is_and {
+                    (x, lits)
+                } else {
+                    (neg(x), lits.into_iter().map(neg).collect())
+                };
+                for &l in &lits {
+                    self.clauses.push(vec![neg(x), l]);
+                }
+                self.clauses.push(std::iter::once(x).chain(lits.into_iter().map(neg)).collect());
+                if is_and {
+                    x
+                } else {
+                    neg(x)
+                }
+            }
+        }
+    }
+}
+
+/// The state of a CDCL search: a DPLL search with two watched literals per clause, which learns a
+/// new clause from each conflict and then jumps back to the decision that caused it.
+struct Cdcl {
+    clauses: Vec<Vec<Lit>>,
+    /// For each literal, the clauses in which it is one of the two watched literals (which are
+    /// always the first two literals of a clause).
+    watches: Vec<Vec<usize>>,
+    /// The value of each variable, if it has one.
+    values: Vec<Option<bool>>,
+    /// For each variable with a value, the decision level at which it got its value.
+    levels: Vec<usize>,
+    /// For each variable with a value, the clause that forced its value (which has the true
+    /// literal first), or [None] if the value was decided or given by a unit clause.
+    reasons: Vec<Option<usize>>,
+    /// The literals that are true, in the order in which they became true.
+    trail: Vec<Lit>,
+    /// For each decision level, the length of the trail before its decision was made.
+    trail_lims: Vec<usize>,
+    /// The index in the trail up to which unit propagation has been done.
+    propagated: usize,
+    /// How much each variable took part in recent conflicts. The most active variable is decided
+    /// on first.
+    activity: Vec<f64>,
+    /// The amount by which an activity is increased; it grows, so recent conflicts count more.
+    bump: f64,
+    /// The variables without a value, ordered by activity (and then by number). There may be
+    /// outdated entries.
+    queue: BinaryHeap<(u64, Reverse<usize>)>,
+    /// Clauses that are unit or empty from the start.
+    units: Vec<Vec<Lit>>,
+    /// For each variable, whether [Cdcl::analyze] has seen it already. This is all `false`
+    /// outside of [Cdcl::analyze].
+    seen: Vec<bool>,
+}
+
+impl Cdcl {
+    fn new(cnf: Cnf) -> Cdcl {
+        let mut cdcl = Cdcl {
+            clauses: vec![],
+            watches: vec![vec![]; 2 * cnf.num_vars],
+            values: vec![None; cnf.num_vars],
+            levels: vec![0; cnf.num_vars],
+            reasons: vec![None; cnf.num_vars],
+            trail: vec![],
+            trail_lims: vec![],
+            propagated: 0,
+            activity: vec![0.0; cnf.num_vars],
+            bump: 1.0,
+            queue: (0..cnf.num_vars).map(|v| (0, Reverse(v))).collect(),
+            units: vec![],
+            seen: vec![false; cnf.num_vars],
+        };
+        for mut clause in cnf.clauses {
+            clause.sort_unstable();
+            clause.dedup();
+            if clause.windows(2).any(|w| w[0] == neg(w[1])) {
+                continue; // a tautological clause
+            }
+            if clause.len() < 2 {
+                cdcl.units.push(clause);
+            } else {
+                cdcl.add_clause(clause);
+            }
+        }
+        cdcl
+    }
+
+    fn add_clause(&mut self, clause: Vec<Lit>) -> usize {
+        self.watches[clause[0]].push(self.clauses.len());
+        self.watches[clause[1]].push(self.clauses.len());
+        self.clauses.push(clause);
+        self.clauses.len() - 1
+    }
+
+    fn value(&self, lit: Lit) -> Option<bool> {
+        lit_value(&self.values, lit)
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        self.values[lit / 2] = Some(lit & 1 == 0);
+        self.levels[lit / 2] = self.trail_lims.len();
+        self.reasons[lit / 2] = reason;
+        self.trail.push(lit);
+    }
+
+    /// Makes all unit clauses true. Returns the clause that became false if there is a conflict.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let false_lit = neg(self.trail[self.propagated]);
+            self.propagated += 1;
+            let watching = std::mem::take(&mut self.watches[false_lit]);
+            let mut i = 0;
+            while i < watching.len() {
+                let c = watching[i];
+                i += 1;
+                let clause = &mut self.clauses[c];
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                let other = clause[0];
+                if lit_value(&self.values, other) == Some(true) {
+                    self.watches[false_lit].push(c);
+                    continue;
+                }
+                // look for a new literal to watch
+                let new_watch =
+                    (2..clause.len()).find(|&j| lit_value(&self.values, clause[j]) != Some(false));
+                if let Some(j) = new_watch {
+                    clause.swap(1, j);
+                    self.watches[clause[1]].push(c);
+                    continue;
+                }
+                self.watches[false_lit].push(c);
+                match self.value(other) {
+                    None => self.assign(other, Some(c)),
+                    Some(false) => {
+                        // conflict; keep the remaining watches
+                        self.watches[false_lit].extend_from_slice(&watching[i..]);
+                        return Some(c);
+                    }
+                    Some(true) => unreachable!(),
+                }
+            }
+        }
+        None
+    }
+
+    fn bump_activity(&mut self, var: usize) {
+        self.activity[var] += self.bump;
+        if self.activity[var] > 1e100 {
+            self.activity.iter_mut().for_each(|a| *a *= 1e-100);
+            self.bump *= 1e-100;
+            self.queue = self
+                .queue
+                .iter()
+                .map(|&(_, Reverse(v))| (self.activity[v].to_bits(), Reverse(v)))
+                .collect();
+        }
+        // the bits of a non-negative float are ordered like the float itself
+        self.queue.push((self.activity[var].to_bits(), Reverse(var)));
+    }
+
+    /// Learns a clause from a conflict: a clause that follows from the clauses, is false now, and
+    /// has exactly one literal of the current decision level (the first one). This is the clause
+    /// at the "first unique implication point".
+    fn analyze(&mut self, conflict: usize) -> Vec<Lit> {
+        let level = self.trail_lims.len();
+        let mut learnt = vec![0];
+        let mut seen_vars = vec![];
+        // the number of seen literals of the current level that have not been resolved away
+        let mut open = 0;
+        let mut clause = conflict;
+        let mut index = self.trail.len();
+        loop {
+            // the first literal of a reason clause is the one that it forced
+            let skip = usize::from(clause != conflict);
+            for j in skip..self.clauses[clause].len() {
+                let lit = self.clauses[clause][j];
+                let var = lit / 2;
+                if !self.seen[var] && self.levels[var] > 0 {
+                    self.seen[var] = true;
+                    seen_vars.push(var);
+                    self.bump_activity(var);
+                    if self.levels[var] == level {
+                        open += 1;
+                    } else {
+                        learnt.push(lit);
+                    }
+                }
+            }
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index] / 2] {
+                    break;
+                }
+            }
+            let lit = self.trail[index];
+            open -= 1;
+            if open == 0 {
+                learnt[0] = neg(lit);
+                break;
+            }
+            clause = self.reasons[lit / 2].unwrap();
+        }
+        seen_vars.into_iter().for_each(|var| self.seen[var] = false);
+        self.bump *= 1.05;
+        learnt
+    }
+
+    /// Undoes all assignments of the decision levels above `level`.
+    fn backjump(&mut self, level: usize) {
+        let trail_len = self.trail_lims[level];
+        self.trail_lims.truncate(level);
+        for lit in self.trail.drain(trail_len..) {
+            self.values[lit / 2] = None;
+            self.queue.push((self.activity[lit / 2].to_bits(), Reverse(lit / 2)));
+        }
+        self.propagated = trail_len;
+    }
+
+    fn solve(mut self, max_conflicts: usize) -> SatResult {
+        for unit in std::mem::take(&mut self.units) {
+            match unit.first() {
+                None => return SatResult::Unsat,
+                Some(&lit) => match self.value(lit) {
+                    Some(false) => return SatResult::Unsat,
+                    Some(true) => {}
+                    None => self.assign(lit, None),
+                },
+            }
+        }
+        let mut conflicts = 0;
+        loop {
+            if let Some(conflict) = self.propagate() {
+                conflicts += 1;
+                if self.trail_lims.is_empty() {
+                    return SatResult::Unsat;
+                }
+                if conflicts > max_conflicts {
+                    return SatResult::Unknown;
+                }
+                let mut learnt = self.analyze(conflict);
+                // jump back to the highest level at which the learnt clause forces its first literal
+                let second = (1..learnt.len()).max_by_key(|&j| self.levels[learnt[j] / 2]);
+                match second {
+                    None => {
+                        self.backjump(0);
+                        self.assign(learnt[0], None);
+                    }
+                    Some(j) => {
+                        learnt.swap(1, j);
+                        self.backjump(self.levels[learnt[1] / 2]);
+                        let lit = learnt[0];
+                        let c = self.add_clause(learnt);
+                        self.assign(lit, Some(c));
+                    }
+                }
+                continue;
+            }
+            let next = std::iter::from_fn(|| self.queue.pop())
+                .map(|(_, Reverse(var))| var)
+                .find(|&var| self.values[var].is_none());
+            match next {
+                None => return SatResult::Sat(self.values.iter().map(|v| v.unwrap()).collect()),
+                Some(var) => {
+                    self.trail_lims.push(self.trail.len());
+                    // try "false" first: most atoms are false in a small model
+                    self.assign(2 * var + 1, None);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_satisfy() {
+        let (a, b) = (Prop::Var(0), Prop::Var(1));
+        assert_eq!(
+            satisfy(&[Prop::or([a.clone(), b.clone()]), Prop::not(a.clone())], 2, 100),
+            SatResult::Sat(vec![false, true])
+        );
+        assert_eq!(
+            satisfy(&[Prop::iff(a.clone(), Prop::not(b.clone())), Prop::iff(a, b)], 2, 100),
+            SatResult::Unsat
+        );
+        assert_eq!(satisfy(&[Prop::f()], 0, 100), SatResult::Unsat);
+        assert_eq!(satisfy(&[Prop::t()], 0, 100), SatResult::Sat(vec![]));
+    }
+
+    #[test]
+    fn test_pigeonhole() {
+        // 4 pigeons do not fit in 3 holes; var 3 * p + h means pigeon p is in hole h
+        let var = |p: usize, h: usize| Prop::Var(3 * p + h);
+        let mut formulas: Vec<Prop> = (0..4).map(|p| Prop::or((0..3).map(|h| var(p, h)))).collect();
+        for h in 0..3 {
+            for p1 in 0..4 {
+                for p2 in p1 + 1..4 {
+                    formulas.push(Prop::not(Prop::and([var(p1, h), var(p2, h)])));
+                }
+            }
+        }
+        assert_eq!(satisfy(&formulas, 12, 10000), SatResult::Unsat);
+        assert_eq!(satisfy(&formulas, 12, 1), SatResult::Unknown);
+    }
+}
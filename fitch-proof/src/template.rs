@@ -0,0 +1,586 @@
+use crate::checker::contains_modal_operator;
+use crate::data::*;
+use crate::fol::{self, FolResult};
+use crate::formatter::{format_term, format_wff};
+use crate::truth_table::{self, TruthTableResult};
+use std::iter::zip;
+
+/// How strictly the premises and the conclusion of a proof are compared to those of its template.
+/// By default, they must be exactly the same, in the same order. This can be relaxed, like this:
+/// ```notrust
+/// TemplateMatching::strict().with_alpha_equivalence(true).with_any_premise_order(true)
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TemplateMatching {
+    /// Whether sentences match if they only differ in the names of bound variables, like `∀y P(y)`
+    /// and `∀x P(x)`.
+    alpha_equivalence: bool,
+    /// Whether the premises of the proof may be in another order than in the template.
+    any_premise_order: bool,
+    /// Whether premises of the template may be left out of the proof.
+    omitted_premises: bool,
+}
+
+impl TemplateMatching {
+    /// Sentences must be exactly the same, and the premises must be in the same order.
+    pub fn strict() -> TemplateMatching {
+        TemplateMatching::default()
+    }
+
+    /// All relaxations are allowed.
+    pub fn lenient() -> TemplateMatching {
+        TemplateMatching {
+            alpha_equivalence: true,
+            any_premise_order: true,
+            omitted_premises: true,
+        }
+    }
+
+    /// Returns the policy with the given name: "strict", "lenient", or a comma-separated list of
+    /// the relaxations "alpha-equivalence", "any-premise-order" and "omitted-premises". Returns
+    /// [None] if one of the names is unknown.
+    pub fn from_names(names: &str) -> Option<TemplateMatching> {
+        match names.trim() {
+            "strict" | "" => return Some(TemplateMatching::strict()),
+            "lenient" => return Some(TemplateMatching::lenient()),
+            _ => {}
+        }
+        let mut matching = TemplateMatching::strict();
+        for name in names.split(',').map(str::trim) {
+            match name {
+                "alpha-equivalence" => matching.alpha_equivalence = true,
+                "any-premise-order" => matching.any_premise_order = true,
+                "omitted-premises" => matching.omitted_premises = true,
+                _ => return None,
+            }
+        }
+        Some(matching)
+    }
+
+    /// Allows or disallows sentences that only differ in the names of bound variables.
+    pub fn with_alpha_equivalence(mut self, alpha_equivalence: bool) -> TemplateMatching {
+        self.alpha_equivalence = alpha_equivalence;
+        self
+    }
+
+    /// Allows or disallows premises in another order than in the template.
+    pub fn with_any_premise_order(mut self, any_premise_order: bool) -> TemplateMatching {
+        self.any_premise_order = any_premise_order;
+        self
+    }
+
+    /// Allows or disallows leaving premises of the template out of the proof. A proof that does
+    /// not have a premise cannot use it, so this is only useful for templates with premises that
+    /// are not needed.
+    pub fn with_omitted_premises(mut self, omitted_premises: bool) -> TemplateMatching {
+        self.omitted_premises = omitted_premises;
+        self
+    }
+
+    /// Whether a sentence of a proof matches a sentence of a template. If it does, returns
+    /// whether bound variables had to be renamed for that.
+    pub(crate) fn sentences_match(&self, in_proof: &Wff, in_template: &Wff) -> Option<bool> {
+        if in_proof == in_template {
+            Some(false)
+        } else if self.alpha_equivalence && alpha_equivalent(in_proof, in_template) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// Matches every premise of a proof (with its line number) to its own premise of the
+    /// template, as far as the policy allows. If that works, the relaxations that were needed are
+    /// returned: the lines of the premises that only match after renaming bound variables, and
+    /// possibly [TemplateRelaxation::PremiseOrder] and [TemplateRelaxation::OmittedPremises].
+    pub(crate) fn match_premises(
+        &self,
+        in_proof: &[(usize, Wff)],
+        in_template: &[Wff],
+    ) -> Option<(Vec<usize>, Vec<TemplateRelaxation>)> {
+        // the index of the template premise that each premise of the proof is matched to
+        let mut matched: Vec<usize> = vec![];
+        let mut renamed: Vec<usize> = vec![];
+        for (n, wff) in in_proof {
+            // Since matching is an equivalence relation, it is fine to take the first premise of
+            // the template that matches. With any order, any premise that is still free can be
+            // taken; otherwise, only those after the one that was taken last.
+            let first_free = if self.any_premise_order {
+                0
+            } else {
+                matched.last().map_or(0, |&i| i + 1)
+            };
+            let free = || (first_free..in_template.len()).filter(|i| !matched.contains(i));
+            // a premise that is exactly the same is preferred
+            match free().find(|&i| in_template[i] == *wff) {
+                Some(i) => matched.push(i),
+                None => {
+                    let i =
+                        free().find(|&i| self.sentences_match(wff, &in_template[i]).is_some())?;
+                    matched.push(i);
+                    renamed.push(*n);
+                }
+            }
+        }
+
+        let mut relaxations = vec![];
+        if matched.windows(2).any(|w| w[0] > w[1]) {
+            relaxations.push(TemplateRelaxation::PremiseOrder);
+        }
+        if matched.len() < in_template.len() {
+            if !self.omitted_premises {
+                return None;
+            }
+            relaxations.push(TemplateRelaxation::OmittedPremises(
+                (0..in_template.len())
+                    .filter(|i| !matched.contains(i))
+                    .map(|i| format_wff(&in_template[i]))
+                    .collect(),
+            ));
+        }
+        Some((renamed, relaxations))
+    }
+}
+
+/// Decides whether the conclusion of a proof template (its last sentence) follows from its
+/// premises (the other sentences), that is, whether the exercise can be done at all.
+///
+/// First, a truth table is made. If that shows that the template is valid, it is. If the template
+/// is propositional, the truth table decides it either way. Otherwise, a template with
+/// quantifiers, predicates or identity is decided by [fol::fo_consequence], which looks for a
+/// proof and for a counter-model with at most [fol::MAX_DOMAIN_SIZE] objects. A template with □
+/// or ◇ that is not valid by truth tables alone cannot be decided.
+///
+/// PRECONDITION (panics otherwise): !template.is_empty()
+pub fn template_validity(template: &[Wff]) -> TemplateValidity {
+    let (conclusion, premises) = template.split_last().unwrap();
+    let premises: Vec<&Wff> = premises.iter().collect();
+    let truth_table_result = truth_table::tautological_consequence(&premises, conclusion);
+    if truth_table_result == TruthTableResult::Consequence {
+        return TemplateValidity::Valid;
+    }
+    if template.iter().any(contains_modal_operator) {
+        return TemplateValidity::Undecided(
+            "the template contains □ or ◇, and its conclusion does not follow from its premises \
+            by truth tables alone, so whether it is valid depends on the modal system."
+                .to_owned(),
+        );
+    }
+    if !template.iter().any(is_first_order) {
+        return match truth_table_result {
+            TruthTableResult::Counterexample(valuation) => TemplateValidity::Invalid(format!(
+                "the premises are true, but the conclusion is false, {}.",
+                truth_table::describe_valuation(&valuation)
+            )),
+            TruthTableResult::TooManyAtoms(n) => TemplateValidity::Undecided(format!(
+                "the template contains {n} different atomic sentences, which is too many to \
+                build a truth table for (the maximum is {}).",
+                truth_table::MAX_ATOMS
+            )),
+            TruthTableResult::Consequence => unreachable!(),
+        };
+    }
+
+    let mut signature = fol::Signature::default();
+    template.iter().for_each(|wff| signature.add_symbols_of(wff));
+    if let Some(name) = symbol_with_several_arities(&signature) {
+        return TemplateValidity::Undecided(format!(
+            "the template uses {name} with different numbers of arguments."
+        ));
+    }
+    match fol::fo_consequence(&premises, conclusion, &signature) {
+        FolResult::Consequence => TemplateValidity::Valid,
+        FolResult::Counterexample(model) => TemplateValidity::Invalid(format!(
+            "in this model, the premises are true, but the conclusion is false: {model}."
+        )),
+        FolResult::Undecided => TemplateValidity::Undecided(format!(
+            "no counter-model with at most {} objects was found, but no proof either.",
+            fol::MAX_DOMAIN_SIZE
+        )),
+    }
+}
+
+/// Returns whether a [Wff] contains a quantifier, a predicate with arguments or an identity.
+fn is_first_order(wff: &Wff) -> bool {
+    match wff {
+        Wff::Forall(..) | Wff::Exists(..) | Wff::PredApp(..) | Wff::Equals(..) => true,
+        Wff::Bottom | Wff::Atomic(_) => false,
+        Wff::And(li) | Wff::Or(li) => li.iter().any(is_first_order),
+        Wff::Implies(w1, w2) | Wff::Bicond(w1, w2) => is_first_order(w1) || is_first_order(w2),
+        Wff::Not(w) | Wff::Necessarily(w) | Wff::Possibly(w) => is_first_order(w),
+    }
+}
+
+/// Returns a name that occurs in a [fol::Signature] with more than one arity (or both as a
+/// constant and as a function symbol), if there is one.
+fn symbol_with_several_arities(signature: &fol::Signature) -> Option<&str> {
+    let mut names: Vec<&str> = signature
+        .constants
+        .iter()
+        .map(|c| c.as_str())
+        .chain(signature.functions.iter().map(|(f, _)| f.as_str()))
+        .chain(signature.predicates.iter().map(|(p, _)| p.as_str()))
+        .collect();
+    names.sort();
+    names.windows(2).find(|w| w[0] == w[1]).map(|w| w[0])
+}
+
+/// Compares the premises of a proof (with their line numbers) to the premises of its template,
+/// and returns a [Diagnostic] for every difference.
+///
+/// If the proof has the same premises in another order, one diagnostic says so. Otherwise, a
+/// premise of the template that is not in the proof and a premise of the proof that is not in the
+/// template are taken to be the same premise written differently, in the order in which they
+/// occur, and the diagnostic explains how they differ (see [explain_difference]). What is left
+/// over is reported as missing or extra.
+pub(crate) fn compare_premises(in_proof: &[(usize, Wff)], in_template: &[Wff]) -> Vec<Diagnostic> {
+    let proof_wffs: Vec<&Wff> = in_proof.iter().map(|(_, wff)| wff).collect();
+    if proof_wffs.iter().copied().eq(in_template) {
+        return vec![];
+    }
+    if is_permutation(&proof_wffs, in_template) {
+        return vec![Diagnostic::error(
+            ErrorCode::TemplatePremises,
+            None,
+            format!(
+                "Your proof has the same premises as the proof template, but in another order. \
+                The template has them in this order: {}.",
+                in_template
+                    .iter()
+                    .map(|wff| format!("'{}'", format_wff(wff)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+        .with_related_lines(in_proof.iter().map(|(n, _)| *n).collect())];
+    }
+
+    let extra: Vec<&(usize, Wff)> =
+        in_proof.iter().filter(|(_, wff)| !in_template.contains(wff)).collect();
+    let missing: Vec<&Wff> = in_template.iter().filter(|wff| !proof_wffs.contains(wff)).collect();
+    let mut diagnostics: Vec<Diagnostic> = extra
+        .iter()
+        .zip(&missing)
+        .map(|((n, wff), template_wff)| {
+            Diagnostic::error(
+                ErrorCode::TemplatePremises,
+                Some(*n),
+                format!(
+                    "the premise '{}' does not match the premise '{}' of the proof template{}.",
+                    format_wff(wff),
+                    format_wff(template_wff),
+                    explain_difference(wff, template_wff)
+                        .map(|e| format!("; {e}"))
+                        .unwrap_or_default()
+                ),
+            )
+        })
+        .collect();
+    diagnostics.extend(missing.iter().skip(extra.len()).map(|wff| {
+        Diagnostic::error(
+            ErrorCode::TemplatePremises,
+            None,
+            format!(
+                "The premise '{}' of the proof template is missing from your proof.",
+                format_wff(wff)
+            ),
+        )
+    }));
+    diagnostics.extend(extra.iter().skip(missing.len()).map(|(n, wff)| {
+        Diagnostic::error(
+            ErrorCode::TemplatePremises,
+            Some(*n),
+            format!("the premise '{}' is not in the proof template.", format_wff(wff)),
+        )
+    }));
+    if diagnostics.is_empty() {
+        // the premises are the same, but some occur more often in one than in the other
+        diagnostics.push(Diagnostic::error(
+            ErrorCode::TemplatePremises,
+            None,
+            "The premises of your proof do not match the premises in the proof template: some \
+            premise occurs more often in one than in the other."
+                .to_owned(),
+        ));
+    }
+    diagnostics
+}
+
+/// Explains how a sentence in a proof differs from the sentence in the template that it should
+/// have been, as a clause like "they differ in 'a', where the template has 'b'". If the sentences
+/// only differ in how the parentheses group a conjunction or disjunction (like `A ∧ B ∧ C` and
+/// `(A ∧ B) ∧ C`, which are parsed differently), the explanation says so.
+///
+/// Returns [None] if there is nothing to add to the two sentences themselves: they differ as a
+/// whole.
+///
+/// PRECONDITION: `in_proof != in_template`
+pub(crate) fn explain_difference(in_proof: &Wff, in_template: &Wff) -> Option<String> {
+    let (part_in_proof, part_in_template) =
+        differing_parts(Part::Sentence(in_proof), Part::Sentence(in_template));
+    if let (Part::Sentence(a), Part::Sentence(b)) = (part_in_proof, part_in_template) {
+        if ungrouped(a) == ungrouped(b) {
+            let (a_str, b_str) = (format_wff(a), format_wff(b));
+            return Some(match (a, b) {
+                (Wff::And(x), Wff::And(y)) | (Wff::Or(x), Wff::Or(y)) if x.len() != y.len() => {
+                    let connective = if matches!(a, Wff::And(_)) {
+                        "conjunction"
+                    } else {
+                        "disjunction"
+                    };
+                    format!(
+                        "'{a_str}' is a {connective} of {} sentences and '{b_str}' is a \
+                        {connective} of {}: they only differ in the parentheses, but that makes \
+                        them different sentences",
+                        x.len(),
+                        y.len()
+                    )
+                }
+                _ => {
+                    let grouped = match a {
+                        Wff::And(_) => "the conjuncts",
+                        Wff::Or(_) => "the disjuncts",
+                        _ => "conjunctions and disjunctions",
+                    };
+                    format!(
+                        "'{a_str}' and '{b_str}' only differ in how the parentheses group \
+                        {grouped}, but that makes them different sentences"
+                    )
+                }
+            });
+        }
+        if a == in_proof {
+            return None;
+        }
+    }
+    Some(format!(
+        "they differ in '{}', where the template has '{}'",
+        part_in_proof.format(),
+        part_in_template.format()
+    ))
+}
+
+/// A part of a sentence: a subformula or a term.
+#[derive(PartialEq, Clone, Copy)]
+enum Part<'a> {
+    Sentence(&'a Wff),
+    Term(&'a Term),
+}
+
+impl Part<'_> {
+    fn format(&self) -> String {
+        match self {
+            Part::Sentence(wff) => format_wff(wff),
+            Part::Term(term) => format_term(term),
+        }
+    }
+}
+
+/// Returns the smallest corresponding parts of `a` and `b` that contain all their differences:
+/// as long as `a` and `b` have the same shape and differ in exactly one immediate part, that part
+/// is looked at instead.
+fn differing_parts<'a>(mut a: Part<'a>, mut b: Part<'a>) -> (Part<'a>, Part<'a>) {
+    while let Some(parts) = immediate_parts(a, b) {
+        let mut differing = parts.into_iter().filter(|(x, y)| x != y);
+        match (differing.next(), differing.next()) {
+            (Some((x, y)), None) => (a, b) = (x, y),
+            _ => break,
+        }
+    }
+    (a, b)
+}
+
+/// If `a` and `b` have the same shape (the same connective, quantified variable, predicate or
+/// function, with as many arguments), returns their immediate parts in pairs.
+fn immediate_parts<'a>(a: Part<'a>, b: Part<'a>) -> Option<Vec<(Part<'a>, Part<'a>)>> {
+    fn pairs<'a, T>(
+        x: &'a [T],
+        y: &'a [T],
+        part: fn(&'a T) -> Part<'a>,
+    ) -> Option<Vec<(Part<'a>, Part<'a>)>> {
+        (x.len() == y.len()).then(|| x.iter().zip(y).map(|(x, y)| (part(x), part(y))).collect())
+    }
+    let sentence = |x: &'a Wff, y: &'a Wff| Some(vec![(Part::Sentence(x), Part::Sentence(y))]);
+    match (a, b) {
+        (Part::Sentence(a), Part::Sentence(b)) => match (a, b) {
+            (Wff::And(x), Wff::And(y)) | (Wff::Or(x), Wff::Or(y)) => pairs(x, y, Part::Sentence),
+            (Wff::Implies(x1, x2), Wff::Implies(y1, y2))
+            | (Wff::Bicond(x1, x2), Wff::Bicond(y1, y2)) => Some(vec![
+                (Part::Sentence(x1), Part::Sentence(y1)),
+                (Part::Sentence(x2), Part::Sentence(y2)),
+            ]),
+            (Wff::Not(x), Wff::Not(y))
+            | (Wff::Necessarily(x), Wff::Necessarily(y))
+            | (Wff::Possibly(x), Wff::Possibly(y)) => sentence(x, y),
+            (Wff::Forall(v, x), Wff::Forall(w, y)) | (Wff::Exists(v, x), Wff::Exists(w, y))
+                if v == w =>
+            {
+                sentence(x, y)
+            }
+            (Wff::PredApp(p, x), Wff::PredApp(q, y)) if p == q => pairs(x, y, Part::Term),
+            (Wff::Equals(x1, x2), Wff::Equals(y1, y2)) => {
+                Some(vec![(Part::Term(x1), Part::Term(y1)), (Part::Term(x2), Part::Term(y2))])
+            }
+            _ => None,
+        },
+        (Part::Term(Term::FuncApp(f, x)), Part::Term(Term::FuncApp(g, y))) if f == g => {
+            pairs(x, y, Part::Term)
+        }
+        _ => None,
+    }
+}
+
+/// Returns `wff` with every conjunction inside a conjunction (and disjunction inside a
+/// disjunction) merged into it, so that all ways to group them with parentheses look the same.
+fn ungrouped(wff: &Wff) -> Wff {
+    fn merge(li: &[Wff], unwrap: fn(&Wff) -> Option<&Vec<Wff>>) -> Vec<Wff> {
+        li.iter()
+            .map(ungrouped)
+            .flat_map(|w| match unwrap(&w) {
+                Some(inner) => inner.clone(),
+                None => vec![w],
+            })
+            .collect()
+    }
+    match wff {
+        Wff::And(li) => Wff::And(merge(li, |w| match w {
+            Wff::And(inner) => Some(inner),
+            _ => None,
+        })),
+        Wff::Or(li) => Wff::Or(merge(li, |w| match w {
+            Wff::Or(inner) => Some(inner),
+            _ => None,
+        })),
+        Wff::Implies(w1, w2) => Wff::Implies(Box::new(ungrouped(w1)), Box::new(ungrouped(w2))),
+        Wff::Bicond(w1, w2) => Wff::Bicond(Box::new(ungrouped(w1)), Box::new(ungrouped(w2))),
+        Wff::Not(w) => Wff::Not(Box::new(ungrouped(w))),
+        Wff::Necessarily(w) => Wff::Necessarily(Box::new(ungrouped(w))),
+        Wff::Possibly(w) => Wff::Possibly(Box::new(ungrouped(w))),
+        Wff::Forall(v, w) => Wff::Forall(v.clone(), Box::new(ungrouped(w))),
+        Wff::Exists(v, w) => Wff::Exists(v.clone(), Box::new(ungrouped(w))),
+        Wff::Bottom | Wff::Atomic(_) | Wff::PredApp(..) | Wff::Equals(..) => wff.clone(),
+    }
+}
+
+/// Returns whether `a` and `b` contain the same sentences, equally often.
+fn is_permutation(a: &[&Wff], b: &[Wff]) -> bool {
+    a.len() == b.len()
+        && a.iter().all(|wff| {
+            a.iter().filter(|w| w == &wff).count() == b.iter().filter(|&w| w == *wff).count()
+        })
+}
+
+/// Returns whether two sentences are the same up to the names of their bound variables, like
+/// `∀x ∃y R(x,y)` and `∀y ∃z R(y,z)`.
+fn alpha_equivalent(a: &Wff, b: &Wff) -> bool {
+    /// The pairs of variables that are bound at the same place in both sentences, innermost last.
+    type Bindings<'a> = Vec<(&'a str, &'a str)>;
+
+    fn terms<'a>(a: &'a Term, b: &'a Term, bound: &Bindings<'a>) -> bool {
+        match (a, b) {
+            (Term::Atomic(x), Term::Atomic(y)) => {
+                let binder_of_x = bound.iter().rposition(|(v, _)| v == x);
+                let binder_of_y = bound.iter().rposition(|(_, w)| w == y);
+                binder_of_x == binder_of_y && (binder_of_x.is_some() || x == y)
+            }
+            (Term::FuncApp(f, xs), Term::FuncApp(g, ys)) => {
+                f == g && xs.len() == ys.len() && zip(xs, ys).all(|(x, y)| terms(x, y, bound))
+            }
+            _ => false,
+        }
+    }
+
+    fn wffs<'a>(a: &'a Wff, b: &'a Wff, bound: &mut Bindings<'a>) -> bool {
+        match (a, b) {
+            (Wff::And(xs), Wff::And(ys)) | (Wff::Or(xs), Wff::Or(ys)) => {
+                xs.len() == ys.len() && zip(xs, ys).all(|(x, y)| wffs(x, y, bound))
+            }
+            (Wff::Implies(x1, x2), Wff::Implies(y1, y2))
+            | (Wff::Bicond(x1, x2), Wff::Bicond(y1, y2)) => {
+                wffs(x1, y1, bound) && wffs(x2, y2, bound)
+            }
+            (Wff::Not(x), Wff::Not(y))
+            | (Wff::Necessarily(x), Wff::Necessarily(y))
+            | (Wff::Possibly(x), Wff::Possibly(y)) => wffs(x, y, bound),
+            (Wff::Forall(v, x), Wff::Forall(w, y)) | (Wff::Exists(v, x), Wff::Exists(w, y)) => {
+                bound.push((v, w));
+                let result = wffs(x, y, bound);
+                bound.pop();
+                result
+            }
+            (Wff::PredApp(p, xs), Wff::PredApp(q, ys)) => {
+                p == q && xs.len() == ys.len() && zip(xs, ys).all(|(x, y)| terms(x, y, bound))
+            }
+            (Wff::Equals(x1, x2), Wff::Equals(y1, y2)) => {
+                terms(x1, y1, bound) && terms(x2, y2, bound)
+            }
+            (Wff::Bottom, Wff::Bottom) => true,
+            (Wff::Atomic(p), Wff::Atomic(q)) => p == q,
+            _ => false,
+        }
+    }
+
+    wffs(a, b, &mut vec![])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_logical_expression_string;
+
+    fn wff(s: &str) -> Wff {
+        parse_logical_expression_string(s).unwrap()
+    }
+
+    #[test]
+    fn test_alpha_equivalent() {
+        assert!(alpha_equivalent(&wff("∀x P(x)"), &wff("∀y P(y)")));
+        assert!(alpha_equivalent(&wff("∀x ∃y R(x,y)"), &wff("∀y ∃x R(y,x)")));
+        assert!(alpha_equivalent(
+            &wff("∀x (P(x) ∧ ∃x Q(x,f(x)))"),
+            &wff("∀y (P(y) ∧ ∃z Q(z,f(z)))")
+        ));
+        // the variables must be bound at the same place
+        assert!(!alpha_equivalent(&wff("∀x ∃y R(x,y)"), &wff("∀x ∃y R(y,x)")));
+        assert!(!alpha_equivalent(&wff("∀x ∃x R(x,x)"), &wff("∀x ∃y R(x,y)")));
+        // names that are not bound cannot be renamed
+        assert!(!alpha_equivalent(&wff("P(a)"), &wff("P(b)")));
+        assert!(!alpha_equivalent(&wff("∀x P(x)"), &wff("∃x P(x)")));
+    }
+
+    #[test]
+    fn test_match_premises() {
+        let premises = [(1, wff("Q")), (2, wff("∀y P(y)"))];
+        let template = [wff("∀x P(x)"), wff("R"), wff("Q")];
+        assert_eq!(TemplateMatching::strict().match_premises(&premises, &template), None);
+        assert_eq!(
+            TemplateMatching::lenient().match_premises(&premises, &template),
+            Some((
+                vec![2],
+                vec![
+                    TemplateRelaxation::PremiseOrder,
+                    TemplateRelaxation::OmittedPremises(vec!["R".to_owned()])
+                ]
+            ))
+        );
+        let in_order = TemplateMatching::lenient().with_any_premise_order(false);
+        assert_eq!(in_order.match_premises(&premises, &template), None);
+        assert_eq!(
+            in_order.match_premises(&premises[1..], &template),
+            Some((
+                vec![2],
+                vec![TemplateRelaxation::OmittedPremises(vec!["R".to_owned(), "Q".to_owned()])]
+            ))
+        );
+        assert_eq!(TemplateMatching::from_names("lenient"), Some(TemplateMatching::lenient()));
+        assert_eq!(
+            TemplateMatching::from_names("any-premise-order, alpha-equivalence"),
+            Some(
+                TemplateMatching::strict()
+                    .with_any_premise_order(true)
+                    .with_alpha_equivalence(true)
+            )
+        );
+        assert_eq!(TemplateMatching::from_names("alpha"), None);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::data::*;
+use crate::formatter;
+
+/// The maximum number of atoms for which a truth table will be built. A truth table for `n`
+/// atoms has `2^n` rows.
+pub const MAX_ATOMS: usize = 20;
+
+/// The outcome of [tautological_consequence].
+#[derive(Debug, PartialEq)]
+pub enum TruthTableResult {
+    /// The conclusion is a tautological consequence of the premises.
+    Consequence,
+    /// The conclusion is not a tautological consequence of the premises. This valuation of the
+    /// atoms makes all premises true and the conclusion false.
+    Counterexample(Vec<(Wff, bool)>),
+    /// There are too many atoms (more than [MAX_ATOMS]) to build the truth table. The number of
+    /// atoms is included.
+    TooManyAtoms(usize),
+}
+
+/// Decides whether `conclusion` is a tautological consequence of `premises`, i.e. whether every
+/// row of the truth table that makes all premises true also makes the conclusion true.
+///
+/// Only the truth-functional connectives (∧, ∨, ¬, →, ↔ and ⊥) are looked at. Every other
+/// subformula (an atomic proposition, a predicate application, an identity, a quantified
+/// sentence or a sentence that starts with □ or ◇) is treated as an atom. So `∀x P(x) → ∀x P(x)` is a tautology, but `∀x P(x) → P(a)` is
+/// not.
+pub fn tautological_consequence(premises: &[&Wff], conclusion: &Wff) -> TruthTableResult {
+    let mut atoms: Vec<Wff> = vec![];
+    for wff in premises.iter().copied().chain([conclusion]) {
+        collect_atoms(wff, &mut atoms);
+    }
+    if atoms.len() > MAX_ATOMS {
+        return TruthTableResult::TooManyAtoms(atoms.len());
+    }
+
+    for row in 0..1usize << atoms.len() {
+        // the first row makes all atoms true, like in a textbook truth table
+        let valuation: Vec<bool> = (0..atoms.len()).map(|i| row >> i & 1 == 0).collect();
+        if premises.iter().all(|p| evaluate(p, &atoms, &valuation))
+            && !evaluate(conclusion, &atoms, &valuation)
+        {
+            return TruthTableResult::Counterexample(atoms.into_iter().zip(valuation).collect());
+        }
+    }
+    TruthTableResult::Consequence
+}
+
+/// Describes a row of a truth table in words, like "when A is true, P(a) is false and B is true".
+pub fn describe_valuation(valuation: &[(Wff, bool)]) -> String {
+    let parts: Vec<String> = valuation
+        .iter()
+        .map(|(atom, value)| format!("{} is {value}", formatter::format_wff(atom)))
+        .collect();
+    match parts.split_last() {
+        Some((last, [])) => format!("when {last}"),
+        Some((last, rest)) => format!("when {} and {last}", rest.join(", ")),
+        None => "in every row of the truth table".to_string(),
+    }
+}
+
+/* ------------------ PRIVATE -------------------- */
+
+/// Adds the atoms of a [Wff] (see [tautological_consequence]) to `atoms`, in order of first
+/// appearance and without duplicates.
+fn collect_atoms(wff: &Wff, atoms: &mut Vec<Wff>) {
+    match wff {
+        Wff::Bottom => {}
+        Wff::And(li) | Wff::Or(li) => li.iter().for_each(|w| collect_atoms(w, atoms)),
+        Wff::Not(w) => collect_atoms(w, atoms),
+        Wff::Implies(w1, w2) | Wff::Bicond(w1, w2) => {
+            collect_atoms(w1, atoms);
+            collect_atoms(w2, atoms);
+        }
+        _ => {
+            if !atoms.contains(wff) {
+                atoms.push(wff.clone());
+            }
+        }
+    }
+}
+
+/// Computes the truth value of a [Wff], given a truth value for each of its atoms.
+///
+/// PRECONDITION (panics otherwise): all atoms of `wff` are in `atoms`.
+fn evaluate(wff: &Wff, atoms: &[Wff], valuation: &[bool]) -> bool {
+    match wff {
+        Wff::Bottom => false,
+        Wff::And(li) => li.iter().all(|w| evaluate(w, atoms, valuation)),
+        Wff::Or(li) => li.iter().any(|w| evaluate(w, atoms, valuation)),
+        Wff::Not(w) => !evaluate(w, atoms, valuation),
+        Wff::Implies(w1, w2) => !evaluate(w1, atoms, valuation) || evaluate(w2, atoms, valuation),
+        Wff::Bicond(w1, w2) => evaluate(w1, atoms, valuation) == evaluate(w2, atoms, valuation),
+        _ => valuation[atoms.iter().position(|a| a == wff).unwrap()],
+    }
+}
@@ -2595,3 +2595,1172 @@ fn test_exists_elim_10(){
 ";
     assert!(proof_is_not_correct_ultra_pedantic(proof));
 }
+#[test]
+fn test_diagnostic_ref_closed_subproof() {
+    let proof = "
+1 | A
+  |----
+2 | | B
+  | |----
+3 | | A ∧ B                  ∧ Intro: 1, 2
+4 | B                        Reit: 2
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert!(errs.iter().any(|d| d.code == fitch_proof::ErrorCode::RefClosedSubproof
+        && d.line == Some(4)
+        && d.related_lines == vec![2]
+        && d.rule == Some(fitch_proof::Rule::Reit)));
+}
+#[test]
+fn test_diagnostic_wrong_connective() {
+    let proof = "
+1 | A ∨ B
+  |----
+2 | A                        ∧ Elim: 1
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::WrongConnective);
+    assert_eq!(errs[0].code.as_str(), "E_WRONG_CONNECTIVE");
+    assert_eq!(errs[0].severity, fitch_proof::Severity::Error);
+    assert_eq!(errs[0].line, Some(2));
+    assert_eq!(errs[0].related_lines, vec![1]);
+}
+#[test]
+fn test_diagnostic_arity_mentions_lines() {
+    let proof = "
+1 | P(a)
+2 | P(a, b)
+  |----
+3 | P(a)                     Reit: 1
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    let arity_err = errs.iter().find(|d| d.code == fitch_proof::ErrorCode::ArityMismatch).unwrap();
+    assert_eq!(arity_err.line, None);
+    assert_eq!(arity_err.related_lines, vec![1, 2, 3]);
+}
+#[test]
+fn test_diagnostic_fatal_structure() {
+    let proof = "
+1 | A
+  |----
+2 | | | A                    Reit: 1
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::FatalError(err) = res else { panic!("expected a fatal error") };
+    assert_eq!(err.severity, fitch_proof::Severity::Fatal);
+    assert_eq!(err.code, fitch_proof::ErrorCode::Structure);
+}
+#[test]
+fn test_parse_error_does_not_hide_other_errors() {
+    let proof = "
+1 | A ∧ B
+  |----
+2 | A ∧∧ B                   Reit: 1
+3 | C                        ∧ Elim: 1
+4 | A                        ∧ Elim: 2
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    // line 4 cites the unparsed line 2, so it cannot be checked
+    assert_eq!(errs.len(), 2);
+    assert!(errs.iter().any(|d| d.code == fitch_proof::ErrorCode::Parser
+        && d.severity == fitch_proof::Severity::Error
+        && d.location.as_ref().unwrap().line == 4));
+    assert!(errs.iter().any(|d| d.line == Some(3) && d.rule == Some(fitch_proof::Rule::AndElim)));
+}
+#[test]
+fn test_unparsed_line_inside_subproof() {
+    let proof = "
+1 | A
+  |----
+2 | | B $
+  | |----
+3 | | A                      Reit: 1
+4 | B → A                    → Intro: 2-3
+5 | A                        Reit: 7
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert_eq!(errs.len(), 2);
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::RefNonexistent);
+    assert_eq!(errs[1].code, fitch_proof::ErrorCode::Lexer);
+}
+#[test]
+fn test_broken_structure_reports_parse_error() {
+    let proof = "
+1 | A
+  |----
+  | | A Reit:1
+2 | A                        Reit: 1
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::FatalError(err) = res else { panic!("expected a fatal error") };
+    assert_eq!(err.code, fitch_proof::ErrorCode::Parser);
+}
+#[test]
+fn test_ascii_proof() {
+    let proof = "
+1 | A v B
+2 | ~A
+  |----
+3 | | A
+  | |----
+4 | | _|_                    _|_ Intro: 3, 2
+5 | | B                      _|_ Elim: 4
+  |
+6 | | B
+  | |----
+7 | | B                      Reit: 6
+8 | B                        v Elim: 1, 3-5, 6-7
+9 | Ax (B -> B) | B          | Intro: 8
+";
+    assert!(proof_is_correct_ultra_pedantic(proof));
+    // the formatter normalises everything to Unicode
+    assert_eq!(
+        fitch_proof::format_proof(proof),
+        "1 | A ∨ B
+2 | ¬A
+  |----
+3 | | A
+  | |----
+4 | | ⊥                    ⊥ Intro: 3, 2
+5 | | B                    ⊥ Elim: 4
+  |
+6 | | B
+  | |----
+7 | | B                    Reit: 6
+8 | B                      ∨ Elim: 1, 3-5, 6-7
+9 | ∀x (B → B) ∨ B         ∨ Intro: 8"
+    );
+}
+#[test]
+fn test_comments() {
+    let proof = "
+# commutativity of conjunction
+1 | A ∧ B          // the only premise
+  |----
+2 | A    ∧ Elim: 1
+  | # now the other conjunct
+3 | B    ∧ Elim: 1   # see line 1
+// put them back together
+4 | B ∧ A   ∧ Intro: 3, 2
+";
+    assert!(fitch_proof::proof_is_correct(proof));
+    // a comment is not part of the sentence
+    assert!(!fitch_proof::proof_is_correct("1 | A # ∧ B\n  |----\n2 | A ∧ B    ∧ Intro: 1, 1"));
+    let formatted = "# commutativity of conjunction
+1 | A ∧ B                          // the only premise
+  |----
+2 | A             ∧ Elim: 1
+  |                                # now the other conjunct
+3 | B             ∧ Elim: 1        # see line 1
+// put them back together
+4 | B ∧ A         ∧ Intro: 3, 2";
+    assert_eq!(fitch_proof::format_proof(proof), formatted);
+    assert_eq!(
+        fitch_proof::fix_line_numbers_in_proof(&proof.replace("3 |", "7 |").replace("3, 2", "7, 2")),
+        formatted
+    );
+    assert_eq!(
+        fitch_proof::export_to_latex(proof),
+        "$
+\\begin{nd}
+% commutativity of conjunction
+\\hypo{1}{A\\land B} % the only premise
+\\have{2}{A}\\ae{1}
+% now the other conjunct
+\\have{3}{B}\\ae{1} % see line 1
+% put them back together
+\\have{4}{B\\land A}\\ai{3,2}
+\\end{nd}
+$"
+    );
+}
+#[test]
+fn test_names_with_digits_underscores_and_primes() {
+    let proof = "
+1 | ∀x' (P2(x') → R_1(f1(x'), c'))
+2 | P2(a_1)
+  |----
+3 | P2(a_1) → R_1(f1(a_1), c')       ∀ Elim: 1
+4 | R_1(f1(a_1), c')                 → Elim: 3, 2
+5 | ∃y2 R_1(f1(a_1), y2)             ∃ Intro: 4
+";
+    assert!(fitch_proof::check_proof(proof, "x',y2") == "The proof is correct!");
+    // x' is not a variable here, so it cannot be quantified over
+    assert!(fitch_proof::check_proof(proof, "x,y2") != "The proof is correct!");
+    // arity is still checked per name
+    assert!(proof_is_not_correct_ultra_pedantic("1 | P2(a1) ∧ P2\n  |----\n2 | P2      ∧ Elim: 1"));
+}
+#[test]
+fn test_taut_con() {
+    assert!(proof_is_correct_ultra_pedantic("
+1 | A → B
+2 | ¬B
+  |----
+3 | ¬A                       Taut Con: 1, 2
+4 | A ∨ ¬A                   Taut Con:
+5 | ∀x P(x) ∨ ¬∀x P(x)       Taut Con:
+6 | ¬A ∧ (C → C)             Taut Con: 3
+"));
+    assert!(proof_is_not_correct_ultra_pedantic("
+1 | A → B
+2 | B
+  |----
+3 | A                        Taut Con: 1, 2
+"));
+    // quantified sentences are atoms, so this needs ∀ Elim
+    assert!(proof_is_not_correct_ultra_pedantic("
+1 | ∀x P(x)
+  |----
+2 | P(a)                     Taut Con: 1
+"));
+}
+#[test]
+fn test_taut_con_counterexample() {
+    let proof = "
+1 | A → B
+2 | B
+  |----
+3 | A                        Taut Con: 1, 2
+4 | A ∧ ¬A                   Taut Con:
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert_eq!(errs.len(), 2);
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::NotConsequence);
+    assert_eq!(errs[0].related_lines, vec![1, 2]);
+    assert_eq!(
+        errs[0].to_string(),
+        "Line 3: the rule Taut Con is used, but the sentence in this line is not a tautological \
+        consequence of the referenced lines: when A is false and B is true, all referenced lines \
+        are true, but the sentence in this line is false."
+    );
+    assert_eq!(
+        errs[1].to_string(),
+        "Line 4: the rule Taut Con is used without referenced lines, but the sentence in this line \
+        is not a tautology: it is false when A is true."
+    );
+}
+#[test]
+fn test_fo_con() {
+    assert!(proof_is_correct_ultra_pedantic("
+1 | ∀x (P(x) → Q(x))
+2 | P(a)
+  |----
+3 | Q(a)                     FO Con: 1, 2
+4 | ∃y (Q(y) ∧ a = a)        FO Con: 3
+5 | ¬∀x ¬Q(x)                FO Con: 4
+6 | ∃x (P(x) → ∀y P(y))      FO Con:
+"));
+    assert!(proof_is_correct_ultra_pedantic("
+1 | b = a
+2 | P(f(a), a)
+  |----
+3 | P(f(b), b)               FO Con: 1, 2
+"));
+}
+#[test]
+fn test_fo_con_counter_model() {
+    let proof = "
+1 | ∀x ∃y R(x, y)
+  |----
+2 | ∃y ∀x R(x, y)            FO Con: 1
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::NotConsequence);
+    assert!(errs[0].message.contains("domain {0, 1}"));
+}
+#[test]
+fn test_fo_con_undecided() {
+    // every model of lines 1 and 2 is infinite, so there is no counter-model to find
+    let proof = "
+1 | ∀x ∀y (s(x) = s(y) → x = y)
+2 | ∀x ¬(s(x) = c)
+  |----
+3 | P                        FO Con: 1, 2
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::Undecided);
+    assert_eq!(errs[0].code.as_str(), "E_UNDECIDED");
+}
+#[test]
+fn test_ana_con() {
+    assert!(proof_is_correct_ultra_pedantic("
+1 | Cube(a)
+2 | Larger(a, b)
+  |----
+3 | ¬Tet(a) ∧ Smaller(b, a)     Ana Con: 1, 2
+4 | ¬(a = b)                    Ana Con: 2
+"));
+    // every Ana Con step searches through many worlds, so this one is not checked pedantically
+    assert!(fitch_proof::proof_is_correct("
+1 | ∀x (Cube(x) → Large(x))
+2 | Between(c, a, b)
+  |----
+3 | Cube(a) → ¬Small(a)         Ana Con: 1
+4 | ¬SameRow(a, b) ∨ SameRow(a, c)  Ana Con: 2
+5 | ¬∃x Larger(x, x)            Ana Con:
+"));
+}
+#[test]
+fn test_ana_con_counter_world() {
+    let proof = "
+1 | SameRow(a, b)
+2 | Cube(a)
+  |----
+3 | Adjoins(a, b)               Ana Con: 1, 2
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::NotConsequence);
+    assert!(errs[0].message.contains("in this world"));
+    assert!(errs[0].message.contains("block 0 is a cube in column"));
+}
+#[test]
+fn test_line_with_only_intro_does_not_panic() {
+    for line in ["  Intro", "I", "2 | Intro"] {
+        let proof = format!("1 | P\n  |---\n{line}\n3 | P   Reit: 1");
+        assert!(!fitch_proof::proof_is_correct(&proof));
+        fitch_proof::check_proof(&proof, "x,y,z");
+        fitch_proof::format_proof(&proof);
+        fitch_proof::fix_line_numbers_in_proof(&proof);
+        fitch_proof::export_to_latex(&proof);
+        fitch_proof::hint(&proof, vec![], "x,y,z", 1);
+        fitch_proof::infer_justifications(&proof, "x,y,z");
+        fitch_proof::proven_sequent(&proof, vec![], "x,y,z");
+        fitch_proof::source_line_numbers(&proof);
+    }
+}
+#[test]
+fn test_ana_con_with_more_than_four_blocks() {
+    // a consistent premise does not prove ⊥, even if it takes five blocks to see that
+    let proof = "
+1 | LeftOf(a, b) ∧ LeftOf(b, c) ∧ LeftOf(c, d) ∧ LeftOf(d, e)
+  |----
+2 | ⊥                           Ana Con: 1
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::NotConsequence);
+
+    let proof = "
+  |----
+1 | ¬∃x ∃y ∃z ∃u ∃w (LeftOf(x, y) ∧ LeftOf(y, z) ∧ LeftOf(z, u) ∧ LeftOf(u, w))   Ana Con:
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::NotConsequence);
+
+    // when a counterexample may need more blocks than are searched, the step is not accepted
+    let proof = "
+1 | ∀x ∃y LeftOf(x, y)
+  |----
+2 | ⊥                           Ana Con: 1
+";
+    let res = fitch_proof::check_proof_to_proofresult(proof, &fitch_proof::CheckOptions::default()).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::Undecided);
+}
+fn check_forallx(proof: &str) -> fitch_proof::ProofResult {
+    let options = fitch_proof::CheckOptions::default().with_rule_set(fitch_proof::RuleSet::forallx());
+    fitch_proof::check_proof_to_proofresult(proof, &options).0
+}
+#[test]
+fn test_forallx_derived_rules() {
+    assert_eq!(
+        check_forallx(
+            "
+1 | P ∨ Q ∨ R
+2 | ¬Q
+3 | S → P
+4 | ¬P
+  |----
+5 | P ∨ R                DS: 1, 2
+6 | ¬S                   MT: 4, 3
+7 | | ¬¬S
+  | |----
+8 | | S                  DNE: 7
+9 | | ⊥                  ¬E: 8, 6
+10 | ¬¬¬S                ¬I: 7-9
+11 | | ¬(P ∧ S)
+   | |----
+12 | | ¬P ∨ ¬S           DeM: 11
+13 | | ¬P ∨ ¬S           R: 12
+   |
+14 | | P ∧ S
+   | |----
+15 | | ¬P ∨ ¬S           ∨I: 6
+16 | ¬P ∨ ¬S             LEM: 14-15, 11-13
+17 | | ¬R
+   | |----
+18 | | P                 DS: 5, 17
+19 | | ⊥                 ¬E: 18, 4
+20 | R                   IP: 17-19
+"
+        ),
+        fitch_proof::ProofResult::Correct
+    );
+    assert_eq!(
+        check_forallx(
+            "
+1 | ¬∃x P(x)
+2 | ∃x ¬Q(x)
+  |----
+3 | ∀x ¬P(x)             CQ: 1
+4 | ¬∀x Q(x)             CQ: 2
+5 | ¬∃x ¬¬P(x)           CQ: 3
+"
+        ),
+        fitch_proof::ProofResult::Correct
+    );
+}
+#[test]
+fn test_forallx_derived_rules_misapplied() {
+    for (proof, code) in [
+        ("1 | P ∨ Q\n2 | ¬R\n  |----\n3 | Q    DS: 1, 2", fitch_proof::ErrorCode::RuleMisapplied),
+        ("1 | P ∧ Q\n2 | ¬P\n  |----\n3 | Q    DS: 1, 2", fitch_proof::ErrorCode::WrongConnective),
+        ("1 | P → Q\n2 | ¬P\n  |----\n3 | ¬Q    MT: 1, 2", fitch_proof::ErrorCode::RuleMisapplied),
+        ("1 | P\n  |----\n2 | ¬¬P    DNE: 1", fitch_proof::ErrorCode::WrongDirection),
+        ("1 | ¬(P ∧ Q)\n  |----\n2 | ¬P ∧ ¬Q    DeM: 1", fitch_proof::ErrorCode::RuleMisapplied),
+        ("1 | ∀x P(x)\n  |----\n2 | ¬∃x P(x)    CQ: 1", fitch_proof::ErrorCode::RuleMisapplied),
+        ("1 | ¬P\n  |----\n2 | | P\n  | |----\n3 | | ⊥    ¬E: 2, 1\n4 | ¬P    IP: 2-3", fitch_proof::ErrorCode::RuleMisapplied),
+    ] {
+        let fitch_proof::ProofResult::Error(errs) = check_forallx(proof) else {
+            panic!("expected an error in {proof}")
+        };
+        assert_eq!(errs[0].code, code, "{proof}");
+    }
+}
+#[test]
+fn test_rule_not_allowed() {
+    let fitch_proof::ProofResult::Error(errs) =
+        check_forallx("1 | P\n2 | Q\n  |----\n3 | P ∧ Q    Taut Con: 1, 2")
+    else {
+        panic!("expected an error")
+    };
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::RuleNotAllowed);
+    assert_eq!(errs[0].message, "rule Taut Con is not allowed in this exercise.");
+
+    // DS is not an LPL rule, and forall x calls ⊥ Intro ¬E
+    assert!(proof_is_not_correct_ultra_pedantic("1 | P ∨ Q\n2 | ¬P\n  |----\n3 | Q    DS: 1, 2"));
+    let options = fitch_proof::CheckOptions::default()
+        .with_allowed_variable_names("x")
+        .with_rule_set(fitch_proof::RuleSet::forallx().forbid(fitch_proof::Rule::BottomIntro));
+    let res = fitch_proof::check_proof_to_proofresult(
+        "1 | P\n2 | ¬P\n  |----\n3 | ⊥    ⊥ Intro: 1, 2\n4 | Q    ⊥ Elim: 3",
+        &options,
+    )
+    .0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].message, "rule ¬E is not allowed in this exercise.");
+    assert!(fitch_proof::check_proof_with_rule_set("1 | P", "x", "calgary")
+        .contains("there is no rule set called 'calgary'"));
+}
+#[test]
+fn test_intuitionistic() {
+    let intuitionistic = fitch_proof::RuleSet::lpl().with_intuitionistic_logic(true);
+    let options = fitch_proof::CheckOptions::default().with_rule_set(intuitionistic);
+    let check = |proof: &str| fitch_proof::check_proof_to_proofresult(proof, &options).0;
+    // constructive steps are fine
+    assert_eq!(
+        check(
+            "
+1 | P → Q
+2 | ¬Q
+  |----
+3 | | P
+  | |----
+4 | | Q                  → Elim: 1, 3
+5 | | ⊥                  ⊥ Intro: 4, 2
+6 | ¬P                   ¬ Intro: 3-5
+7 | | ¬¬P
+  | |----
+8 | | ⊥                  ⊥ Intro: 6, 7
+9 | ¬¬¬P                 ¬ Intro: 7-8
+"
+        ),
+        fitch_proof::ProofResult::Correct
+    );
+    let fitch_proof::ProofResult::Error(errs) = check("1 | ¬¬P\n  |----\n2 | P    ¬ Elim: 1") else {
+        panic!("expected an error")
+    };
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::ClassicalStep);
+    assert!(errs[0].message.contains("double negation elimination"));
+    // the consequence rules are not available at all, even for steps that are valid
+    // intuitionistically
+    for proof in ["  |----\n1 | P ∨ ¬P    Taut Con:", "1 | A ∧ B\n  |----\n2 | A    Taut Con: 1"] {
+        let fitch_proof::ProofResult::Error(errs) = check(proof) else {
+            panic!("expected an error in {proof}")
+        };
+        assert_eq!(errs[0].code, fitch_proof::ErrorCode::RuleNotAllowed, "{proof}");
+        assert!(errs[0].message.contains("Taut Con is not available in intuitionistic mode"), "{proof}");
+    }
+
+    // the derived rules of forall x, in both directions
+    let forallx = fitch_proof::RuleSet::from_name("forallx-intuitionistic").unwrap();
+    let options = fitch_proof::CheckOptions::default().with_rule_set(forallx);
+    let check = |proof: &str| fitch_proof::check_proof_to_proofresult(proof, &options).0;
+    assert_eq!(
+        check("1 | ¬(P ∨ Q)\n2 | ¬∃x F(x)\n  |----\n3 | ¬P ∧ ¬Q    DeM: 1\n4 | ∀x ¬F(x)    CQ: 2"),
+        fitch_proof::ProofResult::Correct
+    );
+    for proof in [
+        "1 | ¬(P ∧ Q)\n  |----\n2 | ¬P ∨ ¬Q    DeM: 1",
+        "1 | ¬∀x F(x)\n  |----\n2 | ∃x ¬F(x)    CQ: 1",
+        "1 | ¬∃x ¬F(x)\n  |----\n2 | ∀x F(x)    CQ: 1",
+        "1 | ¬∀x ¬F(x)\n  |----\n2 | ∃x F(x)    CQ: 1",
+        "1 | ¬P\n  |----\n2 | | ¬Q\n  | |----\n3 | | ⊥    ¬E: 1, 1\n4 | Q    IP: 2-3",
+    ] {
+        let fitch_proof::ProofResult::Error(errs) = check(proof) else {
+            panic!("expected an error in {proof}")
+        };
+        assert_eq!(errs.last().unwrap().code, fitch_proof::ErrorCode::ClassicalStep, "{proof}");
+    }
+}
+#[test]
+fn test_intuitionistic_with_template() {
+    let options = fitch_proof::CheckOptions::default()
+        .with_template(vec!["¬¬P".to_owned(), "Q".to_owned()])
+        .with_allowed_variable_names("x")
+        .with_rule_set(fitch_proof::RuleSet::lpl().with_intuitionistic_logic(true));
+    let res = fitch_proof::check_proof_to_proofresult("1 | ¬¬P\n  |----\n2 | P    ¬ Elim: 1", &options).0;
+    let fitch_proof::ProofResult::Error(errs) = res else { panic!("expected an error") };
+    let codes: Vec<_> = errs.iter().map(|e| e.code).collect();
+    assert!(codes.contains(&fitch_proof::ErrorCode::ClassicalStep));
+    assert!(codes.contains(&fitch_proof::ErrorCode::TemplateConclusion));
+}
+
+fn check_modal(proof: &str, modal_system: fitch_proof::ModalSystem) -> fitch_proof::ProofResult {
+    let options = fitch_proof::CheckOptions::default()
+        .with_rule_set(fitch_proof::RuleSet::lpl().with_modal_system(modal_system));
+    fitch_proof::check_proof_to_proofresult(proof, &options).0
+}
+fn modal_error_code(
+    proof: &str,
+    modal_system: fitch_proof::ModalSystem,
+) -> Option<fitch_proof::ErrorCode> {
+    match check_modal(proof, modal_system) {
+        fitch_proof::ProofResult::Error(errs) => Some(errs[0].code),
+        _ => None,
+    }
+}
+#[test]
+fn test_modal_k() {
+    use fitch_proof::ModalSystem::K;
+    let proof = "
+1 | □(P → Q)
+2 | □P
+  |----
+  | |□----
+3 | | P → Q              □ Elim: 1
+4 | | P                  □ Elim: 2
+5 | | Q                  → Elim: 3, 4
+6 | □Q                   □ Intro: 3-5
+7 | | □¬Q
+  | |----
+8 | | | Q                □ Elim: 7
+  | | |□----
+";
+    // the last strict Fitch bar is misplaced: it can only be the first line of a subproof
+    assert!(matches!(check_modal(proof, K), fitch_proof::ProofResult::FatalError(_)));
+    let proof = &proof[..proof.find("7 |").unwrap()];
+    assert_eq!(check_modal(proof, K), fitch_proof::ProofResult::Correct);
+    let formatted = fitch_proof::format_proof(proof);
+    assert!(formatted.contains("  | |□----\n3 | | P → Q"));
+    assert_eq!(check_modal(&formatted, K), fitch_proof::ProofResult::Correct);
+
+    // Reit cannot bring anything into a strict subproof
+    assert_eq!(
+        modal_error_code("1 | □P\n  |----\n  | |□----\n2 | | □P    Reit: 1\n3 | □□P    □ Intro: 2-2", K),
+        Some(fitch_proof::ErrorCode::StrictSubproof)
+    );
+    // and sentences without □ cannot be brought in at all
+    assert_eq!(
+        modal_error_code("1 | P\n  |----\n  | |□----\n2 | | P    □ Elim: 1\n3 | □P    □ Intro: 2-2", K),
+        Some(fitch_proof::ErrorCode::StrictSubproof)
+    );
+    // a strict subproof can only be cited by □ Intro
+    assert_eq!(
+        modal_error_code(
+            "1 | □P\n  |----\n  | |□----\n2 | | P    □ Elim: 1\n3 | P → P    → Intro: 2-2",
+            K
+        ),
+        Some(fitch_proof::ErrorCode::StrictSubproof)
+    );
+    // □ Intro needs a strict subproof
+    assert_eq!(
+        modal_error_code("  |----\n1 | | P\n  | |----\n2 | | P    Reit: 1\n3 | □P    □ Intro: 1-2", K),
+        Some(fitch_proof::ErrorCode::SubproofKind)
+    );
+    // in K, □A does not give A outside a strict subproof
+    assert_eq!(
+        modal_error_code("1 | □P\n  |----\n2 | P    □ Elim: 1", K),
+        Some(fitch_proof::ErrorCode::StrictSubproof)
+    );
+    assert_eq!(
+        check_modal("1 | ◇P\n  |----\n2 | ¬□¬P    ◇ Elim: 1\n3 | ◇P    ◇ Intro: 2", K),
+        fitch_proof::ProofResult::Correct
+    );
+    // FO Con cannot look inside modal sentences
+    assert_eq!(
+        modal_error_code("1 | □P\n  |----\n2 | □P ∨ Q    FO Con: 1", K),
+        Some(fitch_proof::ErrorCode::ModalConsequence)
+    );
+    assert_eq!(
+        check_modal("1 | □P\n  |----\n2 | □P ∨ Q    Taut Con: 1", K),
+        fitch_proof::ProofResult::Correct
+    );
+}
+#[test]
+fn test_modal_t_s4_s5() {
+    use fitch_proof::ModalSystem::{K, S4, S5, T};
+    let t = "1 | □P\n  |----\n2 | P    □ Elim: 1\n3 | ◇P    ◇ Intro: 2";
+    assert_eq!(check_modal(t, T), fitch_proof::ProofResult::Correct);
+    assert_eq!(check_modal(t, S5), fitch_proof::ProofResult::Correct);
+
+    let s4 = "1 | □P\n  |----\n  | |□----\n2 | | □P    R□: 1\n3 | □□P    □ Intro: 2-2";
+    assert_eq!(check_modal(s4, S4), fitch_proof::ProofResult::Correct);
+    assert_eq!(modal_error_code(s4, T), Some(fitch_proof::ErrorCode::StrictSubproof));
+    assert_eq!(modal_error_code(s4, K), Some(fitch_proof::ErrorCode::StrictSubproof));
+
+    let s5 = "1 | ◇P\n  |----\n  | |□----\n2 | | ◇P    R□: 1\n3 | □◇P    □ Intro: 2-2";
+    assert_eq!(check_modal(s5, S5), fitch_proof::ProofResult::Correct);
+    assert_eq!(modal_error_code(s5, S4), Some(fitch_proof::ErrorCode::StrictSubproof));
+
+    // only the lines right outside a strict subproof can be brought into it
+    let nested = "
+1 | □□P
+  |----
+  | |□----
+2 | | □P                 □ Elim: 1
+  | | |□----
+3 | | | □□P              R□: 1
+4 | | □□P                R□: 1
+";
+    assert_eq!(modal_error_code(nested, S5), Some(fitch_proof::ErrorCode::StrictSubproof));
+}
+
+/// Checks that a proof found by `prove_sequent` is correct, using only the primitive rules.
+fn check_found_proof(premises: &[&str], goal: &str) -> Option<fitch_proof::ProofResult> {
+    let premises: Vec<String> = premises.iter().map(|s| s.to_string()).collect();
+    let proof = fitch_proof::prove_sequent(premises, goal)?;
+    let primitive_rules = fitch_proof::RuleSet::lpl()
+        .forbid(fitch_proof::Rule::TautCon)
+        .forbid(fitch_proof::Rule::FoCon)
+        .forbid(fitch_proof::Rule::AnaCon);
+    let options = fitch_proof::CheckOptions::default().with_rule_set(primitive_rules);
+    Some(fitch_proof::check_proof_to_proofresult(&proof, &options).0)
+}
+#[test]
+fn test_prove_sequent() {
+    let valid: &[(&[&str], &str)] = &[
+        (&["P"], "P"),
+        (&["P ∧ Q"], "Q ∧ P"),
+        (&["P ∨ Q", "¬P"], "Q"),
+        (&["P → Q", "¬Q"], "¬P"),
+        (&[], "P ∨ ¬P"),
+        (&[], "((P → Q) → P) → P"),
+        (&["¬(P ∧ Q)"], "¬P ∨ ¬Q"),
+        (&["¬(P ∨ Q)"], "¬P ∧ ¬Q"),
+        (&["P ∧ (Q ∨ R)"], "(P ∧ Q) ∨ (P ∧ R)"),
+        (&["(P ∨ Q) ∧ (P ∨ R)"], "P ∨ (Q ∧ R)"),
+        (&["P ↔ Q", "Q ↔ R"], "P ↔ R"),
+        (&["¬(P ↔ Q)"], "P ↔ ¬Q"),
+        (&[], "(P → Q) ∨ (Q → P)"),
+        (&["¬(P → Q)"], "P ∧ ¬Q"),
+        (&["P", "¬P"], "Q"),
+        (&["P ∨ Q ∨ R", "¬P", "¬R"], "Q"),
+        (&["¬¬¬P"], "¬P"),
+        (&["Cube(a) → ∀x Small(x)", "Cube(a)"], "∀x Small(x) ∨ a = b"),
+        (&["(P ↔ Q) ↔ R"], "P ↔ (Q ↔ R)"),
+        (&[], "⊥ → P"),
+        (&["P → ⊥"], "¬P"),
+    ];
+    for (premises, goal) in valid {
+        assert_eq!(
+            check_found_proof(premises, goal),
+            Some(fitch_proof::ProofResult::Correct),
+            "{premises:?} ⊢ {goal}"
+        );
+    }
+    for (premises, goal) in [(&["P ∨ Q"][..], "P"), (&[][..], "P → Q"), (&["¬(P ∧ Q)"][..], "¬P")] {
+        assert_eq!(check_found_proof(premises, goal), None);
+    }
+    assert_eq!(fitch_proof::prove_sequent(vec![], "P ∧"), None);
+}
+#[test]
+fn test_prove_random_sequents() {
+    // a small linear congruential generator, so that the test is deterministic
+    let mut seed: u64 = 42;
+    let mut random = move |n: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % n
+    };
+    fn sentence(random: &mut impl FnMut(u64) -> u64, depth: u32) -> String {
+        if depth == 0 || random(4) == 0 {
+            return ["P", "Q", "R"][random(3) as usize].to_string();
+        }
+        let connective = random(5);
+        let mut part = || sentence(random, depth - 1);
+        match connective {
+            0 => format!("¬{}", part()),
+            1 => format!("({} ∧ {})", part(), part()),
+            2 => format!("({} ∨ {})", part(), part()),
+            3 => format!("({} → {})", part(), part()),
+            _ => format!("({} ↔ {})", part(), part()),
+        }
+    }
+    for _ in 0..200 {
+        let premises: Vec<String> = (0..random(3)).map(|_| sentence(&mut random, 3)).collect();
+        let goal = sentence(&mut random, 3);
+        let premise_lines: String =
+            premises.iter().enumerate().map(|(i, p)| format!("{} | {p}\n", i + 1)).collect();
+        let cited = (1..=premises.len()).map(|i| i.to_string()).collect::<Vec<_>>().join(", ");
+        let taut_con_proof = format!(
+            "{premise_lines}  |----\n{} | {goal}    Taut Con: {cited}",
+            premises.len() + 1
+        );
+        let valid = fitch_proof::proof_is_correct(&taut_con_proof);
+        let premises: Vec<&str> = premises.iter().map(|s| s.as_str()).collect();
+        let result = check_found_proof(&premises, &goal);
+        if valid {
+            assert_eq!(result, Some(fitch_proof::ProofResult::Correct), "{premises:?} ⊢ {goal}");
+        } else {
+            assert_eq!(result, None, "{premises:?} ⊢ {goal}");
+        }
+    }
+}
+#[test]
+fn test_validate_template() {
+    use fitch_proof::TemplateValidity;
+    let validity = |template: &[&str]| {
+        let template: Vec<String> = template.iter().map(|s| s.to_string()).collect();
+        fitch_proof::validate_template_to_validity(&template)
+    };
+    assert_eq!(validity(&["P → Q", "P", "Q"]), Ok(TemplateValidity::Valid));
+    assert_eq!(
+        validity(&["P → Q", "Q", "P"]),
+        Ok(TemplateValidity::Invalid(
+            "the premises are true, but the conclusion is false, when P is false and Q is true."
+                .to_string()
+        ))
+    );
+    assert_eq!(validity(&["∀x (Cube(x) → Small(x))", "Cube(a)", "Small(a)"]), Ok(TemplateValidity::Valid));
+    let Ok(TemplateValidity::Invalid(model)) = validity(&["∃x Cube(x)", "Cube(a)"]) else {
+        panic!("expected a counter-model");
+    };
+    assert!(model.starts_with("in this model, the premises are true, but the conclusion is false: domain"));
+    // a tautology is valid, whatever its atoms are
+    assert_eq!(validity(&["□P ∨ ¬□P"]), Ok(TemplateValidity::Valid));
+    assert!(matches!(validity(&["□P", "P"]), Ok(TemplateValidity::Undecided(_))));
+    assert!(matches!(validity(&["P(a)", "P", "Q(b)"]), Ok(TemplateValidity::Undecided(_))));
+
+    let err = validity(&["P", "P ∧"]).unwrap_err();
+    assert_eq!(err.code, fitch_proof::ErrorCode::TemplateUnparsable);
+    assert!(err.message.contains("sentence 2"));
+    assert_eq!(validity(&[]).unwrap_err().code, fitch_proof::ErrorCode::TemplateEmpty);
+    assert!(fitch_proof::validate_template(vec!["P".to_string(), "Q".to_string()])
+        .starts_with("The template is not valid: "));
+}
+
+#[test]
+fn test_hint() {
+    use fitch_proof::{HintLevel, RuleSet};
+    let hint = |proof: &str, template: &[&str], rule_set: &RuleSet| {
+        let template: Vec<String> = template.iter().map(|s| s.to_string()).collect();
+        fitch_proof::hint_for_proof(proof, &template, "x,y,z", rule_set).unwrap()
+    };
+
+    let proof = "1 | P → Q\n2 | Q → R\n  |---\n3 | P → R";
+    let h = hint(proof, &["P → Q", "Q → R", "P → R"], &RuleSet::lpl());
+    assert_eq!(h.at_level(HintLevel::Nudge), "Line 3 has no justification yet. P → R is a conditional; look at its main connective.");
+    assert_eq!(h.at_level(HintLevel::Rule), "Line 3 has no justification yet. To prove P → R, use → Intro.");
+    assert_eq!(h.at_level(HintLevel::Step), "Open a subproof assuming P to prove P → R: derive R in it, and cite the subproof with → Intro. Put the new lines above line 3.");
+
+    let proof = "1 | P → Q\n2 | Q → R\n  |---\n3 | | P\n  | |---\n4 | | Q   → Elim: 1, 3";
+    let h = hint(proof, &["P → Q", "Q → R", "P → R"], &RuleSet::lpl());
+    assert_eq!(h.step, "You can get R from lines 2 and 4 by → Elim: add it as a new line with '→ Elim: 2, 4'.");
+    assert_eq!(h.lines, vec![2, 4]);
+    let proof = "1 | P → Q\n2 | Q → R\n  |---\n3 | | P\n  | |---\n4 | | Q   → Elim: 1, 3\n5 | | R   → Elim: 2, 4";
+    let h = hint(proof, &["P → Q", "Q → R", "P → R"], &RuleSet::lpl());
+    assert_eq!(h.step, "End the subproof after line 5, and cite it (3-5) with → Intro to get P → R.");
+
+    // the hint uses the names of the rule set, and only the rules in it
+    let proof = "1 | ¬(P ∧ Q)\n  |---\n2 | ¬P ∨ ¬Q";
+    let h = hint(proof, &["¬(P ∧ Q)", "¬P ∨ ¬Q"], &RuleSet::forallx());
+    assert_eq!(h.rule, "Line 2 has no justification yet. Prove ¬P ∨ ¬Q indirectly, with IP.");
+    let h = hint(proof, &["¬(P ∧ Q)", "¬P ∨ ¬Q"], &RuleSet::lpl().with_intuitionistic_logic(true));
+    assert!(h.rule.starts_with("Line 2 has no justification yet. Look at the sentences in scope"));
+
+    let proof = "1 | ∃x Cube(x)\n  |---\n2 | ∃y Cube(y)";
+    let h = hint(proof, &["∃x Cube(x)", "∃y Cube(y)"], &RuleSet::lpl());
+    assert_eq!(h.rule, "Line 2 has no justification yet. Line 1 is an existential sentence; consider ∃ Elim.");
+
+    let h = hint("1 | P\n  |---\n2 | P   Reit: 1", &["P", "P"], &RuleSet::lpl());
+    assert_eq!(h.nudge, "The proof is complete: there is nothing left to do.");
+
+    assert_eq!(
+        fitch_proof::hint("1 | P ∧ Q\n  |---\n2 | Q", vec![], "x,y,z", 2),
+        "Line 2 has no justification yet. It follows by ∧ Elim."
+    );
+    assert!(fitch_proof::hint("1 | P", vec![], "x,y,z", 4).starts_with("Fatal error: "));
+    assert!(fitch_proof::hint("1 | P\n  |---\n2 | P", vec!["P ∧".to_string()], "x,y,z", 1)
+        .starts_with("Fatal error: "));
+
+    // a proof that ends with a Fitch bar gets a hint about what to write below it
+    let h = hint("1 | B\n  |---", &["B", "A → B"], &RuleSet::lpl());
+    assert_eq!(h.step, "Open a subproof assuming A to prove A → B: derive B in it, and cite the subproof with → Intro.");
+    let h = hint("1 | A\n  |---\n2 | | B\n  | |---", &["A", "B → A"], &RuleSet::lpl());
+    assert_eq!(h.step, "You can get A from line 1 by Reit: add it as a new line with 'Reit: 1'.");
+    let h = hint("  |---", &["A ∨ ¬A"], &RuleSet::lpl());
+    assert_eq!(h.rule, "Prove A ∨ ¬A indirectly, with ¬ Intro.");
+    let h = hint("1 | A\n  |---", &["A", "A"], &RuleSet::lpl());
+    assert_eq!(h.rule, "You can get A by Reit.");
+    // the checker still does not accept such a proof
+    assert_eq!(fitch_proof::check_proof("1 | A\n  |---", "x,y,z"), "Fatal error: The proof ends with a Fitch bar.");
+
+    // a template whose conclusion does not follow cannot be done
+    let h = hint("1 | A\n  |---\n2 | A   Reit: 1", &["A", "B"], &RuleSet::lpl());
+    assert_eq!(h.step, "The conclusion of the template, B, does not follow from its premises, so it cannot be proved: the premises are true, but the conclusion is false, when A is true and B is false.");
+    assert_eq!(h.nudge, h.step);
+}
+
+#[test]
+fn test_infer_justifications() {
+    let proof = "1 | P → Q\n2 | P\n  |---\n3 | Q\n4 | Q ∧ P   ∧ Elim: 3\n5 | R";
+    assert_eq!(
+        fitch_proof::infer_justifications(proof, "x,y,z"),
+        "Line 3 can be justified with '→ Elim: 1,2'.\nThe justification of line 4 is wrong; '∧ Intro: 3, 2' would be correct.\nNo justification was found for line 5."
+    );
+    let inferred = fitch_proof::infer_justifications_in_proof(proof, "x,y,z", &fitch_proof::RuleSet::forallx()).unwrap();
+    assert_eq!(inferred[0].justification, Some("→ Elim: 1,2".to_string()));
+    assert!(inferred[0].was_missing);
+    assert!(!inferred[1].was_missing);
+    assert_eq!(
+        fitch_proof::infer_justifications("1 | P\n  |---\n2 | P   Reit: 1", "x,y,z"),
+        "Every line already has a correct justification."
+    );
+
+    let proof = "1 | ∀x (P(x) → Q(x))\n2 | P(a)\n  |---\n3 | P(a) → Q(a)\n4 | Q(a)\n5 | ∃x Q(x)";
+    let filled_in = fitch_proof::fill_in_justifications_in_proof(proof, "x,y,z");
+    assert!(filled_in.contains("∀ Elim: 1") && filled_in.contains("→ Elim: 3,2") && filled_in.contains("∃ Intro: 4"));
+    assert!(proof_is_correct_ultra_pedantic(&filled_in));
+    // a proof that cannot be parsed is returned as it is
+    assert_eq!(fitch_proof::fill_in_justifications_in_proof("1 | P ∧", "x,y,z"), "1 | P ∧");
+    assert_eq!(fitch_proof::fill_in_justifications_in_proof("", "x,y,z"), "");
+    // like infer_justifications, it skips the lines that cannot be parsed
+    let proof = "1 | P ∧ Q\n  |---\n2 | P\n3 | Q ∧ $  // later\n4 | Q   ∧ Intro: 1";
+    assert_eq!(
+        fitch_proof::fill_in_justifications_in_proof(proof, "x,y,z"),
+        "1 | P ∧ Q\n  |----\n2 | P             ∧ Elim: 1\n3 | Q ∧ $  // later\n4 | Q             ∧ Elim: 1"
+    );
+    // a line that cannot be parsed is never cited, since nothing is known about its sentence
+    let proof = "1 | A → B\n2 | A\n  |----\n3 | B\n4 | A ∧ B  ∨ Intro: 2, 3\n5 | B ∨ C";
+    assert_eq!(
+        fitch_proof::infer_justifications(proof, "x,y,z"),
+        "Line 3 can be justified with '→ Elim: 1,2'.\nLine 5 can be justified with '∨ Intro: 3'."
+    );
+    assert_eq!(
+        fitch_proof::fill_in_justifications_in_proof(proof, "x,y,z"),
+        "1 | A → B\n2 | A\n  |----\n3 | B             → Elim: 1,2\n4 | A ∧ B  ∨ Intro: 2, 3\n5 | B ∨ C         ∨ Intro: 3"
+    );
+}
+
+#[test]
+fn test_repair_suggestions() {
+    let suggestions = |proof: &str, rule_set: &fitch_proof::RuleSet| {
+        let options = fitch_proof::CheckOptions::default()
+            .with_allowed_variable_names("x,y,z")
+            .with_rule_set(rule_set.clone());
+        match fitch_proof::check_proof_to_proofresult(proof, &options).0 {
+            fitch_proof::ProofResult::Error(errors) => {
+                errors.into_iter().map(|d| (d.line, d.suggestions)).collect::<Vec<_>>()
+            }
+            _ => panic!("the proof should have errors"),
+        }
+    };
+    let lpl = fitch_proof::RuleSet::lpl();
+
+    // swapped citations
+    let proof = "1 | P\n2 | P → Q\n  |---\n3 | Q   → Elim: 1,2";
+    assert_eq!(suggestions(proof, &lpl), vec![(Some(3), vec!["→ Elim: 2,1".to_string()])]);
+
+    // the wrong direction, and a citation that is off by one
+    let proof = "1 | P ∧ Q\n2 | R\n  |---\n3 | P   ∧ Intro: 1\n4 | R   Reit: 1";
+    assert_eq!(
+        suggestions(proof, &lpl),
+        vec![(Some(3), vec!["∧ Elim: 1".to_string()]), (Some(4), vec!["Reit: 2".to_string()])]
+    );
+
+    // the suggestions use the names of the rule set, and only its rules
+    let proof = "1 | ¬¬P\n  |---\n2 | P   Reit: 1";
+    assert_eq!(suggestions(proof, &lpl), vec![(Some(2), vec!["¬ Elim: 1".to_string()])]);
+    assert_eq!(
+        suggestions(proof, &fitch_proof::RuleSet::forallx()),
+        vec![(Some(2), vec!["DNE: 1".to_string()])]
+    );
+    let intuitionistic = fitch_proof::RuleSet::lpl().with_intuitionistic_logic(true);
+    assert_eq!(suggestions(proof, &intuitionistic), vec![(Some(2), vec![])]);
+
+    // no suggestion if nothing nearby is correct
+    let proof = "1 | P\n  |---\n2 | Q   Reit: 1";
+    assert_eq!(suggestions(proof, &lpl), vec![(Some(2), vec![])]);
+
+    // nor if the only candidates cite a line that cannot be parsed
+    let proof = "1 | A\n2 | B\n  |----\n3 | A ∧ ^^ B\n4 | C   ∧ Intro: 1, 2";
+    let line_4 = suggestions(proof, &lpl).into_iter().find(|(line, _)| *line == Some(4));
+    assert_eq!(line_4, Some((Some(4), vec![])));
+
+    let result = fitch_proof::check_proof("1 | P\n2 | P → Q\n  |---\n3 | Q   → Elim: 1,2", "x,y,z");
+    assert!(result.ends_with("Did you mean '→ Elim: 2,1'?"), "{result}");
+}
+
+#[test]
+fn test_proven_sequent() {
+    let proof = "1 | P → Q\n2 | P\n3 | R\n  |---\n4 | | S\n  | |---\n5 | | Q   → Elim: 1,2\n6 | S → Q   → Intro: 4-5";
+    assert_eq!(
+        fitch_proof::proven_sequent(proof, vec![], "x,y,z"),
+        "Your proof shows P → Q, P ⊢ S → Q.\nPremise 3 is not used."
+    );
+    let template = ["P → Q", "P", "R", "S → Q"].map(String::from).to_vec();
+    let lpl = fitch_proof::RuleSet::lpl();
+    let sequent = fitch_proof::proven_sequent_of_proof(proof, &template, "x,y,z", &lpl).unwrap();
+    assert_eq!(sequent.used_premises, vec![1, 2]);
+    assert_eq!(sequent.unneeded_template_premises, vec!["R".to_string()]);
+    assert_eq!(sequent.dependencies[3], (4, vec![4]));
+    assert_eq!(sequent.dependencies[4], (5, vec![1, 2]));
+    assert_eq!(sequent.dependencies[5], (6, vec![1, 2]));
+
+    // ∃ Elim discharges the assumption of its subproof, and a line without justification is an
+    // open assumption
+    let proof = "1 | ∃x P(x)\n  |---\n2 | | [a] P(a)\n  | |---\n3 | | Q\n4 | Q   ∃ Elim: 1, 2-3";
+    assert_eq!(
+        fitch_proof::proven_sequent(proof, vec![], "x,y,z"),
+        "Your proof shows ∃x P(x), Q ⊢ Q.\nThe conclusion depends on line 3, which is not a premise of the proof."
+    );
+    assert!(fitch_proof::proven_sequent("1 | P ∧", vec![], "x,y,z").starts_with("Fatal error"));
+
+    // a line that cites itself does not follow from what it cites
+    let proof = "1 | P → Q\n2 | P\n  |---\n3 | Q   → Elim: 1, 2\n4 | Q ∨ R   ∨ Intro: 4";
+    assert_eq!(
+        fitch_proof::proven_sequent(proof, vec![], "x,y,z"),
+        "Your proof shows Q ∨ R ⊢ Q ∨ R.\nPremise 1 is not used.\nPremise 2 is not used.\nThe conclusion depends on line 4, which is not a premise of the proof."
+    );
+
+    // a modal proof, read with a modal rule set: a strict subproof discharges nothing
+    let proof = "1 | □P\n  |---\n  | |□----\n2 | | P   □ Elim: 1\n3 | □P   □ Intro: 2-2";
+    let k = fitch_proof::RuleSet::lpl().with_modal_system(fitch_proof::ModalSystem::K);
+    let sequent = fitch_proof::proven_sequent_of_proof(proof, &[], "x,y,z", &k).unwrap();
+    assert_eq!(sequent.sequent, "□P ⊢ □P");
+    assert_eq!(sequent.dependencies, vec![(1, vec![1]), (2, vec![1]), (3, vec![1])]);
+}
+
+#[test]
+fn test_template_mismatch_diagnostics() {
+    let template_errors = |proof: &str, template: &[&str]| {
+        let template: Vec<String> = template.iter().map(|s| s.to_string()).collect();
+        let options = fitch_proof::CheckOptions::default().with_template(template).with_allowed_variable_names("x,y,z");
+        match fitch_proof::check_proof_to_proofresult(proof, &options).0 {
+            fitch_proof::ProofResult::Error(errs) => errs.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            _ => panic!("the proof should not match the template"),
+        }
+    };
+
+    let proof = "1 | P\n2 | Q\n  |---\n3 | P   Reit: 1";
+    assert_eq!(
+        template_errors(proof, &["Q", "P", "P"]),
+        vec!["Your proof has the same premises as the proof template, but in another order. The template has them in this order: 'Q', 'P'."]
+    );
+    assert_eq!(
+        template_errors(proof, &["P", "Q", "R", "P"]),
+        vec!["The premise 'R' of the proof template is missing from your proof."]
+    );
+    assert_eq!(
+        template_errors(proof, &["P", "P"]),
+        vec!["Line 2: the premise 'Q' is not in the proof template."]
+    );
+    assert_eq!(
+        template_errors(proof, &["P", "¬Q", "P"]),
+        vec!["Line 2: the premise 'Q' does not match the premise '¬Q' of the proof template."]
+    );
+
+    // the differing part is pointed out, also if it is a term
+    let proof = "1 | ∀x (F(x) → G(f(a)))\n  |---\n2 | ∀x (F(x) → G(f(a)))   Reit: 1";
+    assert_eq!(
+        template_errors(proof, &["∀x (F(x) → G(f(b)))", "∀x (F(x) → G(f(a)))"]),
+        vec!["Line 1: the premise '∀x (F(x) → G(f(a)))' does not match the premise '∀x (F(x) → G(f(b)))' of the proof template; they differ in 'a', where the template has 'b'."]
+    );
+
+    // sentences that only differ in the grouping of a conjunction or disjunction
+    let proof = "1 | A ∧ (B ∧ C)\n  |---\n2 | A   ∧ Elim: 1\n3 | A ∨ B ∨ C   ∨ Intro: 2";
+    assert_eq!(
+        template_errors(proof, &["(A ∧ B) ∧ C", "(A ∨ B) ∨ C"]),
+        vec![
+            "Line 1: the premise 'A ∧ (B ∧ C)' does not match the premise '(A ∧ B) ∧ C' of the proof template; 'A ∧ (B ∧ C)' and '(A ∧ B) ∧ C' only differ in how the parentheses group the conjuncts, but that makes them different sentences.",
+            "The conclusion of your proof, 'A ∨ B ∨ C', does not match the conclusion in the proof template, '(A ∨ B) ∨ C'; 'A ∨ B ∨ C' is a disjunction of 3 sentences and '(A ∨ B) ∨ C' is a disjunction of 2: they only differ in the parentheses, but that makes them different sentences."
+        ]
+    );
+    let proof = "1 | (A ∧ B ∧ C) → (D ∨ E ∨ F)\n  |---\n2 | (A ∧ B ∧ C) → (D ∨ E ∨ F)   Reit: 1";
+    assert_eq!(
+        template_errors(proof, &["(A ∧ B ∧ C) → (D ∨ E ∨ F)", "((A ∧ B) ∧ C) → ((D ∨ E) ∨ F)"]),
+        vec!["The conclusion of your proof, '(A ∧ B ∧ C) → (D ∨ E ∨ F)', does not match the conclusion in the proof template, '((A ∧ B) ∧ C) → ((D ∨ E) ∨ F)'; '(A ∧ B ∧ C) → (D ∨ E ∨ F)' and '((A ∧ B) ∧ C) → ((D ∨ E) ∨ F)' only differ in how the parentheses group conjunctions and disjunctions, but that makes them different sentences."]
+    );
+}
+
+#[test]
+fn test_template_matching() {
+    let proof = "1 | Q\n2 | ∀y P(y)\n  |---\n3 | P(a)   ∀ Elim: 2\n4 | ∃z P(z)   ∃ Intro: 3";
+    let template: Vec<String> = ["∀x P(x)", "R", "Q", "∃x P(x)"].map(String::from).to_vec();
+    let options = fitch_proof::CheckOptions::default().with_template(template).with_allowed_variable_names("x,y,z");
+    let check = |matching: fitch_proof::TemplateMatching| {
+        fitch_proof::check_proof_to_proofresult(proof, &options.clone().with_template_matching(matching))
+    };
+
+    let (result, relaxations) = check(fitch_proof::TemplateMatching::lenient());
+    assert_eq!(result, fitch_proof::ProofResult::Correct);
+    assert_eq!(
+        relaxations,
+        vec![
+            fitch_proof::TemplateRelaxation::AlphaEquivalence(vec![2, 4]),
+            fitch_proof::TemplateRelaxation::PremiseOrder,
+            fitch_proof::TemplateRelaxation::OmittedPremises(vec!["R".to_string()]),
+        ]
+    );
+    assert_eq!(relaxations[2].to_string(), "These premises of the proof template do not appear in your proof: 'R'.");
+
+    // each relaxation is needed
+    let lenient = fitch_proof::TemplateMatching::lenient();
+    for matching in [
+        lenient.with_alpha_equivalence(false),
+        lenient.with_any_premise_order(false),
+        lenient.with_omitted_premises(false),
+    ] {
+        assert!(matches!(check(matching).0, fitch_proof::ProofResult::Error(_)), "{matching:?}");
+    }
+    // strict matching is the default
+    assert_eq!(check(fitch_proof::TemplateMatching::strict()), fitch_proof::check_proof_to_proofresult(proof, &options));
+
+    assert_eq!(
+        fitch_proof::check_proof_with_template_matching(
+            "1 | ∀y P(y)\n  |---\n2 | ∀y P(y)   Reit: 1",
+            vec!["∀x P(x)".to_string(), "∀y P(y)".to_string()],
+            "x,y,z",
+            "alpha-equivalence"
+        ),
+        "The proof is correct!\nNote: The sentence at line 1 only matches the proof template after renaming bound variables."
+    );
+    assert!(fitch_proof::check_proof_with_template_matching("1 | P\n  |---\n2 | P   Reit: 1", vec![], "x", "loose").starts_with("Fatal error: 'loose' is not a template matching policy"));
+}
+#[test]
+fn test_exercise() {
+    let proof = "1 | ∀x P(x)\n  |---\n2 | | [c]\n  | |---\n3 | | P(c)   ∀ Elim: 1\n4 | ∀x P(x)   ∀ Intro: 2-3";
+    let exercise = |settings: &str| {
+        fitch_proof::Exercise::from_toml(&format!("premises = ['∀x P(x)']\nconclusion = '∀x P(x)'\n{settings}")).unwrap()
+    };
+    let check = |settings: &str| fitch_proof::check_proof_to_proofresult(proof, &(&exercise(settings)).into()).0;
+
+    assert_eq!(check(""), fitch_proof::ProofResult::Correct);
+    assert_eq!(check("max_lines = 4\nboxed_constants = ['a', 'b', 'c']"), fitch_proof::ProofResult::Correct);
+    let fitch_proof::ProofResult::Error(errs) = check("max_lines = 3\nboxed_constants = ['a', 'b']") else {
+        panic!("the exercise should not be done");
+    };
+    assert_eq!(errs.iter().map(|e| e.code).collect::<Vec<_>>(), vec![fitch_proof::ErrorCode::TooManyLines, fitch_proof::ErrorCode::BoxedConstantNotAllowed]);
+    assert_eq!(errs[0].to_string(), "Your proof has 4 lines, but this exercise allows at most 3.");
+    assert_eq!(errs[1].line, Some(2));
+
+    // the mistakes against the exercise are sorted in between the other mistakes
+    let wrong_proof = proof.replace("∀ Intro: 2-3", "∃ Intro: 3");
+    let fitch_proof::ProofResult::Error(errs) = fitch_proof::check_proof_to_proofresult(&wrong_proof, &(&exercise("boxed_constants = ['a']")).into()).0 else {
+        panic!("the proof should not be correct");
+    };
+    assert_eq!(errs.iter().map(|e| e.line).collect::<Vec<_>>(), vec![Some(2), Some(4)]);
+    assert_eq!(errs[0].code, fitch_proof::ErrorCode::BoxedConstantNotAllowed);
+
+    // the variable names and the rule set of the exercise are used
+    assert!(matches!(check("variables = ['y']"), fitch_proof::ProofResult::Error(_)));
+    let proof = "1 | ¬¬P\n  |---\n2 | P   ¬ Elim: 1";
+    let exercise = fitch_proof::Exercise::from_toml("premises = ['¬¬P']\nconclusion = 'P'\nrule_set = 'lpl-intuitionistic'").unwrap();
+    assert!(matches!(fitch_proof::check_proof_to_proofresult(proof, &(&exercise).into()).0, fitch_proof::ProofResult::Error(_)));
+
+    // the feedback level decides how much is said
+    let proof = "1 | P\n  |---\n2 | Q   Reit: 1\n3 | R   Reit: 1";
+    let exercise = |feedback: &str| format!("premises = ['P']\nconclusion = 'R'\nfeedback = '{feedback}'");
+    assert_eq!(fitch_proof::check_proof_with_exercise(proof, &exercise("verdict")), "The proof is not correct.");
+    let full = fitch_proof::check_proof_with_exercise(proof, &exercise("full"));
+    let first = fitch_proof::check_proof_with_exercise(proof, &exercise("first-mistake"));
+    assert!(full.starts_with(&first) && full.len() > first.len());
+    assert!(fitch_proof::check_proof_with_exercise(proof, "premises = ['P']").starts_with("Fatal error:"));
+}
+#[test]
+fn test_grade_proof() {
+    let exercise = fitch_proof::Exercise::from_toml("premises = ['P', 'P → Q']\nconclusion = 'Q ∧ P'").unwrap();
+    let grade = |proof: &str| fitch_proof::grade_proof(proof, &exercise);
+
+    let correct = grade("1 | P\n2 | P → Q\n  |---\n3 | Q   → Elim: 2, 1\n4 | Q ∧ P   ∧ Intro: 3, 1");
+    assert_eq!((correct.result, correct.line_count, correct.score), (fitch_proof::ProofResult::Correct, 4, 100));
+
+    // three of the four lines are fine
+    let wrong = grade("1 | P\n2 | P → Q\n  |---\n3 | Q   → Elim: 2, 1\n4 | Q ∧ P   ∧ Intro: 3");
+    assert!(matches!(wrong.result, fitch_proof::ProofResult::Error(_)));
+    assert_eq!((wrong.line_count, wrong.score), (4, 37));
+
+    let fatal = grade("1 | P\n  | | Q\n2 | P");
+    assert!(matches!(fatal.result, fitch_proof::ProofResult::FatalError(_)));
+    assert_eq!((fatal.line_count, fatal.score), (2, 0));
+
+    // an empty proof has no lines to score
+    let empty = grade("");
+    assert!(!matches!(empty.result, fitch_proof::ProofResult::Correct));
+    assert_eq!((empty.line_count, empty.score), (0, 0));
+
+    // a proof that is only too long has no line with an error, so it gets all of the 50
+    let exercise = fitch_proof::Exercise::from_toml("premises = ['P', 'P → Q']\nconclusion = 'Q ∧ P'\nmax_lines = 3").unwrap();
+    let too_long = fitch_proof::grade_proof("1 | P\n2 | P → Q\n  |---\n3 | Q   → Elim: 2, 1\n4 | Q ∧ P   ∧ Intro: 3, 1", &exercise);
+    let fitch_proof::ProofResult::Error(errs) = &too_long.result else { panic!("the proof is too long") };
+    assert_eq!(errs.iter().map(|e| e.code).collect::<Vec<_>>(), vec![fitch_proof::ErrorCode::TooManyLines]);
+    assert_eq!((too_long.line_count, too_long.score), (4, 50));
+}
+#[test]
+fn test_rewrites_to_result() {
+    let proof = "1|P\n|---\n3|P   Reit:1";
+    assert_eq!(fitch_proof::format_proof_to_result(proof).unwrap(), fitch_proof::format_proof(proof));
+    assert_eq!(fitch_proof::fix_line_numbers_to_result(proof).unwrap(), fitch_proof::fix_line_numbers_in_proof(proof));
+    assert_eq!(fitch_proof::export_to_latex_to_result(proof).unwrap(), fitch_proof::export_to_latex(proof));
+
+    // the String versions give back the proof, but these say what is wrong
+    let broken = "1 | P ∧\n  |---";
+    assert_eq!(fitch_proof::format_proof(broken), broken);
+    assert_eq!(fitch_proof::format_proof_to_result(broken).unwrap_err().code, fitch_proof::ErrorCode::Parser);
+    assert_eq!(fitch_proof::fix_line_numbers_to_result("").unwrap_err().code, fitch_proof::ErrorCode::EmptyProof);
+}
+#[test]
+fn test_editor_support() {
+    // line 6 is still being written
+    let proof = "1 | P\n2 | Q\n  |---\n3 | | R\n  | |---\n4 | | P   Reit: 1\n5 | R → P   → Intro: 3-4\n\n6 | P ∧ Q   ∧ Intro:";
+    assert_eq!(
+        fitch_proof::source_line_numbers(proof),
+        vec![Some(1), Some(2), None, Some(3), None, Some(4), Some(5), None, Some(6)]
+    );
+
+    let cited = fitch_proof::cited_sentences(proof, 5).unwrap();
+    assert_eq!((cited[0].citation.as_str(), cited[0].sentences.clone()), ("3-4", vec!["R".to_owned(), "P".to_owned()]));
+    assert_eq!(fitch_proof::cited_sentences(proof, 1), None);
+
+    let rule_set = fitch_proof::RuleSet::default();
+    let scope = fitch_proof::scope_at_line(proof, "x,y,z", &rule_set, 6).unwrap();
+    assert_eq!((scope.lines, scope.subproofs), (vec![1, 2, 5], vec![(3, 4)]));
+    let scope = fitch_proof::scope_at_line(proof, "x,y,z", &rule_set, 4).unwrap();
+    assert_eq!(scope.lines, vec![1, 2, 3]);
+    assert_eq!(fitch_proof::scope_at_line(proof, "x,y,z", &rule_set, 7), None);
+}
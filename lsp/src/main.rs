@@ -0,0 +1,69 @@
+extern crate fitch_proof;
+
+mod position;
+mod server;
+
+use std::error::Error;
+
+use lsp_server::{Connection, Message};
+use lsp_types::{
+    CompletionOptions, HoverProviderCapability, InitializeParams, OneOf, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind,
+};
+use server::Server;
+
+/// A language server for Fitch proofs (`.fitch` files), which speaks LSP over `stdin` and
+/// `stdout`. It shows the mistakes that the checker finds while the proof is written, formats
+/// proofs, offers to fix the line numbers, shows the sentences that a justification cites on
+/// hover, and completes rule names and the lines that may be cited.
+///
+/// The rule set and the variable names can be set in the initialization options of the client
+/// (see [Server::new]).
+fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
+    let (connection, io_threads) = Connection::stdio();
+    let (id, params) = connection.initialize_start()?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+    let mut server = Server::new(params.initialization_options.as_ref());
+    connection.initialize_finish(
+        id,
+        serde_json::json!({
+            "capabilities": capabilities(),
+            "serverInfo": { "name": "fitch-lsp", "version": env!("CARGO_PKG_VERSION") },
+        }),
+    )?;
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(req) => {
+                if connection.handle_shutdown(&req)? {
+                    break;
+                }
+                connection.sender.send(server.handle_request(req).into())?;
+            }
+            Message::Notification(notification) => {
+                if let Some(diagnostics) = server.handle_notification(notification) {
+                    connection.sender.send(diagnostics.into())?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    // the writer thread stops once the connection is gone
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        code_action_provider: Some(true.into()),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".to_string(), ",".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        ..ServerCapabilities::default()
+    }
+}
@@ -0,0 +1,84 @@
+use lsp_types::{Position, Range, TextEdit};
+
+/// Returns physical line `row` of a document (the first one is row 0), or "" if there is none.
+pub fn line_at(text: &str, row: usize) -> &str {
+    text.lines().nth(row).unwrap_or("")
+}
+
+/// Converts a column in characters, which is how the checker counts, to a column in UTF-16 code
+/// units, which is how LSP counts.
+pub fn utf16_column(line: &str, chars: usize) -> u32 {
+    line.chars().take(chars).map(char::len_utf16).sum::<usize>() as u32
+}
+
+/// Converts a column in UTF-16 code units to a column in characters.
+pub fn char_column(line: &str, utf16: u32) -> usize {
+    let mut units = 0;
+    for (i, c) in line.chars().enumerate() {
+        if units >= utf16 as usize {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    line.chars().count()
+}
+
+/// The range of the text on row `row`, without the indentation.
+pub fn row_range(text: &str, row: usize) -> Range {
+    let line = line_at(text, row);
+    let indentation = line.chars().take_while(|c| c.is_whitespace()).count();
+    Range::new(
+        Position::new(row as u32, utf16_column(line, indentation)),
+        Position::new(row as u32, utf16_column(line, usize::MAX)),
+    )
+}
+
+/// The range of a whole document.
+fn document_range(text: &str) -> Range {
+    let rows: Vec<&str> = text.split('\n').collect();
+    let last = rows.last().copied().unwrap_or("");
+    Range::new(
+        Position::new(0, 0),
+        Position::new(rows.len() as u32 - 1, utf16_column(last, usize::MAX)),
+    )
+}
+
+/// The edits that turn `text` into `rewritten` (which gets a final newline): none if nothing
+/// changes, and otherwise one edit that replaces the whole document.
+pub fn rewrite_edits(text: &str, rewritten: &str) -> Vec<TextEdit> {
+    let rewritten = format!("{}\n", rewritten.trim_end());
+    if rewritten == text {
+        return vec![];
+    }
+    vec![TextEdit::new(document_range(text), rewritten)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns() {
+        let line = "1 | 𝔸 ∧ B";
+        // '𝔸' takes two UTF-16 code units, '∧' one
+        assert_eq!(utf16_column(line, 5), 6);
+        assert_eq!(utf16_column(line, 100), 10);
+        assert_eq!(char_column(line, 6), 5);
+        assert_eq!(char_column(line, 100), 9);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let text = "1 | P\n  |---\r\n  2 | P   Reit: 1";
+        assert_eq!(
+            row_range(text, 2),
+            Range::new(Position::new(2, 2), Position::new(2, 17))
+        );
+        assert_eq!(
+            document_range(text),
+            Range::new(Position::new(0, 0), Position::new(2, 17))
+        );
+        assert!(rewrite_edits("1 | P\n", "1 | P").is_empty());
+        assert_eq!(rewrite_edits("1 | P", "1 | P")[0].new_text, "1 | P\n");
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use fitch_proof::{CheckOptions, ProofResult, Rule, RuleSet};
+use lsp_server::{ErrorCode, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::{
+    CodeActionRequest, Completion, Formatting, HoverRequest, Request as RequestTrait,
+};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CompletionItem,
+    CompletionItemKind, CompletionParams, CompletionResponse, Diagnostic,
+    DiagnosticRelatedInformation, DiagnosticSeverity, DocumentFormattingParams, Hover,
+    HoverContents, HoverParams, Location, MarkupContent, MarkupKind, NumberOrString, Position,
+    PublishDiagnosticsParams, Range, TextEdit, Uri, WorkspaceEdit,
+};
+use serde_json::Value;
+
+use crate::position::{char_column, line_at, rewrite_edits, row_range, utf16_column};
+
+const DEFAULT_ALLOWED_VARIABLE_NAMES: &str = "x,y,z,u,v,w";
+
+/// The open documents, and the settings that they are checked with.
+pub struct Server {
+    documents: HashMap<Uri, String>,
+    allowed_variable_names: String,
+    rule_set: RuleSet,
+}
+
+impl Server {
+    /// Creates a server with the settings in the initialization options of the client, like
+    /// `{"ruleSet": "forallx", "variables": "x,y,z"}`. Settings that are missing or unknown are
+    /// left at the defaults: the LPL rules and the variables `x,y,z,u,v,w`.
+    pub fn new(options: Option<&Value>) -> Server {
+        let option = |name: &str| options.and_then(|o| o.get(name)).and_then(Value::as_str);
+        let rule_set = match option("ruleSet") {
+            Some(name) => RuleSet::from_name(name).unwrap_or_else(|| {
+                eprintln!("There is no rule set '{name}'; using the LPL rules instead.");
+                RuleSet::default()
+            }),
+            None => RuleSet::default(),
+        };
+        Server {
+            documents: HashMap::new(),
+            allowed_variable_names: option("variables")
+                .unwrap_or(DEFAULT_ALLOWED_VARIABLE_NAMES)
+                .to_string(),
+            rule_set,
+        }
+    }
+
+    pub fn handle_request(&self, req: Request) -> Response {
+        match req.method.as_str() {
+            Formatting::METHOD => respond::<Formatting>(req, |p| self.formatting(p)),
+            CodeActionRequest::METHOD => {
+                respond::<CodeActionRequest>(req, |p| self.code_actions(p))
+            }
+            HoverRequest::METHOD => respond::<HoverRequest>(req, |p| self.hover(p)),
+            Completion::METHOD => respond::<Completion>(req, |p| self.completion(p)),
+            method => Response::new_err(
+                req.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("there is no method '{method}'"),
+            ),
+        }
+    }
+
+    /// Keeps track of the open documents. Returns the diagnostics to publish if a document was
+    /// opened, changed or closed.
+    pub fn handle_notification(&mut self, notification: Notification) -> Option<Notification> {
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = extract::<DidOpenTextDocument>(notification)?;
+                let document = params.text_document;
+                self.documents.insert(document.uri.clone(), document.text);
+                document.uri
+            }
+            DidChangeTextDocument::METHOD => {
+                // the whole document is synchronized, so the last change holds all of the text
+                let params = extract::<DidChangeTextDocument>(notification)?;
+                let text = params.content_changes.into_iter().last()?.text;
+                let uri = params.text_document.uri;
+                self.documents.insert(uri.clone(), text);
+                uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let uri = extract::<DidCloseTextDocument>(notification)?
+                    .text_document
+                    .uri;
+                self.documents.remove(&uri);
+                uri
+            }
+            _ => return None,
+        };
+        let diagnostics = match self.documents.get(&uri) {
+            Some(text) => self.diagnostics(&uri, text),
+            None => vec![],
+        };
+        Some(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            PublishDiagnosticsParams::new(uri, diagnostics, None),
+        ))
+    }
+
+    /// Checks a document. A mistake is shown where the parser found it, or else on the line with
+    /// the line number of the mistake, and the lines it involves are related information.
+    fn diagnostics(&self, uri: &Uri, text: &str) -> Vec<Diagnostic> {
+        let options = CheckOptions::default()
+            .with_allowed_variable_names(&self.allowed_variable_names)
+            .with_rule_set(self.rule_set.clone());
+        let errors = match fitch_proof::check_proof_to_proofresult(text, &options).0 {
+            ProofResult::Correct => vec![],
+            ProofResult::Error(errs) => errs,
+            ProofResult::FatalError(err) => vec![err],
+        };
+        let source_line_numbers = fitch_proof::source_line_numbers(text);
+        let row_of = |line_num: usize| {
+            source_line_numbers
+                .iter()
+                .position(|&n| n == Some(line_num))
+        };
+        errors
+            .into_iter()
+            .map(|err| {
+                let range = match (&err.location, err.line.and_then(row_of)) {
+                    (Some(location), _) => {
+                        let row = location.line as u32 - 1;
+                        let line = &location.source_line;
+                        let end = location.span.end.max(location.span.start + 1);
+                        Range::new(
+                            Position::new(row, utf16_column(line, location.span.start)),
+                            Position::new(row, utf16_column(line, end)),
+                        )
+                    }
+                    (None, Some(row)) => row_range(text, row),
+                    (None, None) => row_range(text, 0),
+                };
+                let related_information = err
+                    .related_lines
+                    .iter()
+                    .filter_map(|&n| {
+                        Some(DiagnosticRelatedInformation {
+                            location: Location::new(uri.clone(), row_range(text, row_of(n)?)),
+                            message: format!("line {n}"),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let message = match err.suggestions_text() {
+                    Some(suggestions) => format!("{}\n{suggestions}", err.message),
+                    None => err.message,
+                };
+                Diagnostic {
+                    range,
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(err.code.as_str().to_string())),
+                    source: Some("fitch".to_string()),
+                    message,
+                    related_information: (!related_information.is_empty())
+                        .then_some(related_information),
+                    ..Diagnostic::default()
+                }
+            })
+            .collect()
+    }
+
+    /// Formats a document, or does nothing if it cannot be parsed.
+    fn formatting(&self, params: DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+        let text = self.documents.get(&params.text_document.uri)?;
+        let formatted = fitch_proof::format_proof_to_result(text).ok()?;
+        Some(rewrite_edits(text, &formatted))
+    }
+
+    /// Offers to fix the line numbers, if they are not right yet. Since fixing the line numbers
+    /// formats the proof too, the action is only offered if it does more than formatting.
+    fn code_actions(&self, params: CodeActionParams) -> Option<Vec<CodeActionOrCommand>> {
+        let uri = params.text_document.uri;
+        let text = self.documents.get(&uri)?;
+        let renumbered = fitch_proof::fix_line_numbers_to_result(text).ok()?;
+        if fitch_proof::format_proof_to_result(text).ok()? == renumbered {
+            return Some(vec![]);
+        }
+        let edit = WorkspaceEdit {
+            changes: Some(HashMap::from([(uri, rewrite_edits(text, &renumbered))])),
+            ..WorkspaceEdit::default()
+        };
+        Some(vec![CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Renumber the lines".to_string(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(edit),
+            ..CodeAction::default()
+        })])
+    }
+
+    /// Shows the sentences that the justification of the hovered line cites.
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let text = self.documents.get(&position.text_document.uri)?;
+        let row = position.position.line as usize;
+        let line_num = (*fitch_proof::source_line_numbers(text).get(row)?)?;
+        let cited = fitch_proof::cited_sentences(text, line_num)?;
+        if cited.is_empty() {
+            return None;
+        }
+        let value = cited
+            .iter()
+            .map(|c| format!("- **{}**: `{}`", c.citation, c.sentences.join("` … `")))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(row_range(text, row)),
+        })
+    }
+
+    /// Offers the lines and subproofs that may be cited after the `:` of a justification, and
+    /// the names of the rules before it.
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let position = params.text_document_position;
+        let text = self.documents.get(&position.text_document.uri)?;
+        let row = position.position.line as usize;
+        let line = line_at(text, row);
+        let before_cursor: String = line
+            .chars()
+            .take(char_column(line, position.position.character))
+            .collect();
+        let items = if before_cursor.contains(':') {
+            let line_num = (*fitch_proof::source_line_numbers(text).get(row)?)?;
+            self.citation_items(text, line_num)?
+        } else {
+            self.rule_items()
+        };
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn citation_items(&self, text: &str, line_num: usize) -> Option<Vec<CompletionItem>> {
+        let scope = fitch_proof::scope_at_line(
+            text,
+            &self.allowed_variable_names,
+            &self.rule_set,
+            line_num,
+        )?;
+        let lines = scope.lines.iter().map(|n| (n.to_string(), "line"));
+        let subproofs = scope
+            .subproofs
+            .iter()
+            .map(|(begin, end)| (format!("{begin}-{end}"), "subproof"));
+        let imports = scope
+            .imports
+            .iter()
+            .map(|n| (n.to_string(), "line outside the strict subproof"));
+        let items = lines
+            .chain(subproofs)
+            .chain(imports)
+            .enumerate()
+            .map(|(i, (label, detail))| CompletionItem {
+                label,
+                kind: Some(CompletionItemKind::REFERENCE),
+                detail: Some(detail.to_string()),
+                // keep the citations from top to bottom, instead of sorted as text
+                sort_text: Some(format!("{i:04}")),
+                ..CompletionItem::default()
+            })
+            .collect();
+        Some(items)
+    }
+
+    fn rule_items(&self) -> Vec<CompletionItem> {
+        Rule::ALL
+            .into_iter()
+            .filter(|&rule| self.rule_set.is_allowed(rule))
+            .map(|rule| {
+                let name = self.rule_set.name(rule);
+                CompletionItem {
+                    label: name.to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    insert_text: Some(format!("{name}: ")),
+                    ..CompletionItem::default()
+                }
+            })
+            .collect()
+    }
+}
+
+/// Answers a request of type `R` with `handle`, or with an error if its parameters are invalid.
+fn respond<R: RequestTrait>(req: Request, handle: impl FnOnce(R::Params) -> R::Result) -> Response {
+    match serde_json::from_value(req.params) {
+        Ok(params) => Response::new_ok(req.id, handle(params)),
+        Err(err) => Response::new_err(req.id, ErrorCode::InvalidParams as i32, err.to_string()),
+    }
+}
+
+/// Returns the parameters of a notification of type `N`, or [None] if they are invalid.
+fn extract<N: NotificationTrait>(notification: Notification) -> Option<N::Params> {
+    serde_json::from_value(notification.params).ok()
+}
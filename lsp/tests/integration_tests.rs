@@ -0,0 +1,264 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use serde_json::{json, Value};
+
+// These tests run the language server as an editor would: a small LSP client below starts the
+// binary and exchanges JSON-RPC messages with it over stdin and stdout.
+
+const URI: &str = "file:///tmp/proof.fitch";
+
+struct Client {
+    server: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i64,
+}
+
+impl Client {
+    /// Starts the server and initializes it with `options`.
+    fn start(options: Value) -> (Client, Value) {
+        let mut server = Command::new(env!("CARGO_BIN_EXE_fitch-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to start the language server");
+        let stdin = server.stdin.take().unwrap();
+        let stdout = BufReader::new(server.stdout.take().unwrap());
+        let mut client = Client {
+            server,
+            stdin,
+            stdout,
+            next_id: 1,
+        };
+        let result = client.request(
+            "initialize",
+            json!({"capabilities": {}, "initializationOptions": options}),
+        );
+        client.notify("initialized", json!({}));
+        (client, result)
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            self.stdout.read_line(&mut header).unwrap();
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = Some(value.parse().unwrap());
+            }
+        }
+        let mut body = vec![0; length.expect("A message without Content-Length")];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Sends a request and returns the result of the response.
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}));
+        loop {
+            let message = self.receive();
+            if message["id"] == id {
+                assert_eq!(message["error"], Value::Null, "{method} failed");
+                return message["result"].clone();
+            }
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({"jsonrpc": "2.0", "method": method, "params": params}));
+    }
+
+    /// Opens (or changes) the document, and returns the diagnostics that are published for it.
+    fn open(&mut self, text: &str) -> Vec<Value> {
+        self.notify(
+            "textDocument/didOpen",
+            json!({"textDocument": {"uri": URI, "languageId": "fitch", "version": 1, "text": text}}),
+        );
+        self.diagnostics()
+    }
+
+    fn change(&mut self, text: &str) -> Vec<Value> {
+        self.notify(
+            "textDocument/didChange",
+            json!({"textDocument": {"uri": URI, "version": 2}, "contentChanges": [{"text": text}]}),
+        );
+        self.diagnostics()
+    }
+
+    fn diagnostics(&mut self) -> Vec<Value> {
+        let message = self.receive();
+        assert_eq!(message["method"], "textDocument/publishDiagnostics");
+        assert_eq!(message["params"]["uri"], URI);
+        message["params"]["diagnostics"].as_array().unwrap().clone()
+    }
+
+    fn at(&mut self, method: &str, line: u32, character: u32) -> Value {
+        self.request(
+            method,
+            json!({"textDocument": {"uri": URI}, "position": {"line": line, "character": character}}),
+        )
+    }
+
+    /// Shuts the server down, and checks that it exits cleanly.
+    fn stop(mut self) {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        assert!(self.server.wait().unwrap().success());
+    }
+}
+
+fn labels(completion: &Value) -> Vec<&str> {
+    completion
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_initialize() {
+    let (client, result) = Client::start(Value::Null);
+    let capabilities = &result["capabilities"];
+    assert_eq!(capabilities["textDocumentSync"], 1);
+    assert_eq!(capabilities["documentFormattingProvider"], true);
+    assert_eq!(capabilities["codeActionProvider"], true);
+    assert_eq!(capabilities["hoverProvider"], true);
+    assert_eq!(
+        capabilities["completionProvider"]["triggerCharacters"],
+        json!([":", ","])
+    );
+    assert_eq!(result["serverInfo"]["name"], "fitch-lsp");
+    client.stop();
+}
+
+#[test]
+fn test_diagnostics() {
+    let (mut client, _) = Client::start(Value::Null);
+    let proof = "1 | P\n2 | Q\n  |---\n3 | P ∧ Q   ∧ Intro: 1, 2\n";
+    assert_eq!(client.open(proof), Vec::<Value>::new());
+
+    // a mistake in a justification is shown on its line, with the cited lines as related information
+    let diagnostics = client.change("1 | P\n2 | Q\n  |---\n\n3 | P ∨ Q   ∧ Intro: 1, 2\n");
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic["source"], "fitch");
+    assert_eq!(diagnostic["severity"], 1);
+    assert_eq!(
+        diagnostic["range"],
+        json!({"start": {"line": 4, "character": 0}, "end": {"line": 4, "character": 25}})
+    );
+    let related: Vec<&Value> = diagnostic["relatedInformation"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| &r["location"]["range"]["start"]["line"])
+        .collect();
+    assert_eq!(related, vec![&json!(0), &json!(1)]);
+
+    // a parse error is shown at the columns where it was found
+    let diagnostics = client.change("1 | P\n  |---\n2 | P ∧ ∧ Q   Reit: 1\n");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "E_PARSE");
+    assert_eq!(
+        diagnostics[0]["range"]["start"],
+        json!({"line": 2, "character": 4})
+    );
+
+    // closing the document clears its diagnostics
+    client.notify(
+        "textDocument/didClose",
+        json!({"textDocument": {"uri": URI}}),
+    );
+    assert_eq!(client.diagnostics(), Vec::<Value>::new());
+    client.stop();
+}
+
+#[test]
+fn test_formatting_and_renumbering() {
+    let (mut client, _) = Client::start(Value::Null);
+    client.open("1|P\n2|Q\n|---\n5|P∧Q   ∧Intro:1,2\n");
+    let edits = client.request(
+        "textDocument/formatting",
+        json!({"textDocument": {"uri": URI}, "options": {"tabSize": 4, "insertSpaces": true}}),
+    );
+    assert_eq!(
+        edits[0]["newText"],
+        "1 | P\n2 | Q\n  |----\n5 | P ∧ Q         ∧ Intro: 1, 2\n"
+    );
+    assert_eq!(
+        edits[0]["range"],
+        json!({"start": {"line": 0, "character": 0}, "end": {"line": 4, "character": 0}})
+    );
+
+    let range = json!({"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 0}});
+    let actions = client.request(
+        "textDocument/codeAction",
+        json!({"textDocument": {"uri": URI}, "range": range, "context": {"diagnostics": []}}),
+    );
+    assert_eq!(actions[0]["title"], "Renumber the lines");
+    assert_eq!(
+        actions[0]["edit"]["changes"][URI][0]["newText"],
+        "1 | P\n2 | Q\n  |----\n3 | P ∧ Q         ∧ Intro: 1, 2\n"
+    );
+
+    // a formatted proof with the right line numbers needs neither
+    client.change("1 | P\n2 | Q\n  |----\n3 | P ∧ Q         ∧ Intro: 1, 2\n");
+    let edits = client.request(
+        "textDocument/formatting",
+        json!({"textDocument": {"uri": URI}, "options": {"tabSize": 4, "insertSpaces": true}}),
+    );
+    assert_eq!(edits, json!([]));
+    let actions = client.request(
+        "textDocument/codeAction",
+        json!({"textDocument": {"uri": URI}, "range": range, "context": {"diagnostics": []}}),
+    );
+    assert_eq!(actions, json!([]));
+    client.stop();
+}
+
+#[test]
+fn test_hover() {
+    let (mut client, _) = Client::start(Value::Null);
+    client.open("1 | P\n  |---\n2 | | Q\n  | |---\n3 | | P   Reit: 1\n4 | Q → P   → Intro: 2-3\n");
+    let hover = client.at("textDocument/hover", 5, 16);
+    assert_eq!(hover["contents"]["kind"], "markdown");
+    assert_eq!(hover["contents"]["value"], "- **2-3**: `Q` … `P`");
+    let hover = client.at("textDocument/hover", 4, 12);
+    assert_eq!(hover["contents"]["value"], "- **1**: `P`");
+    // premises have no justification
+    assert_eq!(client.at("textDocument/hover", 0, 4), Value::Null);
+    client.stop();
+}
+
+#[test]
+fn test_completion() {
+    let (mut client, _) = Client::start(json!({"ruleSet": "forallx"}));
+    client.open("1 | P\n  |---\n2 | | Q\n  | |---\n3 | | P   Reit: 1\n4 | Q → P   → Intro: \n");
+
+    // after the colon, the lines and subproofs in scope of line 4
+    let completion = client.at("textDocument/completion", 5, 20);
+    assert_eq!(labels(&completion), vec!["1", "2-3"]);
+    let completion = client.at("textDocument/completion", 4, 15);
+    assert_eq!(labels(&completion), vec!["1", "2"]);
+
+    // before it, the names of the rules of the rule set
+    let completion = client.at("textDocument/completion", 5, 10);
+    let labels = labels(&completion);
+    assert!(labels.contains(&"→I"), "{labels:?}");
+    assert!(!labels.contains(&"→ Intro"), "{labels:?}");
+    client.stop();
+}